@@ -1,8 +1,11 @@
 use std::env;
 use std::fmt;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
+use json_parser_rust::json_diagnostics::{render_error, ColorMode};
+
 // Example: naive recursive parser (v1)
 use json_parser_rust::json_parsing_naive;
 
@@ -63,7 +66,8 @@ fn main() {
             print!("{:?}", json_value);
         }
         Err(json_parsing_error) => {
-            println!("Error parsing JSON: {:?}", json_parsing_error);
+            let mode = if std::io::stdout().is_terminal() { ColorMode::Ansi } else { ColorMode::Plain };
+            print!("{}", render_error(&json_parsing_error, &json_string, mode));
         }
     };
 }
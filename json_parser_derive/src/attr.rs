@@ -0,0 +1,198 @@
+use syn::{Attribute, ExprPath, Ident, LitStr, Result};
+
+// =============================================================================
+// #[json(...)] attributes
+// =============================================================================
+//
+//   container  rename_all = "...", tag = "...", content = "...", untagged
+//   field      rename = "...", default, default = "path", skip, flatten
+//   variant    rename = "..."
+//
+// Unknown keys are an error at the key's span, so a typo does not silently
+// fall back to the default behaviour.
+//
+// =============================================================================
+
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub rename_all: Option<RenameRule>,
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub untagged: bool,
+}
+
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub default: Option<FieldDefault>,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+pub enum FieldDefault {
+    Trait,          // #[json(default)]
+    Path(ExprPath), // #[json(default = "path::to_fn")]
+}
+
+#[derive(Default)]
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut out = Self::default();
+        for attr in json_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.rename_all = Some(RenameRule::parse(&lit)?);
+                } else if meta.path.is_ident("tag") {
+                    out.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("content") {
+                    out.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("untagged") {
+                    out.untagged = true;
+                } else {
+                    return Err(meta.error("unknown container attribute, expected rename_all, tag, content or untagged"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut out = Self::default();
+        for attr in json_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    out.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    out.default = Some(if meta.input.peek(syn::Token![=]) {
+                        FieldDefault::Path(meta.value()?.parse::<LitStr>()?.parse()?)
+                    } else {
+                        FieldDefault::Trait
+                    });
+                } else if meta.path.is_ident("skip") {
+                    out.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    out.flatten = true;
+                } else {
+                    return Err(meta.error("unknown field attribute, expected rename, default, skip or flatten"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rename.is_none() && self.default.is_none() && !self.skip && !self.flatten
+    }
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut out = Self::default();
+        for attr in json_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    out.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error("unknown variant attribute, expected rename"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+fn json_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("json"))
+}
+
+/// The name an identifier is written under: `r#type` is "type".
+pub fn ident_name(ident: &Ident) -> String {
+    let name = ident.to_string();
+    match name.strip_prefix("r#") {
+        Some(raw) => raw.to_string(),
+        None => name,
+    }
+}
+
+impl RenameRule {
+    fn parse(lit: &LitStr) -> Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "camelCase" => Self::Camel,
+            "PascalCase" => Self::Pascal,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            _ => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "unknown rename_all rule, expected one of lowercase, UPPERCASE, camelCase, \
+                     PascalCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case",
+                ));
+            }
+        })
+    }
+
+    /// Field names are snake_case and variant names PascalCase; both are split
+    /// into words at '_' and at lower-to-upper boundaries, then re-joined.
+    pub fn apply(self, name: &str) -> String {
+        let words = split_words(name);
+        let capitalized = |word: &str| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+        };
+        match self {
+            Self::Lower => words.concat(),
+            Self::Upper => words.concat().to_uppercase(),
+            Self::Camel => {
+                let mut out = words.first().cloned().unwrap_or_default();
+                out.extend(words.iter().skip(1).map(|word| capitalized(word)));
+                out
+            }
+            Self::Pascal => words.iter().map(|word| capitalized(word)).collect(),
+            Self::Snake => words.join("_"),
+            Self::ScreamingSnake => words.join("_").to_uppercase(),
+            Self::Kebab => words.join("-"),
+        }
+    }
+}
+
+/// Lowercase words of an identifier: `HttpStatus` and `http_status` both give ["http", "status"].
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut word = String::new();
+        let mut prev_lower = false;
+        for c in part.chars() {
+            if c.is_uppercase() && prev_lower {
+                words.push(std::mem::take(&mut word));
+            }
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            word.extend(c.to_lowercase());
+        }
+        words.push(word);
+    }
+    words
+}
@@ -0,0 +1,210 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::attr::FieldDefault;
+use crate::{convert, json_value, Body, Container, Shape, Tagging, Variant};
+
+pub fn expand(container: &Container) -> TokenStream {
+    let k = convert();
+    let ident = container.ident;
+    let generics = container.bounded_generics(quote!(#k::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let value = Ident::new("value", proc_macro2::Span::call_site());
+
+    let body = match &container.body {
+        Body::Struct(shape) => shape_from(shape, quote!(Self), &value, None),
+        Body::Enum(Tagging::External, variants) => external(variants, &value),
+        Body::Enum(Tagging::Internal { tag }, variants) => internal(variants, tag, &value),
+        Body::Enum(Tagging::Adjacent { tag, content }, variants) => adjacent(variants, tag, content, &value),
+        Body::Enum(Tagging::Untagged, variants) => untagged(variants, &ident.to_string(), &value),
+    };
+    let json_value = json_value();
+
+    quote! {
+        impl #impl_generics #k::FromJson for #ident #ty_generics #where_clause {
+            fn from_json(#value: &#json_value) -> ::core::result::Result<Self, #k::FromJsonError> {
+                #body
+            }
+        }
+    }
+}
+
+/// A fallible call, `?`-propagated; with `within`, errors are first moved
+/// under that path token (the variant name or content key).
+fn try_(expr: TokenStream, within: Option<&str>) -> TokenStream {
+    match within {
+        Some(token) => quote!(#expr.map_err(|e| e.within(#token))?),
+        None => quote!(#expr?),
+    }
+}
+
+/// Builds `construct` out of `content`, as a block ending in Ok(...).
+fn shape_from(shape: &Shape, construct: TokenStream, content: &Ident, within: Option<&str>) -> TokenStream {
+    let k = convert();
+    match shape {
+        Shape::Unit => {
+            let check = try_(quote!(<() as #k::FromJson>::from_json(#content)), within);
+            quote!({ #check; ::core::result::Result::Ok(#construct) })
+        }
+        Shape::Newtype(ty) => {
+            let inner = try_(quote!(<#ty as #k::FromJson>::from_json(#content)), within);
+            quote!(::core::result::Result::Ok(#construct(#inner)))
+        }
+        Shape::Tuple(types) => {
+            let len = types.len();
+            let items = try_(quote!(#k::expect_tuple(#content, #len)), within);
+            let elements = types.iter().enumerate().map(|(index, ty)| {
+                try_(quote!(#k::element::<#ty>(items, #index)), within)
+            });
+            quote!({
+                let items = #items;
+                ::core::result::Result::Ok(#construct(#(#elements),*))
+            })
+        }
+        Shape::Named(fields) => {
+            let members = try_(quote!(#k::expect_object(#content)), within);
+            let inits = fields.iter().map(|field| {
+                let member = &field.member;
+                let ty = field.ty;
+                let name = &field.name;
+                if field.attrs.skip {
+                    return quote!(#member: ::core::default::Default::default());
+                }
+                let expr = match &field.attrs.default {
+                    // a flattened field reads its members from the same object
+                    _ if field.attrs.flatten => quote!(<#ty as #k::FromJson>::from_json(#content)),
+                    None => quote!(#k::field::<#ty>(members, #name)),
+                    Some(FieldDefault::Trait) => {
+                        quote!(#k::field_or_else::<#ty>(members, #name, ::core::default::Default::default))
+                    }
+                    Some(FieldDefault::Path(path)) => quote!(#k::field_or_else::<#ty>(members, #name, #path)),
+                };
+                let expr = try_(expr, within);
+                quote!(#member: #expr)
+            });
+            quote!({
+                let members = #members;
+                ::core::result::Result::Ok(#construct { #(#inits),* })
+            })
+        }
+    }
+}
+
+fn variant_names(variants: &[Variant]) -> TokenStream {
+    let names = variants.iter().map(|variant| &variant.name);
+    quote!(&[#(#names),*])
+}
+
+fn construct(variant: &Variant) -> TokenStream {
+    let ident = variant.ident;
+    quote!(Self::#ident)
+}
+
+// "Unit" or {"Name": content}
+fn external(variants: &[Variant], value: &Ident) -> TokenStream {
+    let k = convert();
+    let content = Ident::new("content", proc_macro2::Span::call_site());
+    let names = variant_names(variants);
+    let arms = variants.iter().map(|variant| {
+        let name = &variant.name;
+        let construct = construct(variant);
+        let bare = match variant.shape {
+            Shape::Unit => quote!(::core::result::Result::Ok(#construct)),
+            _ => quote!(::core::result::Result::Err(#k::FromJsonError::type_mismatch("single-member object", #value))),
+        };
+        let body = shape_from(&variant.shape, construct, &content, Some(name));
+        quote! {
+            #name => {
+                let ::core::option::Option::Some(#content) = #content else {
+                    return #bare;
+                };
+                #body
+            }
+        }
+    });
+    quote! {
+        let (name, #content) = #k::expect_variant(#value)?;
+        match name {
+            #(#arms)*
+            other => ::core::result::Result::Err(#k::FromJsonError::unknown_variant(other, #names)),
+        }
+    }
+}
+
+// {"tag": "Name", ...fields}
+fn internal(variants: &[Variant], tag: &str, value: &Ident) -> TokenStream {
+    let k = convert();
+    let names = variant_names(variants);
+    let arms = variants.iter().map(|variant| {
+        let name = &variant.name;
+        let construct = construct(variant);
+        let body = match variant.shape {
+            // the tag is all there is; other members are ignored like unknown fields
+            Shape::Unit => quote!(::core::result::Result::Ok(#construct)),
+            _ => shape_from(&variant.shape, construct, value, None),
+        };
+        quote!(#name => #body,)
+    });
+    quote! {
+        let members = #k::expect_object(#value)?;
+        let tag = #k::field::<::std::string::String>(members, #tag)?;
+        match tag.as_str() {
+            #(#arms)*
+            other => ::core::result::Result::Err(#k::FromJsonError::unknown_variant(other, #names).within(#tag)),
+        }
+    }
+}
+
+// {"tag": "Name", "content": content}
+fn adjacent(variants: &[Variant], tag: &str, content_key: &str, value: &Ident) -> TokenStream {
+    let k = convert();
+    let content = Ident::new("content", proc_macro2::Span::call_site());
+    let names = variant_names(variants);
+    let arms = variants.iter().map(|variant| {
+        let name = &variant.name;
+        let construct = construct(variant);
+        let body = match variant.shape {
+            Shape::Unit => quote!(::core::result::Result::Ok(#construct)),
+            _ => {
+                let body = shape_from(&variant.shape, construct, &content, Some(content_key));
+                quote!({
+                    let ::core::option::Option::Some(#content) = members.get(#content_key) else {
+                        return ::core::result::Result::Err(
+                            #k::FromJsonError::new(#k::FromJsonErrorKind::MissingField).within(#content_key),
+                        );
+                    };
+                    #body
+                })
+            }
+        };
+        quote!(#name => #body,)
+    });
+    quote! {
+        let members = #k::expect_object(#value)?;
+        let tag = #k::field::<::std::string::String>(members, #tag)?;
+        match tag.as_str() {
+            #(#arms)*
+            other => ::core::result::Result::Err(#k::FromJsonError::unknown_variant(other, #names).within(#tag)),
+        }
+    }
+}
+
+// the first variant that converts
+fn untagged(variants: &[Variant], ty: &str, value: &Ident) -> TokenStream {
+    let k = convert();
+    let attempts = variants.iter().map(|variant| {
+        let body = shape_from(&variant.shape, construct(variant), value, None);
+        quote! {
+            let attempt = || -> ::core::result::Result<Self, #k::FromJsonError> { #body };
+            if let ::core::result::Result::Ok(found) = attempt() {
+                return ::core::result::Result::Ok(found);
+            }
+        }
+    });
+    let error = quote!(#k::FromJsonError::new(#k::FromJsonErrorKind::NoMatchingVariant { ty: #ty }));
+    quote! {
+        #(#attempts)*
+        ::core::result::Result::Err(#error)
+    }
+}
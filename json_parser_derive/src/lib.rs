@@ -0,0 +1,196 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Ident, Member, Type};
+
+mod attr;
+mod from_json;
+mod to_json;
+
+use attr::{ident_name, ContainerAttrs, FieldAttrs, RenameRule, VariantAttrs};
+
+// =============================================================================
+// #[derive(FromJson, ToJson)] for json_parser_rust
+// =============================================================================
+//
+// The attributes and the JSON shapes they produce are documented with the
+// traits, in json_parser_rust::json_convert. This crate only turns a type
+// definition into impls of those traits:
+//
+//   1. read the type into a small model (Shape, Field, Variant) with the
+//      #[json(...)] attributes resolved: every field and variant knows the
+//      name it is written under
+//   2. check the combinations that make no sense (tag on a struct, content
+//      without tag, a tuple variant in an internally tagged enum)
+//   3. from_json / to_json generate the impl bodies from the model
+//
+// The generated code calls the helpers in json_convert (field, element,
+// expect_object, ...) rather than spelling the logic out per field, so it
+// stays short and the error paths are built in one place.
+//
+// =============================================================================
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Container::from_input(&input)
+        .map(|container| from_json::expand(&container))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Container::from_input(&input)
+        .map(|container| to_json::expand(&container))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// The paths the generated code goes through.
+fn convert() -> TokenStream2 {
+    quote!(::json_parser_rust::json_convert)
+}
+
+fn json_value() -> TokenStream2 {
+    quote!(::json_parser_rust::json_definitions::JsonValue)
+}
+
+fn index_map() -> TokenStream2 {
+    quote!(::json_parser_rust::json_macro::IndexMap)
+}
+
+// =============================================================================
+// The model
+// =============================================================================
+
+struct Container<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    body: Body<'a>,
+}
+
+enum Body<'a> {
+    Struct(Shape<'a>),
+    Enum(Tagging, Vec<Variant<'a>>),
+}
+
+enum Tagging {
+    External,
+    Internal { tag: String },
+    Adjacent { tag: String, content: String },
+    Untagged,
+}
+
+struct Variant<'a> {
+    ident: &'a Ident,
+    name: String,
+    shape: Shape<'a>,
+}
+
+enum Shape<'a> {
+    Unit,
+    Newtype(&'a Type),
+    Tuple(Vec<&'a Type>),
+    Named(Vec<Field<'a>>),
+}
+
+struct Field<'a> {
+    member: Member,
+    name: String,
+    ty: &'a Type,
+    attrs: FieldAttrs,
+}
+
+impl<'a> Container<'a> {
+    fn from_input(input: &'a DeriveInput) -> syn::Result<Self> {
+        let attrs = ContainerAttrs::parse(&input.attrs)?;
+        let body = match &input.data {
+            Data::Struct(data) => {
+                if attrs.tag.is_some() || attrs.content.is_some() || attrs.untagged {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "tag, content and untagged only apply to enums",
+                    ));
+                }
+                Body::Struct(Shape::from_fields(&data.fields, attrs.rename_all)?)
+            }
+            Data::Enum(data) => {
+                let tagging = match (attrs.tag, attrs.content, attrs.untagged) {
+                    (None, None, false) => Tagging::External,
+                    (Some(tag), None, false) => Tagging::Internal { tag },
+                    (Some(tag), Some(content), false) => Tagging::Adjacent { tag, content },
+                    (None, None, true) => Tagging::Untagged,
+                    (None, Some(_), false) => {
+                        return Err(syn::Error::new_spanned(&input.ident, "content requires tag"));
+                    }
+                    (_, _, true) => {
+                        return Err(syn::Error::new_spanned(&input.ident, "untagged excludes tag and content"));
+                    }
+                };
+                let mut variants = Vec::new();
+                for variant in &data.variants {
+                    let variant_attrs = VariantAttrs::parse(&variant.attrs)?;
+                    let name = variant_attrs.rename.unwrap_or_else(|| {
+                        let name = ident_name(&variant.ident);
+                        attrs.rename_all.map_or(name.clone(), |rule| rule.apply(&name))
+                    });
+                    let shape = Shape::from_fields(&variant.fields, None)?;
+                    if matches!(tagging, Tagging::Internal { .. }) && matches!(shape, Shape::Tuple(_)) {
+                        return Err(syn::Error::new_spanned(
+                            &variant.ident,
+                            "an internally tagged enum cannot have tuple variants",
+                        ));
+                    }
+                    variants.push(Variant { ident: &variant.ident, name, shape });
+                }
+                Body::Enum(tagging, variants)
+            }
+            Data::Union(_) => return Err(syn::Error::new_spanned(&input.ident, "unions are not supported")),
+        };
+        Ok(Self { ident: &input.ident, generics: &input.generics, body })
+    }
+
+    /// The type's generics with `bound` added to every type parameter.
+    fn bounded_generics(&self, bound: TokenStream2) -> Generics {
+        let mut generics = self.generics.clone();
+        for param in generics.type_params_mut() {
+            param.bounds.push(parse_quote!(#bound));
+        }
+        generics
+    }
+}
+
+impl<'a> Shape<'a> {
+    fn from_fields(fields: &'a Fields, rename_all: Option<RenameRule>) -> syn::Result<Self> {
+        match fields {
+            Fields::Unit => Ok(Shape::Unit),
+            Fields::Unnamed(unnamed) => {
+                for field in &unnamed.unnamed {
+                    if !FieldAttrs::parse(&field.attrs)?.is_empty() {
+                        return Err(syn::Error::new_spanned(field, "field attributes only apply to named fields"));
+                    }
+                }
+                let types: Vec<&Type> = unnamed.unnamed.iter().map(|field| &field.ty).collect();
+                Ok(match types.as_slice() {
+                    [ty] => Shape::Newtype(ty),
+                    _ => Shape::Tuple(types),
+                })
+            }
+            Fields::Named(named) => {
+                let mut out = Vec::new();
+                for field in &named.named {
+                    let ident = field.ident.as_ref().expect("named fields have identifiers");
+                    let attrs = FieldAttrs::parse(&field.attrs)?;
+                    let name = attrs.rename.clone().unwrap_or_else(|| {
+                        let name = ident_name(ident);
+                        rename_all.map_or(name.clone(), |rule| rule.apply(&name))
+                    });
+                    out.push(Field { member: Member::Named(ident.clone()), name, ty: &field.ty, attrs });
+                }
+                Ok(Shape::Named(out))
+            }
+        }
+    }
+}
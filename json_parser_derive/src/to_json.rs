@@ -0,0 +1,169 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::{convert, index_map, json_value, Body, Container, Field, Shape, Tagging, Variant};
+
+pub fn expand(container: &Container) -> TokenStream {
+    let k = convert();
+    let ident = container.ident;
+    let generics = container.bounded_generics(quote!(#k::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &container.body {
+        Body::Struct(shape) => {
+            let accessors = match shape {
+                Shape::Unit => Vec::new(),
+                Shape::Newtype(_) => vec![quote!(&self.0)],
+                Shape::Tuple(types) => (0..types.len()).map(|index| {
+                    let index = syn::Index::from(index);
+                    quote!(&self.#index)
+                }).collect(),
+                Shape::Named(fields) => fields.iter().map(|field| {
+                    let member = &field.member;
+                    quote!(&self.#member)
+                }).collect(),
+            };
+            shape_to(shape, &accessors)
+        }
+        Body::Enum(tagging, variants) => {
+            let arms = variants.iter().map(|variant| variant_arm(variant, tagging));
+            quote!(match self { #(#arms)* })
+        }
+    };
+    let json_value = json_value();
+
+    quote! {
+        impl #impl_generics #k::ToJson for #ident #ty_generics #where_clause {
+            fn to_json(&self) -> #json_value {
+                #body
+            }
+        }
+    }
+}
+
+/// The JSON for a shape whose fields are reached through `accessors` (one
+/// `&T` expression per field, in order).
+fn shape_to(shape: &Shape, accessors: &[TokenStream]) -> TokenStream {
+    let k = convert();
+    let json_value = json_value();
+    match shape {
+        Shape::Unit => quote!(#json_value::Null),
+        Shape::Newtype(_) => {
+            let inner = &accessors[0];
+            quote!(#k::ToJson::to_json(#inner))
+        }
+        Shape::Tuple(_) => quote!(#json_value::Array(::std::vec![#(#k::ToJson::to_json(#accessors)),*])),
+        Shape::Named(fields) => {
+            let map = index_map();
+            let inserts = insert_fields(fields, accessors);
+            quote!({
+                let mut members = #map::new();
+                #inserts
+                #json_value::Object(members)
+            })
+        }
+    }
+}
+
+/// Inserts the fields into `members`. A flattened field contributes its
+/// members when it converts to an object, and nothing otherwise (None, say).
+fn insert_fields(fields: &[Field], accessors: &[TokenStream]) -> TokenStream {
+    let k = convert();
+    let inserts = fields.iter().zip(accessors).filter(|(field, _)| !field.attrs.skip).map(|(field, accessor)| {
+        let name = &field.name;
+        if field.attrs.flatten {
+            quote!(#k::flatten_into(&mut members, #k::ToJson::to_json(#accessor));)
+        } else {
+            quote!(members.insert(::std::string::String::from(#name), #k::ToJson::to_json(#accessor));)
+        }
+    });
+    quote!(#(#inserts)*)
+}
+
+fn string(name: &str) -> TokenStream {
+    let json_value = json_value();
+    quote!(#json_value::JsonString(::std::string::String::from(#name)))
+}
+
+fn variant_arm(variant: &Variant, tagging: &Tagging) -> TokenStream {
+    let k = convert();
+    let json_value = json_value();
+    let map = index_map();
+    let ident = variant.ident;
+    let name = &variant.name;
+
+    // bind every field to __field<i>, so field names cannot shadow anything
+    let bindings: Vec<Ident> = match &variant.shape {
+        Shape::Unit => Vec::new(),
+        Shape::Newtype(_) => vec![binding(0)],
+        Shape::Tuple(types) => (0..types.len()).map(binding).collect(),
+        Shape::Named(fields) => (0..fields.len()).map(binding).collect(),
+    };
+    let pattern = match &variant.shape {
+        Shape::Unit => quote!(Self::#ident),
+        Shape::Newtype(_) | Shape::Tuple(_) => quote!(Self::#ident(#(#bindings),*)),
+        Shape::Named(fields) => {
+            let members = fields.iter().zip(&bindings).map(|(field, binding)| {
+                let member = &field.member;
+                if field.attrs.skip { quote!(#member: _) } else { quote!(#member: #binding) }
+            });
+            quote!(Self::#ident { #(#members),* })
+        }
+    };
+    let accessors: Vec<TokenStream> = bindings.iter().map(|binding| quote!(#binding)).collect();
+
+    let body = match tagging {
+        Tagging::External => match variant.shape {
+            Shape::Unit => string(name),
+            _ => {
+                let content = shape_to(&variant.shape, &accessors);
+                quote!({
+                    let mut members = #map::new();
+                    members.insert(::std::string::String::from(#name), #content);
+                    #json_value::Object(members)
+                })
+            }
+        },
+        Tagging::Internal { tag } => {
+            let tag_value = string(name);
+            // the tag comes first, then the variant's own members
+            let rest = match &variant.shape {
+                Shape::Unit | Shape::Tuple(_) => quote!(),
+                Shape::Newtype(_) => {
+                    let inner = &accessors[0];
+                    quote!(#k::flatten_into(&mut members, #k::ToJson::to_json(#inner));)
+                }
+                Shape::Named(fields) => insert_fields(fields, &accessors),
+            };
+            quote!({
+                let mut members = #map::new();
+                members.insert(::std::string::String::from(#tag), #tag_value);
+                #rest
+                #json_value::Object(members)
+            })
+        }
+        Tagging::Adjacent { tag, content } => {
+            let tag_value = string(name);
+            let content = match variant.shape {
+                Shape::Unit => quote!(),
+                _ => {
+                    let value = shape_to(&variant.shape, &accessors);
+                    quote!(members.insert(::std::string::String::from(#content), #value);)
+                }
+            };
+            quote!({
+                let mut members = #map::new();
+                members.insert(::std::string::String::from(#tag), #tag_value);
+                #content
+                #json_value::Object(members)
+            })
+        }
+        Tagging::Untagged => shape_to(&variant.shape, &accessors),
+    };
+    quote!(#pattern => #body,)
+}
+
+fn binding(index: usize) -> Ident {
+    Ident::new(&format!("__field{}", index), Span::call_site())
+}
@@ -0,0 +1,552 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use indexmap::IndexMap;
+
+use crate::json_definitions::JsonValue;
+use crate::json_number::JsonNumber;
+use crate::json_pointer::JsonPointer;
+
+pub use json_parser_derive::{FromJson, ToJson};
+
+// =============================================================================
+// FromJson / ToJson — typed values to and from JsonValue
+// =============================================================================
+//
+//   #[derive(FromJson, ToJson)]
+//   struct Server { name: String, port: u16, tags: Vec<String>, tls: Option<Tls> }
+//
+//   let server = Server::from_json(&process_json_string_v3(text)?)?;
+//   let value = server.to_json();
+//
+// The traits are implemented here for the std types (bool, integers, floats,
+// String, Option, Vec, maps, tuples, Box, JsonValue itself); the derive
+// macros in the json_parser_derive crate, re-exported from this module,
+// build struct and enum impls out of those.
+//
+// ERRORS:
+//   A failed conversion says where it failed: FromJsonError carries the JSON
+//   Pointer of the offending value. Each container adds its own token while
+//   the error travels up (`within`), so the path is built only on failure:
+//
+//     at /servers/2/port: 70000 is out of range for u16
+//
+// MISSING FIELDS:
+//   `field` asks the field type what a missing member means
+//   (FromJson::from_missing): an error for most types, None for Option<T>.
+//
+// DERIVE ATTRIBUTES (#[json(...)]):
+//   container  rename_all = "camelCase" | "snake_case" | "kebab-case" |
+//              "PascalCase" | "lowercase" | "UPPERCASE" | "SCREAMING_SNAKE_CASE"
+//              tag = "type"                  internally tagged enum
+//              tag = "t", content = "c"      adjacently tagged enum
+//              untagged                      first variant that converts wins
+//   field      rename = "name", default, default = "path::to_fn", skip, flatten
+//   variant    rename = "name"
+//
+//   Enums are externally tagged unless told otherwise: "Unit", {"Newtype": v},
+//   {"Tuple": [..]}, {"Struct": {..}}. An internally tagged newtype variant
+//   should hold a struct; its members share the object with the tag.
+//
+// =============================================================================
+
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError>;
+
+    /// The value of an object member that is not there at all, if the type
+    /// has one. Option<T> is None; everything else is a MissingField error.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    kind: FromJsonErrorKind,
+    // innermost token first: containers push theirs as the error travels up
+    reversed_path: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromJsonErrorKind {
+    TypeMismatch { expected: &'static str, found: &'static str },
+    MissingField,                                                   // the path names the missing member
+    OutOfRange { value: String, ty: &'static str },                 // number that does not fit the target type
+    InvalidLength { expected: usize, found: usize },                // tuples
+    UnknownVariant { found: String, expected: &'static [&'static str] },
+    NoMatchingVariant { ty: &'static str },                         // untagged enums
+    Custom(String),
+}
+
+impl FromJsonError {
+    pub fn new(kind: FromJsonErrorKind) -> Self {
+        Self { kind, reversed_path: Vec::new() }
+    }
+
+    pub fn custom(message: impl Into<String>) -> Self {
+        Self::new(FromJsonErrorKind::Custom(message.into()))
+    }
+
+    pub fn type_mismatch(expected: &'static str, found: &JsonValue) -> Self {
+        Self::new(FromJsonErrorKind::TypeMismatch { expected, found: type_name(found) })
+    }
+
+    pub fn unknown_variant(found: &str, expected: &'static [&'static str]) -> Self {
+        Self::new(FromJsonErrorKind::UnknownVariant { found: found.to_string(), expected })
+    }
+
+    pub fn kind(&self) -> &FromJsonErrorKind {
+        &self.kind
+    }
+
+    /// Where the conversion failed, relative to the value passed to `from_json`.
+    pub fn path(&self) -> JsonPointer {
+        JsonPointer::from_tokens(self.reversed_path.iter().rev().cloned())
+    }
+
+    /// The same error, one level further down: `token` is the member name or
+    /// array index under which the failing value was found.
+    pub fn within(mut self, token: impl ToString) -> Self {
+        self.reversed_path.push(token.to_string());
+        self
+    }
+}
+
+// =============================================================================
+// Helpers for FromJson impls (and for the derive macros)
+// =============================================================================
+
+pub fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Object(_) => "object",
+        JsonValue::Array(_) => "array",
+        JsonValue::JsonString(_) => "string",
+        JsonValue::Number(_) => "number",
+        JsonValue::Boolean(_) => "boolean",
+        JsonValue::Null => "null",
+    }
+}
+
+pub fn expect_object(value: &JsonValue) -> Result<&IndexMap<String, JsonValue>, FromJsonError> {
+    match value {
+        JsonValue::Object(members) => Ok(members),
+        _ => Err(FromJsonError::type_mismatch("object", value)),
+    }
+}
+
+pub fn expect_array(value: &JsonValue) -> Result<&[JsonValue], FromJsonError> {
+    match value {
+        JsonValue::Array(items) => Ok(items),
+        _ => Err(FromJsonError::type_mismatch("array", value)),
+    }
+}
+
+/// An array of exactly `len` elements, as tuples are written.
+pub fn expect_tuple(value: &JsonValue, len: usize) -> Result<&[JsonValue], FromJsonError> {
+    let items = expect_array(value)?;
+    if items.len() != len {
+        return Err(FromJsonError::new(FromJsonErrorKind::InvalidLength { expected: len, found: items.len() }));
+    }
+    Ok(items)
+}
+
+/// An externally tagged enum value, split into the variant name and its
+/// content: "Name" has none, {"Name": content} has one.
+pub fn expect_variant(value: &JsonValue) -> Result<(&str, Option<&JsonValue>), FromJsonError> {
+    match value {
+        JsonValue::JsonString(name) => Ok((name, None)),
+        JsonValue::Object(members) if members.len() == 1 => {
+            let (name, content) = members.first().expect("object has one member");
+            Ok((name, Some(content)))
+        }
+        _ => Err(FromJsonError::type_mismatch("string or single-member object", value)),
+    }
+}
+
+/// Member `key` converted to T; a missing member is T::from_missing().
+pub fn field<T: FromJson>(members: &IndexMap<String, JsonValue>, key: &str) -> Result<T, FromJsonError> {
+    match members.get(key) {
+        Some(value) => T::from_json(value).map_err(|e| e.within(key)),
+        None => T::from_missing().ok_or_else(|| FromJsonError::new(FromJsonErrorKind::MissingField).within(key)),
+    }
+}
+
+/// Member `key` converted to T; a missing member is `default()`.
+pub fn field_or_else<T: FromJson>(
+    members: &IndexMap<String, JsonValue>,
+    key: &str,
+    default: impl FnOnce() -> T,
+) -> Result<T, FromJsonError> {
+    match members.get(key) {
+        Some(value) => T::from_json(value).map_err(|e| e.within(key)),
+        None => Ok(default()),
+    }
+}
+
+/// Moves the members of `value` into `members` if it is an object, and drops
+/// it otherwise: what ToJson does with a flattened field.
+pub fn flatten_into(members: &mut IndexMap<String, JsonValue>, mut value: JsonValue) {
+    if let JsonValue::Object(inner) = &mut value {
+        members.extend(std::mem::take(inner));
+    }
+}
+
+pub fn element<T: FromJson>(items: &[JsonValue], index: usize) -> Result<T, FromJsonError> {
+    T::from_json(&items[index]).map_err(|e| e.within(index))
+}
+
+// =============================================================================
+// std impls
+// =============================================================================
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Boolean(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Boolean(b) => Ok(*b),
+            _ => Err(FromJsonError::type_mismatch("boolean", value)),
+        }
+    }
+}
+
+// Integers go through i128, which every JSON integer lexeme of a Rust-sized
+// integer fits in; 1.0 or 1e2 are not integers and are rejected.
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::Number(JsonNumber::from(*self))
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                    let JsonValue::Number(n) = value else {
+                        return Err(FromJsonError::type_mismatch("integer", value));
+                    };
+                    if !n.is_integer() {
+                        return Err(FromJsonError::type_mismatch("integer", value));
+                    }
+                    n.as_i128().and_then(|i| <$t>::try_from(i).ok()).ok_or_else(|| {
+                        FromJsonError::new(FromJsonErrorKind::OutOfRange { value: n.to_string(), ty: stringify!($t) })
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+// u128 is the one integer type beyond i128.
+impl ToJson for u128 {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(JsonNumber::from(*self))
+    }
+}
+
+impl FromJson for u128 {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Number(n) if n.is_integer() => n.as_str().and_then(|s| s.parse().ok()).ok_or_else(|| {
+                FromJsonError::new(FromJsonErrorKind::OutOfRange { value: n.to_string(), ty: "u128" })
+            }),
+            _ => Err(FromJsonError::type_mismatch("integer", value)),
+        }
+    }
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::Number(JsonNumber::from(*self))
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                    match value {
+                        JsonValue::Number(n) => Ok(n.as_f64() as $t),
+                        _ => Err(FromJsonError::type_mismatch("number", value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+impl ToJson for JsonNumber {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(self.clone())
+    }
+}
+
+impl FromJson for JsonNumber {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Number(n) => Ok(n.clone()),
+            _ => Err(FromJsonError::type_mismatch("number", value)),
+        }
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::JsonString(self.to_string())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::JsonString(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::JsonString(s) => Ok(s.clone()),
+            _ => Err(FromJsonError::type_mismatch("string", value)),
+        }
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::JsonString(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let JsonValue::JsonString(s) = value else {
+            return Err(FromJsonError::type_mismatch("single-character string", value));
+        };
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FromJsonError::type_mismatch("single-character string", value)),
+        }
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Null
+    }
+}
+
+impl FromJson for () {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(()),
+            _ => Err(FromJsonError::type_mismatch("null", value)),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, ToJson::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let items = expect_array(value)?;
+        (0..items.len()).map(|index| element(items, index)).collect()
+    }
+}
+
+impl<T: ToJson> ToJson for VecDeque<T> {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for VecDeque<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        Vec::from_json(value).map(VecDeque::from)
+    }
+}
+
+// Map keys are the member names as they are; any key type that converts
+// from and to a string will do.
+fn map_entries<K: From<String>, V: FromJson>(
+    value: &JsonValue,
+) -> Result<impl Iterator<Item = Result<(K, V), FromJsonError>>, FromJsonError> {
+    let members = expect_object(value)?;
+    Ok(members.iter().map(|(k, v)| Ok((K::from(k.clone()), V::from_json(v).map_err(|e| e.within(k))?))))
+}
+
+fn map_to_json<'a, K: AsRef<str> + 'a, V: ToJson + 'a>(entries: impl Iterator<Item = (&'a K, &'a V)>) -> JsonValue {
+    JsonValue::Object(entries.map(|(k, v)| (k.as_ref().to_string(), v.to_json())).collect())
+}
+
+impl<K: AsRef<str>, V: ToJson, S> ToJson for IndexMap<K, V, S> {
+    fn to_json(&self) -> JsonValue {
+        map_to_json(self.iter())
+    }
+}
+
+impl<K: From<String> + Hash + Eq, V: FromJson, S: BuildHasher + Default> FromJson for IndexMap<K, V, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        map_entries(value)?.collect()
+    }
+}
+
+// HashMap has no order of its own; members come in its iteration order.
+impl<K: AsRef<str>, V: ToJson, S> ToJson for HashMap<K, V, S> {
+    fn to_json(&self) -> JsonValue {
+        map_to_json(self.iter())
+    }
+}
+
+impl<K: From<String> + Hash + Eq, V: FromJson, S: BuildHasher + Default> FromJson for HashMap<K, V, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        map_entries(value)?.collect()
+    }
+}
+
+impl<K: AsRef<str>, V: ToJson> ToJson for BTreeMap<K, V> {
+    fn to_json(&self) -> JsonValue {
+        map_to_json(self.iter())
+    }
+}
+
+impl<K: From<String> + Ord, V: FromJson> FromJson for BTreeMap<K, V> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        map_entries(value)?.collect()
+    }
+}
+
+macro_rules! impl_tuple {
+    ($len:expr => $($name:ident $index:tt),+) => {
+        impl<$($name: ToJson),+> ToJson for ($($name,)+) {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Array(vec![$(self.$index.to_json()),+])
+            }
+        }
+
+        impl<$($name: FromJson),+> FromJson for ($($name,)+) {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                let items = expect_tuple(value, $len)?;
+                Ok(($(element::<$name>(items, $index)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => A 0);
+impl_tuple!(2 => A 0, B 1);
+impl_tuple!(3 => A 0, B 1, C 2);
+impl_tuple!(4 => A 0, B 1, C 2, D 3);
+impl_tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+
+impl fmt::Display for FromJsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromJsonErrorKind::TypeMismatch { expected, found } => write!(f, "expected {}, found {}", expected, found),
+            FromJsonErrorKind::MissingField => write!(f, "missing field"),
+            FromJsonErrorKind::OutOfRange { value, ty } => write!(f, "{} is out of range for {}", value, ty),
+            FromJsonErrorKind::InvalidLength { expected, found } => {
+                write!(f, "expected an array of {} elements, found {}", expected, found)
+            }
+            FromJsonErrorKind::UnknownVariant { found, expected } => {
+                write!(f, "unknown variant {:?}, expected one of {}", found, expected.join(", "))
+            }
+            FromJsonErrorKind::NoMatchingVariant { ty } => write!(f, "value matches no variant of {}", ty),
+            FromJsonErrorKind::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.reversed_path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "at {}: {}", self.path(), self.kind)
+        }
+    }
+}
+
+impl Error for FromJsonError {}
@@ -1,5 +1,10 @@
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::hash::Hash;
+
 use indexmap::IndexMap;
-use crate::json_lexer::{NumberError, StringError, TokenKind};
+use crate::json_lexer::{NumberError, SourceLocation, Span, StringError, TokenKind};
+use crate::json_number::JsonNumber;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonParsingError {
@@ -15,11 +20,11 @@ pub enum JsonParsingError {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenTag {
     LBrace, RBrace, LBracket, RBracket, Colon, Comma,
-    Bool, Null, String, Number, Eof,
+    Bool, Null, String, Number, Comment, Eof,
 }
 
 pub fn token_tag_of(k: &TokenKind) -> TokenTag {
-    return match k {
+    match k {
         TokenKind::LBrace => TokenTag::LBrace,
         TokenKind::RBrace => TokenTag::RBrace,
         TokenKind::LBracket => TokenTag::LBracket,
@@ -30,6 +35,7 @@ pub fn token_tag_of(k: &TokenKind) -> TokenTag {
         TokenKind::Null => TokenTag::Null,
         TokenKind::String(_) => TokenTag::String,
         TokenKind::Number(_) => TokenTag::Number,
+        TokenKind::Comment(_) => TokenTag::Comment,
         TokenKind::Eof => TokenTag::Eof,
     }
 }
@@ -38,28 +44,280 @@ pub fn token_tag_of(k: &TokenKind) -> TokenTag {
 pub enum JsonParsingErrorV2 {
     EmptyJsonFile,
 
-    UnexpectedEOF { at: usize },
+    UnexpectedEOF { at: SourceLocation },
+
+    ExpectedEOF { found: TokenTag, at: Option<SourceLocation> },
 
-    ExpectedEOF { found: TokenTag, at: Option<usize> },
+    UnexpectedToken { found: TokenTag, at: Option<SourceLocation> },
 
-    UnexpectedToken { found: TokenTag, at: Option<usize> },
+    InvalidArray { found: TokenTag, at: Option<SourceLocation> },
+    InvalidJsonObject { found: TokenTag, at: Option<SourceLocation> },
 
-    InvalidArray { found: TokenTag, at: Option<usize> },
-    InvalidJsonObject { found: TokenTag, at: Option<usize> },
+    DuplicateKey { key: String, first: Span, second: Span },
 
     LexError(LexerError),
 }
 
 
+// =============================================================================
+// RESOURCE LIMITS
+// =============================================================================
+// Guards against hostile input. The lexer enforces the byte-based limits
+// (document, string, number), the v3 parser the structural ones (depth,
+// array length, object members). Everything defaults to unlimited, so the
+// plain entry points behave exactly as before.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Depth,         // nested arrays/objects open at the same time
+    DocumentBytes, // size of the whole input
+    StringBytes,   // source bytes between the quotes, escapes counted as written
+    ArrayLength,   // elements in one array
+    ObjectMembers, // distinct keys in one object
+    NumberLength,  // bytes in one number lexeme, sign included
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_depth: usize,
+    pub max_document_bytes: usize,
+    pub max_string_bytes: usize,
+    pub max_array_len: usize,
+    pub max_object_members: usize,
+    pub max_number_len: usize,
+}
+
+impl ParseLimits {
+    pub const UNLIMITED: ParseLimits = ParseLimits {
+        max_depth: usize::MAX,
+        max_document_bytes: usize::MAX,
+        max_string_bytes: usize::MAX,
+        max_array_len: usize::MAX,
+        max_object_members: usize::MAX,
+        max_number_len: usize::MAX,
+    };
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
+// =============================================================================
+// DUPLICATE KEYS
+// =============================================================================
+// RFC 8259 leaves repeated object keys undefined, so the caller picks:
+//   LastWins  — {"a": 1, "a": 2} → {"a": 2}       (default, previous behaviour)
+//   FirstWins — {"a": 1, "a": 2} → {"a": 1}
+//   Error     — DuplicateKey error with the spans of both keys
+//   Collect   — {"a": 1, "a": 2} → {"a": [1, 2]}  (single occurrences stay as-is)
+// A repeated key keeps the position of its first occurrence in every policy.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    #[default]
+    LastWins,
+    FirstWins,
+    Error,
+    Collect,
+}
+
+// =============================================================================
+// LONE SURROGATES
+// =============================================================================
+// Characters above U+FFFF are escaped as a UTF-16 surrogate pair, high then
+// low: "\uD83D\uDE00" is one emoji. A high surrogate without a low one after
+// it (or a low one on its own) encodes nothing, so the caller picks:
+//   Error   — LoneHighSurrogate / LoneLowSurrogate at the offending escape (default)
+//   Replace — decode it as U+FFFD, like JavaScript's TextDecoder and most browsers
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoneSurrogatePolicy {
+    #[default]
+    Error,
+    Replace,
+}
+
+// =============================================================================
+// DIALECT
+// =============================================================================
+// Which grammar the lexer and the frame validation accept:
+//   Json  — strict RFC 8259 (default)
+//   Json5 — JSON5 (https://spec.json5.org), for hand-written files:
+//             // line and /* block */ comments, one trailing comma in arrays
+//             and objects, unquoted identifier keys, 'single-quoted' strings
+//             with the extra escapes (\' \v \0 \xHH, line continuations),
+//             hex numbers, leading/trailing decimal points, '+' signs,
+//             Infinity and NaN
+// Numbers are normalised to JSON text (0x1F → 31, .5 → 0.5, +1 → 1), so
+// nothing downstream ever sees JSON5 syntax. Infinity and NaN become
+// non-finite JsonNumbers, which the writer prints as null.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Json,
+    Json5,
+}
+
+// =============================================================================
+// COMMENTS
+// =============================================================================
+// "JSON with comments" (VS Code settings, tsconfig) without the rest of JSON5:
+//   Error  — a '/' is an unexpected byte, as RFC 8259 says (default)
+//   Skip   — // line and /* block */ comments are skipped like whitespace
+//   Tokens — the lexer returns each comment as a Comment token, for tools that
+//            keep them (formatters, config editors); parsers skip them
+// An unclosed /* is UnterminatedBlockComment. Dialect::Json5 always allows
+// comments, so there Error means Skip.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommentPolicy {
+    #[default]
+    Error,
+    Skip,
+    Tokens,
+}
+
+// v1 honours `lone_surrogates` only, v2 everything but the structural limits
+// (depth, array length, object members), v3 everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    pub limits: ParseLimits,
+    pub duplicate_keys: DuplicateKeyPolicy,
+    pub lone_surrogates: LoneSurrogatePolicy,
+    pub dialect: Dialect,
+    pub comments: CommentPolicy,
+}
+
+impl ParseOptions {
+    /// The same options with comments skipped instead of returned as tokens:
+    /// what the parsers lex with, since a comment has no place in a value.
+    pub(crate) fn skipping_comments(&self) -> ParseOptions {
+        let comments = match self.comments {
+            CommentPolicy::Tokens => CommentPolicy::Skip,
+            other => other,
+        };
+        ParseOptions { comments, ..*self }
+    }
+}
+
+// Object members under construction, shared by v2 and v3 so both apply the
+// policy the same way. The side tables are only filled for the policy that
+// needs them: key spans for Error, "already an array of collected values"
+// flags for Collect. Both are indexed like `items`.
+pub(crate) struct ObjectBuilder<V: TreeValue = JsonValue> {
+    policy: DuplicateKeyPolicy,
+    items: IndexMap<V::Key, V>,
+    key_spans: Vec<Span>,
+    collected: Vec<bool>,
+}
+
+impl<V: TreeValue> ObjectBuilder<V> {
+    pub(crate) fn new(policy: DuplicateKeyPolicy) -> Self {
+        Self { policy, items: IndexMap::new(), key_spans: Vec::new(), collected: Vec::new() }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.items.contains_key(key)
+    }
+
+    /// On a rejected duplicate (Error policy) returns the key and the span of its first occurrence.
+    pub(crate) fn insert(&mut self, key: V::Key, key_span: Span, value: V) -> Result<(), (String, Span)> {
+        let Some(index) = self.items.get_index_of(key.borrow()) else {
+            match self.policy {
+                DuplicateKeyPolicy::Error => self.key_spans.push(key_span),
+                DuplicateKeyPolicy::Collect => self.collected.push(false),
+                DuplicateKeyPolicy::LastWins | DuplicateKeyPolicy::FirstWins => {}
+            }
+            self.items.insert(key, value);
+            return Ok(());
+        };
+
+        match self.policy {
+            DuplicateKeyPolicy::LastWins => self.items[index] = value,
+            DuplicateKeyPolicy::FirstWins => {}
+            DuplicateKeyPolicy::Error => return Err((key.into(), self.key_spans[index])),
+            DuplicateKeyPolicy::Collect => {
+                let existing = &mut self.items[index];
+                if !self.collected[index] {
+                    let first = std::mem::take(existing);
+                    *existing = V::array(vec![first]);
+                    self.collected[index] = true;
+                }
+                if let Some(values) = existing.as_array_mut() {
+                    values.push(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> V {
+        V::object(self.items)
+    }
+}
+
+// What the object and tree builders need from the value they build, so the
+// owned (JsonValue) and borrowed (JsonValueRef) trees share one implementation.
+pub(crate) trait TreeValue: Default {
+    type Key: Hash + Eq + Borrow<str> + Into<String>;
+
+    fn array(items: Vec<Self>) -> Self;
+    fn object(members: IndexMap<Self::Key, Self>) -> Self;
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>>;
+}
+
+impl TreeValue for JsonValue {
+    type Key = String;
+
+    fn array(items: Vec<Self>) -> Self {
+        JsonValue::Array(items)
+    }
+
+    fn object(members: IndexMap<String, Self>) -> Self {
+        JsonValue::Object(members)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> TreeValue for JsonValueRef<'a> {
+    type Key = Cow<'a, str>;
+
+    fn array(items: Vec<Self>) -> Self {
+        JsonValueRef::Array(items)
+    }
+
+    fn object(members: IndexMap<Cow<'a, str>, Self>) -> Self {
+        JsonValueRef::Object(members)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            JsonValueRef::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
 // =============================================================================
 // V3 PARSER ERROR
 // =============================================================================
 // Design principles vs v2:
-//   - Every error carries `at: usize` (byte offset) for source location
+//   - Every error carries `at: SourceLocation` (byte offset + line + column)
 //   - Errors that involve a token carry `found: TokenTag`
 //   - Errors that involve frame state carry `frame: FrameTypeTag`
-//   - TODO next session: upgrade `at: usize` to `at: SourceLocation`
-//     once lexer Span is extended to track line + column (easy upgrade)
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonParsingErrorV3 {
@@ -67,79 +325,627 @@ pub enum JsonParsingErrorV3 {
     // Same as v2 — no extra context needed.
     EmptyJsonFile,
 
-    // Ran out of tokens while still inside a structure.
-    // TODO: add `at: usize` — byte offset where EOF was hit
-    // TODO: add `frame: FrameTypeTag` — which frame was open when EOF hit
+    // Ran out of tokens while still inside a structure (or before any value).
+    // `frame` is the frame that was open when EOF hit, None at root level.
     // Example: parsing {"a": [1,2 and then nothing
-    UnexpectedEOF,
+    UnexpectedEOF { at: SourceLocation, frame: Option<FrameTypeTag> },
 
     // Parsing completed successfully but there are leftover tokens after the root value.
-    // TODO: add `at: usize`
-    // TODO: add `found: TokenTag` — the unexpected token that followed the root value
-    ExpectedEOF,
+    // `found` is the unexpected token that followed the root value.
+    ExpectedEOF { at: SourceLocation, found: TokenTag },
 
     // A closing token didn't match the open frame.
     // Example: opened with '[' but saw '}' — mismatch
-    // TODO: add `at: usize`
-    // TODO: add `found: TokenTag` — the closing token we got (e.g. RBrace)
-    // TODO: add `frame: FrameTypeTag` — the frame that was open (e.g. ArrayFrame)
-    MismatchedClosing,
+    // `found` is the closing token we got, `frame` the frame that was open.
+    MismatchedClosing { at: SourceLocation, found: TokenTag, frame: FrameTypeTag },
 
     // A closing token arrived but the stack was empty — nothing to close.
     // Example: "1, 2]" — the ] has no matching [
-    // TODO: add `at: usize`
-    // TODO: add `found: TokenTag` — the unexpected closing token
-    UnexpectedClosing,
+    UnexpectedClosing { at: SourceLocation, found: TokenTag },
 
-    // Inside an object, got a non-string value where a key was expected.
-    // pending_key was None but the incoming value wasn't a JsonString.
+    // Inside an object, got a non-string token where a key was expected.
     // Example: { 1: "value" } — 1 is not a valid key
-    // TODO: add `at: usize`
-    // TODO: add `found: TokenTag` — the token that was not a string
-    ObjectKeyNotString,
+    ObjectKeyNotString { at: SourceLocation, found: TokenTag },
 
-    // Got '}' while pending_key is Some — key was parsed but value never came.
+    // Object closed while a key is still waiting for its value.
     // Example: { "a": } — key "a" consumed, colon consumed, but } arrived
-    // TODO: add `at: usize`
-    // TODO: add `found: String` — the key that was left hanging (useful for debugging)
-    ObjectKeyWithoutValue,
+    // `key` is the key that was left hanging (useful for debugging).
+    ObjectKeyWithoutValue { at: SourceLocation, found: TokenTag, key: String },
+
+    // Key was parsed but the next token is not ':'.
+    // Example: { "a" 1 }
+    MissingColon { at: SourceLocation, found: TokenTag },
+
+    // Two values in a row without ',' between them.
+    // Example: [1 2] or {"a": 1 "b": 2}
+    MissingComma { at: SourceLocation, found: TokenTag, frame: FrameTypeTag },
+
+    // ',' with no structure open at all.
+    // Example: 1, 2
+    CommaOutsideStructure { at: SourceLocation },
+
+    // ':' outside of an object. `frame` is None at root level.
+    // Example: [1: 2] or "a": 1
+    ColonOutsideObject { at: SourceLocation, frame: Option<FrameTypeTag> },
+
+    // ':' inside an object where no colon is allowed.
+    // Example: {"a":: 1}
+    UnexpectedColon { at: SourceLocation },
+
+    // ',' directly after the opening token.
+    // Example: [, 1] or {, "a": 1}
+    LeadingComma { at: SourceLocation, frame: FrameTypeTag },
+
+    // ',' where a value (or a second ',') is not allowed.
+    // Example: [1,, 2] or {"a":, "b": 1}
+    UnexpectedComma { at: SourceLocation, frame: FrameTypeTag },
+
+    // Closing token directly after ','.
+    // Example: [1, 2,] or {"a": 1,}
+    TrailingComma { at: SourceLocation, found: TokenTag, frame: FrameTypeTag },
+
+    // One of the ParseLimits was exceeded. Limits checked by the lexer are
+    // reported here too (not as LexError), so callers match a single variant.
+    // Example: [[[1]]] with max_depth = 2
+    LimitExceeded { limit: Limit, at: SourceLocation },
+
+    // Same key twice in one object under DuplicateKeyPolicy::Error.
+    // `first` and `second` are the spans of the two key strings.
+    // Example: {"a": 1, "a": 2}
+    DuplicateKey { key: String, first: Span, second: Span },
 
     // Lexer failed — bubble it up unchanged, same as v2.
     LexError(LexerError),
-    
-    ///Duno if i want to surface that to user ... 
-    UneExpectedFrameType
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// Drop, Clone, PartialEq and Debug are implemented by hand below (not derived):
+// the derived versions recurse once per nesting level and overflow the stack
+// on the deep trees v3 happily produces. Each one keeps its own explicit stack.
+//
+// Having a Drop impl means a JsonValue cannot be destructured by value:
+// `let JsonValue::Array(v) = value` is error E0509. Match on `&value`, or match
+// on `&mut value` and move the contents out with `std::mem::take`.
+#[derive(Default)]
 pub enum JsonValue {
     Object(IndexMap<String, JsonValue>),
     Array(Vec<JsonValue>),
     JsonString(String),
-    Number(f64),
+    Number(JsonNumber),
+    Boolean(bool),
+    #[default]
+    Null,
+}
+
+impl JsonValue {
+    // Copies a leaf, or an empty shell of a container (children are filled in by the caller).
+    fn shallow_clone(&self) -> JsonValue {
+        match self {
+            JsonValue::Object(_) => JsonValue::Object(IndexMap::new()),
+            JsonValue::Array(_) => JsonValue::Array(Vec::new()),
+            JsonValue::JsonString(s) => JsonValue::JsonString(s.clone()),
+            JsonValue::Number(n) => JsonValue::Number(n.clone()),
+            JsonValue::Boolean(b) => JsonValue::Boolean(*b),
+            JsonValue::Null => JsonValue::Null,
+        }
+    }
+}
+
+impl Drop for JsonValue {
+    fn drop(&mut self) {
+        // Move all children into one flat Vec and drop them from there.
+        // Every child is emptied before it is dropped, so its own drop()
+        // returns immediately — recursion depth never exceeds one.
+        let mut pending: Vec<JsonValue> = match self {
+            JsonValue::Array(items) if !items.is_empty() => std::mem::take(items),
+            JsonValue::Object(members) if !members.is_empty() => members.drain(..).map(|(_, v)| v).collect(),
+            _ => return,
+        };
+
+        while let Some(mut value) = pending.pop() {
+            match &mut value {
+                JsonValue::Array(items) => pending.append(items),
+                JsonValue::Object(members) => pending.extend(members.drain(..).map(|(_, v)| v)),
+                _ => {}
+            }
+        }
+    }
+}
+
+// One container being cloned: the source children still to visit and the copy built so far.
+// `key` is where the finished copy goes in the parent (None for array parents and the root).
+struct CloneFrame<'a> {
+    key: Option<&'a String>,
+    kind: CloneFrameKind<'a>,
+}
+
+enum CloneFrameKind<'a> {
+    Array(std::slice::Iter<'a, JsonValue>, Vec<JsonValue>),
+    Object(indexmap::map::Iter<'a, String, JsonValue>, IndexMap<String, JsonValue>),
+}
+
+impl<'a> CloneFrame<'a> {
+    fn open(key: Option<&'a String>, value: &'a JsonValue) -> Option<Self> {
+        let kind = match value {
+            JsonValue::Array(items) if !items.is_empty() => {
+                CloneFrameKind::Array(items.iter(), Vec::with_capacity(items.len()))
+            }
+            JsonValue::Object(members) if !members.is_empty() => {
+                CloneFrameKind::Object(members.iter(), IndexMap::with_capacity(members.len()))
+            }
+            _ => return None,
+        };
+        Some(Self { key, kind })
+    }
+
+    fn attach(&mut self, key: Option<&'a String>, value: JsonValue) {
+        match &mut self.kind {
+            CloneFrameKind::Array(_, out) => out.push(value),
+            CloneFrameKind::Object(_, out) => {
+                if let Some(k) = key {
+                    out.insert(k.clone(), value);
+                }
+            }
+        }
+    }
+}
+
+impl Clone for JsonValue {
+    fn clone(&self) -> Self {
+        let Some(root) = CloneFrame::open(None, self) else {
+            return self.shallow_clone();
+        };
+        let mut stack = vec![root];
+
+        loop {
+            let Some(frame) = stack.last_mut() else {
+                unreachable!("root frame returns before the stack empties");
+            };
+
+            let next = match &mut frame.kind {
+                CloneFrameKind::Array(src, _) => src.next().map(|v| (None, v)),
+                CloneFrameKind::Object(src, _) => src.next().map(|(k, v)| (Some(k), v)),
+            };
+
+            match next {
+                Some((key, child)) => match CloneFrame::open(key, child) {
+                    Some(child_frame) => stack.push(child_frame),
+                    None => frame.attach(key, child.shallow_clone()),
+                },
+                None => {
+                    let Some(done) = stack.pop() else { unreachable!() };
+                    let value = match done.kind {
+                        CloneFrameKind::Array(_, out) => JsonValue::Array(out),
+                        CloneFrameKind::Object(_, out) => JsonValue::Object(out),
+                    };
+                    match stack.last_mut() {
+                        Some(parent) => parent.attach(done.key, value),
+                        None => return value,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for JsonValue {
+    // Same semantics as the derived impl: objects compare as maps (order-insensitive),
+    // numbers with JsonNumber's numeric equality (1 == 1.0, exact beyond f64 precision,
+    // and NaN != NaN).
+    fn eq(&self, other: &Self) -> bool {
+        let mut pending: Vec<(&JsonValue, &JsonValue)> = vec![(self, other)];
+
+        while let Some((a, b)) = pending.pop() {
+            match (a, b) {
+                (JsonValue::Null, JsonValue::Null) => {}
+                (JsonValue::Boolean(x), JsonValue::Boolean(y)) if x == y => {}
+                (JsonValue::Number(x), JsonValue::Number(y)) if x == y => {}
+                (JsonValue::JsonString(x), JsonValue::JsonString(y)) if x == y => {}
+                (JsonValue::Array(x), JsonValue::Array(y)) if x.len() == y.len() => {
+                    pending.extend(x.iter().zip(y.iter()));
+                }
+                (JsonValue::Object(x), JsonValue::Object(y)) if x.len() == y.len() => {
+                    for (k, xv) in x {
+                        match y.get(k) {
+                            Some(yv) => pending.push((xv, yv)),
+                            None => return false,
+                        }
+                    }
+                }
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+// Debug prints JsonValue and JsonValueRef the same way, so the printer works
+// on this view of a node instead of either enum.
+enum DebugNode<'a, T: DebugTree> {
+    Null,
     Boolean(bool),
+    Number(&'a JsonNumber),
+    JsonString(&'a str),
+    Array(&'a [T]),
+    Object(&'a IndexMap<T::Key, T>),
+}
+
+trait DebugTree: Sized {
+    type Key: fmt::Debug;
+
+    fn debug_node(&self) -> DebugNode<'_, Self>;
+}
+
+impl DebugTree for JsonValue {
+    type Key = String;
+
+    fn debug_node(&self) -> DebugNode<'_, Self> {
+        match self {
+            JsonValue::Null => DebugNode::Null,
+            JsonValue::Boolean(b) => DebugNode::Boolean(*b),
+            JsonValue::Number(n) => DebugNode::Number(n),
+            JsonValue::JsonString(s) => DebugNode::JsonString(s),
+            JsonValue::Array(items) => DebugNode::Array(items),
+            JsonValue::Object(members) => DebugNode::Object(members),
+        }
+    }
+}
+
+// What is left to print, innermost first (the Vec is used as a stack).
+enum DebugStep<'a, T: DebugTree> {
+    Value(&'a T, usize),
+    Key(&'a T::Key),
+    Text(&'static str),
+    Newline(usize),
+}
+
+impl fmt::Debug for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_tree(self, f)
+    }
+}
+
+// Produces exactly what #[derive(Debug)] would, for both {:?} and {:#?},
+// without recursing. In {:#?} mode every nesting level indents by 4 spaces.
+fn debug_tree<T: DebugTree>(root: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let pretty = f.alternate();
+    let mut steps = vec![DebugStep::Value(root, 0)];
+
+    while let Some(step) = steps.pop() {
+        match step {
+            DebugStep::Text(t) => f.write_str(t)?,
+            DebugStep::Key(k) => write!(f, "{:?}", k)?,
+            DebugStep::Newline(depth) => write_newline(f, depth)?,
+            DebugStep::Value(value, depth) => {
+                let node = value.debug_node();
+                let name = match node {
+                    DebugNode::Null => {
+                        f.write_str("Null")?;
+                        continue;
+                    }
+                    DebugNode::Boolean(_) => "Boolean",
+                    DebugNode::Number(_) => "Number",
+                    DebugNode::JsonString(_) => "JsonString",
+                    DebugNode::Array(_) => "Array",
+                    DebugNode::Object(_) => "Object",
+                };
+
+                f.write_str(name)?;
+                f.write_str("(")?;
+                if pretty {
+                    write_newline(f, depth + 1)?;
+                }
+
+                // The tuple field itself
+                match node {
+                    DebugNode::Boolean(b) => write!(f, "{:?}", b)?,
+                    DebugNode::Number(n) => write!(f, "{:?}", n)?,
+                    DebugNode::JsonString(s) => write!(f, "{:?}", s)?,
+                    _ => {}
+                }
+
+                // Closing of the tuple, pushed first so it runs last
+                if pretty {
+                    steps.push(DebugStep::Text(")"));
+                    steps.push(DebugStep::Newline(depth));
+                    steps.push(DebugStep::Text(","));
+                } else {
+                    steps.push(DebugStep::Text(")"));
+                }
+
+                match node {
+                    DebugNode::Array(items) => push_array_steps(&mut steps, items, depth + 1, pretty),
+                    DebugNode::Object(members) => push_object_steps(&mut steps, members, depth + 1, pretty),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_newline(f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+    f.write_str("\n")?;
+    for _ in 0..depth {
+        f.write_str("    ")?;
+    }
+    Ok(())
+}
+
+// `depth` is the indentation level of the opening '['.
+fn push_array_steps<'a, T: DebugTree>(steps: &mut Vec<DebugStep<'a, T>>, items: &'a [T], depth: usize, pretty: bool) {
+    if items.is_empty() {
+        steps.push(DebugStep::Text("[]"));
+        return;
+    }
+
+    steps.push(DebugStep::Text("]"));
+    if pretty {
+        steps.push(DebugStep::Newline(depth));
+    }
+    for (i, item) in items.iter().enumerate().rev() {
+        if pretty {
+            steps.push(DebugStep::Text(","));
+            steps.push(DebugStep::Value(item, depth + 1));
+            steps.push(DebugStep::Newline(depth + 1));
+        } else {
+            steps.push(DebugStep::Value(item, depth + 1));
+            if i > 0 {
+                steps.push(DebugStep::Text(", "));
+            }
+        }
+    }
+    steps.push(DebugStep::Text("["));
+}
+
+// `depth` is the indentation level of the opening '{'.
+fn push_object_steps<'a, T: DebugTree>(
+    steps: &mut Vec<DebugStep<'a, T>>,
+    members: &'a IndexMap<T::Key, T>,
+    depth: usize,
+    pretty: bool,
+) {
+    if members.is_empty() {
+        steps.push(DebugStep::Text("{}"));
+        return;
+    }
+
+    steps.push(DebugStep::Text("}"));
+    if pretty {
+        steps.push(DebugStep::Newline(depth));
+    }
+    for (i, (key, value)) in members.iter().enumerate().rev() {
+        if pretty {
+            steps.push(DebugStep::Text(","));
+            steps.push(DebugStep::Value(value, depth + 1));
+            steps.push(DebugStep::Text(": "));
+            steps.push(DebugStep::Key(key));
+            steps.push(DebugStep::Newline(depth + 1));
+        } else {
+            steps.push(DebugStep::Value(value, depth + 1));
+            steps.push(DebugStep::Text(": "));
+            steps.push(DebugStep::Key(key));
+            if i > 0 {
+                steps.push(DebugStep::Text(", "));
+            }
+        }
+    }
+    steps.push(DebugStep::Text("{"));
+}
+
+// =============================================================================
+// BORROWED VALUES
+// =============================================================================
+// JsonValueRef mirrors JsonValue, but strings and object keys are Cow<'a, str>:
+// a string without escapes points straight into the input buffer, only
+// escaped ones (which have to be decoded) own their text. For input made of
+// plain strings, parsing allocates little more than the Vec/IndexMap
+// containers. `to_owned()` copies the whole tree into a standalone JsonValue.
+//
+// Like JsonValue, Drop, PartialEq and Debug are hand-written so deep trees
+// never recurse.
+#[derive(Default)]
+pub enum JsonValueRef<'a> {
+    Object(IndexMap<Cow<'a, str>, JsonValueRef<'a>>),
+    Array(Vec<JsonValueRef<'a>>),
+    JsonString(Cow<'a, str>),
+    Number(JsonNumber),
+    Boolean(bool),
+    #[default]
     Null,
 }
 
+// One container being copied: the source children still to visit and the copy so far.
+enum OwnedFrame<'v, 'a> {
+    Array(std::slice::Iter<'v, JsonValueRef<'a>>, Vec<JsonValue>),
+    Object(indexmap::map::Iter<'v, Cow<'a, str>, JsonValueRef<'a>>, IndexMap<String, JsonValue>),
+}
+
+impl<'a> JsonValueRef<'a> {
+    /// Copies the tree into a JsonValue that no longer borrows from the input.
+    pub fn to_owned(&self) -> JsonValue {
+        let Some(root) = Self::open_owned_frame(self) else {
+            return self.shallow_to_owned();
+        };
+        // `key` is where the finished copy goes in the parent (None for arrays and the root)
+        let mut stack: Vec<(Option<&Cow<'a, str>>, OwnedFrame<'_, 'a>)> = vec![(None, root)];
+
+        loop {
+            let Some((_, frame)) = stack.last_mut() else {
+                unreachable!("root frame returns before the stack empties");
+            };
+
+            let next = match frame {
+                OwnedFrame::Array(src, _) => src.next().map(|v| (None, v)),
+                OwnedFrame::Object(src, _) => src.next().map(|(k, v)| (Some(k), v)),
+            };
+
+            let (key, value) = match next {
+                Some((key, child)) => match Self::open_owned_frame(child) {
+                    Some(child_frame) => {
+                        stack.push((key, child_frame));
+                        continue;
+                    }
+                    None => (key, child.shallow_to_owned()),
+                },
+                None => {
+                    let Some((key, done)) = stack.pop() else { unreachable!() };
+                    let value = match done {
+                        OwnedFrame::Array(_, out) => JsonValue::Array(out),
+                        OwnedFrame::Object(_, out) => JsonValue::Object(out),
+                    };
+                    if stack.is_empty() {
+                        return value;
+                    }
+                    (key, value)
+                }
+            };
+
+            match stack.last_mut() {
+                Some((_, OwnedFrame::Array(_, out))) => out.push(value),
+                Some((_, OwnedFrame::Object(_, out))) => {
+                    if let Some(k) = key {
+                        out.insert(k.to_string(), value);
+                    }
+                }
+                None => unreachable!("checked above"),
+            }
+        }
+    }
+
+    fn open_owned_frame<'v>(value: &'v JsonValueRef<'a>) -> Option<OwnedFrame<'v, 'a>> {
+        match value {
+            JsonValueRef::Array(items) if !items.is_empty() => {
+                Some(OwnedFrame::Array(items.iter(), Vec::with_capacity(items.len())))
+            }
+            JsonValueRef::Object(members) if !members.is_empty() => {
+                Some(OwnedFrame::Object(members.iter(), IndexMap::with_capacity(members.len())))
+            }
+            _ => None,
+        }
+    }
+
+    // Copies a leaf, or an empty shell of a container.
+    fn shallow_to_owned(&self) -> JsonValue {
+        match self {
+            JsonValueRef::Object(_) => JsonValue::Object(IndexMap::new()),
+            JsonValueRef::Array(_) => JsonValue::Array(Vec::new()),
+            JsonValueRef::JsonString(s) => JsonValue::JsonString(s.to_string()),
+            JsonValueRef::Number(n) => JsonValue::Number(n.clone()),
+            JsonValueRef::Boolean(b) => JsonValue::Boolean(*b),
+            JsonValueRef::Null => JsonValue::Null,
+        }
+    }
+}
+
+// A string that already owns its text is moved, not copied; anything else goes through to_owned().
+impl From<JsonValueRef<'_>> for JsonValue {
+    fn from(mut value: JsonValueRef<'_>) -> Self {
+        match &mut value {
+            JsonValueRef::JsonString(s) => JsonValue::JsonString(std::mem::take(s).into_owned()),
+            _ => value.to_owned(),
+        }
+    }
+}
+
+impl Drop for JsonValueRef<'_> {
+    fn drop(&mut self) {
+        // Same flattening as JsonValue::drop
+        let mut pending: Vec<JsonValueRef> = match self {
+            JsonValueRef::Array(items) if !items.is_empty() => std::mem::take(items),
+            JsonValueRef::Object(members) if !members.is_empty() => members.drain(..).map(|(_, v)| v).collect(),
+            _ => return,
+        };
+
+        while let Some(mut value) = pending.pop() {
+            match &mut value {
+                JsonValueRef::Array(items) => pending.append(items),
+                JsonValueRef::Object(members) => pending.extend(members.drain(..).map(|(_, v)| v)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl PartialEq for JsonValueRef<'_> {
+    // Same semantics as JsonValue: objects compare as maps, borrowed and owned strings alike.
+    fn eq(&self, other: &Self) -> bool {
+        let mut pending: Vec<(&JsonValueRef, &JsonValueRef)> = vec![(self, other)];
+
+        while let Some((a, b)) = pending.pop() {
+            match (a, b) {
+                (JsonValueRef::Null, JsonValueRef::Null) => {}
+                (JsonValueRef::Boolean(x), JsonValueRef::Boolean(y)) if x == y => {}
+                (JsonValueRef::Number(x), JsonValueRef::Number(y)) if x == y => {}
+                (JsonValueRef::JsonString(x), JsonValueRef::JsonString(y)) if x == y => {}
+                (JsonValueRef::Array(x), JsonValueRef::Array(y)) if x.len() == y.len() => {
+                    pending.extend(x.iter().zip(y.iter()));
+                }
+                (JsonValueRef::Object(x), JsonValueRef::Object(y)) if x.len() == y.len() => {
+                    for (k, xv) in x {
+                        match y.get(k) {
+                            Some(yv) => pending.push((xv, yv)),
+                            None => return false,
+                        }
+                    }
+                }
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl<'a> DebugTree for JsonValueRef<'a> {
+    type Key = Cow<'a, str>;
+
+    fn debug_node(&self) -> DebugNode<'_, Self> {
+        match self {
+            JsonValueRef::Null => DebugNode::Null,
+            JsonValueRef::Boolean(b) => DebugNode::Boolean(*b),
+            JsonValueRef::Number(n) => DebugNode::Number(n),
+            JsonValueRef::JsonString(s) => DebugNode::JsonString(s),
+            JsonValueRef::Array(items) => DebugNode::Array(items),
+            JsonValueRef::Object(members) => DebugNode::Object(members),
+        }
+    }
+}
+
+// Prints like the equivalent JsonValue, walking the borrowed tree without copying it.
+impl fmt::Debug for JsonValueRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_tree(self, f)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum LexerError {
     /// Cursor ended up past the input length (should be rare, but you want runtime error)
     CursorOutOfBounds { cursor: usize, len: usize },
 
     /// Ran out of input while trying to consume something specific (e.g. "true")
-    UnexpectedEof { at: usize, expected: &'static str },
+    UnexpectedEof { at: SourceLocation, expected: &'static str },
 
     /// Current byte can't start any token (or a specific byte was expected)
-    UnexpectedByte { at: usize, found: u8, expected: &'static str },
+    UnexpectedByte { at: SourceLocation, found: u8, expected: &'static str },
 
     /// The bytes at `at` did not match the expected literal (e.g. "true")
-    InvalidLiteral { at: usize, expected: &'static str },
+    InvalidLiteral { at: SourceLocation, expected: &'static str },
 
-    InvalidString { at: usize, reason: StringError },
+    InvalidString { at: SourceLocation, reason: StringError },
 
-    InvalidNumber { at: usize, reason: NumberError },
+    InvalidNumber { at: SourceLocation, reason: NumberError },
+
+    /// A "/*" without its "*/"; `at` is where the comment starts
+    UnterminatedBlockComment { at: SourceLocation },
+
+    /// One of the `ParseLimits` was exceeded; `at` is where the offending token starts
+    LimitExceeded { limit: Limit, at: SourceLocation },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FrameTypeTag {
     ObjectFrame,
     ArrayFrame,
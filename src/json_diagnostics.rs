@@ -0,0 +1,829 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::json_definitions::{
+    FrameTypeTag, JsonParsingError, JsonParsingErrorV2, JsonParsingErrorV3, LexerError, Limit, TokenTag,
+};
+use crate::json_lexer::{relocate, NumberError, SourceLocation, Span, StringError};
+use crate::json_lines::JsonLinesError;
+use crate::json_path::JsonPathError;
+use crate::json_sequence::JsonSequenceError;
+#[cfg(feature = "serde")]
+use crate::json_serde::SerdeError;
+
+// =============================================================================
+// Diagnostics — human-readable error reports
+// =============================================================================
+//
+// Every parser error converts into a `Diagnostic`: a one-line message, the
+// location it points at (if the error has one) and an optional help hint.
+// `Diagnostic::render` then turns it into a rustc-style report:
+//
+//   error: expected ',' or '}' after object member, found string
+//    --> line 3, column 3
+//     |
+//   3 |   "b": 2
+//     |   ^^^
+//     = help: separate object members with ','
+//
+// `Display` on the error types prints only the first line (message + location),
+// which is what you want in logs; `render` needs the source text for the snippet.
+//
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Plain,
+    Ansi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub location: Option<SourceLocation>,
+    pub help: Option<String>,
+}
+
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self) -> Diagnostic;
+}
+
+pub fn render_error<E: ToDiagnostic>(error: &E, source: &str, mode: ColorMode) -> String {
+    error.to_diagnostic().render(source, mode)
+}
+
+const TAB_WIDTH: usize = 4;
+
+struct Palette {
+    error: &'static str,
+    gutter: &'static str,
+    caret: &'static str,
+    help: &'static str,
+    reset: &'static str,
+}
+
+impl Palette {
+    fn for_mode(mode: ColorMode) -> Self {
+        match mode {
+            ColorMode::Plain => Self { error: "", gutter: "", caret: "", help: "", reset: "" },
+            ColorMode::Ansi => Self {
+                error: "\x1b[1;31m",
+                gutter: "\x1b[1;34m",
+                caret: "\x1b[1;31m",
+                help: "\x1b[1;36m",
+                reset: "\x1b[0m",
+            },
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(message: String, location: Option<SourceLocation>, help: Option<&str>) -> Self {
+        Self { message, location, help: help.map(str::to_owned) }
+    }
+
+    pub fn render(&self, source: &str, mode: ColorMode) -> String {
+        let p = Palette::for_mode(mode);
+        let mut out = format!("{}error{}: {}\n", p.error, p.reset, self.message);
+
+        let snippet = self.location.and_then(|loc| source_line(source, loc).map(|line| (loc, line)));
+
+        let gutter_width = match snippet {
+            Some((loc, _)) => loc.line.to_string().len(),
+            None => 0,
+        };
+        let pad = " ".repeat(gutter_width);
+
+        if let Some((loc, line)) = snippet {
+            out.push_str(&format!("{pad}{}-->{} line {}, column {}\n", p.gutter, p.reset, loc.line, loc.column));
+            out.push_str(&format!("{pad} {}|{}\n", p.gutter, p.reset));
+
+            let (shown, caret_pad) = expand_tabs(line, loc.column - 1);
+            let width = underline_width(source, loc.offset);
+            out.push_str(&format!("{}{} |{} {}\n", p.gutter, loc.line, p.reset, shown));
+            out.push_str(&format!(
+                "{pad} {}|{} {}{}{}{}\n",
+                p.gutter,
+                p.reset,
+                " ".repeat(caret_pad),
+                p.caret,
+                "^".repeat(width),
+                p.reset
+            ));
+        } else if let Some(loc) = self.location {
+            out.push_str(&format!(" --> line {}, column {}\n", loc.line, loc.column));
+        }
+
+        if let Some(help) = &self.help {
+            out.push_str(&format!("{pad} {}={} {}help{}: {}\n", p.gutter, p.reset, p.help, p.reset, help));
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some(loc) => write!(f, "{} at {}", self.message, loc),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Returns the text of the line containing `loc`, without the line break.
+fn source_line(source: &str, loc: SourceLocation) -> Option<&str> {
+    if loc.offset > source.len() || !source.is_char_boundary(loc.offset) {
+        return None;
+    }
+
+    let start = source[..loc.offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[loc.offset..].find('\n').map_or(source.len(), |i| loc.offset + i);
+    Some(source[start..end].trim_end_matches('\r'))
+}
+
+// Replaces tabs so the caret lines up, returns the rendered line and
+// the rendered width of the first `chars_before` chars.
+fn expand_tabs(line: &str, chars_before: usize) -> (String, usize) {
+    let mut shown = String::with_capacity(line.len());
+    let mut caret_pad = 0;
+
+    for (i, c) in line.chars().enumerate() {
+        let w = if c == '\t' { TAB_WIDTH } else { 1 };
+        if c == '\t' {
+            shown.push_str(&" ".repeat(TAB_WIDTH));
+        } else {
+            shown.push(c);
+        }
+        if i < chars_before {
+            caret_pad += w;
+        }
+    }
+
+    (shown, caret_pad)
+}
+
+// Width (in chars) of the token starting at `offset`, so the whole token gets underlined.
+// Strings run to their closing quote, words/numbers to the next delimiter,
+// anything else (punctuation, EOF) gets a single caret.
+fn underline_width(source: &str, offset: usize) -> usize {
+    let rest = &source[offset..];
+    let mut chars = rest.chars();
+
+    match chars.next() {
+        Some('"') => {
+            let mut width = 1;
+            let mut escaped = false;
+            for c in chars {
+                if c == '\n' {
+                    break;
+                }
+                width += 1;
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    break;
+                }
+            }
+            width
+        }
+        Some(c) if is_word_char(c) => 1 + chars.take_while(|&c| is_word_char(c)).count(),
+        _ => 1,
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '+' | '.' | '_')
+}
+
+fn describe_byte(b: u8) -> String {
+    if b.is_ascii_graphic() || b == b' ' {
+        format!("'{}'", b as char)
+    } else {
+        format!("byte 0x{:02X}", b)
+    }
+}
+
+// =============================================================================
+// Display for the building blocks
+// =============================================================================
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for TokenTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TokenTag::LBrace => "'{'",
+            TokenTag::RBrace => "'}'",
+            TokenTag::LBracket => "'['",
+            TokenTag::RBracket => "']'",
+            TokenTag::Colon => "':'",
+            TokenTag::Comma => "','",
+            TokenTag::Bool => "boolean",
+            TokenTag::Null => "null",
+            TokenTag::String => "string",
+            TokenTag::Number => "number",
+            TokenTag::Comment => "comment",
+            TokenTag::Eof => "end of input",
+        };
+        f.write_str(s)
+    }
+}
+
+impl fmt::Display for FrameTypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameTypeTag::ObjectFrame => f.write_str("object"),
+            FrameTypeTag::ArrayFrame => f.write_str("array"),
+        }
+    }
+}
+
+impl fmt::Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringError::Unterminated => write!(f, "unterminated string"),
+            StringError::InvalidEscape { found } => write!(f, "invalid escape sequence '\\{}'", *found as char),
+            StringError::TrailingBackslash => write!(f, "string ends right after '\\'"),
+            StringError::ControlChar { found } => write!(f, "raw control character 0x{:02X} in string", found),
+            StringError::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+            StringError::InvalidUnicodeEscape => write!(f, "invalid \\u escape"),
+            StringError::LoneHighSurrogate { code } => {
+                write!(f, "high surrogate \\u{:04X} is not followed by a low surrogate", code)
+            }
+            StringError::LoneLowSurrogate { code } => {
+                write!(f, "low surrogate \\u{:04X} is not preceded by a high surrogate", code)
+            }
+        }
+    }
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            NumberError::Empty => "number has no digits",
+            NumberError::LeadingZero => "number has a leading zero",
+            NumberError::MissingFracDigit => "missing digits after '.'",
+            NumberError::MissingExpDigit => "missing digits in exponent",
+            NumberError::InvalidChar => "unexpected character in number",
+            NumberError::ParseFloatFailed => "number could not be parsed",
+            NumberError::NonFinite => "number is out of range",
+        };
+        f.write_str(s)
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Limit::Depth => "maximum nesting depth",
+            Limit::DocumentBytes => "maximum document size",
+            Limit::StringBytes => "maximum string length",
+            Limit::ArrayLength => "maximum array length",
+            Limit::ObjectMembers => "maximum number of object members",
+            Limit::NumberLength => "maximum number length",
+        };
+        f.write_str(s)
+    }
+}
+
+fn limit_diagnostic(limit: &Limit, at: &SourceLocation) -> Diagnostic {
+    let help = match limit {
+        Limit::Depth => "raise ParseLimits::max_depth if this input is trusted",
+        Limit::DocumentBytes => "raise ParseLimits::max_document_bytes if this input is trusted",
+        Limit::StringBytes => "raise ParseLimits::max_string_bytes if this input is trusted",
+        Limit::ArrayLength => "raise ParseLimits::max_array_len if this input is trusted",
+        Limit::ObjectMembers => "raise ParseLimits::max_object_members if this input is trusted",
+        Limit::NumberLength => "raise ParseLimits::max_number_len if this input is trusted",
+    };
+    Diagnostic::new(format!("{} exceeded", limit), Some(*at), Some(help))
+}
+
+fn duplicate_key_diagnostic(key: &str, first: &Span, second: &Span) -> Diagnostic {
+    Diagnostic {
+        message: format!("duplicate key \"{}\"", key),
+        location: Some(second.start),
+        help: Some(format!("first defined at {}", first.start)),
+    }
+}
+
+// `expected` is one of the lexer's fixed descriptions; only some of them have advice.
+fn unexpected_byte_help(expected: &str) -> Option<&'static str> {
+    match expected {
+        "token" => Some("a JSON value starts with '{', '[', '\"', a digit, '-', true, false or null"),
+        "'/' or '*' after '/'" => Some("comments are // ... or /* ... */"),
+        "'u' after '\\' in an identifier" => Some("the only escape allowed in an unquoted key is \\uXXXX"),
+        _ => None,
+    }
+}
+
+fn string_help(reason: &StringError) -> Option<&'static str> {
+    match reason {
+        StringError::Unterminated => Some("add the closing '\"'"),
+        StringError::InvalidEscape { .. } | StringError::TrailingBackslash => {
+            Some("valid escapes are \\\" \\\\ \\/ \\b \\f \\n \\r \\t and \\uXXXX")
+        }
+        StringError::ControlChar { .. } => Some("escape control characters, e.g. \\n or \\u0001"),
+        StringError::InvalidUnicodeEscape => Some("\\u must be followed by exactly 4 hex digits"),
+        StringError::LoneHighSurrogate { .. } | StringError::LoneLowSurrogate { .. } => {
+            Some("escape characters above U+FFFF as a pair, e.g. \\uD83D\\uDE00, or parse with LoneSurrogatePolicy::Replace")
+        }
+        StringError::InvalidUtf8 => None,
+    }
+}
+
+fn number_help(reason: &NumberError) -> Option<&'static str> {
+    match reason {
+        NumberError::LeadingZero => Some("remove the leading zero, e.g. 7 instead of 07"),
+        NumberError::MissingFracDigit => Some("add a digit after '.', e.g. 1.0"),
+        NumberError::MissingExpDigit => Some("add digits to the exponent, e.g. 1e3"),
+        NumberError::InvalidChar => Some("JSON numbers look like -12.5e+3; '+' is only allowed in the exponent"),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// Lexer
+// =============================================================================
+
+impl ToDiagnostic for LexerError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            LexerError::CursorOutOfBounds { cursor, len } => Diagnostic::new(
+                format!("lexer cursor {} is past the end of input ({} bytes)", cursor, len),
+                None,
+                None,
+            ),
+            LexerError::UnexpectedEof { at, expected } => Diagnostic::new(
+                format!("unexpected end of input, expected `{}`", expected),
+                Some(*at),
+                None,
+            ),
+            LexerError::UnexpectedByte { at, found, expected } => Diagnostic::new(
+                format!("unexpected {}, expected {}", describe_byte(*found), expected),
+                Some(*at),
+                unexpected_byte_help(expected),
+            ),
+            LexerError::InvalidLiteral { at, expected } => Diagnostic::new(
+                format!("invalid literal, expected `{}`", expected),
+                Some(*at),
+                Some("literals are lowercase: true, false, null"),
+            ),
+            LexerError::InvalidString { at, reason } => {
+                Diagnostic::new(format!("invalid string: {}", reason), Some(*at), string_help(reason))
+            }
+            LexerError::InvalidNumber { at, reason } => {
+                Diagnostic::new(format!("invalid number: {}", reason), Some(*at), number_help(reason))
+            }
+            LexerError::UnterminatedBlockComment { at } => Diagnostic::new(
+                "unterminated block comment".to_string(),
+                Some(*at),
+                Some("close the comment with */"),
+            ),
+            LexerError::LimitExceeded { limit, at } => limit_diagnostic(limit, at),
+        }
+    }
+}
+
+// =============================================================================
+// v1
+// =============================================================================
+
+impl ToDiagnostic for JsonParsingError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            JsonParsingError::EmptyJsonFile => Diagnostic::new("input is empty".to_string(), None, None),
+            JsonParsingError::InvalidJsonFile => Diagnostic::new("invalid JSON".to_string(), None, None),
+            JsonParsingError::LeadingZero => Diagnostic::new(
+                "invalid number: number has a leading zero".to_string(),
+                None,
+                number_help(&NumberError::LeadingZero),
+            ),
+            JsonParsingError::InvalidUnicodeInString => Diagnostic::new(
+                "invalid string: invalid \\u escape".to_string(),
+                None,
+                string_help(&StringError::InvalidUnicodeEscape),
+            ),
+            JsonParsingError::InvalidArray => Diagnostic::new(
+                "invalid array".to_string(),
+                None,
+                Some("separate array elements with ',' and close with ']'"),
+            ),
+            JsonParsingError::InvalidJsonObject => Diagnostic::new(
+                "invalid object".to_string(),
+                None,
+                Some("object members look like \"key\": value, separated by ','"),
+            ),
+            JsonParsingError::LexError(e) => e.to_diagnostic(),
+            JsonParsingError::UnexpectedEOF => Diagnostic::new("unexpected end of input".to_string(), None, None),
+        }
+    }
+}
+
+// =============================================================================
+// v2
+// =============================================================================
+
+impl ToDiagnostic for JsonParsingErrorV2 {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            JsonParsingErrorV2::EmptyJsonFile => Diagnostic::new("input is empty".to_string(), None, None),
+            JsonParsingErrorV2::UnexpectedEOF { at } => Diagnostic::new(
+                "unexpected end of input".to_string(),
+                Some(*at),
+                Some("the document ends before the value is complete"),
+            ),
+            JsonParsingErrorV2::ExpectedEOF { found, at } => Diagnostic::new(
+                format!("unexpected {} after the root value", found),
+                *at,
+                Some("a JSON document contains exactly one root value"),
+            ),
+            JsonParsingErrorV2::UnexpectedToken { found, at } => Diagnostic::new(
+                format!("expected a value, found {}", found),
+                *at,
+                Some("a value is an object, array, string, number, true, false or null"),
+            ),
+            JsonParsingErrorV2::InvalidArray { found, at } => Diagnostic::new(
+                format!("expected ',' or ']' after array element, found {}", found),
+                *at,
+                Some("separate array elements with ','"),
+            ),
+            JsonParsingErrorV2::InvalidJsonObject { found, at } => Diagnostic::new(
+                format!("unexpected {} in object", found),
+                *at,
+                Some("object members look like \"key\": value, separated by ','"),
+            ),
+            JsonParsingErrorV2::DuplicateKey { key, first, second } => duplicate_key_diagnostic(key, first, second),
+            JsonParsingErrorV2::LexError(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+// =============================================================================
+// v3
+// =============================================================================
+
+impl ToDiagnostic for JsonParsingErrorV3 {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            JsonParsingErrorV3::EmptyJsonFile => Diagnostic::new("input is empty".to_string(), None, None),
+            JsonParsingErrorV3::UnexpectedEOF { at, frame } => match frame {
+                Some(frame) => Diagnostic::new(
+                    format!("unexpected end of input inside {}", frame),
+                    Some(*at),
+                    Some(match frame {
+                        FrameTypeTag::ObjectFrame => "close the object with '}'",
+                        FrameTypeTag::ArrayFrame => "close the array with ']'",
+                    }),
+                ),
+                None => Diagnostic::new(
+                    "unexpected end of input, expected a value".to_string(),
+                    Some(*at),
+                    None,
+                ),
+            },
+            JsonParsingErrorV3::ExpectedEOF { at, found } => Diagnostic::new(
+                format!("unexpected {} after the root value", found),
+                Some(*at),
+                Some("a JSON document contains exactly one root value"),
+            ),
+            JsonParsingErrorV3::MismatchedClosing { at, found, frame } => Diagnostic::new(
+                format!("{} does not close the open {}", found, frame),
+                Some(*at),
+                Some(match frame {
+                    FrameTypeTag::ObjectFrame => "objects are closed with '}'",
+                    FrameTypeTag::ArrayFrame => "arrays are closed with ']'",
+                }),
+            ),
+            JsonParsingErrorV3::UnexpectedClosing { at, found } => Diagnostic::new(
+                format!("unexpected {}, nothing to close", found),
+                Some(*at),
+                None,
+            ),
+            JsonParsingErrorV3::ObjectKeyNotString { at, found } => Diagnostic::new(
+                format!("expected a string key, found {}", found),
+                Some(*at),
+                Some("object keys must be double-quoted strings"),
+            ),
+            JsonParsingErrorV3::ObjectKeyWithoutValue { at, found, key } => Diagnostic::new(
+                format!("key \"{}\" has no value, found {}", key, found),
+                Some(*at),
+                Some("every key needs a value: \"key\": value"),
+            ),
+            JsonParsingErrorV3::MissingColon { at, found } => Diagnostic::new(
+                format!("expected ':' after object key, found {}", found),
+                Some(*at),
+                Some("separate key and value with ':'"),
+            ),
+            JsonParsingErrorV3::MissingComma { at, found, frame } => match frame {
+                FrameTypeTag::ObjectFrame => Diagnostic::new(
+                    format!("expected ',' or '}}' after object member, found {}", found),
+                    Some(*at),
+                    Some("separate object members with ','"),
+                ),
+                FrameTypeTag::ArrayFrame => Diagnostic::new(
+                    format!("expected ',' or ']' after array element, found {}", found),
+                    Some(*at),
+                    Some("separate array elements with ','"),
+                ),
+            },
+            JsonParsingErrorV3::CommaOutsideStructure { at } => Diagnostic::new(
+                "',' outside of any array or object".to_string(),
+                Some(*at),
+                Some("a JSON document contains exactly one root value"),
+            ),
+            JsonParsingErrorV3::ColonOutsideObject { at, frame } => Diagnostic::new(
+                match frame {
+                    Some(frame) => format!("':' inside {}", frame),
+                    None => "':' outside of any object".to_string(),
+                },
+                Some(*at),
+                Some("':' only separates a key from its value inside an object"),
+            ),
+            JsonParsingErrorV3::UnexpectedColon { at } => Diagnostic::new(
+                "unexpected ':', expected a value".to_string(),
+                Some(*at),
+                None,
+            ),
+            JsonParsingErrorV3::LeadingComma { at, frame } => Diagnostic::new(
+                format!("leading ',' in {}", frame),
+                Some(*at),
+                Some("remove the ','"),
+            ),
+            JsonParsingErrorV3::UnexpectedComma { at, frame } => Diagnostic::new(
+                format!("unexpected ',' in {}", frame),
+                Some(*at),
+                Some(match frame {
+                    FrameTypeTag::ObjectFrame => "expected a key or value before ','",
+                    FrameTypeTag::ArrayFrame => "expected a value before ','",
+                }),
+            ),
+            JsonParsingErrorV3::TrailingComma { at, found, frame } => Diagnostic::new(
+                format!("trailing ',' before {} in {}", found, frame),
+                Some(*at),
+                Some("JSON does not allow trailing commas; remove the last ','"),
+            ),
+            JsonParsingErrorV3::LimitExceeded { limit, at } => limit_diagnostic(limit, at),
+            JsonParsingErrorV3::DuplicateKey { key, first, second } => duplicate_key_diagnostic(key, first, second),
+            JsonParsingErrorV3::LexError(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+// =============================================================================
+// JSONPath queries
+// =============================================================================
+
+impl ToDiagnostic for JsonPathError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            JsonPathError::UnexpectedChar { at, found, expected } => Diagnostic::new(
+                format!("unexpected '{}' in query, expected {}", found, expected),
+                Some(*at),
+                None,
+            ),
+            JsonPathError::UnexpectedEnd { at, expected } => {
+                Diagnostic::new(format!("query ends early, expected {}", expected), Some(*at), None)
+            }
+            JsonPathError::InvalidString { at, reason } => Diagnostic::new(
+                format!("invalid string: {}", reason),
+                Some(*at),
+                match reason {
+                    StringError::InvalidEscape { .. } | StringError::TrailingBackslash => {
+                        Some("valid escapes are \\\\ \\/ \\b \\f \\n \\r \\t \\uXXXX and the string's own quote")
+                    }
+                    StringError::Unterminated => Some("add the closing quote"),
+                    _ => string_help(reason),
+                },
+            ),
+            JsonPathError::InvalidNumber { at, reason } => {
+                Diagnostic::new(format!("invalid number: {}", reason), Some(*at), number_help(reason))
+            }
+            JsonPathError::IndexOutOfRange { at } => Diagnostic::new(
+                "index is out of range".to_string(),
+                Some(*at),
+                Some("indices and slice bounds must be between -(2^53 - 1) and 2^53 - 1"),
+            ),
+            JsonPathError::TooDeep { at } => Diagnostic::new("query is nested too deeply".to_string(), Some(*at), None),
+            JsonPathError::UnknownFunction { at, name } => Diagnostic::new(
+                format!("unknown function `{}`", name),
+                Some(*at),
+                Some("available functions: length, count, match, search, value"),
+            ),
+            JsonPathError::ArgumentCount { at, function, expected, found } => Diagnostic::new(
+                format!(
+                    "`{}` takes {} argument{}, found {}",
+                    function,
+                    expected,
+                    if *expected == 1 { "" } else { "s" },
+                    found
+                ),
+                Some(*at),
+                None,
+            ),
+            JsonPathError::ArgumentType { at, function, expected } => {
+                Diagnostic::new(format!("argument of `{}` must be {}", function, expected), Some(*at), None)
+            }
+            JsonPathError::NonSingularQuery { at } => Diagnostic::new(
+                "only a singular query can be compared".to_string(),
+                Some(*at),
+                Some("a singular query uses only names and indices, e.g. @.a[0]; wildcards, slices, filters and '..' are not allowed"),
+            ),
+            JsonPathError::NotComparable { at, function } => Diagnostic::new(
+                format!("`{}` returns true or false and cannot be compared", function),
+                Some(*at),
+                Some("use it as a test on its own, e.g. ?match(@.a, 'x')"),
+            ),
+            JsonPathError::NotATest { at } => Diagnostic::new(
+                "expected a test: a query, match() or search()".to_string(),
+                Some(*at),
+                Some("compare values instead, e.g. ?length(@.a) > 0"),
+            ),
+        }
+    }
+}
+
+// =============================================================================
+// JSON Lines and text sequences
+// =============================================================================
+
+// A piece's parse error is located relative to that piece (a line, a record);
+// move it into the whole input, so render_error can take the full text.
+fn within_piece(error: &JsonParsingErrorV3, start: SourceLocation) -> Diagnostic {
+    let mut diagnostic = error.to_diagnostic();
+    diagnostic.location = Some(diagnostic.location.map_or(start, |loc| relocate(loc, start)));
+    diagnostic
+}
+
+impl ToDiagnostic for JsonLinesError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            JsonLinesError::Io(e) => Diagnostic::new(format!("I/O error: {}", e), None, None),
+            JsonLinesError::InvalidUtf8 { at } => Diagnostic::new("line is not valid UTF-8".to_string(), Some(*at), None),
+            JsonLinesError::BlankLine { at } => Diagnostic::new(
+                "blank line where a JSON document was expected".to_string(),
+                Some(*at),
+                Some("every line holds one document; set skip_blank_lines to pass over empty ones"),
+            ),
+            JsonLinesError::Parse { line_start, error } => within_piece(error, *line_start),
+        }
+    }
+}
+
+impl ToDiagnostic for JsonSequenceError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            JsonSequenceError::MissingRecordSeparator { at } => Diagnostic::new(
+                "text before the first record separator".to_string(),
+                Some(*at),
+                Some("every record of a JSON text sequence starts with the byte 0x1E"),
+            ),
+            JsonSequenceError::PossiblyTruncated { at } => Diagnostic::new(
+                "record may be truncated".to_string(),
+                Some(*at),
+                Some("a top-level number, true, false or null must be followed by whitespace"),
+            ),
+            JsonSequenceError::Parse { record_start, error } => within_piece(error, *record_start),
+        }
+    }
+}
+
+// =============================================================================
+// serde (feature "serde")
+// =============================================================================
+
+#[cfg(feature = "serde")]
+impl ToDiagnostic for SerdeError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            SerdeError::Syntax(e) => e.to_diagnostic(),
+            SerdeError::Custom { message, at } => Diagnostic::new(message.clone(), *at, None),
+            SerdeError::KeyMustBeString => Diagnostic::new(
+                "map key must be a string".to_string(),
+                None,
+                Some("JSON object keys are strings; only strings, chars and integers can be used as keys"),
+            ),
+            SerdeError::Io(e) => Diagnostic::new(format!("I/O error: {}", e), None, None),
+        }
+    }
+}
+
+// =============================================================================
+// Display + Error
+// =============================================================================
+
+impl fmt::Display for LexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl fmt::Display for JsonParsingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl fmt::Display for JsonParsingErrorV2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl fmt::Display for JsonParsingErrorV3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl fmt::Display for JsonLinesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl fmt::Display for JsonSequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_diagnostic().fmt(f)
+    }
+}
+
+impl Error for LexerError {}
+
+impl Error for JsonPathError {}
+
+impl Error for JsonParsingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonParsingError::LexError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Error for JsonParsingErrorV2 {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonParsingErrorV2::LexError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Error for JsonParsingErrorV3 {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonParsingErrorV3::LexError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Error for JsonLinesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonLinesError::Io(e) => Some(e),
+            JsonLinesError::Parse { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Error for JsonSequenceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonSequenceError::Parse { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for SerdeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SerdeError::Syntax(e) => Some(e),
+            SerdeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
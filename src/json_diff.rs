@@ -0,0 +1,551 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+
+use indexmap::IndexMap;
+
+use crate::json_definitions::JsonValue;
+use crate::json_patch::{PathId, Paths};
+use crate::json_pointer::JsonPointer;
+use crate::json_writer::{to_string, to_string_pretty};
+
+// =============================================================================
+// Structural diff — what changed between two JsonValue trees
+// =============================================================================
+//
+// Where generate_patch answers "which operations turn a into b?", json_diff
+// answers "what would a reviewer want to see?":
+//
+//   a: {"port": 80, "hosts": ["x", "y", "z"], "debug": true}
+//   b: {"port": 8080, "hosts": ["z", "x", "y"], "tls": {}}
+//
+//   Changed   /port      80 → 8080
+//   Moved     /hosts/2 → /hosts/0   ("z", equal on both sides)
+//   Removed   /debug
+//   Added     /tls
+//
+// PATHS:
+//   Removed paths and Moved `from` point into `a`; every other path points
+//   into `b`. Both are tracked while walking, in the same parent-link arena
+//   generate_patch uses, so deep trees never copy whole paths.
+//
+// SAMENESS:
+//   Every node of both trees gets a structural hash up front (post-order,
+//   explicit stack). Two subtrees are only compared in full when their
+//   hashes agree, so skipping equal subtrees and matching array elements
+//   stay cheap. With ignore_key_order / ignore_array_order the hash (and the
+//   comparison) treat members / elements as unordered collections.
+//
+// ARRAYS (order counts):
+//   Equal elements at both ends are skipped, then the longest common
+//   subsequence of the middle anchors the elements that stayed in order. An
+//   element that left that order but exists, equal, on both sides is Moved;
+//   what is left between two anchors is paired up in order, an object with
+//   an object, an array with an array, a scalar with a scalar, and compared
+//   one level down; the surplus is Removed or Added. Middles too large for
+//   the LCS table (LCS_CELLS) get no anchors and are paired up whole.
+//
+// ARRAYS (ignore_array_order):
+//   Equal elements are matched wherever they are; the leftovers are paired
+//   up as above, the surplus Removed or Added.
+//
+// OBJECTS:
+//   Members follow the IndexMap order: a's members (changed or removed),
+//   then the members only b has. Unless ignore_key_order is set, a change
+//   in the order of the shared keys is reported as Reordered.
+//
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added { path: JsonPointer, value: JsonValue },
+    Removed { path: JsonPointer, value: JsonValue },
+    Changed { path: JsonPointer, from: JsonValue, to: JsonValue },
+    Moved { from: JsonPointer, path: JsonPointer, value: JsonValue }, // array element, equal on both sides
+    Reordered { path: JsonPointer, from: Vec<String>, to: Vec<String> }, // order of the keys both objects have
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiffOptions {
+    pub ignore_key_order: bool,
+    pub ignore_array_order: bool,
+}
+
+impl Change {
+    /// Where the change is; for Moved, the element's new position.
+    pub fn path(&self) -> &JsonPointer {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. }
+            | Change::Moved { path, .. }
+            | Change::Reordered { path, .. } => path,
+        }
+    }
+}
+
+pub fn json_diff(a: &JsonValue, b: &JsonValue) -> Vec<Change> {
+    json_diff_with_options(a, b, &DiffOptions::default())
+}
+
+pub fn json_diff_with_options(a: &JsonValue, b: &JsonValue, options: &DiffOptions) -> Vec<Change> {
+    let mut differ = Differ { options: *options, hashes: HashMap::new(), paths: Paths::new() };
+    differ.hash_tree(a);
+    differ.hash_tree(b);
+    differ.run(a, b)
+}
+
+// Above this many cells (middle of a × middle of b) arrays are not searched
+// for a common subsequence.
+const LCS_CELLS: usize = 1 << 22;
+
+enum DiffTask<'v> {
+    // (path in a, path in b)
+    Compare(PathId, PathId, &'v JsonValue, &'v JsonValue),
+    Emit(Change),
+}
+
+struct Differ {
+    options: DiffOptions,
+    hashes: HashMap<*const JsonValue, u64>,
+    paths: Paths,
+}
+
+impl Differ {
+    fn run<'v>(&mut self, a: &'v JsonValue, b: &'v JsonValue) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let mut stack = vec![DiffTask::Compare(Paths::ROOT, Paths::ROOT, a, b)];
+
+        while let Some(task) = stack.pop() {
+            let (a_path, b_path, a, b) = match task {
+                DiffTask::Emit(change) => {
+                    changes.push(change);
+                    continue;
+                }
+                DiffTask::Compare(a_path, b_path, a, b) => (a_path, b_path, a, b),
+            };
+            if self.same(a, b) {
+                continue;
+            }
+
+            // Tasks for this level in document order; pushed reversed so they run in that order.
+            let mut tasks = Vec::new();
+            match (a, b) {
+                (JsonValue::Object(x), JsonValue::Object(y)) => self.diff_objects(a_path, b_path, x, y, &mut tasks),
+                (JsonValue::Array(x), JsonValue::Array(y)) if self.options.ignore_array_order => {
+                    self.diff_unordered(a_path, b_path, x, y, &mut tasks)
+                }
+                (JsonValue::Array(x), JsonValue::Array(y)) => self.diff_ordered(a_path, b_path, x, y, &mut tasks),
+                _ => tasks.push(DiffTask::Emit(Change::Changed {
+                    path: self.paths.pointer(b_path),
+                    from: a.clone(),
+                    to: b.clone(),
+                })),
+            }
+            stack.extend(tasks.into_iter().rev());
+        }
+
+        changes
+    }
+
+    fn diff_objects<'v>(
+        &mut self,
+        a_path: PathId,
+        b_path: PathId,
+        x: &'v IndexMap<String, JsonValue>,
+        y: &'v IndexMap<String, JsonValue>,
+        tasks: &mut Vec<DiffTask<'v>>,
+    ) {
+        if !self.options.ignore_key_order {
+            let from: Vec<String> = x.keys().filter(|key| y.contains_key(*key)).cloned().collect();
+            let to: Vec<String> = y.keys().filter(|key| x.contains_key(*key)).cloned().collect();
+            if from != to {
+                tasks.push(DiffTask::Emit(Change::Reordered { path: self.paths.pointer(b_path), from, to }));
+            }
+        }
+
+        for (key, xv) in x {
+            match y.get(key) {
+                Some(yv) => {
+                    let a_child = self.paths.child(a_path, key.clone());
+                    let b_child = self.paths.child(b_path, key.clone());
+                    tasks.push(DiffTask::Compare(a_child, b_child, xv, yv));
+                }
+                None => {
+                    let path = self.paths.child_pointer(a_path, key.as_str());
+                    tasks.push(DiffTask::Emit(Change::Removed { path, value: xv.clone() }));
+                }
+            }
+        }
+        for (key, yv) in y {
+            if !x.contains_key(key) {
+                let path = self.paths.child_pointer(b_path, key.as_str());
+                tasks.push(DiffTask::Emit(Change::Added { path, value: yv.clone() }));
+            }
+        }
+    }
+
+    fn diff_ordered<'v>(
+        &mut self,
+        a_path: PathId,
+        b_path: PathId,
+        x: &'v [JsonValue],
+        y: &'v [JsonValue],
+        tasks: &mut Vec<DiffTask<'v>>,
+    ) {
+        let shorter = x.len().min(y.len());
+        let prefix = (0..shorter).take_while(|&i| self.same(&x[i], &y[i])).count();
+        let suffix =
+            (0..shorter - prefix).take_while(|&k| self.same(&x[x.len() - 1 - k], &y[y.len() - 1 - k])).count();
+        let (x_end, y_end) = (x.len() - suffix, y.len() - suffix);
+
+        let mut anchors = self.common_subsequence(x, y, prefix, x_end, y_end);
+
+        // Equal elements on both sides that the anchors do not account for have moved.
+        let mut x_anchored = vec![false; x_end - prefix];
+        let mut y_anchored = vec![false; y_end - prefix];
+        for &(i, j) in &anchors {
+            x_anchored[i - prefix] = true;
+            y_anchored[j - prefix] = true;
+        }
+        let mut candidates: HashMap<u64, Vec<usize>> = HashMap::new();
+        for i in (prefix..x_end).filter(|i| !x_anchored[i - prefix]) {
+            candidates.entry(self.hash(&x[i])).or_default().push(i);
+        }
+        let mut moved_to: HashMap<usize, usize> = HashMap::new(); // j → i
+        for j in (prefix..y_end).filter(|j| !y_anchored[j - prefix]) {
+            let Some(found) = candidates.get_mut(&self.hash(&y[j])) else { continue };
+            if let Some(k) = found.iter().position(|&i| self.equivalent(&x[i], &y[j])) {
+                moved_to.insert(j, found.remove(k));
+            }
+        }
+        let mut moved_from = vec![false; x_end - prefix];
+        for &i in moved_to.values() {
+            moved_from[i - prefix] = true;
+        }
+
+        // Walk the gaps between anchors; the end of the middle closes the last one.
+        anchors.push((x_end, y_end));
+        let (mut next_x, mut next_y) = (prefix, prefix);
+        for (anchor_x, anchor_y) in anchors {
+            let gap_x: Vec<usize> = (next_x..anchor_x).filter(|i| !moved_from[i - prefix]).collect();
+            let gap_y: Vec<usize> = (next_y..anchor_y).filter(|j| !moved_to.contains_key(j)).collect();
+            self.pair_leftovers((a_path, b_path), x, y, &gap_x, &gap_y, tasks);
+            for (j, yv) in y.iter().enumerate().take(anchor_y).skip(next_y) {
+                if let Some(&i) = moved_to.get(&j) {
+                    tasks.push(DiffTask::Emit(Change::Moved {
+                        from: self.paths.child_pointer(a_path, i.to_string()),
+                        path: self.paths.child_pointer(b_path, j.to_string()),
+                        value: yv.clone(),
+                    }));
+                }
+            }
+            (next_x, next_y) = (anchor_x + 1, anchor_y + 1);
+        }
+    }
+
+    // Index pairs (into x, into y) of a longest common subsequence of
+    // x[start..x_end] and y[start..y_end], in increasing order.
+    fn common_subsequence(&self, x: &[JsonValue], y: &[JsonValue], start: usize, x_end: usize, y_end: usize) -> Vec<(usize, usize)> {
+        let (n, m) = (x_end - start, y_end - start);
+        if n == 0 || m == 0 || n.saturating_mul(m) > LCS_CELLS {
+            return Vec::new();
+        }
+
+        // lengths[i][j]: LCS length of x[start + i..x_end] and y[start + j..y_end]
+        let width = m + 1;
+        let mut lengths = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i * width + j] = if self.same(&x[start + i], &y[start + j]) {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        let mut pairs = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if lengths[i * width + j] == lengths[(i + 1) * width + j + 1] + 1
+                && self.same(&x[start + i], &y[start + j])
+            {
+                pairs.push((start + i, start + j));
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        pairs
+    }
+
+    fn diff_unordered<'v>(
+        &mut self,
+        a_path: PathId,
+        b_path: PathId,
+        x: &'v [JsonValue],
+        y: &'v [JsonValue],
+        tasks: &mut Vec<DiffTask<'v>>,
+    ) {
+        let mut candidates: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, xv) in x.iter().enumerate() {
+            candidates.entry(self.hash(xv)).or_default().push(i);
+        }
+
+        let mut x_matched = vec![false; x.len()];
+        let mut y_left = Vec::new();
+        for (j, yv) in y.iter().enumerate() {
+            let found = candidates.get_mut(&self.hash(yv)).and_then(|found| {
+                let k = found.iter().position(|&i| self.equivalent(&x[i], yv))?;
+                Some(found.remove(k))
+            });
+            match found {
+                Some(i) => x_matched[i] = true,
+                None => y_left.push(j),
+            }
+        }
+        let x_left: Vec<usize> = (0..x.len()).filter(|&i| !x_matched[i]).collect();
+        self.pair_leftovers((a_path, b_path), x, y, &x_left, &y_left, tasks);
+    }
+
+    // Elements without an equal partner: each one of `y_left` is paired with
+    // the next one of `x_left` of the same kind (object, array or scalar) and
+    // compared one level down; the rest are Removed or Added. Tasks come in
+    // `x_left` order, then the additions.
+    fn pair_leftovers<'v>(
+        &mut self,
+        (a_path, b_path): (PathId, PathId),
+        x: &'v [JsonValue],
+        y: &'v [JsonValue],
+        x_left: &[usize],
+        y_left: &[usize],
+        tasks: &mut Vec<DiffTask<'v>>,
+    ) {
+        let kind = |value: &JsonValue| match value {
+            JsonValue::Object(_) => 0,
+            JsonValue::Array(_) => 1,
+            _ => 2,
+        };
+
+        let mut partner: Vec<Option<usize>> = vec![None; x_left.len()];
+        let mut added = Vec::new();
+        let mut cursor = 0;
+        for &j in y_left {
+            match (cursor..x_left.len()).find(|&k| kind(&x[x_left[k]]) == kind(&y[j])) {
+                Some(k) => {
+                    partner[k] = Some(j);
+                    cursor = k + 1;
+                }
+                None => added.push(j),
+            }
+        }
+
+        for (&i, partner) in x_left.iter().zip(partner) {
+            match partner {
+                Some(j) => {
+                    let a_child = self.paths.child(a_path, i.to_string());
+                    let b_child = self.paths.child(b_path, j.to_string());
+                    tasks.push(DiffTask::Compare(a_child, b_child, &x[i], &y[j]));
+                }
+                None => {
+                    let path = self.paths.child_pointer(a_path, i.to_string());
+                    tasks.push(DiffTask::Emit(Change::Removed { path, value: x[i].clone() }));
+                }
+            }
+        }
+        for j in added {
+            let path = self.paths.child_pointer(b_path, j.to_string());
+            tasks.push(DiffTask::Emit(Change::Added { path, value: y[j].clone() }));
+        }
+    }
+
+    // ---- sameness -----------------------------------------------------------
+
+    fn hash(&self, value: &JsonValue) -> u64 {
+        self.hashes[&(value as *const JsonValue)]
+    }
+
+    fn same(&self, a: &JsonValue, b: &JsonValue) -> bool {
+        self.hash(a) == self.hash(b) && self.equivalent(a, b)
+    }
+
+    // Equality under the options: JsonValue's == for leaves, key order and
+    // array order only where they count.
+    fn equivalent(&self, a: &JsonValue, b: &JsonValue) -> bool {
+        let mut pending = vec![(a, b)];
+
+        while let Some((a, b)) = pending.pop() {
+            if self.hash(a) != self.hash(b) {
+                return false;
+            }
+            match (a, b) {
+                (JsonValue::Object(x), JsonValue::Object(y)) => {
+                    if x.len() != y.len() {
+                        return false;
+                    }
+                    for (index, (key, xv)) in x.iter().enumerate() {
+                        let yv = if self.options.ignore_key_order {
+                            y.get(key)
+                        } else {
+                            y.get_index(index).filter(|(other, _)| *other == key).map(|(_, yv)| yv)
+                        };
+                        let Some(yv) = yv else { return false };
+                        pending.push((xv, yv));
+                    }
+                }
+                (JsonValue::Array(x), JsonValue::Array(y)) => {
+                    if x.len() != y.len() {
+                        return false;
+                    }
+                    if self.options.ignore_array_order {
+                        // Equal multisets line up once sorted by hash (two different
+                        // elements with the same 64-bit hash could spoil that).
+                        let mut xs: Vec<&JsonValue> = x.iter().collect();
+                        let mut ys: Vec<&JsonValue> = y.iter().collect();
+                        xs.sort_by_key(|v| self.hash(v));
+                        ys.sort_by_key(|v| self.hash(v));
+                        pending.extend(xs.into_iter().zip(ys));
+                    } else {
+                        pending.extend(x.iter().zip(y));
+                    }
+                }
+                _ => {
+                    if a != b {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // Post-order over one tree: a container is hashed once all its children are.
+    fn hash_tree(&mut self, root: &JsonValue) {
+        let mut stack = vec![(root, false)];
+
+        while let Some((node, children_done)) = stack.pop() {
+            match node {
+                JsonValue::Array(items) if !children_done => {
+                    stack.push((node, true));
+                    stack.extend(items.iter().map(|item| (item, false)));
+                }
+                JsonValue::Object(members) if !children_done => {
+                    stack.push((node, true));
+                    stack.extend(members.values().map(|member| (member, false)));
+                }
+                _ => {
+                    let hash = self.node_hash(node);
+                    self.hashes.insert(node as *const JsonValue, hash);
+                }
+            }
+        }
+    }
+
+    fn node_hash(&self, node: &JsonValue) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match node {
+            JsonValue::Null => 0u8.hash(&mut hasher),
+            JsonValue::Boolean(b) => (1u8, b).hash(&mut hasher),
+            JsonValue::Number(n) => {
+                // numbers that compare equal (1, 1.0, 1e0) have the same f64; +0.0 stands in for -0.0
+                let f = n.as_f64();
+                (2u8, if f == 0.0 { 0.0f64 } else { f }.to_bits()).hash(&mut hasher);
+            }
+            JsonValue::JsonString(s) => (3u8, s).hash(&mut hasher),
+            JsonValue::Array(items) => {
+                (4u8, items.len()).hash(&mut hasher);
+                let hashes = items.iter().map(|item| self.hash(item));
+                if self.options.ignore_array_order {
+                    hashes.fold(0u64, u64::wrapping_add).hash(&mut hasher);
+                } else {
+                    hashes.for_each(|hash| hash.hash(&mut hasher));
+                }
+            }
+            JsonValue::Object(members) => {
+                (5u8, members.len()).hash(&mut hasher);
+                let hashes = members.iter().map(|(key, member)| {
+                    let mut member_hasher = DefaultHasher::new();
+                    (key, self.hash(member)).hash(&mut member_hasher);
+                    member_hasher.finish()
+                });
+                if self.options.ignore_key_order {
+                    hashes.fold(0u64, u64::wrapping_add).hash(&mut hasher);
+                } else {
+                    hashes.for_each(|hash| hash.hash(&mut hasher));
+                }
+            }
+        }
+        hasher.finish()
+    }
+}
+
+// =============================================================================
+// Unified text
+// =============================================================================
+//
+//   @@ /port @@
+//   -80
+//   +8080
+//   @@ /hosts/2 -> /hosts/0 @@
+//    "z"
+//
+// One hunk per change: removed lines start with '-', added with '+', a moved
+// value is shown once as context. Values are pretty-printed.
+
+/// Renders `changes` as a unified-diff style text, one hunk per change.
+pub fn render_diff(changes: &[Change]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        match change {
+            Change::Added { path, value } => {
+                hunk_header(&mut out, path);
+                hunk_lines(&mut out, '+', &to_string_pretty(value));
+            }
+            Change::Removed { path, value } => {
+                hunk_header(&mut out, path);
+                hunk_lines(&mut out, '-', &to_string_pretty(value));
+            }
+            Change::Changed { path, from, to } => {
+                hunk_header(&mut out, path);
+                hunk_lines(&mut out, '-', &to_string_pretty(from));
+                hunk_lines(&mut out, '+', &to_string_pretty(to));
+            }
+            Change::Moved { from, path, value } => {
+                let _ = writeln!(out, "@@ {} -> {} @@", display_path(from), display_path(path));
+                hunk_lines(&mut out, ' ', &to_string_pretty(value));
+            }
+            Change::Reordered { path, from, to } => {
+                hunk_header(&mut out, path);
+                hunk_lines(&mut out, '-', &key_list(from));
+                hunk_lines(&mut out, '+', &key_list(to));
+            }
+        }
+    }
+    out
+}
+
+fn display_path(path: &JsonPointer) -> String {
+    if path.is_root() { "(root)".to_string() } else { path.to_string() }
+}
+
+fn hunk_header(out: &mut String, path: &JsonPointer) {
+    let _ = writeln!(out, "@@ {} @@", display_path(path));
+}
+
+fn hunk_lines(out: &mut String, marker: char, text: &str) {
+    for line in text.lines() {
+        out.push(marker);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+fn key_list(keys: &[String]) -> String {
+    let quoted: Vec<String> = keys.iter().map(|key| to_string(&JsonValue::JsonString(key.clone()))).collect();
+    format!("keys {}", quoted.join(", "))
+}
@@ -0,0 +1,578 @@
+use std::borrow::Cow;
+
+use crate::json_definitions::{
+    token_tag_of, Dialect, FrameTypeTag, JsonParsingErrorV3, JsonValueRef, LexerError, Limit, ParseLimits,
+    ParseOptions, TokenTag,
+};
+use crate::json_lexer::{Lexer, SourceLocation, Span, Token, TokenKind};
+
+// =============================================================================
+// JSON Events — streaming pull parser
+// =============================================================================
+//
+// CONCEPT:
+//   v3 showed that the call stack can be replaced by an explicit Vec of
+//   frames. Take that one step further and drop the partially built values
+//   from the frames too: what is left is a pure syntax checker whose memory
+//   is proportional to nesting depth, not document size. Instead of building
+//   a tree it reports what it saw, one event at a time:
+//
+//     {"a": [1, true]}   →   StartObject
+//                            Key("a")
+//                            StartArray
+//                            Value(Number(1))
+//                            Value(Boolean(true))
+//                            EndArray
+//                            EndObject
+//
+//   `JsonEventParser` is an Iterator<Item = Result<JsonEvent, JsonParsingErrorV3>>.
+//   v3 (`process_json_string_v3`) is this iterator plus a tree builder, so
+//   both report exactly the same errors at exactly the same locations.
+//
+// KEY INSIGHT — frame state:
+//   Every frame remembers what it saw last (Start, AfterValue, AfterComma,
+//   and for objects also AfterKey / AfterColon). Each incoming token is
+//   checked against that state BEFORE it is applied, so every error can say
+//   exactly which token arrived and which frame it arrived in.
+//
+// KEY INSIGHT — pending_key: Option<(String, Span)>:
+//   In an object, a String token plays double duty:
+//     - pending_key is None    → string is a KEY, store it (and its span) as pending_key
+//     - pending_key is Some(k) → string is a VALUE
+//   The frame state does the disambiguation — not the token itself.
+//   The Key event is only emitted once its value starts, so the key is still
+//   at hand for ObjectKeyWithoutValue when the value never comes: {"a": }.
+//   That one token then yields two events; the second waits in `queued`.
+//
+// MULTIPLE DOCUMENTS:
+//   `JsonEventParser::multiple_documents` accepts any number of top-level
+//   values back to back — {"a":1}{"a":2} or 1 2 3 — where the normal mode
+//   reports ExpectedEOF after the first. A document is complete whenever an
+//   event leaves depth() at 0; empty input is simply zero documents.
+//
+// JSON5:
+//   With Dialect::Json5 the lexer does most of the work (unquoted keys come
+//   out as String tokens); the frames only have to let one trailing comma
+//   through before ']' or '}'. [,] and [1,,] are still errors.
+//
+// LIMITS:
+//   The lexer checks the byte-based limits and the depth is checked before
+//   pushing a frame. Array length and object member count limit what gets
+//   materialised, so they are left to whoever builds values from the events
+//   (v3 does).
+//
+// =============================================================================
+
+// Keys and string values borrow from the input when they had no escapes;
+// `to_owned()` on a Value or `into_owned()` on a Key detaches them.
+#[derive(Debug, PartialEq)]
+pub enum JsonEvent<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'a, str>),
+    // Only leaves: Null, Boolean, Number or JsonString
+    Value(JsonValueRef<'a>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ObjectState {
+    Start,      // just after '{'
+    AfterKey,   // key stored in pending_key, expecting ':'
+    AfterColon, // expecting the value for pending_key
+    AfterValue, // expecting ',' or '}'
+    AfterComma, // expecting the next key
+}
+
+struct ObjectFrame<'a> {
+    pending_key: Option<(Cow<'a, str>, Span)>,
+    state: ObjectState,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ArrayState {
+    Start,      // just after '['
+    AfterValue, // expecting ',' or ']'
+    AfterComma, // expecting the next value
+}
+
+struct ArrayFrame {
+    state: ArrayState,
+}
+
+enum EventFrame<'a> {
+    Object(ObjectFrame<'a>),
+    Array(ArrayFrame),
+}
+
+impl EventFrame<'_> {
+    fn tag(&self) -> FrameTypeTag {
+        match self {
+            EventFrame::Object(_) => FrameTypeTag::ObjectFrame,
+            EventFrame::Array(_) => FrameTypeTag::ArrayFrame,
+        }
+    }
+}
+
+pub struct JsonEventParser<'a> {
+    tokens: Lexer<'a>,
+    cursor: SourceLocation,
+    machine: EventMachine<'a>,
+    // Error found before the first token (empty input)
+    pending_error: Option<JsonParsingErrorV3>,
+    finished: bool,
+}
+
+impl<'a> JsonEventParser<'a> {
+    pub fn new(json_string: &'a str) -> Self {
+        Self::with_limits(json_string, &ParseLimits::UNLIMITED)
+    }
+
+    pub fn with_limits(json_string: &'a str, limits: &ParseLimits) -> Self {
+        Self::with_options(json_string, &ParseOptions { limits: *limits, ..ParseOptions::default() })
+    }
+
+    /// Uses the limits, the lone surrogate policy, the dialect and the comment policy (comment tokens are
+    /// skipped); duplicate keys are left to whoever builds objects.
+    pub fn with_options(json_string: &'a str, options: &ParseOptions) -> Self {
+        let pending_error = json_string.is_empty().then_some(JsonParsingErrorV3::EmptyJsonFile);
+        Self {
+            tokens: Lexer::with_options(json_string.as_bytes(), &options.skipping_comments()),
+            cursor: SourceLocation::START,
+            machine: EventMachine::new(options),
+            pending_error,
+            finished: false,
+        }
+    }
+
+    /// Multi-document mode: values may follow each other at the top level.
+    pub fn multiple_documents(json_string: &'a str, options: &ParseOptions) -> Self {
+        Self {
+            tokens: Lexer::with_options(json_string.as_bytes(), &options.skipping_comments()),
+            cursor: SourceLocation::START,
+            machine: EventMachine::multiple_documents(options),
+            pending_error: None,
+            finished: false,
+        }
+    }
+
+    /// Span of the token behind the event last returned (the key string for Key events).
+    pub fn span(&self) -> Span {
+        self.machine.span()
+    }
+
+    /// Number of arrays/objects currently open.
+    pub fn depth(&self) -> usize {
+        self.machine.depth()
+    }
+
+    // Pulls tokens until one of them produces an event.
+    // Ok(None) means the document ended cleanly.
+    fn step(&mut self) -> Result<Option<JsonEvent<'a>>, JsonParsingErrorV3> {
+        if let Some(err) = self.pending_error.take() {
+            return Err(err);
+        }
+
+        loop {
+            let Some(token) = next_token(&mut self.tokens, &mut self.cursor)? else {
+                return Err(JsonParsingErrorV3::UnexpectedEOF { at: self.cursor, frame: self.machine.frame() })
+            };
+
+            let is_eof = token.kind == TokenKind::Eof;
+            if let Some(event) = self.machine.accept(token)? {
+                return Ok(Some(event));
+            }
+            if is_eof {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for JsonEventParser<'a> {
+    type Item = Result<JsonEvent<'a>, JsonParsingErrorV3>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        if let Some(event) = self.machine.take_queued() {
+            return Some(Ok(event));
+        }
+
+        match self.step() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// The frame state machine on its own: tokens go in one at a time, events
+/// come out. It never asks for input, so the pull parser above and the push
+/// parser (json_push.rs) can both drive it.
+pub(crate) struct EventMachine<'a> {
+    stack: Vec<EventFrame<'a>>,
+    root_done: bool,
+    multiple: bool,        // another root value may follow a complete one
+    trailing_commas: bool, // JSON5
+    limits: ParseLimits,
+    span: Span,
+    queued: Option<(JsonEvent<'a>, Span)>,
+}
+
+impl<'a> EventMachine<'a> {
+    pub(crate) fn new(options: &ParseOptions) -> Self {
+        let start = SourceLocation::START;
+        Self {
+            stack: Vec::new(),
+            root_done: false,
+            multiple: false,
+            trailing_commas: options.dialect == Dialect::Json5,
+            limits: options.limits,
+            span: Span { start, end: start },
+            queued: None,
+        }
+    }
+
+    pub(crate) fn multiple_documents(options: &ParseOptions) -> Self {
+        Self { multiple: true, ..Self::new(options) }
+    }
+
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Kind of the innermost open frame, if any.
+    pub(crate) fn frame(&self) -> Option<FrameTypeTag> {
+        self.stack.last().map(EventFrame::tag)
+    }
+
+    /// Second event of a token that produced two (the value after its Key).
+    pub(crate) fn take_queued(&mut self) -> Option<JsonEvent<'a>> {
+        let (event, span) = self.queued.take()?;
+        self.span = span;
+        Some(event)
+    }
+
+    /// Checks `token` against the frame state and applies it. Ok(None) means the
+    /// token produced no event: ',' ':' an object key, or the Eof of a complete document.
+    pub(crate) fn accept(&mut self, token: Token<'a>) -> Result<Option<JsonEvent<'a>>, JsonParsingErrorV3> {
+        let span = token.span;
+        let at = span.start;
+        let found = token_tag_of(&token.kind);
+
+        let event = match token.kind {
+            TokenKind::Null => self.begin_value(JsonEvent::Value(JsonValueRef::Null), span, found)?,
+            TokenKind::Bool(b) => self.begin_value(JsonEvent::Value(JsonValueRef::Boolean(b)), span, found)?,
+            TokenKind::Number(n) => self.begin_value(JsonEvent::Value(JsonValueRef::Number(n)), span, found)?,
+            TokenKind::String(s) => {
+                validate_value_slot(&self.stack, self.root_done, at, found)?;
+                if let Some(EventFrame::Object(o)) = self.stack.last_mut()
+                    && o.pending_key.is_none()
+                {
+                    // validate_value_slot let a string through in key position
+                    o.pending_key = Some((s, span));
+                    o.state = ObjectState::AfterKey;
+                    return Ok(None);
+                }
+                self.begin_value(JsonEvent::Value(JsonValueRef::JsonString(s)), span, found)?
+            }
+            // the parsers lex with comments skipped; a caller's own lexer may not
+            TokenKind::Comment(_) => return Ok(None),
+            TokenKind::LBracket => self.begin_value(JsonEvent::StartArray, span, found)?,
+            TokenKind::LBrace => self.begin_value(JsonEvent::StartObject, span, found)?,
+            TokenKind::RBracket => {
+                close_array(&mut self.stack, self.trailing_commas, at, found)?;
+                self.complete_value();
+                self.span = span;
+                JsonEvent::EndArray
+            }
+            TokenKind::RBrace => {
+                close_object(&mut self.stack, self.trailing_commas, at, found)?;
+                self.complete_value();
+                self.span = span;
+                JsonEvent::EndObject
+            }
+            TokenKind::Colon => {
+                validate_colon(&mut self.stack, at)?;
+                return Ok(None);
+            }
+            TokenKind::Comma => {
+                validate_comma(&mut self.stack, at)?;
+                return Ok(None);
+            }
+            TokenKind::Eof => {
+                // between documents the input may end; zero documents is fine too
+                validate_end(&self.stack, self.root_done || self.multiple, at)?;
+                self.span = span;
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(event))
+    }
+
+    // A value (leaf or opening token) arrives: validate its slot, release the
+    // pending key of the enclosing object, then either complete the slot (leaf)
+    // or open a new frame (the slot completes when that frame closes).
+    // Inside an object the key is returned first and the value event queued.
+    fn begin_value(
+        &mut self,
+        event: JsonEvent<'a>,
+        span: Span,
+        found: TokenTag,
+    ) -> Result<JsonEvent<'a>, JsonParsingErrorV3> {
+        let at = span.start;
+        validate_value_slot(&self.stack, self.root_done, at, found)?;
+
+        let key = match self.stack.last_mut() {
+            Some(EventFrame::Object(o)) => o.pending_key.take(),
+            _ => None,
+        };
+
+        match event {
+            JsonEvent::StartArray => {
+                let frame = EventFrame::Array(ArrayFrame { state: ArrayState::Start });
+                push_frame(&mut self.stack, frame, at, &self.limits)?
+            }
+            JsonEvent::StartObject => {
+                let frame = EventFrame::Object(ObjectFrame { pending_key: None, state: ObjectState::Start });
+                push_frame(&mut self.stack, frame, at, &self.limits)?
+            }
+            _ => self.complete_value(),
+        }
+
+        if let Some((key, key_span)) = key {
+            self.queued = Some((event, span));
+            self.span = key_span;
+            return Ok(JsonEvent::Key(key));
+        }
+        self.span = span;
+        Ok(event)
+    }
+
+    // The slot on top of the stack (or the root) now holds a complete value.
+    fn complete_value(&mut self) {
+        match self.stack.last_mut() {
+            None => self.root_done = !self.multiple,
+            Some(EventFrame::Array(a)) => a.state = ArrayState::AfterValue,
+            Some(EventFrame::Object(o)) => o.state = ObjectState::AfterValue,
+        }
+    }
+}
+
+fn push_frame<'a>(
+    stack: &mut Vec<EventFrame<'a>>,
+    frame: EventFrame<'a>,
+    at: SourceLocation,
+    limits: &ParseLimits,
+) -> Result<(), JsonParsingErrorV3> {
+    if stack.len() >= limits.max_depth {
+        return Err(JsonParsingErrorV3::LimitExceeded { limit: Limit::Depth, at })
+    }
+    stack.push(frame);
+    Ok(())
+}
+
+/// Checks that a value (leaf or opening token) may appear at this point.
+/// Strings are let through in key position — the caller turns them into keys.
+fn validate_value_slot(
+    stack: &[EventFrame<'_>],
+    root_done: bool,
+    at: SourceLocation,
+    found: TokenTag,
+) -> Result<(), JsonParsingErrorV3> {
+    let Some(frame) = stack.last() else {
+        return match root_done {
+            false => Ok(()),
+            true => Err(JsonParsingErrorV3::ExpectedEOF { at, found }),
+        };
+    };
+
+    match frame {
+        EventFrame::Array(a) => match a.state {
+            ArrayState::Start | ArrayState::AfterComma => Ok(()),
+            ArrayState::AfterValue => {
+                Err(JsonParsingErrorV3::MissingComma { at, found, frame: FrameTypeTag::ArrayFrame })
+            }
+        },
+        EventFrame::Object(o) => match o.state {
+            ObjectState::Start | ObjectState::AfterComma => {
+                if found == TokenTag::String {
+                    Ok(())
+                } else {
+                    Err(JsonParsingErrorV3::ObjectKeyNotString { at, found })
+                }
+            }
+            ObjectState::AfterKey => Err(JsonParsingErrorV3::MissingColon { at, found }),
+            ObjectState::AfterColon => Ok(()),
+            ObjectState::AfterValue => {
+                Err(JsonParsingErrorV3::MissingComma { at, found, frame: FrameTypeTag::ObjectFrame })
+            }
+        },
+    }
+}
+
+fn close_array(
+    stack: &mut Vec<EventFrame<'_>>,
+    trailing_commas: bool,
+    at: SourceLocation,
+    found: TokenTag,
+) -> Result<(), JsonParsingErrorV3> {
+    let Some(frame) = stack.pop() else {
+        return Err(JsonParsingErrorV3::UnexpectedClosing { at, found })
+    };
+
+    match frame {
+        EventFrame::Array(a) => {
+            if a.state == ArrayState::AfterComma && !trailing_commas {
+                return Err(JsonParsingErrorV3::TrailingComma { at, found, frame: FrameTypeTag::ArrayFrame })
+            }
+            Ok(())
+        }
+        EventFrame::Object(_) => {
+            Err(JsonParsingErrorV3::MismatchedClosing { at, found, frame: FrameTypeTag::ObjectFrame })
+        }
+    }
+}
+
+fn close_object(
+    stack: &mut Vec<EventFrame<'_>>,
+    trailing_commas: bool,
+    at: SourceLocation,
+    found: TokenTag,
+) -> Result<(), JsonParsingErrorV3> {
+    let Some(frame) = stack.pop() else {
+        return Err(JsonParsingErrorV3::UnexpectedClosing { at, found })
+    };
+
+    match frame {
+        EventFrame::Object(o) => {
+            if let Some((key, _)) = o.pending_key {
+                return Err(JsonParsingErrorV3::ObjectKeyWithoutValue { at, found, key: key.into_owned() })
+            }
+            if o.state == ObjectState::AfterComma && !trailing_commas {
+                return Err(JsonParsingErrorV3::TrailingComma { at, found, frame: FrameTypeTag::ObjectFrame })
+            }
+            Ok(())
+        },
+        EventFrame::Array(_) => {
+            Err(JsonParsingErrorV3::MismatchedClosing { at, found, frame: FrameTypeTag::ArrayFrame })
+        }
+    }
+}
+
+fn validate_end(
+    stack: &[EventFrame<'_>],
+    root_done: bool,
+    at: SourceLocation,
+) -> Result<(), JsonParsingErrorV3> {
+    if let Some(frame) = stack.last() {
+        return Err(JsonParsingErrorV3::UnexpectedEOF { at, frame: Some(frame.tag()) })
+    }
+    if !root_done {
+        return Err(JsonParsingErrorV3::UnexpectedEOF { at, frame: None })
+    }
+    Ok(())
+}
+
+fn validate_colon(
+    stack: &mut [EventFrame<'_>],
+    at: SourceLocation,
+) -> Result<(), JsonParsingErrorV3> {
+    let Some(frame) = stack.last_mut() else {
+        return Err(JsonParsingErrorV3::ColonOutsideObject { at, frame: None })
+    };
+
+    match frame {
+        EventFrame::Object(o) => match o.state {
+            ObjectState::AfterKey => {
+                o.state = ObjectState::AfterColon;
+                Ok(())
+            }
+            ObjectState::Start | ObjectState::AfterComma => {
+                Err(JsonParsingErrorV3::ObjectKeyNotString { at, found: TokenTag::Colon })
+            }
+            ObjectState::AfterColon => Err(JsonParsingErrorV3::UnexpectedColon { at }),
+            ObjectState::AfterValue => Err(JsonParsingErrorV3::MissingComma {
+                at,
+                found: TokenTag::Colon,
+                frame: FrameTypeTag::ObjectFrame,
+            }),
+        },
+        EventFrame::Array(_) => {
+            Err(JsonParsingErrorV3::ColonOutsideObject { at, frame: Some(FrameTypeTag::ArrayFrame) })
+        }
+    }
+}
+
+fn validate_comma(
+    stack: &mut [EventFrame<'_>],
+    at: SourceLocation,
+) -> Result<(), JsonParsingErrorV3> {
+    let Some(frame) = stack.last_mut() else {
+        return Err(JsonParsingErrorV3::CommaOutsideStructure { at })
+    };
+
+    match frame {
+        EventFrame::Object(o) => match o.state {
+            // comma after a complete key-value pair: {"a": 1, ...
+            ObjectState::AfterValue => {
+                o.state = ObjectState::AfterComma;
+                Ok(())
+            }
+            // comma before any key-value pair: {, "a": 1}
+            ObjectState::Start => {
+                Err(JsonParsingErrorV3::LeadingComma { at, frame: FrameTypeTag::ObjectFrame })
+            }
+            // comma right after a key: {"a", 1}
+            ObjectState::AfterKey => {
+                Err(JsonParsingErrorV3::MissingColon { at, found: TokenTag::Comma })
+            }
+            // comma while waiting for value or key: {"a":, "b": 1} / {"a": 1,, "b": 2}
+            ObjectState::AfterColon | ObjectState::AfterComma => {
+                Err(JsonParsingErrorV3::UnexpectedComma { at, frame: FrameTypeTag::ObjectFrame })
+            }
+        },
+        EventFrame::Array(a) => match a.state {
+            ArrayState::AfterValue => {
+                a.state = ArrayState::AfterComma;
+                Ok(())
+            }
+            ArrayState::Start => {
+                Err(JsonParsingErrorV3::LeadingComma { at, frame: FrameTypeTag::ArrayFrame })
+            }
+            ArrayState::AfterComma => {
+                Err(JsonParsingErrorV3::UnexpectedComma { at, frame: FrameTypeTag::ArrayFrame })
+            }
+        },
+    }
+}
+
+// Limits hit inside the lexer are reported as LimitExceeded (not LexError),
+// so callers match a single variant whichever layer enforced the limit.
+fn next_token<'a>(tokens: &mut Lexer<'a>, cursor: &mut SourceLocation) -> Result<Option<Token<'a>>, JsonParsingErrorV3> {
+    let token = match tokens.next() {
+        None => return Ok(None),
+        Some(Ok(token)) => token,
+        Some(Err(LexerError::LimitExceeded { limit, at })) => {
+            return Err(JsonParsingErrorV3::LimitExceeded { limit, at })
+        }
+        Some(Err(e)) => return Err(JsonParsingErrorV3::LexError(e)),
+    };
+    *cursor = token.span.end;
+    Ok(Some(token))
+}
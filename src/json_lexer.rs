@@ -1,9 +1,13 @@
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::str::from_utf8;
-use crate::json_definitions::LexerError;
+use crate::json_definitions::{
+    CommentPolicy, Dialect, LexerError, Limit, LoneSurrogatePolicy, ParseLimits, ParseOptions,
+};
+use crate::json_number::JsonNumber;
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum TokenKind {
+pub enum TokenKind<'a> {
     // Punctuation (single-byte structural tokens)
     LBrace,   // '{'
     RBrace,   // '}'
@@ -17,11 +21,35 @@ pub enum TokenKind {
     Null,       // "null"
 
     // Atoms (already decoded/validated by the lexer)
-    String(String), // JSON string value (unescaped, UTF-8 validated)
-    Number(f64),    // JSON number value (grammar-validated, parsed)
+    String(Cow<'a, str>), // JSON string value (unescaped, UTF-8 validated, borrowed if escape-free)
+    Number(JsonNumber), // JSON number value (grammar-validated, exact lexeme kept)
+
+    // Trivia (only with CommentPolicy::Tokens)
+    Comment(Cow<'a, str>), // the whole comment, "//" or "/*" "*/" included (borrowed if valid UTF-8)
+
     Eof,            // end of input sentinel
 }
 
+impl TokenKind<'_> {
+    /// Detaches the token from the input buffer (copies a borrowed string).
+    pub fn into_owned(self) -> TokenKind<'static> {
+        match self {
+            TokenKind::LBrace => TokenKind::LBrace,
+            TokenKind::RBrace => TokenKind::RBrace,
+            TokenKind::LBracket => TokenKind::LBracket,
+            TokenKind::RBracket => TokenKind::RBracket,
+            TokenKind::Colon => TokenKind::Colon,
+            TokenKind::Comma => TokenKind::Comma,
+            TokenKind::Bool(b) => TokenKind::Bool(b),
+            TokenKind::Null => TokenKind::Null,
+            TokenKind::String(s) => TokenKind::String(Cow::Owned(s.into_owned())),
+            TokenKind::Number(n) => TokenKind::Number(n),
+            TokenKind::Comment(s) => TokenKind::Comment(Cow::Owned(s.into_owned())),
+            TokenKind::Eof => TokenKind::Eof,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 #[derive(Clone)]
 pub enum NumberError {
@@ -36,42 +64,358 @@ pub enum NumberError {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum StringError {
-    Unterminated,                 // hit EOF before closing quote
-    InvalidEscape { found: u8 },  // e.g. "\x"
-    TrailingBackslash,            // ends right after '\'
-    ControlChar { found: u8 },    // raw < 0x20 in string
-    InvalidUtf8,                  // if you decide to validate input bytes in strings
-    InvalidUnicodeEscape,         // "\u" not followed by 4 hex
-    SurrogateNotAllowed,          // D800–DFFF
+    Unterminated,                    // hit EOF before closing quote
+    InvalidEscape { found: u8 },     // e.g. "\x"
+    TrailingBackslash,               // ends right after '\'
+    ControlChar { found: u8 },       // raw < 0x20 in string
+    InvalidUtf8,                     // if you decide to validate input bytes in strings
+    InvalidUnicodeEscape,            // "\u" not followed by 4 hex
+    LoneHighSurrogate { code: u16 }, // D800–DBFF not followed by an escaped DC00–DFFF
+    LoneLowSurrogate { code: u16 },  // DC00–DFFF without a high surrogate before it
+}
+
+/// Position in the source: byte offset plus 1-based line and column.
+/// Column counts chars (not bytes) from the start of the line.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceLocation {
+    pub const START: SourceLocation = SourceLocation { offset: 0, line: 1, column: 1 };
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Span {
-    pub start: usize,
-    pub end: usize,
+    pub start: SourceLocation,
+    pub end: SourceLocation, // exclusive
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
-    pub kind: TokenKind,
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
     pub span: Span,
 }
 
-pub fn lex_all(input: &[u8]) -> Result<VecDeque<Token>, LexerError> {
-    let mut cursor = 0;
-    let mut tokens = VecDeque::new();
+// Translates byte offsets into line/column incrementally.
+// It remembers the last location it produced and only scans the bytes
+// in between, so lexing a whole file stays linear. Asking for an offset
+// behind the last one (rare — only some error paths) rescans from the start.
+pub(crate) struct LineTracker {
+    last: SourceLocation,
+}
+
+impl LineTracker {
+    pub(crate) fn new() -> Self {
+        Self { last: SourceLocation::START }
+    }
+
+    pub(crate) fn locate(&mut self, bytes: &[u8], offset: usize) -> SourceLocation {
+        if offset < self.last.offset {
+            self.last = SourceLocation::START;
+        }
+
+        let mut loc = self.last;
+        for &b in &bytes[loc.offset..offset.min(bytes.len())] {
+            if b == b'\n' {
+                loc.line += 1;
+                loc.column = 1;
+            } else if b & 0xC0 != 0x80 {
+                // count only the first byte of every UTF-8 sequence
+                loc.column += 1;
+            }
+        }
+        loc.offset = offset;
+
+        self.last = loc;
+        loc
+    }
+
+    fn span(&mut self, bytes: &[u8], start: usize, end: usize) -> Span {
+        Span { start: self.locate(bytes, start), end: self.locate(bytes, end) }
+    }
+}
+
+/// Produces tokens on demand, one `next()` at a time, so a parser never
+/// holds more than the token it is looking at. The last token is always
+/// `Eof`; after it — or after the first error — the iterator is exhausted.
+pub struct Lexer<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    lines: LineTracker,
+    options: ParseOptions,
+    finished: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self::with_limits(input, &ParseLimits::UNLIMITED)
+    }
+
+    /// Rejects input that breaks the byte-based limits (document size,
+    /// string length, number length) with `LimitExceeded`.
+    pub fn with_limits(input: &'a [u8], limits: &ParseLimits) -> Self {
+        Self::with_options(input, &ParseOptions { limits: *limits, ..ParseOptions::default() })
+    }
+
+    /// Uses the byte-based limits, the lone surrogate policy, the dialect and the comment policy;
+    /// the other options are for parsers.
+    pub fn with_options(input: &'a [u8], options: &ParseOptions) -> Self {
+        Self { bytes: input, cursor: 0, lines: LineTracker::new(), options: *options, finished: false }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        // Checked up front (cursor still 0) so nothing is lexed from an oversized document
+        let limits = &self.options.limits;
+        if self.cursor == 0 && self.bytes.len() > limits.max_document_bytes {
+            self.finished = true;
+            return Some(Err(LexerError::LimitExceeded {
+                limit: Limit::DocumentBytes,
+                at: self.lines.locate(self.bytes, limits.max_document_bytes),
+            }));
+        }
+
+        let result = next_token(self.bytes, &mut self.cursor, &mut self.lines, &self.options);
+        self.finished = match &result {
+            Ok(token) => token.kind == TokenKind::Eof,
+            Err(_) => true,
+        };
+        Some(result)
+    }
+}
+
+pub fn lex_all(input: &[u8]) -> Result<VecDeque<Token<'_>>, LexerError> {
+    Lexer::new(input).collect()
+}
+
+pub fn lex_all_with_limits<'a>(input: &'a [u8], limits: &ParseLimits) -> Result<VecDeque<Token<'a>>, LexerError> {
+    Lexer::with_limits(input, limits).collect()
+}
+
+/// Lexer for input that arrives in pieces (network reads, file blocks).
+///
+/// `feed` appends bytes; `next_token` returns the tokens they complete and
+/// None once the rest of the buffer could still be the start of a longer
+/// token — a string without its closing quote, a number that may have more
+/// digits, a half-received escape or literal. Those bytes are kept and lexed
+/// again when the next chunk arrives, so a token split across chunks comes out
+/// exactly as it would from `Lexer`, with document-wide locations. After
+/// `finish` nothing is held back: the last token is `Eof`, as with `Lexer`.
+pub struct PushLexer {
+    pending: Vec<u8>,           // bytes from `cursor` on are not lexed yet
+    cursor: usize,
+    base: SourceLocation,       // document location of pending[cursor]
+    fed: usize,
+    options: ParseOptions,
+    over_limit: bool,           // bytes past max_document_bytes were dropped
+    awaiting_quote: Option<u8>, // pending starts with a string this quote has not closed yet
+    at_end: bool,
+    finished: bool,
+}
+
+impl PushLexer {
+    pub fn new() -> Self {
+        Self::with_limits(&ParseLimits::UNLIMITED)
+    }
+
+    pub fn with_limits(limits: &ParseLimits) -> Self {
+        Self::with_options(&ParseOptions { limits: *limits, ..ParseOptions::default() })
+    }
+
+    pub fn with_options(options: &ParseOptions) -> Self {
+        Self {
+            pending: Vec::new(),
+            cursor: 0,
+            base: SourceLocation::START,
+            fed: 0,
+            options: *options,
+            over_limit: false,
+            awaiting_quote: None,
+            at_end: false,
+            finished: false,
+        }
+    }
+
+    /// Appends the next chunk of input.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        // Drop what has been lexed already, so the buffer only holds one partial token
+        self.pending.drain(..self.cursor);
+        self.cursor = 0;
+
+        // Bytes past the document limit are never lexed; next_token reports
+        // LimitExceeded once it runs out of the bytes that were kept
+        let limits = &self.options.limits;
+        let room = limits.max_document_bytes - self.fed;
+        let kept = &chunk[..chunk.len().min(room)];
+        self.over_limit |= kept.len() < chunk.len();
+        self.fed += kept.len();
+
+        // Rescanning a long string on every chunk would be quadratic: only try
+        // again once a quote arrives or the string limit may have been crossed
+        let quote_arrived = self.awaiting_quote.is_some_and(|quote| kept.contains(&quote));
+        if quote_arrived || self.pending.len() + kept.len() > limits.max_string_bytes {
+            self.awaiting_quote = None;
+        }
+        self.pending.extend_from_slice(kept);
+    }
+
+    /// Marks the end of the input: whatever is still pending is lexed as is.
+    pub fn finish(&mut self) {
+        self.at_end = true;
+    }
+
+    /// Total number of bytes fed so far (up to the document limit).
+    pub fn bytes_fed(&self) -> usize {
+        self.fed
+    }
+
+    /// The next complete token, or None until more input is fed (or, after
+    /// `finish`, once `Eof` or an error has been returned). Tokens own their
+    /// strings: the buffer they were lexed from is reused by the next feed.
+    pub fn next_token(&mut self) -> Option<Result<Token<'static>, LexerError>> {
+        if self.finished || (self.awaiting_quote.is_some() && !self.at_end && !self.over_limit) {
+            return None;
+        }
+
+        let bytes = &self.pending[self.cursor..];
+        let mut lines = LineTracker::new();
+        let mut local = 0;
+        let result = next_token(bytes, &mut local, &mut lines, &self.options);
+
+        // an unterminated block comment may be closed by the next chunk, wherever it starts
+        let at_buffer_end = |e: &LexerError| {
+            matches!(e, LexerError::UnterminatedBlockComment { .. })
+                || error_location(e).is_some_and(|at| at.offset == bytes.len())
+        };
+        let mut ws = 0;
+        let _ = skip_ws(bytes, &mut ws, &mut LineTracker::new(), &self.options, false);
+
+        // A JSON5 word also depends on what follows it: a ':' makes it a key.
+        // The cursor is left after the word, whether it was accepted or not
+        let word = self.options.dialect == Dialect::Json5 && bytes.get(ws).is_some_and(|&b| starts_word(b));
+        let word_at_end = word && {
+            let mut next = local;
+            let look_past = self.options.skipping_comments();
+            let settled = skip_ws(bytes, &mut next, &mut LineTracker::new(), &look_past, true);
+            next == bytes.len() || settled.is_err_and(|e| at_buffer_end(&e))
+        };
+
+        // Reaching the end of the buffer means more input could change the result
+        let reached_end = word_at_end || match &result {
+            Ok(token) => {
+                matches!(token.kind, TokenKind::Number(_) | TokenKind::Comment(_) | TokenKind::Eof)
+                    && local == bytes.len()
+            }
+            Err(e) => local == bytes.len() || at_buffer_end(e),
+        };
+
+        if reached_end && self.over_limit {
+            self.finished = true;
+            let at = self.locate(self.options.limits.max_document_bytes - self.base.offset);
+            return Some(Err(LexerError::LimitExceeded { limit: Limit::DocumentBytes, at }));
+        }
+
+        if reached_end && !self.at_end {
+            // whitespace before the partial token is settled, skip it for good;
+            // an unterminated comment stays pending like a partial token
+            self.base = self.locate(ws);
+            self.cursor += ws;
+            self.awaiting_quote = self.pending.get(self.cursor).copied().filter(|&b| is_quote(b, &self.options));
+            return None;
+        }
+
+        let result = match result {
+            Ok(token) => {
+                let span = Span { start: relocate(token.span.start, self.base), end: relocate(token.span.end, self.base) };
+                self.cursor += local;
+                self.base = span.end;
+                self.finished = token.kind == TokenKind::Eof;
+                Ok(Token { kind: token.kind.into_owned(), span })
+            }
+            Err(e) => {
+                self.finished = true;
+                Err(relocate_error(e, self.base))
+            }
+        };
+        Some(result)
+    }
+
+    // Document location of pending[cursor + offset]
+    fn locate(&self, offset: usize) -> SourceLocation {
+        let mut lines = LineTracker::new();
+        relocate(lines.locate(&self.pending[self.cursor..], offset), self.base)
+    }
+}
+
+impl Default for PushLexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    loop {
-        let tok = next_token(input, &mut cursor)?;
-        let is_eof = matches!(tok.kind, TokenKind::Eof);
-        tokens.push_back(tok);
-        if is_eof { break; }
+// Turns a location inside the buffer (which starts at `base`) into a document location.
+// Also used for pieces of a larger input parsed on their own (JSON Lines lines,
+// text sequence records).
+pub(crate) fn relocate(loc: SourceLocation, base: SourceLocation) -> SourceLocation {
+    let offset = base.offset + loc.offset;
+    if loc.line == 1 {
+        SourceLocation { offset, line: base.line, column: base.column + loc.column - 1 }
+    } else {
+        SourceLocation { offset, line: base.line + loc.line - 1, column: loc.column }
     }
+}
 
-    Ok(tokens)
+fn relocate_error(err: LexerError, base: SourceLocation) -> LexerError {
+    match err {
+        LexerError::CursorOutOfBounds { cursor, len } => {
+            LexerError::CursorOutOfBounds { cursor: base.offset + cursor, len: base.offset + len }
+        }
+        LexerError::UnexpectedEof { at, expected } => LexerError::UnexpectedEof { at: relocate(at, base), expected },
+        LexerError::UnexpectedByte { at, found, expected } => {
+            LexerError::UnexpectedByte { at: relocate(at, base), found, expected }
+        }
+        LexerError::InvalidLiteral { at, expected } => LexerError::InvalidLiteral { at: relocate(at, base), expected },
+        LexerError::InvalidString { at, reason } => LexerError::InvalidString { at: relocate(at, base), reason },
+        LexerError::InvalidNumber { at, reason } => LexerError::InvalidNumber { at: relocate(at, base), reason },
+        LexerError::UnterminatedBlockComment { at } => LexerError::UnterminatedBlockComment { at: relocate(at, base) },
+        LexerError::LimitExceeded { limit, at } => LexerError::LimitExceeded { limit, at: relocate(at, base) },
+    }
+}
+
+fn error_location(err: &LexerError) -> Option<SourceLocation> {
+    match err {
+        LexerError::CursorOutOfBounds { .. } => None,
+        LexerError::UnexpectedEof { at, .. }
+        | LexerError::UnexpectedByte { at, .. }
+        | LexerError::InvalidLiteral { at, .. }
+        | LexerError::InvalidString { at, .. }
+        | LexerError::InvalidNumber { at, .. }
+        | LexerError::UnterminatedBlockComment { at }
+        | LexerError::LimitExceeded { at, .. } => Some(*at),
+    }
 }
 
-fn skip_ws(json_bytes_string: &[u8], current_index: &mut usize) {
+// Comments are skipped with CommentPolicy::Skip and in JSON5, which also skips
+// the rest of ECMAScript's whitespace. On an unterminated comment the cursor
+// is left at its '/'; a line comment that runs into the end of the bytes is
+// unterminated too unless the input is complete.
+fn skip_ws(
+    json_bytes_string: &[u8],
+    current_index: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+    input_complete: bool,
+) -> Result<(), LexerError> {
     while *current_index < json_bytes_string.len() {
         let current_byte = json_bytes_string[*current_index];
         match current_byte {
@@ -79,20 +423,135 @@ fn skip_ws(json_bytes_string: &[u8], current_index: &mut usize) {
                 *current_index += 1;
             }
 
-            _ => break
+            b'/' if skips_comments(options) => {
+                let start = *current_index;
+                skip_comment(json_bytes_string, current_index, lines)?;
+                if *current_index == json_bytes_string.len() && !input_complete {
+                    *current_index = start;
+                    break;
+                }
+            }
+
+            _ if options.dialect == Dialect::Json => break,
+
+            _ => match json5_space_len(&json_bytes_string[*current_index..]) {
+                0 => break,
+                n => *current_index += n,
+            },
         }
     }
+    Ok(())
 }
 
-fn consume_one(cursor: &mut usize) -> Span {
+// We are at '/'. A line comment ends before the line break, a block comment after "*/".
+fn skip_comment(bytes: &[u8], cursor: &mut usize, lines: &mut LineTracker) -> Result<(), LexerError> {
+    let body = *cursor + 2;
+    match bytes.get(*cursor + 1) {
+        Some(b'/') => {
+            let mut end = body;
+            while end < bytes.len() && line_terminator_len(&bytes[end..]) == 0 {
+                end += 1;
+            }
+            *cursor = end;
+            Ok(())
+        }
+        Some(b'*') => match bytes[body..].windows(2).position(|w| w == b"*/") {
+            Some(i) => {
+                *cursor = body + i + 2;
+                Ok(())
+            }
+            None => Err(LexerError::UnterminatedBlockComment { at: lines.locate(bytes, *cursor) }),
+        },
+        Some(&found) => Err(LexerError::UnexpectedByte {
+            at: lines.locate(bytes, *cursor + 1),
+            found,
+            expected: "'/' or '*' after '/'",
+        }),
+        None => Err(LexerError::UnexpectedEof { at: lines.locate(bytes, bytes.len()), expected: "/ or *" }),
+    }
+}
+
+// Length of the JSON5-only whitespace char at the start of `rest`, 0 if there is none:
+// \v, \f, U+2028, U+2029, U+FEFF and the Unicode space separators (Zs).
+fn json5_space_len(rest: &[u8]) -> usize {
+    match rest[0] {
+        0x0B | 0x0C => 1,
+        0x80.. => match char_at(rest, 0) {
+            Some(c @ ('\u{A0}' | '\u{1680}' | '\u{2000}'..='\u{200A}' | '\u{2028}' | '\u{2029}' | '\u{202F}'
+                | '\u{205F}' | '\u{3000}' | '\u{FEFF}')) => c.len_utf8(),
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+// Length of the line break at the start of `rest` (\n, \r, \r\n, U+2028, U+2029), 0 if there is none.
+fn line_terminator_len(rest: &[u8]) -> usize {
+    match rest {
+        [b'\r', b'\n', ..] => 2,
+        [b'\n' | b'\r', ..] => 1,
+        [0xE2, 0x80, 0xA8 | 0xA9, ..] => 3,
+        _ => 0,
+    }
+}
+
+// The char starting at bytes[index], None if the bytes there are not valid UTF-8.
+fn char_at(bytes: &[u8], index: usize) -> Option<char> {
+    let rest = &bytes[index..bytes.len().min(index + 4)];
+    let valid = match from_utf8(rest) {
+        Ok(s) => s,
+        Err(e) => from_utf8(&rest[..e.valid_up_to()]).ok()?,
+    };
+    valid.chars().next()
+}
+
+fn skips_comments(options: &ParseOptions) -> bool {
+    match options.comments {
+        CommentPolicy::Skip => true,
+        CommentPolicy::Error => options.dialect == Dialect::Json5,
+        CommentPolicy::Tokens => false,
+    }
+}
+
+// A comment as a token (CommentPolicy::Tokens); we are at its '/'.
+fn consume_comment<'a>(bytes: &'a [u8], cursor: &mut usize, lines: &mut LineTracker) -> Result<Token<'a>, LexerError> {
+    let start = *cursor;
+    skip_comment(bytes, cursor, lines)?;
+    Ok(Token {
+        kind: TokenKind::Comment(String::from_utf8_lossy(&bytes[start..*cursor])),
+        span: lines.span(bytes, start, *cursor),
+    })
+}
+
+fn is_quote(b: u8, options: &ParseOptions) -> bool {
+    b == b'"' || (b == b'\'' && options.dialect == Dialect::Json5)
+}
+
+// First byte of a JSON5 word: an identifier (or escape in one), true, false, null, Infinity, NaN.
+// Non-ASCII bytes are let through; consume_word decides on the whole char.
+fn starts_word(b: u8) -> bool {
+    b.is_ascii_alphabetic() || matches!(b, b'$' | b'_' | b'\\') || b >= 0x80
+}
+
+// ECMAScript identifiers, approximated with Unicode's alphabetic/alphanumeric
+// properties instead of ID_Start/ID_Continue.
+fn is_identifier_char(c: char, first: bool) -> bool {
+    match first {
+        true => c.is_alphabetic() || c == '$' || c == '_',
+        false => c.is_alphanumeric() || matches!(c, '$' | '_' | '\u{200C}' | '\u{200D}'),
+    }
+}
+
+fn consume_one(bytes: &[u8], cursor: &mut usize, lines: &mut LineTracker) -> Span {
     let start = *cursor;
     *cursor += 1;
-    Span { start, end: *cursor } // [start, end)
+    lines.span(bytes, start, *cursor) // [start, end)
 }
 
-pub fn consume_literal(
+fn consume_literal(
     bytes: &[u8],
     cursor: &mut usize,
+    lines: &mut LineTracker,
     lit: &[u8],
     expected_name: &'static str,
 ) -> Result<Span, LexerError> {
@@ -101,22 +560,22 @@ pub fn consume_literal(
 
     if end > bytes.len() {
         return Err(LexerError::UnexpectedEof {
-            at: bytes.len(),
+            at: lines.locate(bytes, bytes.len()),
             expected: expected_name,
         });
     }
 
     if &bytes[start..end] != lit {
         return Err(LexerError::InvalidLiteral {
-            at: start,
+            at: lines.locate(bytes, start),
             expected: expected_name,
         });
     }
 
     *cursor = end;
-    Ok(Span { start, end })
+    Ok(lines.span(bytes, start, end))
 }
-fn consume_bool(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
+fn consume_bool<'a>(bytes: &[u8], cursor: &mut usize, lines: &mut LineTracker) -> Result<Token<'a>, LexerError> {
     const TRUE_BYTES: &[u8] = b"true";
     const FALSE_BYTES: &[u8] = b"false";
 
@@ -124,34 +583,39 @@ fn consume_bool(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
 
     match b {
         b't' => {
-            let span = consume_literal(bytes, cursor, TRUE_BYTES, "true")?;
+            let span = consume_literal(bytes, cursor, lines, TRUE_BYTES, "true")?;
             Ok(Token { kind: TokenKind::Bool(true), span })
         }
         b'f' => {
-            let span = consume_literal(bytes, cursor, FALSE_BYTES, "false")?;
+            let span = consume_literal(bytes, cursor, lines, FALSE_BYTES, "false")?;
             Ok(Token { kind: TokenKind::Bool(false), span })
         }
         _ => Err(LexerError::UnexpectedByte {
-            at: *cursor,
+            at: lines.locate(bytes, *cursor),
             found: b,
             expected: "bool literal",
         }),
     }
 }
 
-fn consume_null(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
+fn consume_null<'a>(bytes: &[u8], cursor: &mut usize, lines: &mut LineTracker) -> Result<Token<'a>, LexerError> {
     const NULL_LITERAL: &[u8] = b"null";
-    let span = consume_literal(bytes, cursor, NULL_LITERAL, "null")?;
+    let span = consume_literal(bytes, cursor, lines, NULL_LITERAL, "null")?;
     Ok(Token { kind: TokenKind::Null, span })
 }
 
-fn next_token(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
-    skip_ws(bytes, cursor);
+fn next_token<'a>(
+    bytes: &'a [u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+) -> Result<Token<'a>, LexerError> {
+    skip_ws(bytes, cursor, lines, options, true)?;
 
     if *cursor == bytes.len() {
         return Ok(Token {
             kind: TokenKind::Eof,
-            span: Span { start: *cursor, end: *cursor },
+            span: lines.span(bytes, *cursor, *cursor),
         });
     }
 
@@ -163,35 +627,45 @@ fn next_token(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
     }
 
     let current_byte = bytes[*cursor];
+    let json5 = options.dialect == Dialect::Json5;
 
     match current_byte {
-        b'-' | b'0'..=b'9' => consume_number(bytes, cursor),
-        b'\"' => consume_string(bytes, cursor),
-        b'n' => consume_null(bytes, cursor),
-        b't' | b'f' => consume_bool(bytes, cursor),
-
-        b'[' => Ok(Token { kind: TokenKind::LBracket, span: consume_one(cursor) }),
-        b']' => Ok(Token { kind: TokenKind::RBracket, span: consume_one(cursor) }),
-        b'{' => Ok(Token { kind: TokenKind::LBrace, span: consume_one(cursor) }),
-        b'}' => Ok(Token { kind: TokenKind::RBrace, span: consume_one(cursor) }),
-        b':' => Ok(Token { kind: TokenKind::Colon, span: consume_one(cursor) }),
-        b',' => Ok(Token { kind: TokenKind::Comma, span: consume_one(cursor) }),
+        b'-' | b'0'..=b'9' => consume_number(bytes, cursor, lines, options),
+        b'+' | b'.' if json5 => consume_number(bytes, cursor, lines, options),
+        b'\"' => consume_string(bytes, cursor, lines, options),
+        b'\'' if json5 => consume_string(bytes, cursor, lines, options),
+        b if json5 && starts_word(b) => consume_word(bytes, cursor, lines, options),
+        b'/' if options.comments == CommentPolicy::Tokens => consume_comment(bytes, cursor, lines),
+        b'n' => consume_null(bytes, cursor, lines),
+        b't' | b'f' => consume_bool(bytes, cursor, lines),
+
+        b'[' => Ok(Token { kind: TokenKind::LBracket, span: consume_one(bytes, cursor, lines) }),
+        b']' => Ok(Token { kind: TokenKind::RBracket, span: consume_one(bytes, cursor, lines) }),
+        b'{' => Ok(Token { kind: TokenKind::LBrace, span: consume_one(bytes, cursor, lines) }),
+        b'}' => Ok(Token { kind: TokenKind::RBrace, span: consume_one(bytes, cursor, lines) }),
+        b':' => Ok(Token { kind: TokenKind::Colon, span: consume_one(bytes, cursor, lines) }),
+        b',' => Ok(Token { kind: TokenKind::Comma, span: consume_one(bytes, cursor, lines) }),
 
         _ => Err(LexerError::UnexpectedByte {
-            at: *cursor,
+            at: lines.locate(bytes, *cursor),
             found: current_byte,
             expected: "token",
         }),
     }
 }
 
-fn consume_escape_char(bytes: &[u8], cursor: &mut usize) -> Result<([u8; 4], usize), LexerError> {
+fn consume_escape_char(
+    bytes: &[u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+) -> Result<([u8; 4], usize), LexerError> {
     // We are at '\' already.
     *cursor += 1;
 
     if *cursor >= bytes.len() {
         return Err(LexerError::InvalidString {
-            at: *cursor,
+            at: lines.locate(bytes, *cursor),
             reason: StringError::TrailingBackslash,
         });
     }
@@ -241,30 +715,157 @@ fn consume_escape_char(bytes: &[u8], cursor: &mut usize) -> Result<([u8; 4], usi
         }
         b'u' => {
             // cursor currently points at 'u'
-            consume_unicode(bytes, cursor)
+            consume_unicode(bytes, cursor, lines, options)
         }
+        _ if options.dialect == Dialect::Json5 => consume_json5_escape(bytes, cursor, lines),
         _ => Err(LexerError::InvalidString {
-            at: *cursor,
+            at: lines.locate(bytes, *cursor),
             reason: StringError::InvalidEscape { found: bytes[*cursor] },
         }),
     }
 }
 
-fn consume_unicode(bytes: &[u8], cursor: &mut usize) -> Result<([u8; 4], usize), LexerError> {
-    // We are at 'u'
+fn consume_unicode(
+    bytes: &[u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+) -> Result<([u8; 4], usize), LexerError> {
+    let lone_surrogates = options.lone_surrogates;
+    // We are at 'u', the escape started at the '\' before it
+    let escape_start = *cursor - 1;
     *cursor += 1;
 
+    let code = consume_hex4(bytes, cursor, lines)?;
+
+    let scalar = match code {
+        0xD800..=0xDBFF => {
+            let rest = &bytes[*cursor..];
+            // Input that ends here (or on a lone '\') is an unterminated string
+            // whatever comes next; report it like the string loop would
+            if rest.is_empty() {
+                return Err(LexerError::InvalidString {
+                    at: lines.locate(bytes, bytes.len()),
+                    reason: StringError::Unterminated,
+                });
+            }
+            if rest == b"\\" {
+                return Err(LexerError::InvalidString {
+                    at: lines.locate(bytes, bytes.len()),
+                    reason: StringError::TrailingBackslash,
+                });
+            }
+
+            let pair_start = *cursor;
+            let low = match rest.starts_with(b"\\u") {
+                true => {
+                    *cursor += 2;
+                    Some(consume_hex4(bytes, cursor, lines)?)
+                }
+                false => None,
+            };
+
+            match low {
+                Some(low @ 0xDC00..=0xDFFF) => 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00),
+                _ => {
+                    // the next escape (if any) is decoded on its own
+                    *cursor = pair_start;
+                    let reason = StringError::LoneHighSurrogate { code: code as u16 };
+                    lone_surrogate(bytes, escape_start, lines, lone_surrogates, reason)?
+                }
+            }
+        }
+        0xDC00..=0xDFFF => {
+            let reason = StringError::LoneLowSurrogate { code: code as u16 };
+            lone_surrogate(bytes, escape_start, lines, lone_surrogates, reason)?
+        }
+        _ => code,
+    };
+
+    let unicode_char = match char::from_u32(scalar) {
+        Some(c) => c,
+        None => {
+            return Err(LexerError::InvalidString {
+                at: lines.locate(bytes, *cursor),
+                reason: StringError::InvalidUnicodeEscape,
+            });
+        }
+    };
+
+    let mut buf = [0u8; 4];
+    let len = unicode_char.encode_utf8(&mut buf).len();
+
+    Ok((buf, len))
+}
+
+// The escapes JSON5 adds (ECMAScript's): \' \v \0 \xHH, a backslash before a
+// line break (a line continuation, which stands for nothing), and any other
+// character except a digit standing for itself. We are at the char after '\'.
+fn consume_json5_escape(
+    bytes: &[u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+) -> Result<([u8; 4], usize), LexerError> {
+    let mut out = [0u8; 4];
+    let at = *cursor;
+
+    let simple = match bytes[at] {
+        b'\'' => Some(b'\''),
+        b'v' => Some(0x0B),
+        b'0' if !bytes.get(at + 1).is_some_and(u8::is_ascii_digit) => Some(0),
+        _ => None,
+    };
+    if let Some(b) = simple {
+        out[0] = b;
+        *cursor += 1;
+        return Ok((out, 1));
+    }
+
+    let invalid = |lines: &mut LineTracker| LexerError::InvalidString {
+        at: lines.locate(bytes, at),
+        reason: StringError::InvalidEscape { found: bytes[at] },
+    };
+
+    match bytes[at] {
+        b'0'..=b'9' => Err(invalid(lines)),
+        b'x' => {
+            let digits = bytes.get(at + 1..at + 3).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+            let Some(code) = digits.and_then(|hex| u8::from_str_radix(from_utf8(hex).ok()?, 16).ok()) else {
+                return Err(invalid(lines));
+            };
+            *cursor += 3;
+            let len = char::from(code).encode_utf8(&mut out).len();
+            Ok((out, len))
+        }
+        _ => match line_terminator_len(&bytes[at..]) {
+            0 => {
+                // copy the whole UTF-8 sequence; the string is validated once it is closed
+                let len = char_at(bytes, at).map_or(1, char::len_utf8);
+                out[..len].copy_from_slice(&bytes[at..at + len]);
+                *cursor += len;
+                Ok((out, len))
+            }
+            n => {
+                *cursor += n;
+                Ok((out, 0))
+            }
+        },
+    }
+}
+
+// Reads the 4 hex digits of a \u escape.
+fn consume_hex4(bytes: &[u8], cursor: &mut usize, lines: &mut LineTracker) -> Result<u32, LexerError> {
     // Need 4 hex digits
     if *cursor + 4 > bytes.len() {
         return Err(LexerError::InvalidString {
-            at: bytes.len(),
+            at: lines.locate(bytes, bytes.len()),
             reason: StringError::InvalidUnicodeEscape,
         });
     }
 
-    let mut nibbles = [0u8; 4];
+    let mut code: u32 = 0;
 
-    for i in 0..4 {
+    for _ in 0..4 {
         let b = bytes[*cursor];
 
         let v = match b {
@@ -273,77 +874,86 @@ fn consume_unicode(bytes: &[u8], cursor: &mut usize) -> Result<([u8; 4], usize),
             b'A'..=b'F' => (b - b'A') + 0xA,
             _ => {
                 return Err(LexerError::InvalidString {
-                    at: *cursor,
+                    at: lines.locate(bytes, *cursor),
                     reason: StringError::InvalidUnicodeEscape,
                 });
             }
         };
 
-        nibbles[i] = v;
+        code = (code << 4) | v as u32;
         *cursor += 1;
     }
 
-    let code: u32 =
-            ((nibbles[0] as u32) << 12) |
-            ((nibbles[1] as u32) << 8)  |
-            ((nibbles[2] as u32) << 4)  |
-            ((nibbles[3] as u32) << 0);
+    Ok(code)
+}
 
-    if code >= 0xD800 && code <= 0xDFFF {
-        return Err(LexerError::InvalidString {
-            at: *cursor,
-            reason: StringError::SurrogateNotAllowed,
-        });
+// A surrogate that is not part of a pair: U+FFFD or an error at its escape.
+fn lone_surrogate(
+    bytes: &[u8],
+    escape_start: usize,
+    lines: &mut LineTracker,
+    policy: LoneSurrogatePolicy,
+    reason: StringError,
+) -> Result<u32, LexerError> {
+    match policy {
+        LoneSurrogatePolicy::Replace => Ok(char::REPLACEMENT_CHARACTER as u32),
+        LoneSurrogatePolicy::Error => Err(LexerError::InvalidString { at: lines.locate(bytes, escape_start), reason }),
     }
-
-    let unicode_char = match char::from_u32(code) {
-        Some(c) => c,
-        None => {
-            return Err(LexerError::InvalidString {
-                at: *cursor,
-                reason: StringError::InvalidUnicodeEscape,
-            });
-        }
-    };
-
-    let mut buf = [0u8; 4];
-    let len = unicode_char.encode_utf8(&mut buf).len();
-
-    Ok((buf, len))
 }
 
-fn consume_string(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
+fn consume_string<'a>(
+    bytes: &'a [u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+) -> Result<Token<'a>, LexerError> {
     let string_start = *cursor;
+    let quote = bytes[string_start]; // '"', or '\'' in JSON5
 
     // consume opening quote
     *cursor += 1;
 
+    // Only filled once an escape shows up; until then the string is a plain
+    // slice of the input and is borrowed instead of copied
     let mut out: Vec<u8> = Vec::new();
+    let mut escaped = false;
     let mut run_start = *cursor;
     let mut closed = false;
 
     while *cursor < bytes.len() {
+        // bytes consumed so far, not counting the opening quote
+        if *cursor - string_start - 1 > options.limits.max_string_bytes {
+            return Err(LexerError::LimitExceeded {
+                limit: Limit::StringBytes,
+                at: lines.locate(bytes, string_start),
+            });
+        }
+
         match bytes[*cursor] {
             b'\\' => {
                 out.extend_from_slice(&bytes[run_start..*cursor]);
 
-                let (buf, n) = consume_escape_char(bytes, cursor)?;
+                let (buf, n) = consume_escape_char(bytes, cursor, lines, options)?;
                 out.extend_from_slice(&buf[..n]);
 
+                escaped = true;
                 run_start = *cursor;
             }
 
-            b'"' => {
-                out.extend_from_slice(&bytes[run_start..*cursor]);
+            b if b == quote => {
+                if escaped {
+                    out.extend_from_slice(&bytes[run_start..*cursor]);
+                }
 
                 *cursor += 1;
                 closed = true;
                 break;
             }
 
-            0x00..=0x1F => {
+            // JSON5 only rules out raw line breaks
+            b @ 0x00..=0x1F if options.dialect == Dialect::Json || b == b'\n' || b == b'\r' => {
                 return Err(LexerError::InvalidString {
-                    at: *cursor,
+                    at: lines.locate(bytes, *cursor),
                     reason: StringError::ControlChar { found: bytes[*cursor] },
                 });
             }
@@ -356,16 +966,21 @@ fn consume_string(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError>
 
     if !closed {
         return Err(LexerError::InvalidString {
-            at: bytes.len(),
+            at: lines.locate(bytes, bytes.len()),
             reason: StringError::Unterminated,
         });
     }
 
-    let s = match from_utf8(&out) {
-        Ok(v) => v.to_owned(),
-        Err(_) => {
+    let decoded = match escaped {
+        true => String::from_utf8(out).map(Cow::Owned).map_err(drop),
+        false => from_utf8(&bytes[string_start + 1..*cursor - 1]).map(Cow::Borrowed).map_err(drop),
+    };
+
+    let s = match decoded {
+        Ok(v) => v,
+        Err(()) => {
             return Err(LexerError::InvalidString {
-                at: *cursor,
+                at: lines.locate(bytes, *cursor),
                 reason: StringError::InvalidUtf8,
             });
         }
@@ -373,30 +988,46 @@ fn consume_string(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError>
 
     Ok(Token {
         kind: TokenKind::String(s),
-        span: Span { start: string_start, end: *cursor },
+        span: lines.span(bytes, string_start, *cursor),
     })
 }
 
-fn consume_number(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError> {
+fn consume_number<'a>(
+    bytes: &[u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+) -> Result<Token<'a>, LexerError> {
+    let limits = &options.limits;
+    let json5 = options.dialect == Dialect::Json5;
     let start = *cursor;
 
-    if bytes[*cursor] == b'-' {
+    // '+' only gets here in JSON5
+    if bytes[*cursor] == b'-' || bytes[*cursor] == b'+' {
         *cursor += 1;
         if *cursor >= bytes.len() {
-            return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::Empty });
+            return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::Empty });
         }
     }
 
-    // must have at least one digit
-    if *cursor >= bytes.len() || !bytes[*cursor].is_ascii_digit() {
-        return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::Empty });
+    if json5 && let Some(token) = consume_json5_special_number(bytes, cursor, lines, start, limits)? {
+        return Ok(token);
+    }
+
+    // must have at least one digit (JSON5: or a '.' followed by one)
+    let first_digit = match json5 && bytes[*cursor] == b'.' {
+        true => *cursor + 1,
+        false => *cursor,
+    };
+    if !bytes.get(first_digit).is_some_and(u8::is_ascii_digit) {
+        return Err(LexerError::InvalidNumber { at: lines.locate(bytes, first_digit), reason: NumberError::Empty });
     }
 
     // reject leading zeros like 01 (but allow "0", "0.xxx", "0e..")
     if bytes[*cursor] == b'0' {
         let next = *cursor + 1;
         if next < bytes.len() && bytes[next].is_ascii_digit() {
-            return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::LeadingZero });
+            return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::LeadingZero });
         }
     }
 
@@ -421,17 +1052,17 @@ fn consume_number(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError>
 
             b'.' => {
                 if seen_dot || seen_exp {
-                    return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::InvalidChar });
+                    return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::InvalidChar });
                 }
                 seen_dot = true;
-                need_frac_digit = true;
+                need_frac_digit = !json5; // JSON5 allows 5. and 5.e3
                 exp_sign_allowed = false;
                 *cursor += 1;
             }
 
             b'e' | b'E' => {
                 if seen_exp || need_frac_digit {
-                    return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::InvalidChar });
+                    return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::InvalidChar });
                 }
                 seen_exp = true;
                 need_exp_digit = true;
@@ -441,7 +1072,7 @@ fn consume_number(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError>
 
             b'+' | b'-' => {
                 if !exp_sign_allowed {
-                    return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::InvalidChar });
+                    return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::InvalidChar });
                 }
                 exp_sign_allowed = false;
                 *cursor += 1;
@@ -452,37 +1083,231 @@ fn consume_number(bytes: &[u8], cursor: &mut usize) -> Result<Token, LexerError>
     }
 
     if need_frac_digit {
-        return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::MissingFracDigit });
+        return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::MissingFracDigit });
     }
 
     if need_exp_digit {
-        return Err(LexerError::InvalidNumber { at: *cursor, reason: NumberError::MissingExpDigit });
+        return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::MissingExpDigit });
     }
 
     let end = *cursor;
 
+    if end - start > limits.max_number_len {
+        return Err(LexerError::LimitExceeded { limit: Limit::NumberLength, at: lines.locate(bytes, start) });
+    }
+
     // Parse the exact lexeme slice (includes '-' if present)
     let s = match std::str::from_utf8(&bytes[start..end]) {
         Ok(v) => v,
         Err(_) => {
             // Shouldn't happen for ascii number grammar, but keep it explicit.
-            return Err(LexerError::InvalidNumber { at: start, reason: NumberError::ParseFloatFailed });
+            return Err(LexerError::InvalidNumber { at: lines.locate(bytes, start), reason: NumberError::ParseFloatFailed });
+        }
+    };
+    let normalized;
+    let s = match json5 {
+        true => {
+            normalized = json5_decimal_to_json(s);
+            normalized.as_str()
         }
+        false => s,
     };
 
     let num: f64 = match s.parse() {
         Ok(v) => v,
-        Err(_) => return Err(LexerError::InvalidNumber { at: start, reason: NumberError::ParseFloatFailed }),
+        Err(_) => return Err(LexerError::InvalidNumber { at: lines.locate(bytes, start), reason: NumberError::ParseFloatFailed }),
     };
 
     // Policy choice: reject NaN/Inf or allow?
+    // The f64 is only used for this check, the token keeps the exact lexeme.
     if !num.is_finite() {
-        return Err(LexerError::InvalidNumber { at: start, reason: NumberError::NonFinite });
+        return Err(LexerError::InvalidNumber { at: lines.locate(bytes, start), reason: NumberError::NonFinite });
     }
 
     Ok(Token {
-        kind: TokenKind::Number(num),
-        span: Span { start, end },
+        kind: TokenKind::Number(JsonNumber::from_lexeme(s)),
+        span: lines.span(bytes, start, end),
     })
 }
 
+// JSON5 numbers that are not decimals: hex integers, Infinity and NaN (after
+// an optional sign, which starts at `start`). None for anything else.
+fn consume_json5_special_number<'a>(
+    bytes: &[u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    start: usize,
+    limits: &ParseLimits,
+) -> Result<Option<Token<'a>>, LexerError> {
+    let negative = bytes[start] == b'-';
+
+    let special = match bytes[*cursor] {
+        b'I' => Some((&b"Infinity"[..], "Infinity", f64::INFINITY)),
+        b'N' => Some((&b"NaN"[..], "NaN", f64::NAN)),
+        _ => None,
+    };
+    if let Some((lit, name, value)) = special {
+        // locate the sign before the literal moves the tracker past it
+        let start_loc = lines.locate(bytes, start);
+        let end = consume_literal(bytes, cursor, lines, lit, name)?.end;
+        let value = if negative { -value } else { value };
+        let span = Span { start: start_loc, end };
+        return Ok(Some(Token { kind: TokenKind::Number(JsonNumber::from(value)), span }));
+    }
+
+    if bytes[*cursor] != b'0' || !matches!(bytes.get(*cursor + 1), Some(b'x' | b'X')) {
+        return Ok(None);
+    }
+    *cursor += 2;
+    let digits_start = *cursor;
+    while *cursor < bytes.len() && bytes[*cursor].is_ascii_hexdigit() {
+        *cursor += 1;
+    }
+    if *cursor == digits_start {
+        return Err(LexerError::InvalidNumber { at: lines.locate(bytes, *cursor), reason: NumberError::Empty });
+    }
+    if *cursor - start > limits.max_number_len {
+        return Err(LexerError::LimitExceeded { limit: Limit::NumberLength, at: lines.locate(bytes, start) });
+    }
+
+    let sign = if negative { "-" } else { "" };
+    let lexeme = format!("{}{}", sign, hex_to_decimal(&bytes[digits_start..*cursor]));
+    Ok(Some(Token {
+        kind: TokenKind::Number(JsonNumber::from_lexeme(&lexeme)),
+        span: lines.span(bytes, start, *cursor),
+    }))
+}
+
+// Hex digits to decimal digits, exactly (no detour through u64 or f64).
+fn hex_to_decimal(hex: &[u8]) -> String {
+    // most significant first; starts as "0" and only grows on a carry, so no leading zeros
+    let mut digits: Vec<u8> = vec![0];
+    for &h in hex {
+        let mut carry = (h as char).to_digit(16).unwrap_or(0);
+        for d in digits.iter_mut().rev() {
+            let v = *d as u32 * 16 + carry;
+            *d = (v % 10) as u8;
+            carry = v / 10;
+        }
+        while carry > 0 {
+            digits.insert(0, (carry % 10) as u8);
+            carry /= 10;
+        }
+    }
+    digits.iter().map(|d| (b'0' + d) as char).collect()
+}
+
+// Rewrites a JSON5 decimal as JSON: drops a '+' sign and puts a 0 on each
+// bare side of the dot (+.5 → 0.5, 5. → 5.0, 5.e3 → 5.0e3).
+fn json5_decimal_to_json(s: &str) -> String {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    let mut out = String::with_capacity(s.len() + 2);
+    let mut prev = None;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '.' && !prev.is_some_and(|p: char| p.is_ascii_digit()) {
+            out.push('0');
+        }
+        out.push(c);
+        if c == '.' && !chars.peek().is_some_and(char::is_ascii_digit) {
+            out.push('0');
+        }
+        prev = Some(c);
+    }
+    out
+}
+
+// JSON5: an unquoted word. Followed by ':' it is an object key (any
+// identifier, "true" and "null" included) and comes out as a String token;
+// otherwise it has to be one of true, false, null, Infinity or NaN.
+fn consume_word<'a>(
+    bytes: &'a [u8],
+    cursor: &mut usize,
+    lines: &mut LineTracker,
+    options: &ParseOptions,
+) -> Result<Token<'a>, LexerError> {
+    let start = *cursor;
+
+    // Only filled once an escape shows up, like in consume_string
+    let mut out: Vec<u8> = Vec::new();
+    let mut escaped = false;
+    let mut run_start = start;
+
+    while *cursor < bytes.len() {
+        let first = *cursor == start;
+        if bytes[*cursor] != b'\\' {
+            match char_at(bytes, *cursor) {
+                Some(c) if is_identifier_char(c, first) => *cursor += c.len_utf8(),
+                _ => break,
+            }
+            continue;
+        }
+
+        // identifiers only know \uXXXX escapes, and they must decode to an identifier char
+        let escape_start = *cursor;
+        match bytes.get(escape_start + 1) {
+            Some(b'u') => {}
+            Some(&found) => {
+                return Err(LexerError::UnexpectedByte {
+                    at: lines.locate(bytes, escape_start + 1),
+                    found,
+                    expected: "'u' after '\\' in an identifier",
+                });
+            }
+            None => return Err(LexerError::UnexpectedEof { at: lines.locate(bytes, bytes.len()), expected: "u" }),
+        }
+        out.extend_from_slice(&bytes[run_start..escape_start]);
+        let (buf, n) = consume_escape_char(bytes, cursor, lines, options)?;
+        let decoded = from_utf8(&buf[..n]).ok().and_then(|c| c.chars().next());
+        if !decoded.is_some_and(|c| is_identifier_char(c, first)) {
+            return Err(LexerError::InvalidString {
+                at: lines.locate(bytes, escape_start),
+                reason: StringError::InvalidUnicodeEscape,
+            });
+        }
+        out.extend_from_slice(&buf[..n]);
+        escaped = true;
+        run_start = *cursor;
+    }
+
+    let end = *cursor;
+    if end == start {
+        let at = lines.locate(bytes, start);
+        return Err(LexerError::UnexpectedByte { at, found: bytes[start], expected: "token" });
+    }
+    if end - start > options.limits.max_string_bytes {
+        return Err(LexerError::LimitExceeded { limit: Limit::StringBytes, at: lines.locate(bytes, start) });
+    }
+
+    // only whole chars were consumed above
+    let word = match escaped {
+        true => {
+            out.extend_from_slice(&bytes[run_start..end]);
+            Cow::Owned(String::from_utf8(out).expect("identifier is made of whole chars"))
+        }
+        false => Cow::Borrowed(from_utf8(&bytes[start..end]).expect("identifier is made of whole chars")),
+    };
+    let span = lines.span(bytes, start, end);
+
+    // comments between the key and its ':' are looked past even when they are tokens
+    let mut next = end;
+    if skip_ws(bytes, &mut next, lines, &options.skipping_comments(), true).is_ok() && bytes.get(next) == Some(&b':') {
+        return Ok(Token { kind: TokenKind::String(word), span });
+    }
+
+    // an escaped word is never a literal, as in ECMAScript
+    let kind = match (escaped, word.as_ref()) {
+        (false, "true") => TokenKind::Bool(true),
+        (false, "false") => TokenKind::Bool(false),
+        (false, "null") => TokenKind::Null,
+        (false, "Infinity") => TokenKind::Number(JsonNumber::from(f64::INFINITY)),
+        (false, "NaN") => TokenKind::Number(JsonNumber::from(f64::NAN)),
+        _ => {
+            return Err(LexerError::InvalidLiteral {
+                at: lines.locate(bytes, start),
+                expected: "true, false, null, Infinity or NaN",
+            });
+        }
+    };
+    Ok(Token { kind, span })
+}
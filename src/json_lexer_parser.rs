@@ -1,19 +1,32 @@
-use std::collections::VecDeque;
+use std::iter::Peekable;
 use indexmap::IndexMap;
-use crate::json_definitions::{JsonParsingErrorV2, JsonValue, token_tag_of};
-use crate::json_lexer::{lex_all, Token, TokenKind};
+use crate::json_definitions::{Dialect, JsonParsingErrorV2, JsonValue, ObjectBuilder, ParseOptions, token_tag_of};
+use crate::json_lexer::{Lexer, SourceLocation, Span, Token, TokenKind};
+
+// Tokens are pulled from the lexer one at a time; one token of lookahead
+// is all the grammar needs.
+type Tokens<'a> = Peekable<Lexer<'a>>;
 
 pub fn process_json_string_v2(json_string: &str) -> Result<JsonValue, JsonParsingErrorV2>{
+    process_json_string_v2_with_options(json_string, &ParseOptions::default())
+}
+
+/// v2 applies the duplicate-key policy and the lexer's byte limits;
+/// the structural limits (depth, lengths) are only enforced by v3.
+pub fn process_json_string_v2_with_options(
+    json_string: &str,
+    options: &ParseOptions,
+) -> Result<JsonValue, JsonParsingErrorV2> {
 
     if json_string.is_empty() {
         return Err(JsonParsingErrorV2::EmptyJsonFile);
     }
 
-    let mut tokens = lex_all(json_string.as_bytes()).map_err(|e|JsonParsingErrorV2::LexError(e))?;
-    let mut cursor = tokens.front().map_or(0, |token| token.span.start);
-    let json_value = parse_json_value(&mut tokens, &mut cursor)?;
+    let mut tokens = Lexer::with_options(json_string.as_bytes(), &options.skipping_comments()).peekable();
+    let mut cursor = SourceLocation::START;
+    let json_value = parse_json_value(&mut tokens, &mut cursor, options)?;
 
-    let Some(token) = next_token(&mut tokens, &mut cursor) else {
+    let Some(token) = next_token(&mut tokens, &mut cursor)? else {
         return Err(JsonParsingErrorV2::UnexpectedEOF{at: cursor })
     };
 
@@ -24,59 +37,79 @@ pub fn process_json_string_v2(json_string: &str) -> Result<JsonValue, JsonParsin
     Ok(json_value)
 }
 
-fn peek_token(tokens: &VecDeque<Token>) -> Option<&Token> {
-    tokens.front()
+// A lexer error is only reported once the parser actually reaches it.
+fn peek_token<'t, 'a>(tokens: &'t mut Tokens<'a>) -> Result<Option<&'t Token<'a>>, JsonParsingErrorV2> {
+    if let Some(Err(_)) = tokens.peek()
+        && let Some(Err(e)) = tokens.next()
+    {
+        return Err(JsonParsingErrorV2::LexError(e));
+    }
+    Ok(tokens.peek().and_then(|t| t.as_ref().ok()))
 }
 
-fn next_token(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Option<Token>{
-    let token = tokens.pop_front()?;
+fn next_token<'a>(tokens: &mut Tokens<'a>, cursor: &mut SourceLocation) -> Result<Option<Token<'a>>, JsonParsingErrorV2> {
+    let Some(token) = tokens.next().transpose().map_err(JsonParsingErrorV2::LexError)? else {
+        return Ok(None);
+    };
     *cursor = token.span.end;
-    Some(token)
+    Ok(Some(token))
 }
 
-fn parse_json_value(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Result<JsonValue, JsonParsingErrorV2> {
+#[allow(clippy::needless_return)]
+fn parse_json_value(
+    tokens: &mut Tokens,
+    cursor: &mut SourceLocation,
+    options: &ParseOptions,
+) -> Result<JsonValue, JsonParsingErrorV2> {
 
-    let Some(token) = next_token(tokens, cursor) else {
+    let Some(token) = next_token(tokens, cursor)? else {
         return Err(JsonParsingErrorV2::UnexpectedEOF {at: *cursor })
     };
 
     return match token.kind {
         TokenKind::Null => Ok(JsonValue::Null),
         TokenKind::Bool(b) => Ok(JsonValue::Boolean(b)),
-        TokenKind::String(s) => Ok(JsonValue::JsonString(s)),
+        TokenKind::String(s) => Ok(JsonValue::JsonString(s.into_owned())),
         TokenKind::Number(n) => Ok(JsonValue::Number(n)),
-        TokenKind::LBrace => parse_json_object(tokens, cursor),
-        TokenKind::LBracket => parse_json_array(tokens, cursor),
+        TokenKind::LBrace => parse_json_object(tokens, cursor, options),
+        TokenKind::LBracket => parse_json_array(tokens, cursor, options),
         TokenKind::Eof => Err(JsonParsingErrorV2::UnexpectedEOF{at:*cursor}),
         _ => Err(JsonParsingErrorV2::UnexpectedToken { found: token_tag_of(&token.kind), at: Some(token.span.start) }),
     }
 }
 
-fn parse_json_array(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Result<JsonValue, JsonParsingErrorV2> {
-    let Some(t0) = peek_token(tokens) else {
+fn parse_json_array(
+    tokens: &mut Tokens,
+    cursor: &mut SourceLocation,
+    options: &ParseOptions,
+) -> Result<JsonValue, JsonParsingErrorV2> {
+    let Some(t0) = peek_token(tokens)? else {
         return Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor });
     };
 
     if t0.kind == TokenKind::RBracket {
-        let _ = next_token(tokens, cursor);
+        next_token(tokens, cursor)?;
         return Ok(JsonValue::Array(Vec::new()));
     }
 
     let mut out = Vec::new();
-    out.push(parse_json_value(tokens, cursor)?);
+    out.push(parse_json_value(tokens, cursor, options)?);
 
     loop {
-        let Some(t) = peek_token(tokens) else {
+        let Some(t) = peek_token(tokens)? else {
             return Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor });
         };
 
         match t.kind {
             TokenKind::Comma => {
-                let _ = next_token(tokens, cursor);
-                out.push(parse_json_value(tokens, cursor)?);
+                next_token(tokens, cursor)?;
+                if closes_after_comma(tokens, TokenKind::RBracket, options)? {
+                    continue;
+                }
+                out.push(parse_json_value(tokens, cursor, options)?);
             }
             TokenKind::RBracket => {
-                let _ = next_token(tokens, cursor);
+                next_token(tokens, cursor)?;
                 return Ok(JsonValue::Array(out));
             }
             _ => {
@@ -87,45 +120,45 @@ fn parse_json_array(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Result<
 }
 
 
-fn parse_json_object(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Result<JsonValue, JsonParsingErrorV2> {
+fn parse_json_object(
+    tokens: &mut Tokens,
+    cursor: &mut SourceLocation,
+    options: &ParseOptions,
+) -> Result<JsonValue, JsonParsingErrorV2> {
     // We enter here after '{' has already been consumed.
 
     // empty object: "{}"
-    let Some(t0) = peek_token(tokens) else {
+    let Some(t0) = peek_token(tokens)? else {
         return Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor });
     };
 
     if t0.kind == TokenKind::RBrace {
-        let _ = next_token(tokens, cursor); // consume '}'
+        next_token(tokens, cursor)?; // consume '}'
         return Ok(JsonValue::Object(IndexMap::new()));
     }
 
-    let mut out: IndexMap<String, JsonValue> = IndexMap::new();
+    let mut out = ObjectBuilder::new(options.duplicate_keys);
 
     // First pair: key ":" value
-    let key = parse_object_key(tokens, cursor)?;
-    consume_kind(tokens, TokenKind::Colon, cursor)?;
-    let value = parse_json_value(tokens, cursor)?;
-    out.insert(key, value);
+    parse_object_member(tokens, cursor, options, &mut out)?;
 
     loop {
-        let Some(t) = peek_token(tokens) else {
+        let Some(t) = peek_token(tokens)? else {
             return Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor });
         };
 
         match &t.kind {
             TokenKind::Comma => {
-                let _ = next_token(tokens, cursor); // consume ','
-
-                let key = parse_object_key(tokens, cursor)?;
-                consume_kind(tokens, TokenKind::Colon, cursor)?;
-                let value = parse_json_value(tokens, cursor)?;
-                out.insert(key, value);
+                next_token(tokens, cursor)?; // consume ','
+                if closes_after_comma(tokens, TokenKind::RBrace, options)? {
+                    continue;
+                }
+                parse_object_member(tokens, cursor, options, &mut out)?;
             }
 
             TokenKind::RBrace => {
-                let _ = next_token(tokens, cursor); // consume '}'
-                return Ok(JsonValue::Object(out));
+                next_token(tokens, cursor)?; // consume '}'
+                return Ok(out.finish());
             }
 
             _ => {
@@ -135,20 +168,47 @@ fn parse_json_object(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Result
     }
 }
 
-fn parse_object_key(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Result<String, JsonParsingErrorV2> {
-    let Some(t) = next_token(tokens, cursor) else {
+// JSON5 allows one trailing comma: true when `close` follows the ',' just consumed
+// (the loop then reads it as usual).
+fn closes_after_comma(
+    tokens: &mut Tokens,
+    close: TokenKind,
+    options: &ParseOptions,
+) -> Result<bool, JsonParsingErrorV2> {
+    if options.dialect != Dialect::Json5 {
+        return Ok(false);
+    }
+    Ok(peek_token(tokens)?.is_some_and(|t| t.kind == close))
+}
+
+fn parse_object_member(
+    tokens: &mut Tokens,
+    cursor: &mut SourceLocation,
+    options: &ParseOptions,
+    out: &mut ObjectBuilder,
+) -> Result<(), JsonParsingErrorV2> {
+    let (key, key_span) = parse_object_key(tokens, cursor)?;
+    consume_kind(tokens, TokenKind::Colon, cursor)?;
+    let value = parse_json_value(tokens, cursor, options)?;
+
+    out.insert(key, key_span, value)
+        .map_err(|(key, first)| JsonParsingErrorV2::DuplicateKey { key, first, second: key_span })
+}
+
+fn parse_object_key(tokens: &mut Tokens, cursor: &mut SourceLocation) -> Result<(String, Span), JsonParsingErrorV2> {
+    let Some(t) = next_token(tokens, cursor)? else {
         return Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor });
     };
 
     match t.kind {
-        TokenKind::String(s) => Ok(s),
+        TokenKind::String(s) => Ok((s.into_owned(), t.span)),
         TokenKind::Eof => Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor }),
         _ => Err(JsonParsingErrorV2::InvalidJsonObject { found: token_tag_of(&t.kind), at: Some(t.span.start) }),
     }
 }
 
-fn consume_kind(tokens: &mut VecDeque<Token>, expected: TokenKind, cursor: &mut usize) -> Result<(), JsonParsingErrorV2> {
-    let Some(t) = next_token(tokens, cursor) else {
+fn consume_kind(tokens: &mut Tokens, expected: TokenKind, cursor: &mut SourceLocation) -> Result<(), JsonParsingErrorV2> {
+    let Some(t) = next_token(tokens, cursor)? else {
         return Err(JsonParsingErrorV2::UnexpectedEOF { at: *cursor });
     };
 
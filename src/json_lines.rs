@@ -0,0 +1,238 @@
+use std::io::{self, BufRead, Read};
+
+use crate::json_definitions::{JsonParsingErrorV3, JsonValue, Limit, ParseOptions};
+use crate::json_lexer::{LineTracker, SourceLocation};
+use crate::json_non_recursive::process_json_string_v3_with_options;
+use crate::json_writer::{to_string, write_json, WriterOptions};
+
+// =============================================================================
+// JSON Lines (NDJSON) — one document per line
+// =============================================================================
+//
+//   {"event": "login", "user": 1}
+//   {"event": "logout", "user": 1}
+//
+// Every v3 entry point wants exactly one document and rejects the second
+// line above with ExpectedEOF. JsonLinesReader splits its input at '\n' and
+// parses each line on its own with v3, yielding one Result per line:
+//
+//   for record in JsonLinesReader::new(BufReader::new(file)) {
+//       let value = record?;
+//   }
+//
+// Lines may end in "\r\n" (the '\r' is whitespace to v3). A final line
+// without a line break is read like any other.
+//
+// ERRORS:
+//   Parse errors carry the location of the line they occurred on (the v3
+//   error itself is boxed to keep every Result small), and their diagnostic
+//   points into the whole input, so render_error with the full text shows
+//   the right line. By default the first error ends the
+//   iteration; with continue_on_error the reader reports the bad line and
+//   moves on to the next one. An I/O error always ends it.
+//
+//   Blank (whitespace-only) lines are errors unless skip_blank_lines is set,
+//   in which case they are passed over silently.
+//
+// LIMITS:
+//   ParseOptions apply to every line separately: max_document_bytes limits
+//   a single record, not the whole stream. Like the push parser, the reader
+//   never keeps more than max_document_bytes + 1 bytes of a line: a longer
+//   one is LimitExceeded, and with continue_on_error the rest of it is
+//   skipped without being buffered.
+//
+// WRITING:
+//   JsonLinesWriter writes each value compact, followed by '\n'. Compact
+//   output never contains a raw line break (those only occur inside strings,
+//   where they are escaped), so every value stays on its own line.
+//
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JsonLinesOptions {
+    pub parse: ParseOptions,
+    pub skip_blank_lines: bool,
+    pub continue_on_error: bool,
+}
+
+#[derive(Debug)]
+pub enum JsonLinesError {
+    Io(io::Error),                                                        // reading failed; ends the iteration
+    InvalidUtf8 { at: SourceLocation },                                   // first byte that is not UTF-8
+    BlankLine { at: SourceLocation },                                     // only with skip_blank_lines off
+    Parse { line_start: SourceLocation, error: Box<JsonParsingErrorV3> }, // error's locations are relative to the line
+}
+
+impl From<io::Error> for JsonLinesError {
+    fn from(e: io::Error) -> Self {
+        JsonLinesError::Io(e)
+    }
+}
+
+pub struct JsonLinesReader<R> {
+    reader: R,
+    options: JsonLinesOptions,
+    line: Vec<u8>,
+    next_line: SourceLocation, // where the line read next starts
+    done: bool,
+}
+
+impl<R: io::BufRead> JsonLinesReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, &JsonLinesOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: &JsonLinesOptions) -> Self {
+        Self { reader, options: *options, line: Vec::new(), next_line: SourceLocation::START, done: false }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Discards input up to and including the next '\n'; returns how many bytes that was.
+    fn skip_rest_of_line(&mut self) -> io::Result<usize> {
+        let mut skipped = 0;
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(skipped);
+            }
+            let (len, found) = match buf.iter().position(|&b| b == b'\n') {
+                Some(pos) => (pos + 1, true),
+                None => (buf.len(), false),
+            };
+            self.reader.consume(len);
+            skipped += len;
+            if found {
+                return Ok(skipped);
+            }
+        }
+    }
+
+    // Parses the line in `self.line`; Ok(None) for a blank line that is skipped.
+    fn parse_line(&self, start: SourceLocation) -> Result<Option<JsonValue>, JsonLinesError> {
+        let bytes = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                let valid = e.valid_up_to();
+                // valid_up_to is always a char boundary
+                let column = 1 + std::str::from_utf8(&bytes[..valid]).map_or(0, |s| s.chars().count());
+                let at = SourceLocation { offset: start.offset + valid, line: start.line, column };
+                return Err(JsonLinesError::InvalidUtf8 { at });
+            }
+        };
+
+        if text.bytes().all(|b| matches!(b, b' ' | b'\t' | b'\r')) {
+            if self.options.skip_blank_lines {
+                return Ok(None);
+            }
+            return Err(JsonLinesError::BlankLine { at: start });
+        }
+
+        process_json_string_v3_with_options(text, &self.options.parse)
+            .map(Some)
+            .map_err(|error| JsonLinesError::Parse { line_start: start, error: Box::new(error) })
+    }
+}
+
+impl<R: io::BufRead> Iterator for JsonLinesReader<R> {
+    type Item = Result<JsonValue, JsonLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let limit = self.options.parse.limits.max_document_bytes;
+
+        while !self.done {
+            self.line.clear();
+            // one byte past the limit is enough to tell that a line is too long
+            let mut kept = (&mut self.reader).take(limit.saturating_add(1) as u64);
+            let mut read = match kept.read_until(b'\n', &mut self.line) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(JsonLinesError::Io(e)));
+                }
+            };
+
+            let start = self.next_line;
+            let record_len = self.line.strip_suffix(b"\n").unwrap_or(&self.line).len();
+            if record_len > limit {
+                self.done = !self.options.continue_on_error;
+                if self.options.continue_on_error {
+                    match self.skip_rest_of_line() {
+                        Ok(skipped) => read += skipped,
+                        Err(e) => {
+                            self.done = true;
+                            return Some(Err(JsonLinesError::Io(e)));
+                        }
+                    }
+                }
+                self.next_line = SourceLocation { offset: start.offset + read, line: start.line + 1, column: 1 };
+                let at = LineTracker::new().locate(&self.line, limit);
+                let error = Box::new(JsonParsingErrorV3::LimitExceeded { limit: Limit::DocumentBytes, at });
+                return Some(Err(JsonLinesError::Parse { line_start: start, error }));
+            }
+
+            self.next_line = SourceLocation { offset: start.offset + read, line: start.line + 1, column: 1 };
+
+            match self.parse_line(start) {
+                Ok(None) => continue,
+                Ok(Some(value)) => return Some(Ok(value)),
+                Err(e) => {
+                    self.done = !self.options.continue_on_error;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+/// Parses a whole JSON Lines text; stops at the first error.
+pub fn from_json_lines_str(input: &str) -> Result<Vec<JsonValue>, JsonLinesError> {
+    JsonLinesReader::new(input.as_bytes()).collect()
+}
+
+pub struct JsonLinesWriter<W> {
+    writer: W,
+}
+
+impl<W: io::Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes `value` compact, followed by '\n'.
+    pub fn write(&mut self, value: &JsonValue) -> io::Result<()> {
+        write_json(&mut self.writer, value, &WriterOptions::compact())?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn write_all<'a, I: IntoIterator<Item = &'a JsonValue>>(&mut self, values: I) -> io::Result<()> {
+        for value in values {
+            self.write(value)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+pub fn to_json_lines_string<'a, I: IntoIterator<Item = &'a JsonValue>>(values: I) -> String {
+    let mut out = String::new();
+    for value in values {
+        out.push_str(&to_string(value));
+        out.push('\n');
+    }
+    out
+}
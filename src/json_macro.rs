@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::json_definitions::JsonValue;
+use crate::json_number::JsonNumber;
+
+// For json!'s expansion, so callers need no indexmap dependency of their own.
+#[doc(hidden)]
+pub use indexmap::IndexMap;
+
+// =============================================================================
+// json! — building JsonValue trees from JSON-looking Rust
+// =============================================================================
+//
+//   let port = 8080;
+//   let value = json!({
+//       "name": "server",
+//       "ports": [port, port + 1],
+//       "tls": null,
+//       "tags": tags,            // any T where JsonValue: From<T>
+//   });
+//
+// RULES:
+//   - null, true, false, [...] and {...} are taken literally, at any depth
+//   - anything else is a Rust expression, converted with JsonValue::from
+//   - object keys are string literals, or any expression that converts into
+//     String (everything before the ':')
+//   - trailing commas are accepted; members keep their written order and
+//     a repeated key keeps the last value, like DuplicateKeyPolicy::LastWins
+//
+// HOW IT EXPANDS:
+//   json! handles a whole value; arrays and objects are handed to the
+//   internal json_internal! muncher, which walks the tokens one element (or
+//   one member) at a time, since an element like `a + 1` is several tokens
+//   and only the comma tells where it ends. The expansion itself is plain
+//   vec![...] and IndexMap::insert calls.
+//
+// CONVERSIONS:
+//   The From impls below are what `var` in json!({"key": var}) relies on:
+//   bool, every integer type, f32/f64, &str/String, Option<T> (None is null),
+//   Vec<T>/slices and string-keyed maps (IndexMap, HashMap, BTreeMap), each
+//   element converted in turn.
+//
+// =============================================================================
+
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::json_definitions::JsonValue::Null
+    };
+    (true) => {
+        $crate::json_definitions::JsonValue::Boolean(true)
+    };
+    (false) => {
+        $crate::json_definitions::JsonValue::Boolean(false)
+    };
+    ([]) => {
+        $crate::json_definitions::JsonValue::Array(::std::vec::Vec::new())
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::json_definitions::JsonValue::Array($crate::json_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::json_definitions::JsonValue::Object($crate::json_macro::IndexMap::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::json_definitions::JsonValue::Object({
+            let mut object = $crate::json_macro::IndexMap::new();
+            $crate::json_internal!(@object object () ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::json_definitions::JsonValue::from($other)
+    };
+}
+
+// Not public API: the array and object munchers behind json!.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // ---- arrays: @array [converted elements,] remaining tokens -------------
+
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        ::std::vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($last)])
+    };
+    // the comma after a literal, array or object element
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // ---- objects: @object map (key tokens) (remaining tokens) ---------------
+
+    (@object $object:ident () ()) => {};
+    // a member is complete: insert it, then start on the next key
+    (@object $object:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        let _ = $object.insert(($($key)+).into(), $value);
+        $crate::json_internal!(@object $object () ($($rest)*));
+    };
+    (@object $object:ident [$($key:tt)+] ($value:expr)) => {
+        let _ = $object.insert(($($key)+).into(), $value);
+    };
+    // mistakes: a ':' without a key or value, a key without ':'
+    (@object $object:ident () (: $($rest:tt)*)) => {
+        ::std::compile_error!("json!: missing object key before ':'")
+    };
+    (@object $object:ident ($($key:tt)+) (:)) => {
+        ::std::compile_error!("json!: missing value after ':'")
+    };
+    (@object $object:ident ($($key:tt)+) (: , $($rest:tt)*)) => {
+        ::std::compile_error!("json!: missing value after ':'")
+    };
+    (@object $object:ident ($($key:tt)+) ()) => {
+        ::std::compile_error!("json!: missing ':' and value after object key")
+    };
+    (@object $object:ident ($($key:tt)*) (, $($rest:tt)*)) => {
+        ::std::compile_error!("json!: expected ':' after object key, found ','")
+    };
+    // the value after ':'
+    (@object $object:ident ($($key:tt)+) (: null $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!(null)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: true $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!(true)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: false $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!(false)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!([$($array)*])) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!({$($map)*})) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!($value)) , $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr)) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!($value)));
+    };
+    // collect key tokens up to ':'
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object ($($key)* $tt) ($($rest)*));
+    };
+}
+
+// =============================================================================
+// Conversions into JsonValue
+// =============================================================================
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Boolean(b)
+    }
+}
+
+impl From<JsonNumber> for JsonValue {
+    fn from(n: JsonNumber) -> Self {
+        JsonValue::Number(n)
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for JsonValue {
+                fn from(n: $t) -> Self {
+                    JsonValue::Number(JsonNumber::from(n))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::JsonString(s.to_string())
+    }
+}
+
+impl From<&String> for JsonValue {
+    fn from(s: &String) -> Self {
+        JsonValue::JsonString(s.clone())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::JsonString(s)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(items: Vec<T>) -> Self {
+        JsonValue::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<JsonValue>> From<&[T]> for JsonValue {
+    fn from(items: &[T]) -> Self {
+        JsonValue::Array(items.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>, S> From<IndexMap<K, V, S>> for JsonValue {
+    fn from(members: IndexMap<K, V, S>) -> Self {
+        JsonValue::Object(members.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+// HashMap has no order of its own; members come in its iteration order.
+impl<K: Into<String>, V: Into<JsonValue>, S: BuildHasher> From<HashMap<K, V, S>> for JsonValue {
+    fn from(members: HashMap<K, V, S>) -> Self {
+        JsonValue::Object(members.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>> From<BTreeMap<K, V>> for JsonValue {
+    fn from(members: BTreeMap<K, V>) -> Self {
+        JsonValue::Object(members.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
@@ -0,0 +1,134 @@
+use indexmap::IndexMap;
+
+use crate::json_definitions::JsonValue;
+
+// =============================================================================
+// JSON Merge Patch (RFC 7386)
+// =============================================================================
+//
+// A merge patch looks like the document it changes:
+//
+//   target: {"title": "Hello", "author": {"name": "A", "email": "a@x"}, "tags": ["x"]}
+//   patch:  {"title": "Bye",   "author": {"email": null},               "tags": ["y"]}
+//   result: {"title": "Bye",   "author": {"name": "A"},                 "tags": ["y"]}
+//
+// RULES:
+//   - an object patch is merged member by member into the target (a target
+//     that is not an object is replaced by {} first)
+//   - a null member deletes that member from the target
+//   - anything else (string, number, array, ...) replaces the target value
+//
+// ORDER:
+//   Members that already exist keep their position in the IndexMap, new ones
+//   are appended in the patch's order, and a deleted member closes its gap —
+//   so applying a patch to a config file only moves what it touches.
+//
+// NO RECURSION:
+//   Both directions keep an explicit stack, one entry per pair of objects
+//   still to merge (or compare), like the v3 parser and the writer.
+//
+// LIMITATION (of the format, not the code):
+//   A merge patch cannot set a member to null, and cannot change single
+//   array elements — arrays are always replaced whole. generate_merge_patch
+//   therefore cannot express a `to` that contains null inside an object.
+//
+// =============================================================================
+
+impl JsonValue {
+    /// Applies `patch` to `self` with RFC 7386 semantics.
+    pub fn merge_patch(&mut self, patch: &JsonValue) {
+        let mut pending: Vec<(&mut JsonValue, &JsonValue)> = vec![(self, patch)];
+
+        while let Some((target, patch)) = pending.pop() {
+            let JsonValue::Object(patch_members) = patch else {
+                *target = patch.clone();
+                continue;
+            };
+            if !matches!(target, JsonValue::Object(_)) {
+                *target = JsonValue::Object(IndexMap::new());
+            }
+            let JsonValue::Object(members) = target else {
+                unreachable!("target was just made an object");
+            };
+
+            // Deletions, replacements and new members; nested objects are merged on a later round.
+            for (key, value) in patch_members {
+                match value {
+                    JsonValue::Null => {
+                        members.shift_remove(key);
+                    }
+                    JsonValue::Object(_) => {
+                        members.entry(key.clone()).or_insert(JsonValue::Null);
+                    }
+                    _ => {
+                        members.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            for (key, member) in members.iter_mut() {
+                if let Some(value @ JsonValue::Object(_)) = patch_members.get(key) {
+                    pending.push((member, value));
+                }
+            }
+        }
+    }
+}
+
+// One pair of objects being compared, and the patch for them built so far.
+struct DiffFrame<'v> {
+    key: Option<&'v str>,
+    from: &'v IndexMap<String, JsonValue>,
+    to: &'v IndexMap<String, JsonValue>,
+    members: indexmap::map::Iter<'v, String, JsonValue>,
+    patch: IndexMap<String, JsonValue>,
+}
+
+impl<'v> DiffFrame<'v> {
+    fn new(key: Option<&'v str>, from: &'v IndexMap<String, JsonValue>, to: &'v IndexMap<String, JsonValue>) -> Self {
+        Self { key, from, to, members: to.iter(), patch: IndexMap::new() }
+    }
+}
+
+/// The merge patch that turns `from` into `to`: changed and new members in
+/// `to`'s order, then a null for every member `to` no longer has. Equal
+/// values give an empty object.
+pub fn generate_merge_patch(from: &JsonValue, to: &JsonValue) -> JsonValue {
+    let (JsonValue::Object(from), JsonValue::Object(to)) = (from, to) else {
+        return to.clone();
+    };
+    let mut stack = vec![DiffFrame::new(None, from, to)];
+
+    loop {
+        let Some(frame) = stack.last_mut() else {
+            unreachable!("root frame returns before the stack empties");
+        };
+
+        if let Some((key, to_value)) = frame.members.next() {
+            match (frame.from.get(key), to_value) {
+                (Some(JsonValue::Object(x)), JsonValue::Object(y)) => stack.push(DiffFrame::new(Some(key), x, y)),
+                (Some(from_value), _) if from_value == to_value => {}
+                _ => {
+                    frame.patch.insert(key.clone(), to_value.clone());
+                }
+            }
+            continue;
+        }
+
+        for key in frame.from.keys() {
+            if !frame.to.contains_key(key) {
+                frame.patch.insert(key.clone(), JsonValue::Null);
+            }
+        }
+
+        let Some(done) = stack.pop() else { unreachable!() };
+        match (stack.last_mut(), done.key) {
+            (Some(parent), Some(key)) => {
+                // unchanged nested objects leave no trace in the parent's patch
+                if !done.patch.is_empty() {
+                    parent.patch.insert(key.to_string(), JsonValue::Object(done.patch));
+                }
+            }
+            _ => return JsonValue::Object(done.patch),
+        }
+    }
+}
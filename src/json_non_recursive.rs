@@ -1,8 +1,11 @@
-use std::collections::VecDeque;
-use indexmap::IndexMap;
-use crate::json_definitions::{JsonParsingErrorV3, JsonValue};
-use crate::json_definitions::JsonParsingErrorV2::UnexpectedToken;
-use crate::json_lexer::{lex_all, Token, TokenKind};
+use std::borrow::{Borrow, Cow};
+
+use crate::json_definitions::{
+    DuplicateKeyPolicy, JsonParsingErrorV3, JsonValue, JsonValueRef, Limit, ObjectBuilder, ParseLimits,
+    ParseOptions, TreeValue,
+};
+use crate::json_events::{JsonEvent, JsonEventParser};
+use crate::json_lexer::{SourceLocation, Span};
 
 // =============================================================================
 // JSON Parser v3 — Stack-Based (Non-Recursive)
@@ -18,264 +21,209 @@ use crate::json_lexer::{lex_all, Token, TokenKind};
 // HIGH LEVEL FLOW:
 //   input string
 //     ↓
-//   lexer → VecDeque<Token>          (reuse from v2, no changes)
-//     ↓
-//   main loop over tokens
+//   Lexer                            (reuse from v2, tokens pulled on demand)
 //     ↓
+//   JsonEventParser                  (json_events.rs — frame state machine,
+//     ↓                               every syntax error comes from here)
 //   explicit stack of JsonFrame
 //     ↓
 //   JsonValue (the final tree)
 //
-// THE MAIN LOOP — 3 categories of token:
-//   1. Leaf value (Null, Bool, String, Number)
-//      → produce JsonValue immediately, call attach_value()
+// THE MAIN LOOP — one step per event (TreeBuilder::push):
+//   1. Value (leaf)
+//      → call attach_value()
 //
-//   2. Open structure ({ or [)
+//   2. StartArray / StartObject
 //      → push a new frame onto the stack
 //
-//   3. Close structure (} or ])
+//   3. EndArray / EndObject
 //      → pop top frame, build completed JsonValue, call attach_value()
 //
-//   4. Separators (: and ,)
-//      → validate structure that you have value in array in frame aray or in jsonvvalue for ,
-//      -> for : you need to validate that key string was consumed in object array or in json value of type object
+//   4. Key
+//      → remember it in the top (object) frame as pending_key
 //
-//   5. EOF
-//      → validate stack is empty, return result
+//   5. End of events
+//      → the event parser has checked the stack is empty, return result
 //
 // KEY INSIGHT — attach_value():
-//   When a JsonValue is completed (leaf token or just-popped frame), it needs
+//   When a JsonValue is completed (leaf or just-popped frame), it needs
 //   to go somewhere. "Somewhere" depends on what's on top of the stack:
 //     - Stack empty        → this is the root value, store in `result`
 //     - Top is ArrayFrame  → append to frame.items
-//     - Top is ObjectFrame → depends on pending_key state (see below)
+//     - Top is ObjectFrame → insert (pending_key, value) into frame.items
+//   Inserting goes through ObjectBuilder, which applies the DuplicateKeyPolicy.
 //
-// KEY INSIGHT — pending_key: Option<String>:
-//   In an object, a String token plays double duty:
-//     - pending_key is None    → string is a KEY, store it as pending_key
-//     - pending_key is Some(k) → string is a VALUE, insert (k, value) into items
-//   The frame state does the disambiguation — not the token itself.
+// LIMITS:
+//   `process_json_string_v3_with_options` takes a `ParseOptions`. The lexer
+//   checks the byte-based limits and the event parser the depth; array length
+//   and object member count are checked here, before attach_value grows a frame.
 //
 // =============================================================================
 
-enum JsonFrame {
-    Object(ObjectFrame),
-    Array(ArrayFrame),
+enum JsonFrame<V: TreeValue> {
+    Object(ObjectFrame<V>),
+    Array(Vec<V>),
 }
 
-struct ObjectFrame {
-    pending_key: Option<String>,
-    items: IndexMap<String, JsonValue>,
+struct ObjectFrame<V: TreeValue> {
+    pending_key: Option<(V::Key, Span)>,
+    items: ObjectBuilder<V>,
 }
 
-impl ObjectFrame {
-    fn new() -> Self {
-        Self {
-            pending_key: None,
-            items: IndexMap::new(),
-        }
+impl<V: TreeValue> ObjectFrame<V> {
+    fn new(duplicate_keys: DuplicateKeyPolicy) -> Self {
+        Self { pending_key: None, items: ObjectBuilder::new(duplicate_keys) }
     }
 }
 
-struct ArrayFrame {
-    items: Vec<JsonValue>,
+pub fn process_json_string_v3(json_string: &str) -> Result<JsonValue, JsonParsingErrorV3> {
+    process_json_string_v3_with_options(json_string, &ParseOptions::default())
 }
 
-impl ArrayFrame {
-    fn new() -> Self {
-        Self { items: Vec::new() }
-    }
+pub fn process_json_string_v3_with_limits(
+    json_string: &str,
+    limits: &ParseLimits,
+) -> Result<JsonValue, JsonParsingErrorV3> {
+    process_json_string_v3_with_options(json_string, &ParseOptions { limits: *limits, ..ParseOptions::default() })
 }
 
-pub fn process_json_string_v3(json_string: &str) -> Result<JsonValue, JsonParsingErrorV3> {
+pub fn process_json_string_v3_with_options(
+    json_string: &str,
+    options: &ParseOptions,
+) -> Result<JsonValue, JsonParsingErrorV3> {
+    build_tree(json_string, options)
+}
 
-    if json_string.is_empty() {
-        return Result::Err(JsonParsingErrorV3::EmptyJsonFile)
-    }
+/// Like v3, but unescaped strings and object keys borrow from `json_string`
+/// instead of being copied (see `JsonValueRef`).
+pub fn process_json_string_v3_borrowed(json_string: &str) -> Result<JsonValueRef<'_>, JsonParsingErrorV3> {
+    process_json_string_v3_borrowed_with_options(json_string, &ParseOptions::default())
+}
 
-    let mut tokens =  lex_all(json_string.as_bytes())
-        .map_err(|e|JsonParsingErrorV3::LexError(e))?;
+pub fn process_json_string_v3_borrowed_with_options<'a>(
+    json_string: &'a str,
+    options: &ParseOptions,
+) -> Result<JsonValueRef<'a>, JsonParsingErrorV3> {
+    build_tree(json_string, options)
+}
 
-    let mut stack: Vec<JsonFrame> = Vec::new();
-    let mut result: Option<JsonValue> = None;
-    let mut cursor = tokens.front().map_or(0, |token| token.span.start);
+fn build_tree<'a, V>(json_string: &'a str, options: &ParseOptions) -> Result<V, JsonParsingErrorV3>
+where
+    V: TreeValue + From<JsonValueRef<'a>>,
+    V::Key: From<Cow<'a, str>>,
+{
+    let mut events = JsonEventParser::with_options(json_string, options);
+    let mut builder = TreeBuilder::new(options);
 
-    parse_loop(&mut tokens, &mut cursor, &mut stack, &mut result)?;
+    parse_loop(&mut events, &mut builder)?;
 
-    result.ok_or(JsonParsingErrorV3::UnexpectedEOF)
+    builder.finish(events.span().end)
 }
 
-fn parse_loop(
-    tokens: &mut VecDeque<Token>,
-    cursor: &mut usize,
-    stack: &mut Vec<JsonFrame>,
-    result: &mut Option<JsonValue>,
-) -> Result<(), JsonParsingErrorV3> {
-    loop {
-        let Some(token) = next_token(tokens, cursor) else {
-            return Err(JsonParsingErrorV3::UnexpectedEOF)
-        };
-
-        match token.kind {
-            TokenKind::Null => attach_value(stack, result, JsonValue::Null)?,
-            TokenKind::Bool(b) => attach_value(stack, result, JsonValue::Boolean(b))?,
-            TokenKind::String(s) => attach_value(stack, result, JsonValue::JsonString(s))?,
-            TokenKind::Number(n) => attach_value(stack, result, JsonValue::Number(n))?,
-            TokenKind::LBracket => stack.push(JsonFrame::Array(ArrayFrame::new())),
-            TokenKind::LBrace => stack.push(JsonFrame::Object(ObjectFrame::new())),
-            TokenKind::RBracket => close_array(stack, result, cursor)?,
-            TokenKind::RBrace => close_object(stack, result, cursor)?,
-            TokenKind::Colon => validate_colon(stack, cursor)?,
-            TokenKind::Comma => validate_comma(stack, cursor)?,
-            TokenKind::Eof => {
-                validate_end(stack, result, cursor)?;
-                return Ok(());
-            }
-        }
+fn parse_loop<'a, V>(events: &mut JsonEventParser<'a>, builder: &mut TreeBuilder<V>) -> Result<(), JsonParsingErrorV3>
+where
+    V: TreeValue + From<JsonValueRef<'a>>,
+    V::Key: From<Cow<'a, str>>,
+{
+    while let Some(event) = events.next() {
+        builder.push(event?, events.span())?;
     }
+    Ok(())
 }
 
-fn attach_value(
-    stack: &mut Vec<JsonFrame>,
-    result: &mut Option<JsonValue>,
-    value: JsonValue,
-) -> Result<(), JsonParsingErrorV3> {
-    let Some(frame) = stack.last_mut() else {
-        return if result.is_none() {
-            result.replace(value);
-            Ok(())
-        } else {
-            Err(JsonParsingErrorV3::ExpectedEOF)
-        };
-    };
-
-    match frame {
-        JsonFrame::Array(a) => {
-            a.items.push(value);
-            Ok(())
-        }
-        JsonFrame::Object(o) => match o.pending_key.take() {
-            None => match value {
-                JsonValue::JsonString(s) => {
-                    o.pending_key = Some(s);
-                    Ok(())
-                }
-                _ => Err(JsonParsingErrorV3::ObjectKeyNotString),
-            },
-            Some(key) => {
-                o.items.insert(key, value);
-                Ok(())
-            }
-        },
-    }
+/// Builds a JsonValue (or JsonValueRef) from events, one at a time. Shared
+/// with the push parser, which receives its events a chunk at a time.
+pub(crate) struct TreeBuilder<V: TreeValue> {
+    stack: Vec<JsonFrame<V>>,
+    result: Option<V>,
+    options: ParseOptions,
 }
 
-fn close_array(
-    stack: &mut Vec<JsonFrame>,
-    result: &mut Option<JsonValue>,
-    cursor: &usize,
-) -> Result<(), JsonParsingErrorV3> {
-    let Some(frame) = stack.pop() else {
-        return Err(JsonParsingErrorV3::UnexpectedClosing)
-    };
-
-    match frame {
-        JsonFrame::Array(a) => Ok(attach_value(stack, result, JsonValue::Array(a.items))?),
-        _ =>  Err(JsonParsingErrorV3::UneExpectedFrameType)
+impl<V: TreeValue> TreeBuilder<V> {
+    pub(crate) fn new(options: &ParseOptions) -> Self {
+        Self { stack: Vec::new(), result: None, options: *options }
     }
-}
-
-fn close_object(
-    stack: &mut Vec<JsonFrame>,
-    result: &mut Option<JsonValue>,
-    cursor: &usize,
-) -> Result<(), JsonParsingErrorV3> {
-    let Some(frame) = stack.pop() else {
-        return Err(JsonParsingErrorV3::UnexpectedClosing)
-    };
 
-    match frame {
-        JsonFrame::Object(o) => {
-            if o.pending_key.is_some() {
-                return Err(JsonParsingErrorV3::ObjectKeyWithoutValue)
+    /// `span` is the event's span as reported by the event parser.
+    pub(crate) fn push<'a>(&mut self, event: JsonEvent<'a>, span: Span) -> Result<(), JsonParsingErrorV3>
+    where
+        V: From<JsonValueRef<'a>>,
+        V::Key: From<Cow<'a, str>>,
+    {
+        let stack = &mut self.stack;
+        let limits = &self.options.limits;
+
+        match event {
+            JsonEvent::Value(value) => attach_value(stack, &mut self.result, V::from(value), span.start, limits),
+            JsonEvent::Key(key) => {
+                // the event parser only emits keys directly inside an object
+                if let Some(JsonFrame::Object(o)) = stack.last_mut() {
+                    o.pending_key = Some((V::Key::from(key), span));
+                }
+                Ok(())
             }
-
-            Ok(attach_value(stack, result, JsonValue::Object(o.items))?)
-        },
-        _ =>  Err(JsonParsingErrorV3::UneExpectedFrameType)
+            JsonEvent::StartArray => {
+                stack.push(JsonFrame::Array(Vec::new()));
+                Ok(())
+            }
+            JsonEvent::StartObject => {
+                stack.push(JsonFrame::Object(ObjectFrame::new(self.options.duplicate_keys)));
+                Ok(())
+            }
+            JsonEvent::EndArray | JsonEvent::EndObject => {
+                let value = match stack.pop() {
+                    Some(JsonFrame::Array(items)) => V::array(items),
+                    Some(JsonFrame::Object(o)) => o.items.finish(),
+                    None => return Ok(()),
+                };
+                attach_value(stack, &mut self.result, value, span.start, limits)
+            }
+        }
     }
-}
 
-fn validate_end(
-    stack: &Vec<JsonFrame>,
-    result: &Option<JsonValue>,
-    cursor: &usize,
-) -> Result<(), JsonParsingErrorV3> {
-    if !stack.is_empty() || result.is_none(){
-        return Err(JsonParsingErrorV3::UnexpectedEOF)
+    /// The root value as soon as it is complete (it is only set once the stack
+    /// is empty again); lets multi-document callers collect each document.
+    pub(crate) fn take_root(&mut self) -> Option<V> {
+        self.result.take()
     }
-    return Ok(());
-}
-
-fn validate_colon(
-    stack: &mut Vec<JsonFrame>,
-    cursor: &usize,
-) -> Result<(), JsonParsingErrorV3> {
-    let Some(frame) = stack.last() else {
-        // TODO: Need create seprate error probably
-        return Err(JsonParsingErrorV3::UnexpectedClosing)
-    };
 
-    match frame {
-        JsonFrame::Object(o) => {
-            if o.pending_key.is_none() {
-                // TODO: Probaly too new error type
-                return Err(JsonParsingErrorV3::ObjectKeyNotString)
-            }
-        },
-        _ => return Err(JsonParsingErrorV3::UneExpectedFrameType)
+    /// The root value, once the event parser has reported the end of the document.
+    pub(crate) fn finish(self, end: SourceLocation) -> Result<V, JsonParsingErrorV3> {
+        self.result.ok_or(JsonParsingErrorV3::UnexpectedEOF { at: end, frame: None })
     }
-
-    return Ok(());
 }
 
-fn validate_comma(
-    stack: &mut Vec<JsonFrame>,
-    cursor: &usize,
+fn attach_value<V: TreeValue>(
+    stack: &mut [JsonFrame<V>],
+    result: &mut Option<V>,
+    value: V,
+    at: SourceLocation,
+    limits: &ParseLimits,
 ) -> Result<(), JsonParsingErrorV3> {
-    let Some(frame) = stack.last() else {
-        // TODO: Need separate error — comma outside any structure
-        return Err(JsonParsingErrorV3::UnexpectedClosing)
+    let Some(frame) = stack.last_mut() else {
+        result.replace(value);
+        return Ok(());
     };
 
     match frame {
-        JsonFrame::Object(o) => {
-            // comma before any key-value pair: {, "a": 1}
-            if o.items.is_empty() && o.pending_key.is_none() {
-                return Err(JsonParsingErrorV3::MismatchedClosing)
-            }
-            // comma while waiting for value: {"a":, "b": 1}
-            if o.pending_key.is_some() {
-                return Err(JsonParsingErrorV3::MismatchedClosing)
+        JsonFrame::Array(items) => {
+            if items.len() >= limits.max_array_len {
+                return Err(JsonParsingErrorV3::LimitExceeded { limit: Limit::ArrayLength, at })
             }
-        },
-        JsonFrame::Array(a) => {
-            if a.items.is_empty() {
-                // TODO: New error for comma needed
-                return Err(JsonParsingErrorV3::MismatchedClosing)
+            items.push(value);
+        }
+        JsonFrame::Object(o) => {
+            // the event parser emits a Key before every value inside an object
+            let Some((key, key_span)) = o.pending_key.take() else {
+                return Ok(());
+            };
+            if o.items.len() >= limits.max_object_members && !o.items.contains_key(key.borrow()) {
+                return Err(JsonParsingErrorV3::LimitExceeded { limit: Limit::ObjectMembers, at })
             }
+            o.items.insert(key, key_span, value).map_err(|(key, first)| {
+                JsonParsingErrorV3::DuplicateKey { key, first, second: key_span }
+            })?;
         }
     }
-    return Ok(())
-}
-
-
-fn peek_token(tokens: &VecDeque<Token>) -> Option<&Token> {
-    tokens.front()
-}
-
-fn next_token(tokens: &mut VecDeque<Token>, cursor: &mut usize) -> Option<Token> {
-    let token = tokens.pop_front()?;
-    *cursor = token.span.end;
-    Some(token)
+    Ok(())
 }
@@ -0,0 +1,185 @@
+use std::fmt;
+
+use crate::json_writer::format_number;
+
+// =============================================================================
+// JsonNumber — lossless JSON numbers
+// =============================================================================
+//
+// Forcing every number through f64 silently corrupts integers above 2^53
+// (64-bit IDs, timestamps in ns, ...). A JsonNumber keeps the exact lexeme
+// the lexer validated and converts on demand:
+//
+//   as_i64 / as_u64 / as_i128 → Some only for integer lexemes that fit
+//   as_f64                    → nearest f64, always available
+//   as_str                    → the exact decimal text
+//
+// Numbers built from Rust values keep a lexeme too: integers are written out
+// in full, floats use the writer's shortest round-trip form. The only values
+// without a lexeme are non-finite floats, which JSON cannot express — they
+// are kept as f64 and written as `null`, like before.
+//
+// Equality is numeric, not textual: 1, 1.0 and 1e0 are all equal. Lexemes are
+// compared exactly, as decimals — never through f64, which would make
+// 2^53 + 1 equal to 2^53.0 and 2^53.0 equal to 2^53 but not 2^53 + 1 to 2^53.
+// Only non-finite values compare as f64 (so NaN != NaN).
+//
+// =============================================================================
+
+#[derive(Clone)]
+pub struct JsonNumber {
+    repr: NumberRepr,
+}
+
+#[derive(Clone)]
+enum NumberRepr {
+    Lexeme(Box<str>), // follows the JSON number grammar
+    NonFinite(f64),   // NaN / ±inf, only reachable through From<f64> (and JSON5's Infinity/NaN)
+}
+
+impl JsonNumber {
+    /// Wraps text the caller has already checked against the JSON number grammar.
+    pub(crate) fn from_lexeme(lexeme: &str) -> Self {
+        Self { repr: NumberRepr::Lexeme(lexeme.into()) }
+    }
+
+    /// The exact decimal text, None for non-finite values.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.repr {
+            NumberRepr::Lexeme(s) => Some(s),
+            NumberRepr::NonFinite(_) => None,
+        }
+    }
+
+    /// True when the text has no fraction and no exponent (`12`, `-0`; not `12.0` or `1e2`).
+    pub fn is_integer(&self) -> bool {
+        match &self.repr {
+            NumberRepr::Lexeme(s) => !s.contains(['.', 'e', 'E']),
+            NumberRepr::NonFinite(_) => false,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.integer_lexeme()?.parse().ok()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.integer_lexeme()?.parse().ok()
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        self.integer_lexeme()?.parse().ok()
+    }
+
+    /// Nearest f64. Lossy above 2^53, and magnitudes past f64::MAX become infinite.
+    pub fn as_f64(&self) -> f64 {
+        match &self.repr {
+            NumberRepr::Lexeme(s) => s.parse().expect("JsonNumber lexeme follows the JSON number grammar"),
+            NumberRepr::NonFinite(n) => *n,
+        }
+    }
+
+    fn integer_lexeme(&self) -> Option<&str> {
+        if self.is_integer() { self.as_str() } else { None }
+    }
+}
+
+impl PartialEq for JsonNumber {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.repr, &other.repr) {
+            (NumberRepr::Lexeme(a), NumberRepr::Lexeme(b)) => a == b || canonical(a) == canonical(b),
+            (NumberRepr::NonFinite(a), NumberRepr::NonFinite(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+// A lexeme as sign × digits × 10^exponent, with the digits stripped of leading
+// and trailing zeros: equal values give equal triples. Zero is (false, "", 0),
+// whatever its sign. Exponents beyond i128 saturate, which keeps the mapping
+// (and so equality) consistent, only no longer exact for such magnitudes.
+fn canonical(lexeme: &str) -> (bool, String, i128) {
+    let (negative, unsigned) = match lexeme.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, lexeme),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, parse_exponent(exponent)),
+        None => (unsigned, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let digits = format!("{}{}", int, frac);
+    let significant = digits.trim_start_matches('0').trim_end_matches('0');
+    if significant.is_empty() {
+        return (false, String::new(), 0);
+    }
+    let trailing_zeros = digits.len() - digits.trim_end_matches('0').len();
+    let exponent = exponent.saturating_sub(frac.len() as i128).saturating_add(trailing_zeros as i128);
+    (negative, significant.to_string(), exponent)
+}
+
+fn parse_exponent(text: &str) -> i128 {
+    let (negative, digits) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let magnitude = digits.parse::<i128>().unwrap_or(i128::MAX);
+    if negative { -magnitude } else { magnitude }
+}
+
+impl From<f64> for JsonNumber {
+    fn from(n: f64) -> Self {
+        if n.is_finite() {
+            Self::from_lexeme(&format_number(n))
+        } else {
+            Self { repr: NumberRepr::NonFinite(n) }
+        }
+    }
+}
+
+// Formats the f32 itself: widening first would turn 0.1 into 0.10000000149011612.
+impl From<f32> for JsonNumber {
+    fn from(n: f32) -> Self {
+        if n.is_finite() {
+            Self::from_lexeme(&format_number(n))
+        } else {
+            Self { repr: NumberRepr::NonFinite(n as f64) }
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for JsonNumber {
+                fn from(n: $t) -> Self {
+                    Self::from_lexeme(&n.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// Writes the JSON text; non-finite values become `null`, same as the writer.
+impl fmt::Display for JsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            NumberRepr::Lexeme(s) => f.write_str(s),
+            NumberRepr::NonFinite(_) => f.write_str("null"),
+        }
+    }
+}
+
+// Shows the lexeme unquoted, so JsonValue prints as Number(1.5).
+impl fmt::Debug for JsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            NumberRepr::Lexeme(s) => f.write_str(s),
+            NumberRepr::NonFinite(n) => write!(f, "{:?}", n),
+        }
+    }
+}
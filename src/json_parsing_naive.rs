@@ -3,7 +3,8 @@ use std::str::from_utf8;
 use std::string::String;
 use indexmap::IndexMap;
 
-use crate::json_definitions::{JsonParsingError, JsonValue};
+use crate::json_definitions::{JsonParsingError, JsonValue, LoneSurrogatePolicy, ParseOptions};
+use crate::json_number::JsonNumber;
 
 const HEX_ASCI_OFFSET_CAP: u8 = 0x41;
 
@@ -11,6 +12,16 @@ const HEX_ASCI_OFFSET_LOW: u8 = 0x61;
 const HEX_ASCI_OFFSET_NUM: u8 = 0x30;
 
 pub fn process_json_string_v1(json_string: &str) -> Result<JsonValue, JsonParsingError> {
+    process_json_string_v1_with_options(json_string, &ParseOptions::default())
+}
+
+/// v1 only looks at `options.lone_surrogates`.
+pub fn process_json_string_v1_with_options(
+    json_string: &str,
+    options: &ParseOptions,
+) -> Result<JsonValue, JsonParsingError> {
+    let lone_surrogates = options.lone_surrogates;
+
     if json_string.is_empty() {
         return Err(JsonParsingError::EmptyJsonFile);
     }
@@ -23,7 +34,7 @@ pub fn process_json_string_v1(json_string: &str) -> Result<JsonValue, JsonParsin
         return Err(JsonParsingError::InvalidJsonFile);
     }
 
-    let result = parse_json_value(json_string, &mut json_index)?;
+    let result = parse_json_value(json_string, &mut json_index, lone_surrogates)?;
     trim_spaces(json_string, &mut json_index);
 
     if json_index != json_string.len() {
@@ -35,6 +46,7 @@ pub fn process_json_string_v1(json_string: &str) -> Result<JsonValue, JsonParsin
 fn parse_json_value(
     json_string: &str,
     current_index: &mut usize,
+    lone_surrogates: LoneSurrogatePolicy,
 ) -> Result<JsonValue, JsonParsingError> {
     trim_spaces(json_string, current_index);
 
@@ -46,9 +58,9 @@ fn parse_json_value(
         b'n' => parse_null(json_string, current_index),
         b't' | b'f' => parse_bool(json_string, current_index),
         b'-' | b'0'..=b'9' => parse_number(json_string, current_index),
-        b'\"' => parse_string(json_string, current_index),
-        b'[' => parse_array(json_string, current_index),
-        b'{' => parse_json_object(json_string, current_index),
+        b'\"' => parse_string(json_string, current_index, lone_surrogates),
+        b'[' => parse_array(json_string, current_index, lone_surrogates),
+        b'{' => parse_json_object(json_string, current_index, lone_surrogates),
         _ => Err(JsonParsingError::InvalidJsonFile),
     }
 }
@@ -56,6 +68,7 @@ fn parse_json_value(
 fn parse_json_object(
     json_string: &str,
     current_index: &mut usize,
+    lone_surrogates: LoneSurrogatePolicy,
 ) -> Result<JsonValue, JsonParsingError> {
     //consume { token
     *current_index+=1;
@@ -100,9 +113,9 @@ fn parse_json_object(
                 }
 
                 if expecting_string {
-                    let key_value = parse_string(json_string, current_index)?;
-                    let key = match key_value {
-                        JsonValue::JsonString(s) => s,
+                    let mut key_value = parse_string(json_string, current_index, lone_surrogates)?;
+                    let key = match &mut key_value {
+                        JsonValue::JsonString(s) => std::mem::take(s),
                         _ => return Err(JsonParsingError::InvalidJsonObject),
                     };
                     current_key_str = key;
@@ -112,7 +125,7 @@ fn parse_json_object(
                 }
 
                 if expecting_value {
-                    let v = parse_json_value(json_string, current_index)?;
+                    let v = parse_json_value(json_string, current_index, lone_surrogates)?;
                     out_json_object.insert(current_key_str.to_string(),v);
                     expecting_value = false;
                     expecting_coma = true;
@@ -139,7 +152,7 @@ fn parse_json_object(
                 if !expecting_value {
                     return Err(JsonParsingError::InvalidJsonObject);
                 }
-                let v = parse_json_value(json_string, current_index)?;
+                let v = parse_json_value(json_string, current_index, lone_surrogates)?;
                 out_json_object.insert(current_key_str.to_string(),v);
                 expecting_value = false;
                 expecting_coma = true;
@@ -154,6 +167,7 @@ fn parse_json_object(
 fn parse_array(
     json_string: &str,
     current_index: &mut usize,
+    lone_surrogates: LoneSurrogatePolicy,
 ) -> Result<JsonValue, JsonParsingError> {
     // consume '['
     *current_index += 1;
@@ -202,7 +216,7 @@ fn parse_array(
                 if !expect_value {
                     return Err(JsonParsingError::InvalidArray);
                 }
-                let v = parse_json_value(json_string, current_index)?;
+                let v = parse_json_value(json_string, current_index, lone_surrogates)?;
                 out_array.push(v);
                 expect_value = false;
             }
@@ -213,11 +227,48 @@ fn parse_array(
 }
 fn consume_unicode(bytes_stream: &[u8],
                    current_index: &mut usize,
+                   lone_surrogates: LoneSurrogatePolicy,
 ) -> Result<([u8; 4], usize), JsonParsingError> {
 
     // Move after seen u separator
     *current_index += 1;
 
+    let high = consume_hex4(bytes_stream, current_index)?;
+
+    let code = match high {
+        // High surrogate: only valid when an escaped low surrogate follows
+        0xD800..=0xDBFF => {
+            let pair_start = *current_index;
+            let low = if bytes_stream[*current_index..].starts_with(b"\\u") {
+                *current_index += 2;
+                Some(consume_hex4(bytes_stream, current_index)?)
+            } else {
+                None
+            };
+
+            match low {
+                Some(low @ 0xDC00..=0xDFFF) => 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                _ => {
+                    // the next escape (if any) is decoded on its own
+                    *current_index = pair_start;
+                    lone_surrogate(lone_surrogates)?
+                }
+            }
+        }
+        0xDC00..=0xDFFF => lone_surrogate(lone_surrogates)?,
+        _ => high,
+    };
+
+    let  unicode_char  = char::from_u32(code).ok_or(JsonParsingError::InvalidUnicodeInString)?;
+
+    let mut buf = [0u8; 4];
+    let len = unicode_char.encode_utf8(&mut buf).len();
+
+    Ok((buf,len))
+}
+
+#[allow(clippy::needless_range_loop, clippy::identity_op)]
+fn consume_hex4(bytes_stream: &[u8], current_index: &mut usize) -> Result<u32, JsonParsingError> {
     let mut out_buffer = [0u8; 4];
 
     // Gather
@@ -242,27 +293,23 @@ fn consume_unicode(bytes_stream: &[u8],
         *current_index += 1;
     }
 
-    let code: u32 =
-        ((out_buffer[0] as u32) << 12) |
-        ((out_buffer[1] as u32) << 8)  |
-        ((out_buffer[2] as u32) << 4)  |
-        ((out_buffer[3] as u32) << 0);
+    Ok(((out_buffer[0] as u32) << 12) |
+       ((out_buffer[1] as u32) << 8)  |
+       ((out_buffer[2] as u32) << 4)  |
+       ((out_buffer[3] as u32) << 0))
+}
 
-    if code >= 0xD800 && code <= 0xDFFF {
-        return Err(JsonParsingError::InvalidUnicodeInString);
+fn lone_surrogate(lone_surrogates: LoneSurrogatePolicy) -> Result<u32, JsonParsingError> {
+    match lone_surrogates {
+        LoneSurrogatePolicy::Replace => Ok(char::REPLACEMENT_CHARACTER as u32),
+        LoneSurrogatePolicy::Error => Err(JsonParsingError::InvalidUnicodeInString),
     }
-
-    let  unicode_char  = char::from_u32(code).ok_or(JsonParsingError::InvalidUnicodeInString)?;
-
-    let mut buf = [0u8; 4];
-    let len = unicode_char.encode_utf8(&mut buf).len();
-
-    Ok((buf,len))
 }
 
 fn consume_escape_char(
     bytes_stream: &[u8],
     current_index: &mut usize,
+    lone_surrogates: LoneSurrogatePolicy,
 ) -> Result<([u8; 4], usize), JsonParsingError> {
     //check if we have scape char even if it double the work
     if *current_index >= bytes_stream.len() || bytes_stream[*current_index] != b'\\' {
@@ -304,7 +351,7 @@ fn consume_escape_char(
             out[0] = b'\t';
         }
         b'u' => {
-             return consume_unicode(bytes_stream, current_index);
+             return consume_unicode(bytes_stream, current_index, lone_surrogates);
         }
         _ => return Err(JsonParsingError::InvalidJsonFile),
     };
@@ -316,6 +363,7 @@ fn consume_escape_char(
 fn parse_string(
     json_string: &str,
     current_index: &mut usize,
+    lone_surrogates: LoneSurrogatePolicy,
 ) -> Result<JsonValue, JsonParsingError> {
     let bytes = json_string.as_bytes();
 
@@ -334,7 +382,7 @@ fn parse_string(
             b'\\' => {
                 out.extend_from_slice(&bytes[run_start..*current_index]);
 
-                let (buf, n) = consume_escape_char(bytes, current_index)?;
+                let (buf, n) = consume_escape_char(bytes, current_index, lone_surrogates)?;
                 out.extend_from_slice(&buf[..n]);
 
                 run_start = *current_index;
@@ -375,12 +423,10 @@ fn parse_number(
     current_index: &mut usize,
 ) -> Result<JsonValue, JsonParsingError> {
     let bytes = json_string.as_bytes();
-    let negative = if bytes[*current_index] == b'-' {
+    let number_start = *current_index;
+    if bytes[*current_index] == b'-' {
         *current_index += 1;
-        -1.0
-    } else {
-        1.0
-    };
+    }
 
     if *current_index >= bytes.len() || !bytes[*current_index].is_ascii_digit() {
         return Err(JsonParsingError::InvalidJsonFile);
@@ -393,8 +439,6 @@ fn parse_number(
         }
     }
 
-    let start_index = *current_index;
-
     let mut seen_dot = false;
     let mut seen_exp = false;
 
@@ -459,12 +503,8 @@ fn parse_number(
         return Err(JsonParsingError::InvalidJsonFile);
     }
 
-    let string_number_rep = &json_string[start_index..*current_index];
-    let num: f64 = string_number_rep
-        .parse()
-        .map_err(|_| JsonParsingError::InvalidJsonFile)?;
-
-    Ok(JsonValue::Number(num * negative))
+    // The scan above is the full JSON number grammar, so the slice can be kept as-is
+    Ok(JsonValue::Number(JsonNumber::from_lexeme(&json_string[number_start..*current_index])))
 }
 
 fn parse_null(json_string: &str, current_index: &mut usize) -> Result<JsonValue, JsonParsingError> {
@@ -0,0 +1,379 @@
+use std::error::Error;
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use crate::json_definitions::JsonValue;
+use crate::json_pointer::{JsonPointer, PointerError};
+
+// =============================================================================
+// JSON Patch (RFC 6902) — applying and generating patch documents
+// =============================================================================
+//
+// A patch is a JSON array of operations, applied in order:
+//
+//   [
+//     { "op": "test",    "path": "/version", "value": 3 },
+//     { "op": "replace", "path": "/version", "value": 4 },
+//     { "op": "add",     "path": "/tags/-",  "value": "new" },
+//     { "op": "move",    "from": "/old",     "path": "/new" }
+//   ]
+//
+// JsonPatch::from_value reads such a document (already parsed into a
+// JsonValue) and checks every operation up front: known "op", the members
+// that op needs, well-formed pointers. Unknown members are ignored, as the
+// RFC asks. to_value writes it back.
+//
+// APPLYING:
+//   Each operation is built on the JSON Pointer primitives (json_pointer.rs):
+//     add      → insert_at          (objects: insert/replace, arrays: insert)
+//     remove   → remove_at
+//     replace  → the target must exist, then it is overwritten
+//     move     → remove_at(from) + insert_at(path); `from` must not be an
+//                ancestor of `path`
+//     copy     → clone of `from` + insert_at(path)
+//     test     → equality as JsonValue defines it (numbers compare by value)
+//
+//   ATOMICITY: the operations run on a clone of the document, which replaces
+//   the original only once every operation has succeeded. A failure anywhere
+//   leaves the caller's value exactly as it was.
+//
+// GENERATING:
+//   generate_patch(from, to) walks both trees together with an explicit task
+//   stack (no recursion, like the rest of the crate):
+//     - objects: members only in `from` are removed, members only in `to`
+//       are added, common members are compared one level down
+//     - arrays of equal length are compared element by element; otherwise
+//       the common prefix and suffix are skipped, the remaining elements are
+//       paired up, and the surplus is removed (highest index first) or added
+//     - anything else that differs is replaced
+//   Operations come out in document order, and nothing that is equal
+//   produces an operation.
+//
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOperation {
+    Add { path: JsonPointer, value: JsonValue },
+    Remove { path: JsonPointer },
+    Replace { path: JsonPointer, value: JsonValue },
+    Move { from: JsonPointer, path: JsonPointer },
+    Copy { from: JsonPointer, path: JsonPointer },
+    Test { path: JsonPointer, value: JsonValue },
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JsonPatch {
+    pub operations: Vec<PatchOperation>,
+}
+
+// `index` is the position of the operation in the patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    // Reading the patch document
+    NotAnArray,
+    NotAnObject { index: usize },
+    MissingMember { index: usize, member: &'static str },
+    NotAString { index: usize, member: &'static str },  // "op", "path" and "from" are strings
+    UnknownOperation { index: usize, op: String },
+    InvalidPointer { index: usize, member: &'static str, error: PointerError },
+
+    // Applying it
+    PointerFailed { index: usize, error: PointerError },  // target (or `from`) did not resolve
+    TestFailed { index: usize, path: String },
+    MoveIntoChild { index: usize, from: String, path: String },
+}
+
+impl PatchOperation {
+    pub fn op(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+            PatchOperation::Move { .. } => "move",
+            PatchOperation::Copy { .. } => "copy",
+            PatchOperation::Test { .. } => "test",
+        }
+    }
+}
+
+impl JsonPatch {
+    pub fn new(operations: Vec<PatchOperation>) -> Self {
+        Self { operations }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub fn from_value(patch: &JsonValue) -> Result<Self, PatchError> {
+        let JsonValue::Array(items) = patch else {
+            return Err(PatchError::NotAnArray);
+        };
+
+        let operations = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| read_operation(index, item))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { operations })
+    }
+
+    pub fn to_value(&self) -> JsonValue {
+        let operations = self.operations.iter().map(|operation| {
+            let mut members = IndexMap::new();
+            members.insert("op".to_string(), JsonValue::JsonString(operation.op().to_string()));
+            match operation {
+                PatchOperation::Move { from, path } | PatchOperation::Copy { from, path } => {
+                    members.insert("from".to_string(), JsonValue::JsonString(from.to_string()));
+                    members.insert("path".to_string(), JsonValue::JsonString(path.to_string()));
+                }
+                PatchOperation::Remove { path } => {
+                    members.insert("path".to_string(), JsonValue::JsonString(path.to_string()));
+                }
+                PatchOperation::Add { path, value }
+                | PatchOperation::Replace { path, value }
+                | PatchOperation::Test { path, value } => {
+                    members.insert("path".to_string(), JsonValue::JsonString(path.to_string()));
+                    members.insert("value".to_string(), value.clone());
+                }
+            }
+            JsonValue::Object(members)
+        });
+        JsonValue::Array(operations.collect())
+    }
+}
+
+fn read_operation(index: usize, item: &JsonValue) -> Result<PatchOperation, PatchError> {
+    let JsonValue::Object(members) = item else {
+        return Err(PatchError::NotAnObject { index });
+    };
+
+    let string = |member: &'static str| match members.get(member) {
+        Some(JsonValue::JsonString(s)) => Ok(s.as_str()),
+        Some(_) => Err(PatchError::NotAString { index, member }),
+        None => Err(PatchError::MissingMember { index, member }),
+    };
+    let pointer = |member: &'static str| {
+        JsonPointer::parse(string(member)?).map_err(|error| PatchError::InvalidPointer { index, member, error })
+    };
+    let value = || members.get("value").cloned().ok_or(PatchError::MissingMember { index, member: "value" });
+
+    let operation = match string("op")? {
+        "add" => PatchOperation::Add { path: pointer("path")?, value: value()? },
+        "remove" => PatchOperation::Remove { path: pointer("path")? },
+        "replace" => PatchOperation::Replace { path: pointer("path")?, value: value()? },
+        "move" => PatchOperation::Move { from: pointer("from")?, path: pointer("path")? },
+        "copy" => PatchOperation::Copy { from: pointer("from")?, path: pointer("path")? },
+        "test" => PatchOperation::Test { path: pointer("path")?, value: value()? },
+        op => return Err(PatchError::UnknownOperation { index, op: op.to_string() }),
+    };
+    Ok(operation)
+}
+
+/// Applies every operation of `patch` to `value`, or none of them.
+pub fn apply_patch(value: &mut JsonValue, patch: &JsonPatch) -> Result<(), PatchError> {
+    let mut working = value.clone();
+    for (index, operation) in patch.operations.iter().enumerate() {
+        apply_operation(&mut working, index, operation)?;
+    }
+    *value = working;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut JsonValue, index: usize, operation: &PatchOperation) -> Result<(), PatchError> {
+    let failed = |error| PatchError::PointerFailed { index, error };
+
+    match operation {
+        PatchOperation::Add { path, value } => {
+            doc.insert_at(path, value.clone()).map_err(failed)?;
+        }
+        PatchOperation::Remove { path } => {
+            doc.remove_at(path).map_err(failed)?;
+        }
+        PatchOperation::Replace { path, value } => {
+            *doc.get_pointer_mut(path).map_err(failed)? = value.clone();
+        }
+        PatchOperation::Move { from, path } => {
+            if from == path {
+                // still has to exist
+                doc.get_pointer(from).map_err(failed)?;
+                return Ok(());
+            }
+            if from.is_prefix_of(path) {
+                return Err(PatchError::MoveIntoChild { index, from: from.to_string(), path: path.to_string() });
+            }
+            let moved = doc.remove_at(from).map_err(failed)?;
+            doc.insert_at(path, moved).map_err(failed)?;
+        }
+        PatchOperation::Copy { from, path } => {
+            let copied = doc.get_pointer(from).map_err(failed)?.clone();
+            doc.insert_at(path, copied).map_err(failed)?;
+        }
+        PatchOperation::Test { path, value } => {
+            if doc.get_pointer(path).map_err(failed)? != value {
+                return Err(PatchError::TestFailed { index, path: path.to_string() });
+            }
+        }
+    }
+    Ok(())
+}
+
+enum DiffTask<'v> {
+    Compare(PathId, &'v JsonValue, &'v JsonValue),
+    Emit(PatchOperation),
+}
+
+pub(crate) type PathId = usize;
+
+// Paths of the values being compared, each stored once as (parent, token) so
+// a deep tree does not copy its whole path at every level. A JsonPointer is
+// only built for the paths that end up in an operation. Shared with json_diff.
+pub(crate) struct Paths {
+    nodes: Vec<(PathId, String)>,
+}
+
+impl Paths {
+    pub(crate) const ROOT: PathId = 0;
+
+    pub(crate) fn new() -> Self {
+        Self { nodes: vec![(Self::ROOT, String::new())] }
+    }
+
+    pub(crate) fn child(&mut self, parent: PathId, token: String) -> PathId {
+        self.nodes.push((parent, token));
+        self.nodes.len() - 1
+    }
+
+    pub(crate) fn pointer(&self, mut id: PathId) -> JsonPointer {
+        let mut tokens = Vec::new();
+        while id != Self::ROOT {
+            let (parent, token) = &self.nodes[id];
+            tokens.push(token.as_str());
+            id = *parent;
+        }
+        JsonPointer::from_tokens(tokens.into_iter().rev())
+    }
+
+    pub(crate) fn child_pointer(&self, parent: PathId, token: impl Into<String>) -> JsonPointer {
+        self.pointer(parent).child(token)
+    }
+}
+
+/// A patch that turns `from` into `to`.
+pub fn generate_patch(from: &JsonValue, to: &JsonValue) -> JsonPatch {
+    let mut operations = Vec::new();
+    let mut paths = Paths::new();
+    let mut stack = vec![DiffTask::Compare(Paths::ROOT, from, to)];
+
+    while let Some(task) = stack.pop() {
+        let (path, a, b) = match task {
+            DiffTask::Emit(operation) => {
+                operations.push(operation);
+                continue;
+            }
+            DiffTask::Compare(path, a, b) => (path, a, b),
+        };
+
+        // Tasks for this level in document order; pushed reversed so they run in that order.
+        let mut tasks = Vec::new();
+        match (a, b) {
+            (JsonValue::Object(x), JsonValue::Object(y)) => {
+                for (key, xv) in x {
+                    match y.get(key) {
+                        Some(yv) => tasks.push(DiffTask::Compare(paths.child(path, key.clone()), xv, yv)),
+                        None => {
+                            let path = paths.child_pointer(path, key.as_str());
+                            tasks.push(DiffTask::Emit(PatchOperation::Remove { path }));
+                        }
+                    }
+                }
+                for (key, yv) in y {
+                    if !x.contains_key(key) {
+                        let path = paths.child_pointer(path, key.as_str());
+                        tasks.push(DiffTask::Emit(PatchOperation::Add { path, value: yv.clone() }));
+                    }
+                }
+            }
+            (JsonValue::Array(x), JsonValue::Array(y)) => diff_arrays(&mut paths, path, x, y, &mut tasks),
+            (JsonValue::Array(_) | JsonValue::Object(_), _) | (_, JsonValue::Array(_) | JsonValue::Object(_)) => {
+                tasks.push(DiffTask::Emit(PatchOperation::Replace { path: paths.pointer(path), value: b.clone() }));
+            }
+            // two leaves
+            _ => {
+                if a != b {
+                    tasks.push(DiffTask::Emit(PatchOperation::Replace { path: paths.pointer(path), value: b.clone() }));
+                }
+            }
+        }
+        stack.extend(tasks.into_iter().rev());
+    }
+
+    JsonPatch { operations }
+}
+
+fn diff_arrays<'v>(
+    paths: &mut Paths,
+    path: PathId,
+    x: &'v [JsonValue],
+    y: &'v [JsonValue],
+    tasks: &mut Vec<DiffTask<'v>>,
+) {
+    // Equal lengths pair up directly. Skipping equal elements at both ends is
+    // only worth its comparisons when something was inserted or removed.
+    let (prefix, suffix) = if x.len() == y.len() {
+        (0, 0)
+    } else {
+        let shorter = x.len().min(y.len());
+        let prefix = x.iter().zip(y).take_while(|(a, b)| a == b).count();
+        let suffix = x.iter().rev().zip(y.iter().rev()).take(shorter - prefix).take_while(|(a, b)| a == b).count();
+        (prefix, suffix)
+    };
+
+    let x_middle = x.len() - prefix - suffix;
+    let y_middle = y.len() - prefix - suffix;
+    let paired = x_middle.min(y_middle);
+
+    for i in prefix..prefix + paired {
+        tasks.push(DiffTask::Compare(paths.child(path, i.to_string()), &x[i], &y[i]));
+    }
+    // Surplus elements sit right after the paired ones; the paired indices stay valid.
+    for i in (prefix + paired..prefix + x_middle).rev() {
+        tasks.push(DiffTask::Emit(PatchOperation::Remove { path: paths.child_pointer(path, i.to_string()) }));
+    }
+    for (i, value) in y.iter().enumerate().take(prefix + y_middle).skip(prefix + paired) {
+        let path = paths.child_pointer(path, i.to_string());
+        tasks.push(DiffTask::Emit(PatchOperation::Add { path, value: value.clone() }));
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::NotAnArray => write!(f, "a JSON patch must be an array of operations"),
+            PatchError::NotAnObject { index } => write!(f, "operation {} is not an object", index),
+            PatchError::MissingMember { index, member } => write!(f, "operation {} has no {:?} member", index, member),
+            PatchError::NotAString { index, member } => {
+                write!(f, "member {:?} of operation {} must be a string", member, index)
+            }
+            PatchError::UnknownOperation { index, op } => write!(f, "operation {} has unknown op {:?}", index, op),
+            PatchError::InvalidPointer { index, member, error } => {
+                write!(f, "member {:?} of operation {}: {}", member, index, error)
+            }
+            PatchError::PointerFailed { index, error } => write!(f, "operation {} failed: {}", index, error),
+            PatchError::TestFailed { index, path } => write!(f, "operation {} failed: test of {} did not match", index, path),
+            PatchError::MoveIntoChild { index, from, path } => {
+                write!(f, "operation {} failed: cannot move {} into its own child {}", index, from, path)
+            }
+        }
+    }
+}
+
+impl Error for PatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PatchError::InvalidPointer { error, .. } | PatchError::PointerFailed { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
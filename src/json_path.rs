@@ -0,0 +1,1243 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use regex::Regex;
+
+use crate::json_definitions::JsonValue;
+use crate::json_lexer::{NumberError, SourceLocation, StringError};
+use crate::json_number::JsonNumber;
+use crate::json_pointer::JsonPointer;
+
+// =============================================================================
+// JSONPath (RFC 9535) — querying a JsonValue
+// =============================================================================
+//
+// A query is a root `$` followed by segments, each applying its selectors to
+// every node the previous segment produced:
+//
+//   $.store.book[?@.price < 10].title
+//   │ │     │    │              └─ child segment, name selector
+//   │ │     │    └─ child segment, filter selector
+//   │ └─────┴─ child segments, name selectors (shorthand for ['store'])
+//   └─ root node
+//
+//   segments:   [sel, sel]   child            ..[sel]  descendant (node and
+//               .name  .*    shorthands       ..name   everything below it)
+//   selectors:  'name'  *  3  -1  1:5:2  ?<logical expression>
+//
+// PARSING:
+//   JsonPath::parse is a hand-written recursive descent parser over the query
+//   text (queries are short; recursion depth is capped by MAX_NESTING). Errors
+//   carry a SourceLocation in the query, so render_error can point at them.
+//
+//   Filter expressions are type checked while parsing, as the RFC requires:
+//   only singular queries (names and indices only) and value-returning
+//   functions may be compared, match()/search() are tests, count()/value()
+//   take a query, and so on.
+//
+// EVALUATING:
+//   Each segment maps a list of nodes to the next list. The descendant
+//   segment walks the subtree with an explicit stack (parents before
+//   children, arrays in order, objects in member order), so deep documents
+//   are safe. Comparisons follow RFC 9535 2.3.5.2.2: an empty result
+//   ("Nothing") only equals Nothing, numbers compare by value, strings by
+//   code point, and < between anything else is false.
+//
+//   Every result carries its normalized path ($['store']['book'][0]), which
+//   converts to a JsonPointer for use with the pointer and patch modules.
+//
+// I-REGEXP:
+//   match() and search() take I-Regexp patterns (RFC 9485), translated to
+//   the regex crate's syntax: '.' excludes \n and \r, '^' and '$' are plain
+//   characters, and match() anchors at both ends. A pattern that is not
+//   valid I-Regexp makes the function false, never an error.
+//
+// =============================================================================
+
+/// Parsed query, ready to run against any number of values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    query: Query,
+}
+
+/// A node selected by a query: where it is, and the value itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPathNode<'v> {
+    pub path: NormalizedPath,
+    pub value: &'v JsonValue,
+}
+
+/// The unique path of a node, written as `$['a'][0]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NormalizedPath {
+    elements: Vec<PathElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathElement {
+    Name(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonPathError {
+    UnexpectedChar { at: SourceLocation, found: char, expected: &'static str },
+    UnexpectedEnd { at: SourceLocation, expected: &'static str },
+    InvalidString { at: SourceLocation, reason: StringError },
+    InvalidNumber { at: SourceLocation, reason: NumberError },
+    IndexOutOfRange { at: SourceLocation },                       // beyond ±(2^53 - 1)
+    TooDeep { at: SourceLocation },                               // nesting over MAX_NESTING
+
+    // Filter expressions that parse but are not well-typed
+    UnknownFunction { at: SourceLocation, name: String },
+    ArgumentCount { at: SourceLocation, function: &'static str, expected: usize, found: usize },
+    ArgumentType { at: SourceLocation, function: &'static str, expected: &'static str },
+    NonSingularQuery { at: SourceLocation },                      // compared query can select several nodes
+    NotComparable { at: SourceLocation, function: &'static str }, // match()/search() on one side of a comparison
+    NotATest { at: SourceLocation },                              // literal or value function used as a test
+}
+
+// =============================================================================
+// Syntax tree
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    relative: bool, // @ instead of $
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice { start: Option<i64>, end: Option<i64>, step: Option<i64> },
+    Filter(LogicalExpr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LogicalExpr {
+    Or(Vec<LogicalExpr>),
+    And(Vec<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Compare(Comparable, CompareOp, Comparable),
+    Exists(Query),
+    Function(FunctionCall), // returns a logical value or nodes
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Comparable {
+    Literal(JsonValue),
+    Query(Query), // singular
+    Function(FunctionCall), // returns a value
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FunctionCall {
+    function: Function,
+    args: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Argument {
+    Literal(JsonValue),
+    Query(Query),
+    Logical(LogicalExpr),
+    Function(FunctionCall),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+// The three types of RFC 9535 2.4.1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExprType {
+    Value,
+    Logical,
+    Nodes,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "length" => Some(Function::Length),
+            "count" => Some(Function::Count),
+            "match" => Some(Function::Match),
+            "search" => Some(Function::Search),
+            "value" => Some(Function::Value),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::Length => "length",
+            Function::Count => "count",
+            Function::Match => "match",
+            Function::Search => "search",
+            Function::Value => "value",
+        }
+    }
+
+    fn params(self) -> &'static [ExprType] {
+        match self {
+            Function::Length => &[ExprType::Value],
+            Function::Count | Function::Value => &[ExprType::Nodes],
+            Function::Match | Function::Search => &[ExprType::Value, ExprType::Value],
+        }
+    }
+
+    fn result(self) -> ExprType {
+        match self {
+            Function::Length | Function::Count | Function::Value => ExprType::Value,
+            Function::Match | Function::Search => ExprType::Logical,
+        }
+    }
+}
+
+impl Query {
+    // Only name and index selectors, one per segment: selects at most one node.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => {
+                matches!(selectors.as_slice(), [Selector::Name(_)] | [Selector::Index(_)])
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+const MAX_NESTING: usize = 128;
+
+// I-JSON integers: array indices and slice bounds must fit in ±(2^53 - 1).
+const MAX_INDEX: i64 = (1 << 53) - 1;
+
+// An operand before we know whether it is compared or tested.
+enum Operand {
+    Literal(JsonValue),
+    Query(Query),
+    Function(FunctionCall),
+}
+
+struct PathParser<'q> {
+    text: &'q str,
+    bytes: &'q [u8],
+    cursor: usize,
+    nesting: usize,
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<Self, JsonPathError> {
+        let mut parser = PathParser { text: query, bytes: query.as_bytes(), cursor: 0, nesting: 0 };
+
+        if !parser.eat(b'$') {
+            return Err(parser.unexpected("'$' at the start of the query"));
+        }
+        let segments = parser.parse_segments()?;
+        if parser.cursor < parser.bytes.len() {
+            return Err(parser.unexpected("'.', '..', '[' or the end of the query"));
+        }
+
+        Ok(Self { query: Query { relative: false, segments } })
+    }
+
+    /// Every node the query selects, in order, with its normalized path.
+    pub fn query<'v>(&self, value: &'v JsonValue) -> Vec<JsonPathNode<'v>> {
+        let mut evaluator = Evaluator::new(value);
+        let nodes = evaluator.run(&self.query.segments, vec![(NormalizedPath::default(), value)]);
+        nodes.into_iter().map(|(path, value)| JsonPathNode { path, value }).collect()
+    }
+
+    /// Like `query`, without building the paths.
+    pub fn query_values<'v>(&self, value: &'v JsonValue) -> Vec<&'v JsonValue> {
+        let mut evaluator = Evaluator::new(value);
+        evaluator.run(&self.query.segments, vec![((), value)]).into_iter().map(|(_, value)| value).collect()
+    }
+}
+
+impl<'q> PathParser<'q> {
+    // ---- helpers ------------------------------------------------------------
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.cursor).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.cursor += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.bytes[self.cursor..].starts_with(s.as_bytes()) {
+            self.cursor += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, b: u8, expected: &'static str) -> Result<(), JsonPathError> {
+        if self.eat(b) { Ok(()) } else { Err(self.unexpected(expected)) }
+    }
+
+    // Blank space: space, tab, LF, CR (RFC 9535 `S`).
+    fn skip_blank(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.cursor += 1;
+        }
+    }
+
+    // Skips blank space only if `pred` holds for what follows it.
+    fn blank_then(&mut self, pred: impl Fn(&Self) -> bool) -> bool {
+        let save = self.cursor;
+        self.skip_blank();
+        if pred(self) {
+            true
+        } else {
+            self.cursor = save;
+            false
+        }
+    }
+
+    fn current_char(&self) -> Option<char> {
+        self.text[self.cursor..].chars().next()
+    }
+
+    fn locate(&self, offset: usize) -> SourceLocation {
+        let mut loc = SourceLocation { offset, ..SourceLocation::START };
+        for c in self.text[..offset].chars() {
+            if c == '\n' {
+                loc.line += 1;
+                loc.column = 1;
+            } else {
+                loc.column += 1;
+            }
+        }
+        loc
+    }
+
+    fn here(&self) -> SourceLocation {
+        self.locate(self.cursor)
+    }
+
+    fn unexpected(&self, expected: &'static str) -> JsonPathError {
+        match self.current_char() {
+            Some(found) => JsonPathError::UnexpectedChar { at: self.here(), found, expected },
+            None => JsonPathError::UnexpectedEnd { at: self.here(), expected },
+        }
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, JsonPathError>) -> Result<T, JsonPathError> {
+        if self.nesting >= MAX_NESTING {
+            return Err(JsonPathError::TooDeep { at: self.here() });
+        }
+        self.nesting += 1;
+        let result = f(self);
+        self.nesting -= 1;
+        result
+    }
+
+    // ---- segments -----------------------------------------------------------
+
+    fn parse_segments(&mut self) -> Result<Vec<Segment>, JsonPathError> {
+        let mut segments = Vec::new();
+        while self.blank_then(|p| matches!(p.peek(), Some(b'.' | b'['))) {
+            segments.push(self.parse_segment()?);
+        }
+        Ok(segments)
+    }
+
+    fn parse_segment(&mut self) -> Result<Segment, JsonPathError> {
+        if self.eat_str("..") {
+            return match self.peek() {
+                Some(b'[') => Ok(Segment::Descendant(self.parse_bracketed()?)),
+                Some(b'*') => {
+                    self.cursor += 1;
+                    Ok(Segment::Descendant(vec![Selector::Wildcard]))
+                }
+                _ => Ok(Segment::Descendant(vec![self.parse_shorthand_name("a member name, '*' or '[' after '..'")?])),
+            };
+        }
+        if self.eat(b'.') {
+            if self.eat(b'*') {
+                return Ok(Segment::Child(vec![Selector::Wildcard]));
+            }
+            return Ok(Segment::Child(vec![self.parse_shorthand_name("a member name or '*' after '.'")?]));
+        }
+        Ok(Segment::Child(self.parse_bracketed()?))
+    }
+
+    fn parse_shorthand_name(&mut self, expected: &'static str) -> Result<Selector, JsonPathError> {
+        let start = self.cursor;
+        let is_first = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+
+        match self.current_char() {
+            Some(c) if is_first(c) => {}
+            _ => return Err(self.unexpected(expected)),
+        }
+        while let Some(c) = self.current_char() {
+            if !(is_first(c) || c.is_ascii_digit()) {
+                break;
+            }
+            self.cursor += c.len_utf8();
+        }
+        Ok(Selector::Name(self.text[start..self.cursor].to_string()))
+    }
+
+    fn parse_bracketed(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        self.expect(b'[', "'['")?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.parse_selector()?);
+            self.skip_blank();
+            if self.eat(b']') {
+                return Ok(selectors);
+            }
+            self.expect(b',', "',' or ']' after a selector")?;
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some(b'\'' | b'"') => Ok(Selector::Name(self.parse_string()?)),
+            Some(b'*') => {
+                self.cursor += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(b'?') => {
+                self.cursor += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(self.parse_logical_or()?))
+            }
+            Some(b'-' | b'0'..=b'9' | b':') => self.parse_index_or_slice(),
+            _ => Err(self.unexpected("a selector: a quoted name, '*', an index, a slice or '?'")),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector, JsonPathError> {
+        let start = if self.peek() == Some(b':') { None } else { Some(self.parse_int()?) };
+
+        if !self.blank_then(|p| p.peek() == Some(b':')) {
+            let Some(index) = start else { unreachable!("a missing start is always followed by ':'") };
+            return Ok(Selector::Index(index));
+        }
+        self.cursor += 1;
+
+        let mut end = None;
+        let mut step = None;
+        self.skip_blank();
+        if matches!(self.peek(), Some(b'-' | b'0'..=b'9')) {
+            end = Some(self.parse_int()?);
+        }
+        if self.blank_then(|p| p.peek() == Some(b':')) {
+            self.cursor += 1;
+            self.skip_blank();
+            if matches!(self.peek(), Some(b'-' | b'0'..=b'9')) {
+                step = Some(self.parse_int()?);
+            }
+        }
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    // int = "0" / ["-"] DIGIT1 *DIGIT, within the I-JSON range
+    fn parse_int(&mut self) -> Result<i64, JsonPathError> {
+        let start = self.cursor;
+        let negative = self.eat(b'-');
+        let digits_start = self.cursor;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.cursor += 1;
+        }
+        let digits = &self.text[digits_start..self.cursor];
+
+        if digits.is_empty() {
+            return Err(JsonPathError::InvalidNumber { at: self.here(), reason: NumberError::Empty });
+        }
+        if digits.starts_with('0') && (digits.len() > 1 || negative) {
+            return Err(JsonPathError::InvalidNumber { at: self.locate(start), reason: NumberError::LeadingZero });
+        }
+        match self.text[start..self.cursor].parse::<i64>() {
+            Ok(n) if (-MAX_INDEX..=MAX_INDEX).contains(&n) => Ok(n),
+            _ => Err(JsonPathError::IndexOutOfRange { at: self.locate(start) }),
+        }
+    }
+
+    // ---- literals -----------------------------------------------------------
+
+    fn parse_string(&mut self) -> Result<String, JsonPathError> {
+        let start = self.cursor;
+        let quote = self.bytes[self.cursor];
+        self.cursor += 1;
+
+        let mut out = String::new();
+        loop {
+            let Some(c) = self.current_char() else {
+                return Err(JsonPathError::InvalidString { at: self.here(), reason: StringError::Unterminated });
+            };
+            if c as u32 == quote as u32 {
+                self.cursor += 1;
+                return Ok(out);
+            }
+            if (c as u32) < 0x20 {
+                return Err(JsonPathError::InvalidString {
+                    at: self.here(),
+                    reason: StringError::ControlChar { found: c as u8 },
+                });
+            }
+            if c != '\\' {
+                out.push(c);
+                self.cursor += c.len_utf8();
+                continue;
+            }
+
+            let escape_at = self.cursor;
+            self.cursor += 1;
+            let Some(e) = self.peek() else {
+                return Err(JsonPathError::InvalidString { at: self.locate(start), reason: StringError::TrailingBackslash });
+            };
+            self.cursor += 1;
+            match e {
+                b'b' => out.push('\u{08}'),
+                b'f' => out.push('\u{0C}'),
+                b'n' => out.push('\n'),
+                b'r' => out.push('\r'),
+                b't' => out.push('\t'),
+                b'/' => out.push('/'),
+                b'\\' => out.push('\\'),
+                b'u' => out.push(self.parse_unicode_escape(escape_at)?),
+                // only the quote that delimits this string may be escaped
+                _ if e == quote => out.push(quote as char),
+                _ => {
+                    return Err(JsonPathError::InvalidString {
+                        at: self.locate(escape_at),
+                        reason: StringError::InvalidEscape { found: e },
+                    })
+                }
+            }
+        }
+    }
+
+    // After "\u": four hex digits, and a second \uXXXX for a surrogate pair.
+    fn parse_unicode_escape(&mut self, escape_at: usize) -> Result<char, JsonPathError> {
+        let high = self.parse_hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if !self.eat_str("\\u") {
+                    return Err(JsonPathError::InvalidString {
+                        at: self.locate(escape_at),
+                        reason: StringError::LoneHighSurrogate { code: high as u16 },
+                    });
+                }
+                let low = self.parse_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(JsonPathError::InvalidString {
+                        at: self.locate(escape_at),
+                        reason: StringError::LoneHighSurrogate { code: high as u16 },
+                    });
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => {
+                return Err(JsonPathError::InvalidString {
+                    at: self.locate(escape_at),
+                    reason: StringError::LoneLowSurrogate { code: high as u16 },
+                })
+            }
+            _ => high,
+        };
+        char::from_u32(code).ok_or(JsonPathError::InvalidString {
+            at: self.locate(escape_at),
+            reason: StringError::InvalidUnicodeEscape,
+        })
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, JsonPathError> {
+        let hex = self.text.get(self.cursor..self.cursor + 4).unwrap_or("");
+        if hex.len() != 4 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(JsonPathError::InvalidString { at: self.here(), reason: StringError::InvalidUnicodeEscape });
+        }
+        self.cursor += 4;
+        Ok(u32::from_str_radix(hex, 16).expect("four hex digits"))
+    }
+
+    // number = (int / "-0") [ frac ] [ exp ], i.e. the JSON number grammar
+    fn parse_number(&mut self) -> Result<JsonValue, JsonPathError> {
+        let start = self.cursor;
+        let number_error = |p: &Self, reason| JsonPathError::InvalidNumber { at: p.here(), reason };
+
+        self.eat(b'-');
+        let int_start = self.cursor;
+        let int_digits = self.skip_digits();
+        if int_digits == 0 {
+            return Err(number_error(self, NumberError::Empty));
+        }
+        if int_digits > 1 && self.bytes[int_start] == b'0' {
+            return Err(JsonPathError::InvalidNumber { at: self.locate(int_start), reason: NumberError::LeadingZero });
+        }
+        if self.eat(b'.') && self.skip_digits() == 0 {
+            return Err(number_error(self, NumberError::MissingFracDigit));
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            let _ = self.eat(b'+') || self.eat(b'-');
+            if self.skip_digits() == 0 {
+                return Err(number_error(self, NumberError::MissingExpDigit));
+            }
+        }
+        Ok(JsonValue::Number(JsonNumber::from_lexeme(&self.text[start..self.cursor])))
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.cursor;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.cursor += 1;
+        }
+        self.cursor - start
+    }
+
+    // ---- filter expressions -------------------------------------------------
+
+    fn parse_logical_or(&mut self) -> Result<LogicalExpr, JsonPathError> {
+        let first = self.parse_logical_and()?;
+        self.continue_or(first)
+    }
+
+    fn continue_or(&mut self, first: LogicalExpr) -> Result<LogicalExpr, JsonPathError> {
+        let mut items = vec![first];
+        while self.blank_then(|p| p.bytes[p.cursor..].starts_with(b"||")) {
+            self.cursor += 2;
+            self.skip_blank();
+            items.push(self.parse_logical_and()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { LogicalExpr::Or(items) })
+    }
+
+    fn parse_logical_and(&mut self) -> Result<LogicalExpr, JsonPathError> {
+        let first = self.parse_basic()?;
+        self.continue_and(first)
+    }
+
+    fn continue_and(&mut self, first: LogicalExpr) -> Result<LogicalExpr, JsonPathError> {
+        let mut items = vec![first];
+        while self.blank_then(|p| p.bytes[p.cursor..].starts_with(b"&&")) {
+            self.cursor += 2;
+            self.skip_blank();
+            items.push(self.parse_basic()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { LogicalExpr::And(items) })
+    }
+
+    // paren-expr / comparison-expr / test-expr, each possibly negated with '!'
+    fn parse_basic(&mut self) -> Result<LogicalExpr, JsonPathError> {
+        self.nested(|p| {
+            if p.eat(b'!') {
+                p.skip_blank();
+                if p.peek() == Some(b'(') {
+                    return Ok(LogicalExpr::Not(Box::new(p.parse_paren()?)));
+                }
+                let at = p.here();
+                let operand = p.parse_operand()?;
+                return Ok(LogicalExpr::Not(Box::new(test_expr(operand, at)?)));
+            }
+            if p.peek() == Some(b'(') {
+                return p.parse_paren();
+            }
+            let at = p.here();
+            let operand = p.parse_operand()?;
+            p.finish_basic(operand, at)
+        })
+    }
+
+    fn parse_paren(&mut self) -> Result<LogicalExpr, JsonPathError> {
+        self.expect(b'(', "'('")?;
+        self.skip_blank();
+        let expr = self.parse_logical_or()?;
+        self.skip_blank();
+        self.expect(b')', "')' or a logical operator")?;
+        Ok(expr)
+    }
+
+    // The operand is either the left side of a comparison or a test on its own.
+    fn finish_basic(&mut self, operand: Operand, at: SourceLocation) -> Result<LogicalExpr, JsonPathError> {
+        if !self.blank_then(|p| p.peek_compare_op().is_some()) {
+            return test_expr(operand, at);
+        }
+        let Some((op, len)) = self.peek_compare_op() else { unreachable!() };
+        self.cursor += len;
+        self.skip_blank();
+
+        let right_at = self.here();
+        let right = self.parse_operand()?;
+        Ok(LogicalExpr::Compare(comparable(operand, at)?, op, comparable(right, right_at)?))
+    }
+
+    fn peek_compare_op(&self) -> Option<(CompareOp, usize)> {
+        let rest = &self.bytes[self.cursor..];
+        let two = [(b"==", CompareOp::Eq), (b"!=", CompareOp::Ne), (b"<=", CompareOp::Le), (b">=", CompareOp::Ge)];
+        for (s, op) in two {
+            if rest.starts_with(s) {
+                return Some((op, 2));
+            }
+        }
+        match rest.first() {
+            Some(b'<') => Some((CompareOp::Lt, 1)),
+            Some(b'>') => Some((CompareOp::Gt, 1)),
+            _ => None,
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, JsonPathError> {
+        match self.peek() {
+            Some(b'@' | b'$') => Ok(Operand::Query(self.parse_filter_query()?)),
+            Some(b'\'' | b'"') => Ok(Operand::Literal(JsonValue::JsonString(self.parse_string()?))),
+            Some(b'-' | b'0'..=b'9') => Ok(Operand::Literal(self.parse_number()?)),
+            Some(b'a'..=b'z') => {
+                let at = self.here();
+                let start = self.cursor;
+                while let Some(b'a'..=b'z' | b'0'..=b'9' | b'_') = self.peek() {
+                    self.cursor += 1;
+                }
+                let name = &self.text[start..self.cursor];
+                match (name, self.peek()) {
+                    (_, Some(b'(')) => Ok(Operand::Function(self.parse_function(name, at)?)),
+                    ("true", _) => Ok(Operand::Literal(JsonValue::Boolean(true))),
+                    ("false", _) => Ok(Operand::Literal(JsonValue::Boolean(false))),
+                    ("null", _) => Ok(Operand::Literal(JsonValue::Null)),
+                    _ => Err(self.unexpected("'(' after a function name")),
+                }
+            }
+            _ => Err(self.unexpected("a literal, a query ('@' or '$') or a function call")),
+        }
+    }
+
+    fn parse_filter_query(&mut self) -> Result<Query, JsonPathError> {
+        let relative = self.peek() == Some(b'@');
+        self.cursor += 1;
+        Ok(Query { relative, segments: self.parse_segments()? })
+    }
+
+    fn parse_function(&mut self, name: &str, at: SourceLocation) -> Result<FunctionCall, JsonPathError> {
+        let Some(function) = Function::from_name(name) else {
+            return Err(JsonPathError::UnknownFunction { at, name: name.to_string() });
+        };
+        self.expect(b'(', "'('")?;
+
+        let mut args = Vec::new();
+        self.skip_blank();
+        if !self.eat(b')') {
+            loop {
+                let arg_at = self.here();
+                args.push((self.parse_argument()?, arg_at));
+                self.skip_blank();
+                if self.eat(b')') {
+                    break;
+                }
+                self.expect(b',', "',' or ')' after a function argument")?;
+                self.skip_blank();
+            }
+        }
+
+        let params = function.params();
+        if args.len() != params.len() {
+            return Err(JsonPathError::ArgumentCount {
+                at,
+                function: function.name(),
+                expected: params.len(),
+                found: args.len(),
+            });
+        }
+        for ((arg, arg_at), param) in args.iter().zip(params) {
+            if !argument_fits(arg, *param) {
+                return Err(JsonPathError::ArgumentType { at: *arg_at, function: function.name(), expected: describe(*param) });
+            }
+        }
+
+        Ok(FunctionCall { function, args: args.into_iter().map(|(arg, _)| arg).collect() })
+    }
+
+    // literal / filter-query / logical-expr / function-expr
+    fn parse_argument(&mut self) -> Result<Argument, JsonPathError> {
+        self.nested(|p| {
+            if matches!(p.peek(), Some(b'!' | b'(')) {
+                return Ok(Argument::Logical(p.parse_logical_or()?));
+            }
+
+            let at = p.here();
+            let operand = p.parse_operand()?;
+            let continues = p.blank_then(|p| {
+                let rest = &p.bytes[p.cursor..];
+                p.peek_compare_op().is_some() || rest.starts_with(b"&&") || rest.starts_with(b"||")
+            });
+            if continues {
+                let basic = p.finish_basic(operand, at)?;
+                let and = p.continue_and(basic)?;
+                return Ok(Argument::Logical(p.continue_or(and)?));
+            }
+
+            Ok(match operand {
+                Operand::Literal(value) => Argument::Literal(value),
+                Operand::Query(query) => Argument::Query(query),
+                Operand::Function(call) => Argument::Function(call),
+            })
+        })
+    }
+}
+
+// ---- well-typedness (RFC 9535 2.4.3) -----------------------------------------
+
+fn test_expr(operand: Operand, at: SourceLocation) -> Result<LogicalExpr, JsonPathError> {
+    match operand {
+        Operand::Query(query) => Ok(LogicalExpr::Exists(query)),
+        Operand::Function(call) if call.function.result() != ExprType::Value => Ok(LogicalExpr::Function(call)),
+        Operand::Function(_) | Operand::Literal(_) => Err(JsonPathError::NotATest { at }),
+    }
+}
+
+fn comparable(operand: Operand, at: SourceLocation) -> Result<Comparable, JsonPathError> {
+    match operand {
+        Operand::Literal(value) => Ok(Comparable::Literal(value)),
+        Operand::Query(query) if query.is_singular() => Ok(Comparable::Query(query)),
+        Operand::Query(_) => Err(JsonPathError::NonSingularQuery { at }),
+        Operand::Function(call) if call.function.result() == ExprType::Value => Ok(Comparable::Function(call)),
+        Operand::Function(call) => Err(JsonPathError::NotComparable { at, function: call.function.name() }),
+    }
+}
+
+fn argument_fits(arg: &Argument, param: ExprType) -> bool {
+    match (param, arg) {
+        (ExprType::Value, Argument::Literal(_)) => true,
+        (ExprType::Value, Argument::Query(query)) => query.is_singular(),
+        (ExprType::Logical, Argument::Logical(_) | Argument::Query(_)) => true,
+        (ExprType::Nodes, Argument::Query(_)) => true,
+        (ExprType::Logical, Argument::Function(call)) => call.function.result() != ExprType::Value,
+        (_, Argument::Function(call)) => call.function.result() == param,
+        _ => false,
+    }
+}
+
+fn describe(param: ExprType) -> &'static str {
+    match param {
+        ExprType::Value => "a value: a literal, a singular query or length()/count()/value()",
+        ExprType::Logical => "a logical expression",
+        ExprType::Nodes => "a query",
+    }
+}
+
+// =============================================================================
+// Evaluator
+// =============================================================================
+
+// What a node's location is recorded as: a NormalizedPath for results, nothing
+// at all inside filters, where only the values matter.
+trait Location: Clone {
+    fn name(&self, name: &str) -> Self;
+    fn index(&self, index: usize) -> Self;
+}
+
+impl Location for () {
+    fn name(&self, _: &str) -> Self {}
+    fn index(&self, _: usize) -> Self {}
+}
+
+impl Location for NormalizedPath {
+    fn name(&self, name: &str) -> Self {
+        let mut path = self.clone();
+        path.elements.push(PathElement::Name(name.to_string()));
+        path
+    }
+
+    fn index(&self, index: usize) -> Self {
+        let mut path = self.clone();
+        path.elements.push(PathElement::Index(index));
+        path
+    }
+}
+
+enum FunctionResult<'a> {
+    Value(Option<Cow<'a, JsonValue>>), // None is "Nothing"
+    Logical(bool),
+}
+
+struct Evaluator<'v> {
+    root: &'v JsonValue,
+    // compiled I-Regexp patterns, None if the pattern is invalid; keyed by (pattern, anchored)
+    regexes: HashMap<(String, bool), Option<Regex>>,
+}
+
+impl<'v> Evaluator<'v> {
+    fn new(root: &'v JsonValue) -> Self {
+        Self { root, regexes: HashMap::new() }
+    }
+
+    fn run<L: Location>(&mut self, segments: &[Segment], mut nodes: Vec<(L, &'v JsonValue)>) -> Vec<(L, &'v JsonValue)> {
+        for segment in segments {
+            let mut next = Vec::new();
+            match segment {
+                Segment::Child(selectors) => {
+                    for (location, value) in &nodes {
+                        for selector in selectors {
+                            self.select(selector, location, value, &mut next);
+                        }
+                    }
+                }
+                Segment::Descendant(selectors) => {
+                    for (location, value) in &nodes {
+                        let mut stack = vec![(location.clone(), *value)];
+                        while let Some((location, value)) = stack.pop() {
+                            for selector in selectors {
+                                self.select(selector, &location, value, &mut next);
+                            }
+                            match value {
+                                JsonValue::Array(items) => {
+                                    stack.extend(items.iter().enumerate().rev().map(|(i, v)| (location.index(i), v)));
+                                }
+                                JsonValue::Object(members) => {
+                                    stack.extend(members.iter().rev().map(|(k, v)| (location.name(k), v)));
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+            }
+            nodes = next;
+        }
+        nodes
+    }
+
+    fn select<L: Location>(&mut self, selector: &Selector, location: &L, value: &'v JsonValue, out: &mut Vec<(L, &'v JsonValue)>) {
+        match (selector, value) {
+            (Selector::Name(name), JsonValue::Object(members)) => {
+                if let Some(member) = members.get(name) {
+                    out.push((location.name(name), member));
+                }
+            }
+            (Selector::Wildcard, JsonValue::Array(items)) => {
+                out.extend(items.iter().enumerate().map(|(i, v)| (location.index(i), v)));
+            }
+            (Selector::Wildcard, JsonValue::Object(members)) => {
+                out.extend(members.iter().map(|(k, v)| (location.name(k), v)));
+            }
+            (Selector::Index(index), JsonValue::Array(items)) => {
+                let len = items.len() as i64;
+                let i = if *index < 0 { len + index } else { *index };
+                if (0..len).contains(&i) {
+                    out.push((location.index(i as usize), &items[i as usize]));
+                }
+            }
+            (Selector::Slice { start, end, step }, JsonValue::Array(items)) => {
+                for i in slice_indices(items.len(), *start, *end, *step) {
+                    out.push((location.index(i), &items[i]));
+                }
+            }
+            (Selector::Filter(expr), JsonValue::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    if self.test(expr, item) {
+                        out.push((location.index(i), item));
+                    }
+                }
+            }
+            (Selector::Filter(expr), JsonValue::Object(members)) => {
+                for (key, member) in members {
+                    if self.test(expr, member) {
+                        out.push((location.name(key), member));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn test(&mut self, expr: &LogicalExpr, current: &'v JsonValue) -> bool {
+        match expr {
+            LogicalExpr::Or(items) => items.iter().any(|item| self.test(item, current)),
+            LogicalExpr::And(items) => items.iter().all(|item| self.test(item, current)),
+            LogicalExpr::Not(inner) => !self.test(inner, current),
+            LogicalExpr::Exists(query) => !self.nodes(query, current).is_empty(),
+            LogicalExpr::Function(call) => match self.call(call, current) {
+                FunctionResult::Logical(b) => b,
+                FunctionResult::Value(_) => false, // rejected by the type check
+            },
+            LogicalExpr::Compare(left, op, right) => {
+                let left = self.comparable(left, current);
+                let right = self.comparable(right, current);
+                compare(left.as_deref(), *op, right.as_deref())
+            }
+        }
+    }
+
+    fn nodes(&mut self, query: &Query, current: &'v JsonValue) -> Vec<&'v JsonValue> {
+        let start = if query.relative { current } else { self.root };
+        self.run(&query.segments, vec![((), start)]).into_iter().map(|(_, value)| value).collect()
+    }
+
+    // The single node a singular query selects, or Nothing.
+    fn single(&mut self, query: &Query, current: &'v JsonValue) -> Option<&'v JsonValue> {
+        match self.nodes(query, current).as_slice() {
+            [value] => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn comparable<'a>(&mut self, comparable: &'a Comparable, current: &'v JsonValue) -> Option<Cow<'a, JsonValue>>
+    where
+        'v: 'a,
+    {
+        match comparable {
+            Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+            Comparable::Query(query) => self.single(query, current).map(Cow::Borrowed),
+            Comparable::Function(call) => match self.call(call, current) {
+                FunctionResult::Value(value) => value,
+                FunctionResult::Logical(_) => None, // rejected by the type check
+            },
+        }
+    }
+
+    fn value_arg<'a>(&mut self, arg: &'a Argument, current: &'v JsonValue) -> Option<Cow<'a, JsonValue>>
+    where
+        'v: 'a,
+    {
+        match arg {
+            Argument::Literal(value) => Some(Cow::Borrowed(value)),
+            Argument::Query(query) => self.single(query, current).map(Cow::Borrowed),
+            Argument::Function(call) => match self.call(call, current) {
+                FunctionResult::Value(value) => value,
+                FunctionResult::Logical(_) => None,
+            },
+            Argument::Logical(_) => None, // rejected by the type check
+        }
+    }
+
+    fn nodes_arg(&mut self, arg: &Argument, current: &'v JsonValue) -> Vec<&'v JsonValue> {
+        match arg {
+            Argument::Query(query) => self.nodes(query, current),
+            _ => Vec::new(), // no standard function returns nodes
+        }
+    }
+
+    fn call<'a>(&mut self, call: &'a FunctionCall, current: &'v JsonValue) -> FunctionResult<'a>
+    where
+        'v: 'a,
+    {
+        let args = &call.args;
+        match call.function {
+            Function::Length => {
+                let length = match self.value_arg(&args[0], current).as_deref() {
+                    Some(JsonValue::JsonString(s)) => Some(s.chars().count()),
+                    Some(JsonValue::Array(items)) => Some(items.len()),
+                    Some(JsonValue::Object(members)) => Some(members.len()),
+                    _ => None,
+                };
+                FunctionResult::Value(length.map(|n| Cow::Owned(JsonValue::Number(n.into()))))
+            }
+            Function::Count => {
+                let count = self.nodes_arg(&args[0], current).len();
+                FunctionResult::Value(Some(Cow::Owned(JsonValue::Number(count.into()))))
+            }
+            Function::Value => match self.nodes_arg(&args[0], current).as_slice() {
+                [value] => FunctionResult::Value(Some(Cow::Borrowed(*value))),
+                _ => FunctionResult::Value(None),
+            },
+            Function::Match | Function::Search => {
+                let text = self.value_arg(&args[0], current);
+                let pattern = self.value_arg(&args[1], current);
+                let matched = match (text.as_deref(), pattern.as_deref()) {
+                    (Some(JsonValue::JsonString(text)), Some(JsonValue::JsonString(pattern))) => {
+                        self.regex_matches(pattern, text, call.function == Function::Match)
+                    }
+                    _ => false,
+                };
+                FunctionResult::Logical(matched)
+            }
+        }
+    }
+
+    fn regex_matches(&mut self, pattern: &str, text: &str, anchored: bool) -> bool {
+        let key = (pattern.to_string(), anchored);
+        let regex = self.regexes.entry(key).or_insert_with(|| {
+            let translated = translate_iregexp(pattern)?;
+            let full = if anchored { format!("^(?:{})$", translated) } else { translated };
+            Regex::new(&full).ok()
+        });
+        regex.as_ref().is_some_and(|regex| regex.is_match(text))
+    }
+}
+
+fn compare(left: Option<&JsonValue>, op: CompareOp, right: Option<&JsonValue>) -> bool {
+    match op {
+        CompareOp::Eq => left == right,
+        CompareOp::Ne => left != right,
+        CompareOp::Lt => less_than(left, right),
+        CompareOp::Le => less_than(left, right) || left == right,
+        CompareOp::Gt => less_than(right, left),
+        CompareOp::Ge => less_than(right, left) || left == right,
+    }
+}
+
+fn less_than(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (Some(JsonValue::Number(a)), Some(JsonValue::Number(b))) => match (a.as_i128(), b.as_i128()) {
+            (Some(a), Some(b)) => a < b,
+            _ => a.as_f64() < b.as_f64(),
+        },
+        // UTF-8 byte order is code point order
+        (Some(JsonValue::JsonString(a)), Some(JsonValue::JsonString(b))) => a < b,
+        _ => false,
+    }
+}
+
+// RFC 9535 2.3.4.2.2: the array indices a slice selects, in order.
+fn slice_indices(len: usize, start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    if step == 0 {
+        return Vec::new();
+    }
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+
+    let mut indices = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices
+}
+
+// I-Regexp (RFC 9485) → regex crate syntax, None if the pattern is not I-Regexp.
+fn translate_iregexp(pattern: &str) -> Option<String> {
+    let mut out = String::with_capacity(pattern.len());
+    let mut in_class = false;
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let e = chars.next()?;
+                match e {
+                    'p' | 'P' | 'n' | 'r' | 't' | '(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^'
+                    | '{' | '|' | '}' => {
+                        out.push('\\');
+                        out.push(e);
+                    }
+                    _ => return None,
+                }
+            }
+            '[' if in_class => return None,
+            '[' => {
+                in_class = true;
+                out.push('[');
+                if chars.peek() == Some(&'^') {
+                    chars.next();
+                    out.push('^');
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                out.push(']');
+            }
+            // set operators in the regex crate, plain characters in I-Regexp
+            '&' | '~' if in_class => {
+                out.push('\\');
+                out.push(c);
+            }
+            '.' if !in_class => out.push_str("[^\\n\\r]"),
+            '^' | '$' if !in_class => {
+                out.push('\\');
+                out.push(c);
+            }
+            '(' if !in_class && chars.peek() == Some(&'?') => return None,
+            _ => out.push(c),
+        }
+    }
+    if in_class { None } else { Some(out) }
+}
+
+// =============================================================================
+// Normalized paths
+// =============================================================================
+
+impl NormalizedPath {
+    pub fn elements(&self) -> &[PathElement] {
+        &self.elements
+    }
+
+    pub fn to_pointer(&self) -> JsonPointer {
+        JsonPointer::from_tokens(self.elements.iter().map(|element| match element {
+            PathElement::Name(name) => name.clone(),
+            PathElement::Index(index) => index.to_string(),
+        }))
+    }
+}
+
+impl fmt::Display for NormalizedPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for element in &self.elements {
+            match element {
+                PathElement::Index(index) => write!(f, "[{}]", index)?,
+                PathElement::Name(name) => {
+                    f.write_str("['")?;
+                    for c in name.chars() {
+                        match c {
+                            '\u{08}' => f.write_str("\\b")?,
+                            '\u{0C}' => f.write_str("\\f")?,
+                            '\n' => f.write_str("\\n")?,
+                            '\r' => f.write_str("\\r")?,
+                            '\t' => f.write_str("\\t")?,
+                            '\'' => f.write_str("\\'")?,
+                            '\\' => f.write_str("\\\\")?,
+                            '\u{0}'..='\u{1F}' => write!(f, "\\u{:04x}", c as u32)?,
+                            _ => write!(f, "{}", c)?,
+                        }
+                    }
+                    f.write_str("']")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use crate::json_definitions::JsonValue;
+
+// =============================================================================
+// JSON Pointer (RFC 6901) — addressing a value inside a JsonValue
+// =============================================================================
+//
+// SYNTAX:
+//   A pointer is either "" (the whole document) or a sequence of reference
+//   tokens, each introduced by '/':
+//
+//     /a/b/0      → member "a", then member "b", then array element 0
+//     /a~1b       → member "a/b"      (~1 stands for '/')
+//     /m~0n       → member "m~n"      (~0 stands for '~')
+//     /arr/-      → one past the last element (only useful for insertion)
+//
+//   Tokens are unescaped once, when the pointer is parsed; '~' followed by
+//   anything other than 0 or 1 is an error. Array indices are decimal without
+//   leading zeros, exactly as the RFC spells them.
+//
+// WALKING:
+//   Resolution is a plain loop over the tokens, one level per step, so a
+//   pointer as deep as the tree is no problem. Objects are looked up by key
+//   in the IndexMap, arrays by index in the Vec — nothing is copied.
+//
+// MUTATION:
+//   insert_at_pointer and remove_at_pointer resolve the parent and act on the
+//   last token, with the semantics of JSON Patch "add" and "remove":
+//     - object: insert or replace the member / remove it (order of the
+//       remaining members is kept)
+//     - array:  insert before the index, shifting the rest ("-" appends) /
+//       remove the element
+//   The empty pointer addresses the document itself.
+//
+// =============================================================================
+
+/// A parsed JSON Pointer: the unescaped reference tokens. `Display` writes it
+/// back in its escaped form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerError {
+    // Syntax — the pointer string itself is malformed
+    MissingLeadingSlash { pointer: String },
+    InvalidEscape { pointer: String, offset: usize },  // byte offset of the '~'
+
+    // Resolution — `path` is the pointer up to and including the failing token
+    MissingKey { path: String, key: String },
+    IndexOutOfBounds { path: String, index: usize, len: usize },
+    InvalidIndex { path: String, token: String },       // not a number, leading zero or "-"
+    NotAContainer { path: String },                     // tried to step into a leaf
+}
+
+impl JsonPointer {
+    /// The empty pointer, addressing the whole document.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(pointer: &str) -> Result<Self, PointerError> {
+        if pointer.is_empty() {
+            return Ok(Self::root());
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(PointerError::MissingLeadingSlash { pointer: pointer.to_string() });
+        };
+
+        let mut tokens = Vec::new();
+        let mut offset = 1;
+        for raw in rest.split('/') {
+            tokens.push(unescape_token(raw).map_err(|at| PointerError::InvalidEscape {
+                pointer: pointer.to_string(),
+                offset: offset + at,
+            })?);
+            offset += raw.len() + 1;
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Builds a pointer from unescaped tokens, e.g. `["a/b", "0"]` → `/a~1b/0`.
+    pub fn from_tokens<I, S>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { tokens: tokens.into_iter().map(Into::into).collect() }
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// This pointer with one more token appended.
+    pub fn child(&self, token: impl Into<String>) -> JsonPointer {
+        let mut child = self.clone();
+        child.push(token);
+        child
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// The pointer to the containing value, `None` for the root.
+    pub fn parent(&self) -> Option<JsonPointer> {
+        let (_, parent) = self.tokens.split_last()?;
+        Some(Self { tokens: parent.to_vec() })
+    }
+
+    /// `true` if `other` is this pointer or lies below it.
+    pub fn is_prefix_of(&self, other: &JsonPointer) -> bool {
+        other.tokens.starts_with(&self.tokens)
+    }
+
+    // Escaped form of the first `len` tokens, used to say where resolution failed.
+    fn prefix(&self, len: usize) -> String {
+        Self { tokens: self.tokens[..len].to_vec() }.to_string()
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", escape_token(token))?;
+        }
+        Ok(())
+    }
+}
+
+/// `~` → `~0`, `/` → `~1`.
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+// Err carries the offset of the bad '~' within the token.
+fn unescape_token(raw: &str) -> Result<String, usize> {
+    if !raw.contains('~') {
+        return Ok(raw.to_string());
+    }
+
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some((_, '0')) => out.push('~'),
+            Some((_, '1')) => out.push('/'),
+            _ => return Err(i),
+        }
+    }
+    Ok(out)
+}
+
+// "0" or a digit string without a leading zero; anything else (including "-") is None.
+fn parse_index(token: &str) -> Option<usize> {
+    let bytes = token.as_bytes();
+    let well_formed = !bytes.is_empty() && bytes.iter().all(u8::is_ascii_digit) && (bytes[0] != b'0' || bytes.len() == 1);
+    if !well_formed {
+        return None;
+    }
+    token.parse().ok()
+}
+
+// Where in an array an insertion goes: an index up to len, or "-" for the end.
+fn insertion_index(pointer: &JsonPointer, depth: usize, len: usize) -> Result<usize, PointerError> {
+    let token = &pointer.tokens[depth];
+    if token == "-" {
+        return Ok(len);
+    }
+    let Some(index) = parse_index(token) else {
+        return Err(PointerError::InvalidIndex { path: pointer.prefix(depth + 1), token: token.clone() });
+    };
+    if index > len {
+        return Err(PointerError::IndexOutOfBounds { path: pointer.prefix(depth + 1), index, len });
+    }
+    Ok(index)
+}
+
+// An existing element: the index must be below len.
+fn element_index(pointer: &JsonPointer, depth: usize, len: usize) -> Result<usize, PointerError> {
+    let token = &pointer.tokens[depth];
+    if token == "-" {
+        return Err(PointerError::IndexOutOfBounds { path: pointer.prefix(depth + 1), index: len, len });
+    }
+    let index = insertion_index(pointer, depth, len)?;
+    if index == len {
+        return Err(PointerError::IndexOutOfBounds { path: pointer.prefix(depth + 1), index, len });
+    }
+    Ok(index)
+}
+
+impl JsonValue {
+    /// The value `pointer` refers to, e.g. `value.pointer("/a/b/0")`.
+    pub fn pointer(&self, pointer: &str) -> Result<&JsonValue, PointerError> {
+        self.get_pointer(&JsonPointer::parse(pointer)?)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut JsonValue, PointerError> {
+        self.get_pointer_mut(&JsonPointer::parse(pointer)?)
+    }
+
+    /// Adds `value` at `pointer` and returns the member it replaced, if any.
+    /// Array elements are inserted, not overwritten; `/-` appends.
+    pub fn insert_at_pointer(&mut self, pointer: &str, value: JsonValue) -> Result<Option<JsonValue>, PointerError> {
+        self.insert_at(&JsonPointer::parse(pointer)?, value)
+    }
+
+    /// Removes the value at `pointer` and returns it. Removing the root leaves `Null` behind.
+    pub fn remove_at_pointer(&mut self, pointer: &str) -> Result<JsonValue, PointerError> {
+        self.remove_at(&JsonPointer::parse(pointer)?)
+    }
+
+    pub fn get_pointer(&self, pointer: &JsonPointer) -> Result<&JsonValue, PointerError> {
+        let mut current = self;
+        for depth in 0..pointer.tokens.len() {
+            current = step(current, pointer, depth)?;
+        }
+        Ok(current)
+    }
+
+    pub fn get_pointer_mut(&mut self, pointer: &JsonPointer) -> Result<&mut JsonValue, PointerError> {
+        walk_mut(self, pointer, pointer.tokens.len())
+    }
+
+    pub fn insert_at(&mut self, pointer: &JsonPointer, value: JsonValue) -> Result<Option<JsonValue>, PointerError> {
+        let Some(last) = pointer.tokens.len().checked_sub(1) else {
+            return Ok(Some(mem::replace(self, value)));
+        };
+
+        match walk_mut(self, pointer, last)? {
+            JsonValue::Object(members) => Ok(members.insert(pointer.tokens[last].clone(), value)),
+            JsonValue::Array(items) => {
+                let index = insertion_index(pointer, last, items.len())?;
+                items.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(PointerError::NotAContainer { path: pointer.prefix(last + 1) }),
+        }
+    }
+
+    pub fn remove_at(&mut self, pointer: &JsonPointer) -> Result<JsonValue, PointerError> {
+        let Some(last) = pointer.tokens.len().checked_sub(1) else {
+            return Ok(mem::take(self));
+        };
+
+        match walk_mut(self, pointer, last)? {
+            JsonValue::Object(members) => {
+                let key = &pointer.tokens[last];
+                members.shift_remove(key).ok_or_else(|| PointerError::MissingKey {
+                    path: pointer.prefix(last + 1),
+                    key: key.clone(),
+                })
+            }
+            JsonValue::Array(items) => {
+                let index = element_index(pointer, last, items.len())?;
+                Ok(items.remove(index))
+            }
+            _ => Err(PointerError::NotAContainer { path: pointer.prefix(last + 1) }),
+        }
+    }
+}
+
+// One level down: the child of `current` named by token `depth`.
+fn step<'v>(current: &'v JsonValue, pointer: &JsonPointer, depth: usize) -> Result<&'v JsonValue, PointerError> {
+    let token = &pointer.tokens[depth];
+    match current {
+        JsonValue::Object(members) => members.get(token).ok_or_else(|| PointerError::MissingKey {
+            path: pointer.prefix(depth + 1),
+            key: token.clone(),
+        }),
+        JsonValue::Array(items) => Ok(&items[element_index(pointer, depth, items.len())?]),
+        _ => Err(PointerError::NotAContainer { path: pointer.prefix(depth + 1) }),
+    }
+}
+
+// Follows the first `len` tokens of `pointer`.
+fn walk_mut<'v>(value: &'v mut JsonValue, pointer: &JsonPointer, len: usize) -> Result<&'v mut JsonValue, PointerError> {
+    let mut current = value;
+    for depth in 0..len {
+        let token = &pointer.tokens[depth];
+        current = match current {
+            JsonValue::Object(members) => members.get_mut(token).ok_or_else(|| PointerError::MissingKey {
+                path: pointer.prefix(depth + 1),
+                key: token.clone(),
+            })?,
+            JsonValue::Array(items) => {
+                let index = element_index(pointer, depth, items.len())?;
+                &mut items[index]
+            }
+            _ => return Err(PointerError::NotAContainer { path: pointer.prefix(depth + 1) }),
+        };
+    }
+    Ok(current)
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointerError::MissingLeadingSlash { pointer } => {
+                write!(f, "JSON pointer {:?} must be empty or start with '/'", pointer)
+            }
+            PointerError::InvalidEscape { pointer, offset } => {
+                write!(f, "JSON pointer {:?} has an invalid '~' escape at offset {} (expected ~0 or ~1)", pointer, offset)
+            }
+            PointerError::MissingKey { path, key } => write!(f, "no member {:?} at {}", key, path),
+            PointerError::IndexOutOfBounds { path, index, len } => {
+                write!(f, "index {} is out of bounds for an array of length {} at {}", index, len, path)
+            }
+            PointerError::InvalidIndex { path, token } => write!(f, "{:?} is not an array index at {}", token, path),
+            PointerError::NotAContainer { path } => write!(f, "value before {} is neither an object nor an array", path),
+        }
+    }
+}
+
+impl Error for PointerError {}
@@ -0,0 +1,192 @@
+use crate::json_definitions::{JsonParsingErrorV3, JsonValue, LexerError, ParseLimits, ParseOptions};
+use crate::json_events::{EventMachine, JsonEvent};
+use crate::json_lexer::{PushLexer, Span, TokenKind};
+use crate::json_non_recursive::TreeBuilder;
+
+// =============================================================================
+// JSON Push Parser — input in arbitrary chunks
+// =============================================================================
+//
+// CONCEPT:
+//   Every other entry point needs the whole document up front. A network
+//   service gets its body in TCP-sized pieces and would have to buffer all of
+//   them first. The push parser turns control around: the caller pushes each
+//   chunk in as it arrives, and calls finish() after the last one.
+//
+//     let mut parser = JsonPushParser::new();
+//     parser.feed(b"{\"a\": [1, tr")?;
+//     parser.feed(b"ue]}")?;
+//     let value = parser.finish()?;
+//
+// HOW IT SUSPENDS:
+//   Two pieces of state survive between feeds, and nothing else:
+//     - PushLexer keeps the bytes of the token the chunk boundary cut through
+//       (mid-string, mid-number, mid-escape, mid-literal) and lexes it again
+//       once more bytes arrive. Everything before it has been handed on.
+//     - EventMachine (json_events.rs) and TreeBuilder (json_non_recursive.rs)
+//       keep their frame stacks. They never pull input, so they do not care
+//       whether tokens arrive all at once or a few per chunk.
+//
+//   Both are the exact pieces behind JsonEventParser and v3, so a document
+//   split anywhere yields the same events, the same JsonValue and the same
+//   errors at the same locations as the one-shot API.
+//
+// ERRORS:
+//   The first error is returned by feed() (or finish()) and ends the parse;
+//   every later call returns it again. Limits work as in v3, except that the
+//   document size can only be checked as bytes arrive — errors located before
+//   the limit are reported first.
+//
+// =============================================================================
+
+/// Push counterpart of `JsonEventParser`: each `feed` returns the events its
+/// bytes completed.
+pub struct JsonPushEventParser {
+    lexer: PushLexer,
+    machine: EventMachine<'static>,
+    error: Option<JsonParsingErrorV3>,
+}
+
+impl JsonPushEventParser {
+    pub fn new() -> Self {
+        Self::with_limits(&ParseLimits::UNLIMITED)
+    }
+
+    pub fn with_limits(limits: &ParseLimits) -> Self {
+        Self::with_options(&ParseOptions { limits: *limits, ..ParseOptions::default() })
+    }
+
+    /// Uses the limits, the lone surrogate policy, the dialect and the comment policy, like
+    /// `JsonEventParser::with_options`.
+    pub fn with_options(options: &ParseOptions) -> Self {
+        let lexer = PushLexer::with_options(&options.skipping_comments());
+        Self { lexer, machine: EventMachine::new(options), error: None }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<JsonEvent<'static>>, JsonParsingErrorV3> {
+        let mut events = Vec::new();
+        self.feed_with(chunk, &mut |event, _| {
+            events.push(event);
+            Ok(())
+        })?;
+        Ok(events)
+    }
+
+    /// Ends the input and returns the events still held back (e.g. a trailing number).
+    pub fn finish(mut self) -> Result<Vec<JsonEvent<'static>>, JsonParsingErrorV3> {
+        let mut events = Vec::new();
+        self.finish_with(&mut |event, _| {
+            events.push(event);
+            Ok(())
+        })?;
+        Ok(events)
+    }
+
+    /// Number of arrays/objects currently open.
+    pub fn depth(&self) -> usize {
+        self.machine.depth()
+    }
+
+    fn feed_with(&mut self, chunk: &[u8], sink: &mut EventSink) -> Result<(), JsonParsingErrorV3> {
+        if let Some(err) = &self.error {
+            return Err(err.clone());
+        }
+        self.lexer.feed(chunk);
+        self.run(sink)
+    }
+
+    fn finish_with(&mut self, sink: &mut EventSink) -> Result<(), JsonParsingErrorV3> {
+        if let Some(err) = &self.error {
+            return Err(err.clone());
+        }
+        if self.lexer.bytes_fed() == 0 {
+            return Err(JsonParsingErrorV3::EmptyJsonFile);
+        }
+        self.lexer.finish();
+        self.run(sink)
+    }
+
+    // Runs every token the lexer can complete through the state machine.
+    // The first error is kept, so later calls report it again.
+    fn run(&mut self, sink: &mut EventSink) -> Result<(), JsonParsingErrorV3> {
+        let result = drain(&mut self.lexer, &mut self.machine, sink);
+        if let Err(err) = &result {
+            self.error = Some(err.clone());
+        }
+        result
+    }
+}
+
+impl Default for JsonPushEventParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Push counterpart of `process_json_string_v3_with_options`.
+pub struct JsonPushParser {
+    events: JsonPushEventParser,
+    builder: TreeBuilder<JsonValue>,
+}
+
+impl JsonPushParser {
+    pub fn new() -> Self {
+        Self::with_options(&ParseOptions::default())
+    }
+
+    pub fn with_options(options: &ParseOptions) -> Self {
+        Self { events: JsonPushEventParser::with_options(options), builder: TreeBuilder::new(options) }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), JsonParsingErrorV3> {
+        let builder = &mut self.builder;
+        self.events.feed_with(chunk, &mut |event, span| builder.push(event, span))
+    }
+
+    pub fn finish(mut self) -> Result<JsonValue, JsonParsingErrorV3> {
+        let builder = &mut self.builder;
+        self.events.finish_with(&mut |event, span| builder.push(event, span))?;
+        self.builder.finish(self.events.machine.span().end)
+    }
+
+    /// Number of arrays/objects currently open.
+    pub fn depth(&self) -> usize {
+        self.events.depth()
+    }
+}
+
+impl Default for JsonPushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+type EventSink<'s> = dyn FnMut(JsonEvent<'static>, Span) -> Result<(), JsonParsingErrorV3> + 's;
+
+fn drain(
+    lexer: &mut PushLexer,
+    machine: &mut EventMachine<'static>,
+    sink: &mut EventSink,
+) -> Result<(), JsonParsingErrorV3> {
+    while let Some(token) = lexer.next_token() {
+        let token = match token {
+            Ok(token) => token,
+            Err(LexerError::LimitExceeded { limit, at }) => {
+                return Err(JsonParsingErrorV3::LimitExceeded { limit, at })
+            }
+            Err(e) => return Err(JsonParsingErrorV3::LexError(e)),
+        };
+
+        let is_eof = token.kind == TokenKind::Eof;
+        if let Some(event) = machine.accept(token)? {
+            sink(event, machine.span())?;
+            if let Some(event) = machine.take_queued() {
+                sink(event, machine.span())?;
+            }
+        }
+        if is_eof {
+            break;
+        }
+    }
+    Ok(())
+}
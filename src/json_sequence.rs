@@ -0,0 +1,151 @@
+use crate::json_definitions::{JsonParsingErrorV3, JsonValue, ParseOptions};
+use crate::json_events::JsonEventParser;
+use crate::json_lexer::{relocate, LineTracker, SourceLocation};
+use crate::json_non_recursive::{process_json_string_v3_with_options, TreeBuilder};
+
+// =============================================================================
+// Multiple documents — concatenated JSON and RFC 7464 text sequences
+// =============================================================================
+//
+// CONCATENATED JSON:
+//   {"a":1}{"a":2}
+//   [1] [2]
+//
+//   Documents back to back, separated by nothing or by whitespace. v3 stops
+//   with ExpectedEOF at the second one; JsonDocuments runs the event parser
+//   in multi-document mode and hands out each value as soon as its last
+//   token is read. Locations are in the whole input. There is no way to
+//   find the start of the next document after a syntax error, so the first
+//   error ends the iteration.
+//
+// RFC 7464 JSON TEXT SEQUENCES:
+//   <RS>{"a":1}<LF><RS>{"a":2}<LF>        (RS is the byte 0x1E)
+//
+//   Every record starts with RS, which makes recovery possible: a record
+//   that fails to parse (a writer died half-way through it, say) is reported
+//   and JsonTextSequence carries on at the next RS, as the RFC asks.
+//
+//   A top-level number, true, false or null is not self-delimiting: "12"
+//   may be what is left of "123". The RFC requires it to be followed by
+//   whitespace (writers end every record with LF); a record like that
+//   without trailing whitespace is reported as PossiblyTruncated instead of
+//   returning a value that might be wrong.
+//
+//   Records that are empty or only whitespace (RS RS) are skipped. Text
+//   before the first RS is reported as MissingRecordSeparator.
+//
+// =============================================================================
+
+const RECORD_SEPARATOR: char = '\u{1E}';
+
+/// Concatenated JSON: one item per top-level value, in order.
+pub struct JsonDocuments<'a> {
+    events: JsonEventParser<'a>,
+    builder: TreeBuilder<JsonValue>,
+}
+
+impl<'a> JsonDocuments<'a> {
+    pub fn new(json_string: &'a str) -> Self {
+        Self::with_options(json_string, &ParseOptions::default())
+    }
+
+    /// Limits apply to each document, except max_document_bytes, which the
+    /// lexer checks against the whole input.
+    pub fn with_options(json_string: &'a str, options: &ParseOptions) -> Self {
+        Self { events: JsonEventParser::multiple_documents(json_string, options), builder: TreeBuilder::new(options) }
+    }
+}
+
+impl Iterator for JsonDocuments<'_> {
+    type Item = Result<JsonValue, JsonParsingErrorV3>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the event parser is exhausted after its first error
+        while let Some(event) = self.events.next() {
+            if let Err(e) = event.and_then(|event| self.builder.push(event, self.events.span())) {
+                return Some(Err(e));
+            }
+            if let Some(value) = self.builder.take_root() {
+                return Some(Ok(value));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsonSequenceError {
+    MissingRecordSeparator { at: SourceLocation },                         // text before the first RS
+    PossiblyTruncated { at: SourceLocation },                              // number/literal without whitespace after it
+    Parse { record_start: SourceLocation, error: Box<JsonParsingErrorV3> }, // error's locations are relative to the record
+}
+
+/// RFC 7464: one item per record; a bad record does not end the iteration.
+pub struct JsonTextSequence<'a> {
+    input: &'a str,
+    cursor: usize,
+    lines: LineTracker,
+    options: ParseOptions,
+}
+
+impl<'a> JsonTextSequence<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, &ParseOptions::default())
+    }
+
+    /// The options, limits included, apply to each record on its own.
+    pub fn with_options(input: &'a str, options: &ParseOptions) -> Self {
+        Self { input, cursor: 0, lines: LineTracker::new(), options: *options }
+    }
+
+    // Moves past the text up to the next RS (or the end) and returns it with its location.
+    fn take_record(&mut self) -> (&'a str, SourceLocation) {
+        let start = self.lines.locate(self.input.as_bytes(), self.cursor);
+        let rest = &self.input[self.cursor..];
+        let record = &rest[..rest.find(RECORD_SEPARATOR).unwrap_or(rest.len())];
+        self.cursor += record.len();
+        (record, start)
+    }
+
+    fn parse_record(&self, record: &str, start: SourceLocation) -> Result<JsonValue, JsonSequenceError> {
+        let value = process_json_string_v3_with_options(record, &self.options)
+            .map_err(|error| JsonSequenceError::Parse { record_start: start, error: Box::new(error) })?;
+
+        let self_delimited = matches!(value, JsonValue::JsonString(_) | JsonValue::Array(_) | JsonValue::Object(_));
+        if !self_delimited && !record.ends_with(is_json_whitespace) {
+            let value_offset = record.len() - record.trim_start_matches(is_json_whitespace).len();
+            let mut lines = LineTracker::new();
+            let at = lines.locate(record.as_bytes(), value_offset);
+            return Err(JsonSequenceError::PossiblyTruncated { at: relocate(at, start) });
+        }
+        Ok(value)
+    }
+}
+
+impl Iterator for JsonTextSequence<'_> {
+    type Item = Result<JsonValue, JsonSequenceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cursor < self.input.len() {
+            // only the text before the first RS can lack one; every record ends at the next
+            let separated = self.input[self.cursor..].starts_with(RECORD_SEPARATOR);
+            if separated {
+                self.cursor += RECORD_SEPARATOR.len_utf8();
+            }
+
+            let (record, start) = self.take_record();
+            if record.trim_matches(is_json_whitespace).is_empty() {
+                continue;
+            }
+            if !separated {
+                return Some(Err(JsonSequenceError::MissingRecordSeparator { at: start }));
+            }
+            return Some(self.parse_record(record, start));
+        }
+        None
+    }
+}
+
+fn is_json_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
@@ -0,0 +1,1269 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+
+use indexmap::IndexMap;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Unexpected, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+use crate::json_definitions::{
+    token_tag_of, Dialect, FrameTypeTag, JsonParsingErrorV3, JsonValue, LexerError, Limit, ParseLimits, ParseOptions,
+    TokenTag,
+};
+use crate::json_lexer::{Lexer, SourceLocation, Span, Token, TokenKind};
+use crate::json_number::JsonNumber;
+use crate::json_writer::{format_number, write_newline, write_string, Indent, WriterOptions};
+
+// =============================================================================
+// serde integration (feature "serde")
+// =============================================================================
+//
+//   let config: Config = json_serde::from_str(&text)?;
+//   let text = json_serde::to_string_pretty(&config)?;
+//
+// DESERIALIZER:
+//   Runs straight on the lexer's tokens: a typed struct is filled in while
+//   the text is read, no JsonValue in between. Strings without escapes are
+//   handed out borrowed, so &'de str fields work like with serde_json.
+//   Malformed text is reported with the v3 parser's errors (Syntax), wrong
+//   shapes — a missing field, a string where a number belongs — with the
+//   Deserialize impl's message and the location of the value (Custom).
+//
+//   Numbers reach the Deserialize impl as u64, i64, i128 or u128 when the
+//   lexeme is an integer that fits, as f64 otherwise.
+//
+// DEPTH:
+//   serde is recursive by design — every Deserialize impl calls back into
+//   the deserializer once per nesting level — so unlike v3 this cannot take
+//   arbitrarily deep input. ParseLimits::max_depth applies as usual, and an
+//   unlimited max_depth means DEFAULT_MAX_DEPTH here. The other limits, the
+//   lone surrogate policy, the dialect and the comment policy apply too;
+//   duplicate keys are up to the Deserialize impl (JsonValue keeps the last
+//   value, derived structs fail).
+//
+// SERIALIZER:
+//   Writes JSON text to any io::Write, laid out by WriterOptions exactly like
+//   json_writer. Map keys must serialize as strings; chars and integers are
+//   quoted. Non-finite floats are written as null. Serializing is recursive
+//   as well — for very deep JsonValue trees use json_writer instead.
+//
+// ENUMS:
+//   Externally tagged, like serde_json: "Unit", {"Newtype": 1},
+//   {"Tuple": [1, 2]}, {"Struct": {"a": 1}}.
+//
+// =============================================================================
+
+/// Nesting limit when ParseLimits leaves max_depth unlimited (same as serde_json).
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+#[derive(Debug)]
+pub enum SerdeError {
+    Syntax(JsonParsingErrorV3),                             // the text is not JSON; same errors as v3
+    Custom { message: String, at: Option<SourceLocation> }, // from a Serialize / Deserialize impl
+    KeyMustBeString,                                        // a map key that is not a string, char or integer
+    Io(io::Error),
+}
+
+impl SerdeError {
+    // Pins a Deserialize impl's error to the value it was looking at. Errors
+    // travel outwards through every level, so the innermost location wins.
+    fn located(self, at: SourceLocation) -> Self {
+        match self {
+            SerdeError::Custom { message, at: None } => SerdeError::Custom { message, at: Some(at) },
+            other => other,
+        }
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        SerdeError::Custom { message: message.to_string(), at: None }
+    }
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        SerdeError::Custom { message: message.to_string(), at: None }
+    }
+}
+
+impl From<io::Error> for SerdeError {
+    fn from(e: io::Error) -> Self {
+        SerdeError::Io(e)
+    }
+}
+
+// =============================================================================
+// Deserializing
+// =============================================================================
+
+pub fn from_str<'a, T: Deserialize<'a>>(input: &'a str) -> Result<T, SerdeError> {
+    from_slice(input.as_bytes())
+}
+
+pub fn from_str_with_options<'a, T: Deserialize<'a>>(input: &'a str, options: &ParseOptions) -> Result<T, SerdeError> {
+    from_slice_with_options(input.as_bytes(), options)
+}
+
+pub fn from_slice<'a, T: Deserialize<'a>>(input: &'a [u8]) -> Result<T, SerdeError> {
+    from_slice_with_options(input, &ParseOptions::default())
+}
+
+pub fn from_slice_with_options<'a, T: Deserialize<'a>>(input: &'a [u8], options: &ParseOptions) -> Result<T, SerdeError> {
+    let mut deserializer = Deserializer::with_options(input, options);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Reads everything first; the lexer wants the whole text.
+pub fn from_reader<R: io::Read, T: DeserializeOwned>(mut reader: R) -> Result<T, SerdeError> {
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    from_slice(&input)
+}
+
+pub struct Deserializer<'de> {
+    tokens: Lexer<'de>,
+    peeked: Option<Token<'de>>,
+    cursor: SourceLocation, // end of the last token read
+    empty: bool,
+    frames: Vec<FrameTypeTag>,
+    slot: Slot,
+    key: Cow<'de, str>, // last object key read, for ObjectKeyWithoutValue
+    limits: ParseLimits,
+    trailing_commas: bool, // JSON5
+}
+
+// Where the next value goes; decides which error a token that cannot start
+// a value turns into, the same ones v3 reports.
+#[derive(Clone, Copy)]
+enum Slot {
+    Root,
+    ArrayStart,
+    ArrayAfterComma,
+    ObjectValue,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        Self::with_options(input, &ParseOptions::default())
+    }
+
+    pub fn with_options(input: &'de [u8], options: &ParseOptions) -> Self {
+        let mut limits = options.limits;
+        if limits.max_depth == ParseLimits::UNLIMITED.max_depth {
+            limits.max_depth = DEFAULT_MAX_DEPTH;
+        }
+        Self {
+            tokens: Lexer::with_options(input, &options.skipping_comments()),
+            peeked: None,
+            cursor: SourceLocation::START,
+            empty: input.is_empty(),
+            frames: Vec::new(),
+            slot: Slot::Root,
+            key: Cow::Borrowed(""),
+            limits,
+            trailing_commas: options.dialect == Dialect::Json5,
+        }
+    }
+
+    /// Checks that nothing but whitespace follows the value just deserialized.
+    pub fn end(&mut self) -> Result<(), SerdeError> {
+        let (found, at) = self.peek_tag()?;
+        match found {
+            TokenTag::Eof => Ok(()),
+            _ => Err(SerdeError::Syntax(JsonParsingErrorV3::ExpectedEOF { at, found })),
+        }
+    }
+
+    fn peek(&mut self) -> Result<&Token<'de>, SerdeError> {
+        if self.peeked.is_none() {
+            let token = match self.tokens.next() {
+                // the lexer stops after Eof; keep answering Eof
+                None => Token { kind: TokenKind::Eof, span: Span { start: self.cursor, end: self.cursor } },
+                Some(Ok(token)) => token,
+                Some(Err(LexerError::LimitExceeded { limit, at })) => {
+                    return Err(SerdeError::Syntax(JsonParsingErrorV3::LimitExceeded { limit, at }));
+                }
+                Some(Err(e)) => return Err(SerdeError::Syntax(JsonParsingErrorV3::LexError(e))),
+            };
+            self.cursor = token.span.end;
+            self.peeked = Some(token);
+        }
+        Ok(self.peeked.as_ref().expect("peeked token was just read"))
+    }
+
+    fn peek_tag(&mut self) -> Result<(TokenTag, SourceLocation), SerdeError> {
+        let token = self.peek()?;
+        Ok((token_tag_of(&token.kind), token.span.start))
+    }
+
+    fn take(&mut self) -> Result<Token<'de>, SerdeError> {
+        self.peek()?;
+        Ok(self.peeked.take().expect("peeked token was just read"))
+    }
+
+    /// The next token, which must start a value.
+    fn take_value(&mut self) -> Result<Token<'de>, SerdeError> {
+        let token = self.take()?;
+        match token.kind {
+            TokenKind::Null
+            | TokenKind::Bool(_)
+            | TokenKind::Number(_)
+            | TokenKind::String(_)
+            | TokenKind::LBracket
+            | TokenKind::LBrace => Ok(token),
+            _ => Err(SerdeError::Syntax(self.not_a_value(&token))),
+        }
+    }
+
+    fn not_a_value(&self, token: &Token) -> JsonParsingErrorV3 {
+        let at = token.span.start;
+        let found = token_tag_of(&token.kind);
+        let frame = self.frames.last().copied();
+        match (found, self.slot) {
+            (TokenTag::Eof, Slot::Root) if self.empty => JsonParsingErrorV3::EmptyJsonFile,
+            (TokenTag::Eof, _) => JsonParsingErrorV3::UnexpectedEOF { at, frame },
+            (TokenTag::Comma, Slot::Root) => JsonParsingErrorV3::CommaOutsideStructure { at },
+            (TokenTag::Comma, Slot::ArrayStart) => JsonParsingErrorV3::LeadingComma { at, frame: FrameTypeTag::ArrayFrame },
+            (TokenTag::Comma, Slot::ArrayAfterComma) => {
+                JsonParsingErrorV3::UnexpectedComma { at, frame: FrameTypeTag::ArrayFrame }
+            }
+            (TokenTag::Comma, Slot::ObjectValue) => {
+                JsonParsingErrorV3::UnexpectedComma { at, frame: FrameTypeTag::ObjectFrame }
+            }
+            (TokenTag::Colon, Slot::ObjectValue) => JsonParsingErrorV3::UnexpectedColon { at },
+            (TokenTag::Colon, _) => JsonParsingErrorV3::ColonOutsideObject { at, frame },
+            (_, Slot::Root) => JsonParsingErrorV3::UnexpectedClosing { at, found },
+            (_, Slot::ObjectValue) => JsonParsingErrorV3::ObjectKeyWithoutValue { at, found, key: self.key.to_string() },
+            (TokenTag::RBracket, Slot::ArrayAfterComma) => {
+                JsonParsingErrorV3::TrailingComma { at, found, frame: FrameTypeTag::ArrayFrame }
+            }
+            (_, Slot::ArrayStart | Slot::ArrayAfterComma) => {
+                JsonParsingErrorV3::MismatchedClosing { at, found, frame: FrameTypeTag::ArrayFrame }
+            }
+        }
+    }
+
+    /// What follows a complete element or member, when it is neither ',' nor the closing token.
+    fn separator_error(&self, found: TokenTag, at: SourceLocation, frame: FrameTypeTag) -> SerdeError {
+        SerdeError::Syntax(match found {
+            TokenTag::Eof => JsonParsingErrorV3::UnexpectedEOF { at, frame: Some(frame) },
+            TokenTag::RBrace | TokenTag::RBracket => JsonParsingErrorV3::MismatchedClosing { at, found, frame },
+            TokenTag::Colon if frame == FrameTypeTag::ObjectFrame => JsonParsingErrorV3::UnexpectedColon { at },
+            TokenTag::Colon => JsonParsingErrorV3::ColonOutsideObject { at, frame: Some(frame) },
+            _ => JsonParsingErrorV3::MissingComma { at, found, frame },
+        })
+    }
+
+    fn enter(&mut self, frame: FrameTypeTag, at: SourceLocation) -> Result<(), SerdeError> {
+        if self.frames.len() >= self.limits.max_depth {
+            return Err(SerdeError::Syntax(JsonParsingErrorV3::LimitExceeded { limit: Limit::Depth, at }));
+        }
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    // Consumes the closing token once the visitor is done with the container.
+    // A visitor that stops early (a tuple shorter than the array) leaves a ','.
+    fn leave(&mut self, frame: FrameTypeTag) -> Result<(), SerdeError> {
+        let (found, at) = self.peek_tag()?;
+        let close = match frame {
+            FrameTypeTag::ArrayFrame => TokenTag::RBracket,
+            FrameTypeTag::ObjectFrame => TokenTag::RBrace,
+        };
+        if found == close {
+            self.take()?;
+            self.frames.pop();
+            return Ok(());
+        }
+        if found == TokenTag::Comma && self.trailing_commas {
+            self.take()?;
+            if self.peek_tag()?.0 == close {
+                return self.leave(frame);
+            }
+        }
+        if found == TokenTag::Comma {
+            let message = match frame {
+                FrameTypeTag::ArrayFrame => "array has more elements than expected",
+                FrameTypeTag::ObjectFrame => "object has more members than expected",
+            };
+            return Err(SerdeError::Custom { message: message.to_string(), at: Some(at) });
+        }
+        Err(self.separator_error(found, at, frame))
+    }
+
+    /// Steps over the ',' before element `index`; false at the closing ']'.
+    fn array_slot(&mut self, index: usize) -> Result<bool, SerdeError> {
+        let (found, at) = self.peek_tag()?;
+        if found == TokenTag::RBracket {
+            return Ok(false);
+        }
+        if index == 0 {
+            self.slot = Slot::ArrayStart;
+        } else {
+            if found != TokenTag::Comma {
+                return Err(self.separator_error(found, at, FrameTypeTag::ArrayFrame));
+            }
+            self.take()?;
+            if self.trailing_commas && self.peek_tag()?.0 == TokenTag::RBracket {
+                return Ok(false);
+            }
+            self.slot = Slot::ArrayAfterComma;
+        }
+        if index >= self.limits.max_array_len {
+            let at = self.peek()?.span.start;
+            return Err(SerdeError::Syntax(JsonParsingErrorV3::LimitExceeded { limit: Limit::ArrayLength, at }));
+        }
+        Ok(true)
+    }
+
+    /// Reads member `index`'s key, with the ',' before it; None at the closing '}'.
+    fn object_key(&mut self, index: usize) -> Result<Option<(Cow<'de, str>, SourceLocation)>, SerdeError> {
+        let (found, at) = self.peek_tag()?;
+        if found == TokenTag::RBrace {
+            return Ok(None);
+        }
+        if index > 0 {
+            if found != TokenTag::Comma {
+                return Err(self.separator_error(found, at, FrameTypeTag::ObjectFrame));
+            }
+            self.take()?;
+            if self.trailing_commas && self.peek_tag()?.0 == TokenTag::RBrace {
+                return Ok(None);
+            }
+        }
+
+        let token = self.take()?;
+        let at = token.span.start;
+        let found = token_tag_of(&token.kind);
+        let frame = FrameTypeTag::ObjectFrame;
+        let err = match token.kind {
+            TokenKind::String(key) => {
+                if index >= self.limits.max_object_members {
+                    return Err(SerdeError::Syntax(JsonParsingErrorV3::LimitExceeded { limit: Limit::ObjectMembers, at }));
+                }
+                self.key = key.clone();
+                return Ok(Some((key, at)));
+            }
+            TokenKind::Eof => JsonParsingErrorV3::UnexpectedEOF { at, frame: Some(frame) },
+            TokenKind::RBrace => JsonParsingErrorV3::TrailingComma { at, found, frame },
+            TokenKind::RBracket => JsonParsingErrorV3::MismatchedClosing { at, found, frame },
+            TokenKind::Comma if index == 0 => JsonParsingErrorV3::LeadingComma { at, frame },
+            TokenKind::Comma => JsonParsingErrorV3::UnexpectedComma { at, frame },
+            TokenKind::Colon => JsonParsingErrorV3::UnexpectedColon { at },
+            _ => JsonParsingErrorV3::ObjectKeyNotString { at, found },
+        };
+        Err(SerdeError::Syntax(err))
+    }
+
+    fn object_colon(&mut self) -> Result<(), SerdeError> {
+        let token = self.take()?;
+        let at = token.span.start;
+        match token.kind {
+            TokenKind::Colon => {
+                self.slot = Slot::ObjectValue;
+                Ok(())
+            }
+            TokenKind::Eof => Err(SerdeError::Syntax(JsonParsingErrorV3::UnexpectedEOF {
+                at,
+                frame: Some(FrameTypeTag::ObjectFrame),
+            })),
+            _ => Err(SerdeError::Syntax(JsonParsingErrorV3::MissingColon { at, found: token_tag_of(&token.kind) })),
+        }
+    }
+}
+
+fn visit_number<'de, V: Visitor<'de>>(n: &JsonNumber, visitor: V) -> Result<V::Value, SerdeError> {
+    if n.is_integer() {
+        if let Some(u) = n.as_u64() {
+            return visitor.visit_u64(u);
+        }
+        if let Some(i) = n.as_i64() {
+            return visitor.visit_i64(i);
+        }
+        if let Some(i) = n.as_i128() {
+            return visitor.visit_i128(i);
+        }
+        if let Some(u) = n.as_str().and_then(|s| s.parse::<u128>().ok()) {
+            return visitor.visit_u128(u);
+        }
+    }
+    visitor.visit_f64(n.as_f64())
+}
+
+fn unexpected<'a>(kind: &'a TokenKind) -> Unexpected<'a> {
+    match kind {
+        TokenKind::Null => Unexpected::Unit,
+        TokenKind::Bool(b) => Unexpected::Bool(*b),
+        TokenKind::Number(_) => Unexpected::Other("number"),
+        TokenKind::String(s) => Unexpected::Str(s),
+        TokenKind::LBracket => Unexpected::Seq,
+        TokenKind::LBrace => Unexpected::Map,
+        _ => Unexpected::Other("punctuation"),
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let token = self.take_value()?;
+        let at = token.span.start;
+        let value = match token.kind {
+            TokenKind::Null => visitor.visit_unit(),
+            TokenKind::Bool(b) => visitor.visit_bool(b),
+            TokenKind::Number(n) => visit_number(&n, visitor),
+            TokenKind::String(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            TokenKind::String(Cow::Owned(s)) => visitor.visit_string(s),
+            TokenKind::LBracket => {
+                self.enter(FrameTypeTag::ArrayFrame, at)?;
+                visitor
+                    .visit_seq(SeqDeserializer { de: &mut *self, len: 0 })
+                    .and_then(|value| self.leave(FrameTypeTag::ArrayFrame).map(|()| value))
+            }
+            TokenKind::LBrace => {
+                self.enter(FrameTypeTag::ObjectFrame, at)?;
+                visitor
+                    .visit_map(MapDeserializer { de: &mut *self, len: 0 })
+                    .and_then(|value| self.leave(FrameTypeTag::ObjectFrame).map(|()| value))
+            }
+            _ => unreachable!("take_value only returns tokens that start a value"),
+        };
+        value.map_err(|e| e.located(at))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.peek()?.kind == TokenKind::Null {
+            self.take()?;
+            return visitor.visit_none();
+        }
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // "Variant" for a unit variant, {"Variant": content} for the others.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        let token = self.take_value()?;
+        let at = token.span.start;
+        let value = match token.kind {
+            TokenKind::String(name) => visitor.visit_enum(name.into_deserializer()),
+            TokenKind::LBrace => {
+                self.enter(FrameTypeTag::ObjectFrame, at)?;
+                let value = visitor.visit_enum(VariantDeserializer { de: &mut *self, at })?;
+                let (found, end) = self.peek_tag()?;
+                if found != TokenTag::RBrace {
+                    let message = "an enum value is an object with exactly one member".to_string();
+                    return Err(SerdeError::Custom { message, at: Some(end) });
+                }
+                self.leave(FrameTypeTag::ObjectFrame).map(|()| value)
+            }
+            ref other => Err(de::Error::invalid_type(unexpected(other), &visitor)),
+        };
+        value.map_err(|e| e.located(at))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqDeserializer<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer<'_, 'de> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, SerdeError> {
+        if !self.de.array_slot(self.len)? {
+            return Ok(None);
+        }
+        self.len += 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct MapDeserializer<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: usize,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer<'_, 'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SerdeError> {
+        let Some((key, at)) = self.de.object_key(self.len)? else {
+            return Ok(None);
+        };
+        self.len += 1;
+        seed.deserialize(MapKey { key }).map(Some).map_err(|e| e.located(at))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerdeError> {
+        self.de.object_colon()?;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+struct VariantDeserializer<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    at: SourceLocation,
+}
+
+impl<'de> de::EnumAccess<'de> for VariantDeserializer<'_, 'de> {
+    type Error = SerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), SerdeError> {
+        let Some((key, at)) = self.de.object_key(0)? else {
+            let message = "an enum value is an object with exactly one member".to_string();
+            return Err(SerdeError::Custom { message, at: Some(self.at) });
+        };
+        let variant = seed.deserialize(MapKey { key }).map_err(|e| e.located(at))?;
+        self.de.object_colon()?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer<'_, 'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        <()>::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+// Object keys are always strings; a map with integer (or bool, float) keys
+// gets them parsed back out of the string, as serde_json does.
+struct MapKey<'de> {
+    key: Cow<'de, str>,
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident: $t:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                match self.key.parse::<$t>() {
+                    Ok(parsed) => visitor.$visit(parsed),
+                    Err(_) => Err(de::Error::invalid_type(Unexpected::Str(&self.key), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKey<'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.key {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_enum(self.key.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+// =============================================================================
+// Serializing
+// =============================================================================
+
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    to_string_with_options(value, &WriterOptions::compact())
+}
+
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    to_string_with_options(value, &WriterOptions::pretty())
+}
+
+pub fn to_string_with_options<T: Serialize + ?Sized>(value: &T, options: &WriterOptions) -> Result<String, SerdeError> {
+    let out = to_vec_with_options(value, options)?;
+    // Numbers, literals, punctuation, indentation and quoted integer keys are ASCII; strings,
+    // chars and string or char map keys all go through write_string, which copies or escapes a &str
+    Ok(String::from_utf8(out).expect("serializer produced invalid UTF-8"))
+}
+
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SerdeError> {
+    to_vec_with_options(value, &WriterOptions::compact())
+}
+
+pub fn to_vec_with_options<T: Serialize + ?Sized>(value: &T, options: &WriterOptions) -> Result<Vec<u8>, SerdeError> {
+    let mut out = Vec::new();
+    to_writer_with_options(&mut out, value, options)?;
+    Ok(out)
+}
+
+pub fn to_writer<W: io::Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<(), SerdeError> {
+    to_writer_with_options(writer, value, &WriterOptions::compact())
+}
+
+pub fn to_writer_with_options<W: io::Write, T: Serialize + ?Sized>(
+    writer: W,
+    value: &T,
+    options: &WriterOptions,
+) -> Result<(), SerdeError> {
+    value.serialize(&mut Serializer::with_options(writer, options))
+}
+
+pub struct Serializer<W> {
+    writer: W,
+    options: WriterOptions,
+    depth: usize,
+}
+
+impl<W: io::Write> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, &WriterOptions::compact())
+    }
+
+    pub fn with_options(writer: W, options: &WriterOptions) -> Self {
+        Self { writer, options: *options, depth: 0 }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn open(&mut self, token: &[u8]) -> Result<(), SerdeError> {
+        self.writer.write_all(token)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    // Empty containers stay on one line, [] and {}, like json_writer.
+    fn close(&mut self, token: &[u8], empty: bool) -> Result<(), SerdeError> {
+        self.depth -= 1;
+        if !empty {
+            write_newline(&mut self.writer, &self.options, self.depth)?;
+        }
+        self.writer.write_all(token)?;
+        Ok(())
+    }
+
+    fn element(&mut self, first: bool) -> Result<(), SerdeError> {
+        if !first {
+            self.writer.write_all(b",")?;
+        }
+        write_newline(&mut self.writer, &self.options, self.depth)?;
+        Ok(())
+    }
+
+    fn colon(&mut self) -> Result<(), SerdeError> {
+        match self.options.indent {
+            Indent::None => self.writer.write_all(b":")?,
+            _ => self.writer.write_all(b": ")?,
+        }
+        Ok(())
+    }
+
+    fn member_key(&mut self, key: &str, first: bool) -> Result<(), SerdeError> {
+        self.element(first)?;
+        write_string(&mut self.writer, key, self.options.ascii_only)?;
+        self.colon()
+    }
+
+    fn raw(&mut self, text: &str) -> Result<(), SerdeError> {
+        self.writer.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    fn string(&mut self, s: &str) -> Result<(), SerdeError> {
+        write_string(&mut self.writer, s, self.options.ascii_only)?;
+        Ok(())
+    }
+}
+
+/// State of one container being written. `variant` marks the {"Variant": ...}
+/// wrapper around tuple and struct variants, closed together with the container.
+pub struct Compound<'a, W> {
+    ser: &'a mut Serializer<W>,
+    first: bool,
+    variant: bool,
+}
+
+impl<W: io::Write> Compound<'_, W> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.ser.element(self.first)?;
+        self.first = false;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), SerdeError> {
+        self.ser.member_key(key, self.first)?;
+        self.first = false;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self, close: &[u8]) -> Result<(), SerdeError> {
+        self.ser.close(close, self.first)?;
+        if self.variant {
+            self.ser.close(b"}", false)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $t:ty),* $(,)?) => {
+        $(
+            fn $method(self, v: $t) -> Result<(), SerdeError> {
+                self.raw(&v.to_string())
+            }
+        )*
+    };
+}
+
+impl<'a, W: io::Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = SerdeError;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    serialize_display! {
+        serialize_bool: bool,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64, serialize_i128: i128,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64, serialize_u128: u128,
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), SerdeError> {
+        self.raw(&format_number(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), SerdeError> {
+        self.raw(&format_number(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SerdeError> {
+        self.string(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerdeError> {
+        self.string(v)
+    }
+
+    // No bytes in JSON: an array of numbers, like serde_json.
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SerdeError> {
+        ser::Serializer::collect_seq(self, v)
+    }
+
+    fn serialize_none(self) -> Result<(), SerdeError> {
+        self.raw("null")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerdeError> {
+        self.raw("null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerdeError> {
+        self.raw("null")
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), SerdeError> {
+        self.string(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.open(b"{")?;
+        self.member_key(variant, true)?;
+        value.serialize(&mut *self)?;
+        self.close(b"}", false)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, W>, SerdeError> {
+        self.open(b"[")?;
+        Ok(Compound { ser: self, first: true, variant: false })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, W>, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, SerdeError> {
+        self.open(b"{")?;
+        self.member_key(variant, true)?;
+        self.open(b"[")?;
+        Ok(Compound { ser: self, first: true, variant: true })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, W>, SerdeError> {
+        self.open(b"{")?;
+        Ok(Compound { ser: self, first: true, variant: false })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, SerdeError> {
+        self.open(b"{")?;
+        self.member_key(variant, true)?;
+        self.open(b"{")?;
+        Ok(Compound { ser: self, first: true, variant: true })
+    }
+}
+
+impl<W: io::Write> ser::SerializeSeq for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.ser.element(self.first)?;
+        self.first = false;
+        key.serialize(MapKeySerializer { ser: &mut *self.ser })
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.ser.colon()?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"}")
+    }
+}
+
+impl<W: io::Write> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"}")
+    }
+}
+
+impl<W: io::Write> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        Compound::end(self, b"}")
+    }
+}
+
+// Writes a map key as a JSON string. Strings and chars go as they are,
+// integers are quoted; anything else has no string form.
+struct MapKeySerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+}
+
+macro_rules! serialize_quoted {
+    ($($method:ident: $t:ty),* $(,)?) => {
+        $(
+            fn $method(self, v: $t) -> Result<(), SerdeError> {
+                self.ser.raw(&format!("\"{}\"", v))
+            }
+        )*
+    };
+}
+
+macro_rules! reject_key {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<(), SerdeError> {
+                Err(SerdeError::KeyMustBeString)
+            }
+        )*
+    };
+}
+
+impl<W: io::Write> ser::Serializer for MapKeySerializer<'_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<(), SerdeError>;
+    type SerializeTuple = Impossible<(), SerdeError>;
+    type SerializeTupleStruct = Impossible<(), SerdeError>;
+    type SerializeTupleVariant = Impossible<(), SerdeError>;
+    type SerializeMap = Impossible<(), SerdeError>;
+    type SerializeStruct = Impossible<(), SerdeError>;
+    type SerializeStructVariant = Impossible<(), SerdeError>;
+
+    serialize_quoted! {
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64, serialize_i128: i128,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64, serialize_u128: u128,
+    }
+
+    reject_key! {
+        serialize_bool(bool), serialize_f32(f32), serialize_f64(f64), serialize_bytes(&[u8]),
+        serialize_none(), serialize_unit(), serialize_unit_struct(&'static str),
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SerdeError> {
+        self.ser.string(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerdeError> {
+        self.ser.string(v)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<(), SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), SerdeError> {
+        self.ser.string(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(SerdeError::KeyMustBeString)
+    }
+}
+
+// =============================================================================
+// JsonValue and JsonNumber
+// =============================================================================
+//
+// Work with any serde format, not only this one. Integers keep their exact
+// value; other numbers go through f64, so a float's lexeme is not kept
+// (1.50 comes back as 1.5) — parse with v3 when the text matters.
+
+impl Serialize for JsonNumber {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_integer() {
+            if let Some(u) = self.as_u64() {
+                return serializer.serialize_u64(u);
+            }
+            if let Some(i) = self.as_i64() {
+                return serializer.serialize_i64(i);
+            }
+            if let Some(i) = self.as_i128() {
+                return serializer.serialize_i128(i);
+            }
+            if let Some(u) = self.as_str().and_then(|s| s.parse::<u128>().ok()) {
+                return serializer.serialize_u128(u);
+            }
+        }
+        serializer.serialize_f64(self.as_f64())
+    }
+}
+
+impl Serialize for JsonValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Boolean(b) => serializer.serialize_bool(*b),
+            JsonValue::Number(n) => n.serialize(serializer),
+            JsonValue::JsonString(s) => serializer.serialize_str(s),
+            JsonValue::Array(items) => serializer.collect_seq(items),
+            JsonValue::Object(members) => serializer.collect_map(members),
+        }
+    }
+}
+
+struct NumberVisitor;
+
+impl Visitor<'_> for NumberVisitor {
+    type Value = JsonNumber;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON number")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<JsonNumber, E> {
+        Ok(JsonNumber::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<JsonNumber, E> {
+        Ok(JsonNumber::from(v))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<JsonNumber, E> {
+        Ok(JsonNumber::from(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<JsonNumber, E> {
+        Ok(JsonNumber::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<JsonNumber, E> {
+        Ok(JsonNumber::from(v))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonNumber {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<JsonValue, E> {
+        NumberVisitor.visit_i64(v).map(JsonValue::Number)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<JsonValue, E> {
+        NumberVisitor.visit_u64(v).map(JsonValue::Number)
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<JsonValue, E> {
+        NumberVisitor.visit_i128(v).map(JsonValue::Number)
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<JsonValue, E> {
+        NumberVisitor.visit_u128(v).map(JsonValue::Number)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<JsonValue, E> {
+        NumberVisitor.visit_f64(v).map(JsonValue::Number)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::JsonString(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::JsonString(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsonValue::Array(items))
+    }
+
+    // A repeated key keeps its first position and its last value, like DuplicateKeyPolicy::LastWins.
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut members = IndexMap::new();
+        while let Some((key, value)) = map.next_entry::<String, JsonValue>()? {
+            members.insert(key, value);
+        }
+        Ok(JsonValue::Object(members))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
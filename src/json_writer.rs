@@ -0,0 +1,225 @@
+use std::fmt;
+use std::io;
+use std::slice;
+
+use crate::json_definitions::JsonValue;
+
+// =============================================================================
+// JSON Writer — JsonValue → JSON text
+// =============================================================================
+//
+// Same idea as the v3 parser, run backwards: instead of recursing into
+// children we keep an explicit Vec<WriteFrame>, one frame per open container,
+// each holding an iterator over the children still to be written. Nesting
+// depth therefore costs heap memory, never call stack.
+//
+// Output layout is controlled by `WriterOptions`:
+//   - Indent::None        → compact:  {"a":[1,2]}
+//   - Indent::Spaces(n)   → pretty, n spaces per level
+//   - Indent::Tabs(n)     → pretty, n tabs per level
+//   - ascii_only          → every non-ASCII char is written as \uXXXX
+//                           (surrogate pairs above U+FFFF)
+//
+// Numbers are written exactly as parsed (JsonNumber keeps the lexeme); numbers
+// built from an f64 use the shortest representation that parses back to the
+// same f64. Non-finite numbers cannot be expressed in JSON and are written
+// as `null` (same as JavaScript's JSON.stringify).
+//
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    None,
+    Spaces(usize),
+    Tabs(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriterOptions {
+    pub indent: Indent,
+    pub ascii_only: bool,
+}
+
+impl WriterOptions {
+    pub fn compact() -> Self {
+        Self { indent: Indent::None, ascii_only: false }
+    }
+
+    pub fn pretty() -> Self {
+        Self { indent: Indent::Spaces(2), ascii_only: false }
+    }
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self::compact()
+    }
+}
+
+pub fn to_string(value: &JsonValue) -> String {
+    to_string_with_options(value, &WriterOptions::compact())
+}
+
+pub fn to_string_pretty(value: &JsonValue) -> String {
+    to_string_with_options(value, &WriterOptions::pretty())
+}
+
+pub fn to_string_with_options(value: &JsonValue, options: &WriterOptions) -> String {
+    let mut out = Vec::new();
+    // Writing into a Vec cannot fail
+    write_json(&mut out, value, options).expect("writing to Vec<u8> failed");
+    // Only ever contains bytes copied from &str or ASCII we produced ourselves
+    String::from_utf8(out).expect("writer produced invalid UTF-8")
+}
+
+enum WriteFrame<'a> {
+    Array { items: slice::Iter<'a, JsonValue>, first: bool },
+    Object { members: indexmap::map::Iter<'a, String, JsonValue>, first: bool },
+}
+
+pub fn write_json<W: io::Write>(mut writer: W, value: &JsonValue, options: &WriterOptions) -> io::Result<()> {
+    let mut stack: Vec<WriteFrame> = Vec::new();
+
+    begin_value(&mut writer, &mut stack, value, options)?;
+
+    loop {
+        let depth = stack.len();
+        let Some(frame) = stack.last_mut() else {
+            break;
+        };
+
+        match frame {
+            WriteFrame::Array { items, first } => match items.next() {
+                Some(item) => {
+                    if !*first {
+                        writer.write_all(b",")?;
+                    }
+                    *first = false;
+                    write_newline(&mut writer, options, depth)?;
+                    begin_value(&mut writer, &mut stack, item, options)?;
+                }
+                None => {
+                    stack.pop();
+                    write_newline(&mut writer, options, depth - 1)?;
+                    writer.write_all(b"]")?;
+                }
+            },
+            WriteFrame::Object { members, first } => match members.next() {
+                Some((key, item)) => {
+                    if !*first {
+                        writer.write_all(b",")?;
+                    }
+                    *first = false;
+                    write_newline(&mut writer, options, depth)?;
+                    write_string(&mut writer, key, options.ascii_only)?;
+                    match options.indent {
+                        Indent::None => writer.write_all(b":")?,
+                        _ => writer.write_all(b": ")?,
+                    }
+                    begin_value(&mut writer, &mut stack, item, options)?;
+                }
+                None => {
+                    stack.pop();
+                    write_newline(&mut writer, options, depth - 1)?;
+                    writer.write_all(b"}")?;
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+// Writes a leaf completely, or the opening token of a container and pushes its frame.
+// Empty containers are written whole so they stay on one line: [] and {}.
+fn begin_value<'a, W: io::Write>(
+    writer: &mut W,
+    stack: &mut Vec<WriteFrame<'a>>,
+    value: &'a JsonValue,
+    options: &WriterOptions,
+) -> io::Result<()> {
+    match value {
+        JsonValue::Null => writer.write_all(b"null"),
+        JsonValue::Boolean(true) => writer.write_all(b"true"),
+        JsonValue::Boolean(false) => writer.write_all(b"false"),
+        JsonValue::Number(n) => write!(writer, "{}", n),
+        JsonValue::JsonString(s) => write_string(writer, s, options.ascii_only),
+        JsonValue::Array(items) if items.is_empty() => writer.write_all(b"[]"),
+        JsonValue::Object(members) if members.is_empty() => writer.write_all(b"{}"),
+        JsonValue::Array(items) => {
+            stack.push(WriteFrame::Array { items: items.iter(), first: true });
+            writer.write_all(b"[")
+        }
+        JsonValue::Object(members) => {
+            stack.push(WriteFrame::Object { members: members.iter(), first: true });
+            writer.write_all(b"{")
+        }
+    }
+}
+
+pub(crate) fn write_newline<W: io::Write>(writer: &mut W, options: &WriterOptions, depth: usize) -> io::Result<()> {
+    let (unit, width): (&[u8], usize) = match options.indent {
+        Indent::None => return Ok(()),
+        Indent::Spaces(n) => (b" ", n),
+        Indent::Tabs(n) => (b"\t", n),
+    };
+
+    writer.write_all(b"\n")?;
+    for _ in 0..depth * width {
+        writer.write_all(unit)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn write_string<W: io::Write>(writer: &mut W, s: &str, ascii_only: bool) -> io::Result<()> {
+    writer.write_all(b"\"")?;
+
+    // Copy runs of chars that need no escaping in one go
+    let mut run_start = 0;
+    for (i, c) in s.char_indices() {
+        let needs_escape = matches!(c, '"' | '\\' | '\u{0}'..='\u{1F}') || (ascii_only && !c.is_ascii());
+        if !needs_escape {
+            continue;
+        }
+
+        writer.write_all(&s.as_bytes()[run_start..i])?;
+        run_start = i + c.len_utf8();
+
+        match c {
+            '"' => writer.write_all(b"\\\"")?,
+            '\\' => writer.write_all(b"\\\\")?,
+            '\u{08}' => writer.write_all(b"\\b")?,
+            '\u{0C}' => writer.write_all(b"\\f")?,
+            '\n' => writer.write_all(b"\\n")?,
+            '\r' => writer.write_all(b"\\r")?,
+            '\t' => writer.write_all(b"\\t")?,
+            _ => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(writer, "\\u{:04X}", unit)?;
+                }
+            }
+        }
+    }
+    writer.write_all(&s.as_bytes()[run_start..])?;
+
+    writer.write_all(b"\"")
+}
+
+// Rust's float formatting already produces the shortest digits that round-trip.
+// `{}` never uses an exponent though, so very large / very small magnitudes
+// switch to `{:e}` to avoid hundreds of zeros. An f32 is formatted as itself,
+// so 0.1f32 stays 0.1 instead of showing its f64 expansion.
+pub fn format_number<F: Into<f64> + Copy + fmt::Display + fmt::LowerExp>(n: F) -> String {
+    let wide: f64 = n.into();
+    if !wide.is_finite() {
+        return "null".to_string();
+    }
+
+    let abs = wide.abs();
+    if abs != 0.0 && !(1e-6..1e21).contains(&abs) {
+        format!("{:e}", n)
+    } else {
+        format!("{}", n)
+    }
+}
@@ -1,5 +1,21 @@
 pub mod json_definitions;
+pub mod json_number;
 pub mod json_parsing_naive;
 pub mod json_lexer;
 pub mod json_lexer_parser;
-mod json_non_recursive;
+pub mod json_events;
+pub mod json_non_recursive;
+pub mod json_push;
+pub mod json_diagnostics;
+pub mod json_writer;
+pub mod json_lines;
+pub mod json_sequence;
+pub mod json_pointer;
+pub mod json_patch;
+pub mod json_merge_patch;
+pub mod json_path;
+pub mod json_diff;
+pub mod json_macro;
+pub mod json_convert;
+#[cfg(feature = "serde")]
+pub mod json_serde;
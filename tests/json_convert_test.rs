@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, HashMap};
+
+use json_parser_rust::json;
+use json_parser_rust::json_convert::{FromJson, FromJsonError, FromJsonErrorKind, ToJson};
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+fn from<T: FromJson>(input: &str) -> Result<T, FromJsonError> {
+    T::from_json(&parse(input))
+}
+
+/// The failing path and the error, as Display shows them.
+fn error<T: FromJson + std::fmt::Debug>(input: &str) -> String {
+    from::<T>(input).unwrap_err().to_string()
+}
+
+mod std_types {
+    mod pos {
+        use super::super::{from, json, BTreeMap, FromJson, HashMap, ToJson};
+        use json_parser_rust::json_writer;
+
+        #[test]
+        fn scalars() {
+            assert_eq!(from::<bool>("true"), Ok(true));
+            assert_eq!(from::<u8>("255"), Ok(255));
+            assert_eq!(from::<i64>("-9223372036854775808"), Ok(i64::MIN));
+            assert_eq!(from::<u128>("340282366920938463463374607431768211455"), Ok(u128::MAX));
+            assert_eq!(from::<f64>("1.5e3"), Ok(1500.0));
+            assert_eq!(from::<f32>("2"), Ok(2.0));
+            assert_eq!(from::<String>(r#""héllo""#), Ok("héllo".to_string()));
+            assert_eq!(from::<char>(r#""x""#), Ok('x'));
+            assert_eq!(from::<()>("null"), Ok(()));
+        }
+
+        #[test]
+        fn containers() {
+            assert_eq!(from::<Vec<u32>>("[1, 2, 3]"), Ok(vec![1, 2, 3]));
+            assert_eq!(from::<Option<u32>>("null"), Ok(None));
+            assert_eq!(from::<Option<u32>>("7"), Ok(Some(7)));
+            assert_eq!(from::<(u8, String, bool)>(r#"[1, "a", false]"#), Ok((1, "a".to_string(), false)));
+            let map: BTreeMap<String, Vec<i32>> = from(r#"{"b": [1], "a": []}"#).unwrap();
+            assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b"]);
+            let map: HashMap<String, bool> = from(r#"{"x": true}"#).unwrap();
+            assert!(map["x"]);
+        }
+
+        #[test]
+        fn to_json() {
+            assert_eq!(42u16.to_json(), json!(42));
+            assert_eq!("text".to_json(), json!("text"));
+            assert_eq!(Some(1.5).to_json(), json!(1.5));
+            assert_eq!(None::<bool>.to_json(), json!(null));
+            assert_eq!(vec![(1, "a")].to_json(), json!([[1, "a"]]));
+            assert_eq!([true, false].to_json(), json!([true, false]));
+            let map: BTreeMap<&str, u8> = [("z", 1), ("a", 2)].into_iter().collect();
+            assert_eq!(map.to_json(), json!({"a": 2, "z": 1}));
+        }
+
+        #[test]
+        fn f32_round_trips_as_written() {
+            let value = 0.1f32.to_json();
+            assert_eq!(json_writer::to_string(&value), "0.1");
+            assert_eq!(f32::from_json(&value), Ok(0.1f32));
+            assert_eq!(f32::from_json(&3.4e38f32.to_json()), Ok(3.4e38f32));
+        }
+    }
+
+    mod neg {
+        use super::super::{error, from, FromJsonErrorKind};
+
+        #[test]
+        fn type_mismatch() {
+            assert_eq!(error::<bool>("1"), "expected boolean, found number");
+            assert_eq!(error::<String>("null"), "expected string, found null");
+            assert_eq!(error::<char>(r#""ab""#), "expected single-character string, found string");
+        }
+
+        #[test]
+        fn integers_are_range_checked() {
+            assert_eq!(error::<u8>("256"), "256 is out of range for u8");
+            assert_eq!(error::<u32>("-1"), "-1 is out of range for u32");
+            assert_eq!(error::<i64>("1e2"), "expected integer, found number");
+            assert_eq!(error::<i32>("1.0"), "expected integer, found number");
+        }
+
+        #[test]
+        fn paths_point_at_the_failing_value() {
+            let err = from::<Vec<Vec<u8>>>("[[1], [2, 300]]").unwrap_err();
+            assert_eq!(err.path().to_string(), "/1/1");
+            assert_eq!(err.to_string(), "at /1/1: 300 is out of range for u8");
+            assert_eq!(error::<(u8, u8)>("[1, 2, 3]"), "expected an array of 2 elements, found 3");
+            let err = from::<std::collections::BTreeMap<String, u8>>(r#"{"a/b": true}"#).unwrap_err();
+            assert_eq!(err.path().to_string(), "/a~1b");
+            assert!(matches!(err.kind(), FromJsonErrorKind::TypeMismatch { .. }));
+        }
+    }
+}
+
+mod derive {
+    use json_parser_rust::json_convert::{FromJson, ToJson};
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    #[json(rename_all = "camelCase")]
+    pub struct Server {
+        pub host_name: String,
+        pub port: u16,
+        pub tags: Vec<String>,
+        pub tls: Option<Tls>,
+        #[json(rename = "max-conn", default = "default_max_conn")]
+        pub max_conn: u32,
+        #[json(default)]
+        pub debug: bool,
+        #[json(skip)]
+        pub cache: Vec<u8>,
+        pub r#type: String,
+    }
+
+    fn default_max_conn() -> u32 {
+        64
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    pub struct Tls {
+        pub cert: String,
+        pub verify: bool,
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    pub struct Meta {
+        pub id: u64,
+        pub version: u32,
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    pub struct Record {
+        pub name: String,
+        #[json(flatten)]
+        pub meta: Meta,
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    pub struct Meters(pub f64);
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    pub struct Point(pub i32, pub i32);
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    pub struct Wrapper<T> {
+        pub inner: T,
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    #[json(rename_all = "snake_case")]
+    pub enum External {
+        Stopped,
+        Running(u32),
+        Moved(i32, i32),
+        #[json(rename = "failed!")]
+        Failed { code: u8, reason: Option<String> },
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    #[json(tag = "type")]
+    pub enum Internal {
+        Ping,
+        Circle { radius: f64 },
+        Meta(Meta),
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    #[json(tag = "t", content = "c")]
+    pub enum Adjacent {
+        Empty,
+        Count(u32),
+        Pair(bool, bool),
+        Named { x: i8 },
+    }
+
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    #[json(untagged)]
+    pub enum Untagged {
+        Nothing,
+        Number(i64),
+        Text(String),
+        Point { x: i32, y: i32 },
+    }
+
+    mod pos {
+        use super::super::{from, json, parse, ToJson};
+        use super::{Adjacent, External, Internal, Meta, Meters, Point, Record, Server, Tls, Untagged, Wrapper};
+
+        #[test]
+        fn structs_round_trip() {
+            let text = r#"{"hostName": "db", "port": 5432, "tags": ["a"], "tls": {"cert": "c.pem", "verify": true},
+                           "max-conn": 8, "debug": true, "type": "primary"}"#;
+            let server: Server = from(text).unwrap();
+            assert_eq!(server, Server {
+                host_name: "db".to_string(),
+                port: 5432,
+                tags: vec!["a".to_string()],
+                tls: Some(Tls { cert: "c.pem".to_string(), verify: true }),
+                max_conn: 8,
+                debug: true,
+                cache: Vec::new(),
+                r#type: "primary".to_string(),
+            });
+            assert_eq!(server.to_json(), parse(text));
+        }
+
+        #[test]
+        fn missing_fields_use_defaults_and_none() {
+            let server: Server = from(r#"{"hostName": "db", "port": 1, "tags": [], "type": "x", "extra": 0}"#).unwrap();
+            assert_eq!((server.tls, server.max_conn, server.debug), (None, 64, false));
+        }
+
+        #[test]
+        fn flatten() {
+            let text = r#"{"name": "r", "id": 7, "version": 2}"#;
+            let record: Record = from(text).unwrap();
+            assert_eq!(record, Record { name: "r".to_string(), meta: Meta { id: 7, version: 2 } });
+            assert_eq!(record.to_json(), parse(text));
+        }
+
+        #[test]
+        fn newtype_tuple_and_generic_structs() {
+            assert_eq!(from::<Meters>("2.5"), Ok(Meters(2.5)));
+            assert_eq!(Meters(2.5).to_json(), json!(2.5));
+            assert_eq!(from::<Point>("[1, -2]"), Ok(Point(1, -2)));
+            assert_eq!(Point(1, -2).to_json(), json!([1, -2]));
+            assert_eq!(from::<Wrapper<Vec<u8>>>(r#"{"inner": [1]}"#), Ok(Wrapper { inner: vec![1] }));
+        }
+
+        #[test]
+        fn externally_tagged() {
+            let cases = [
+                (External::Stopped, json!("stopped")),
+                (External::Running(3), json!({"running": 3})),
+                (External::Moved(1, 2), json!({"moved": [1, 2]})),
+                (External::Failed { code: 2, reason: None }, json!({"failed!": {"code": 2, "reason": null}})),
+            ];
+            for (value, expected) in cases {
+                assert_eq!(value.to_json(), expected);
+                assert_eq!(from::<External>(&json_parser_rust::json_writer::to_string(&expected)), Ok(value));
+            }
+            assert_eq!(from::<External>(r#"{"stopped": null}"#), Ok(External::Stopped));
+        }
+
+        #[test]
+        fn internally_tagged() {
+            let cases = [
+                (Internal::Ping, json!({"type": "Ping"})),
+                (Internal::Circle { radius: 1.5 }, json!({"type": "Circle", "radius": 1.5})),
+                (Internal::Meta(Meta { id: 1, version: 2 }), json!({"type": "Meta", "id": 1, "version": 2})),
+            ];
+            for (value, expected) in cases {
+                assert_eq!(value.to_json(), expected);
+                assert_eq!(from::<Internal>(&json_parser_rust::json_writer::to_string(&expected)), Ok(value));
+            }
+        }
+
+        #[test]
+        fn adjacently_tagged() {
+            let cases = [
+                (Adjacent::Empty, json!({"t": "Empty"})),
+                (Adjacent::Count(4), json!({"t": "Count", "c": 4})),
+                (Adjacent::Pair(true, false), json!({"t": "Pair", "c": [true, false]})),
+                (Adjacent::Named { x: -1 }, json!({"t": "Named", "c": {"x": -1}})),
+            ];
+            for (value, expected) in cases {
+                assert_eq!(value.to_json(), expected);
+                assert_eq!(from::<Adjacent>(&json_parser_rust::json_writer::to_string(&expected)), Ok(value));
+            }
+        }
+
+        #[test]
+        fn untagged_takes_the_first_match() {
+            assert_eq!(from::<Untagged>("null"), Ok(Untagged::Nothing));
+            assert_eq!(from::<Untagged>("12"), Ok(Untagged::Number(12)));
+            assert_eq!(from::<Untagged>(r#""12""#), Ok(Untagged::Text("12".to_string())));
+            assert_eq!(from::<Untagged>(r#"{"x": 1, "y": 2}"#), Ok(Untagged::Point { x: 1, y: 2 }));
+            assert_eq!(Untagged::Point { x: 1, y: 2 }.to_json(), json!({"x": 1, "y": 2}));
+            assert_eq!(Untagged::Nothing.to_json(), json!(null));
+        }
+    }
+
+    mod neg {
+        use super::super::{error, from};
+        use super::{Adjacent, External, Internal, Record, Server, Untagged};
+
+        #[test]
+        fn missing_and_mistyped_fields() {
+            assert_eq!(error::<Server>(r#"{"hostName": "db", "tags": [], "type": "x"}"#), "at /port: missing field");
+            assert_eq!(
+                error::<Server>(r#"{"hostName": "db", "port": 1, "tags": [], "type": "x", "tls": {"cert": 1}}"#),
+                "at /tls/cert: expected string, found number"
+            );
+            assert_eq!(
+                error::<Server>(r#"{"hostName": "db", "port": 1, "tags": ["a", 2], "type": "x"}"#),
+                "at /tags/1: expected string, found number"
+            );
+            assert_eq!(error::<Server>("[]"), "expected object, found array");
+        }
+
+        #[test]
+        fn flattened_errors_keep_the_outer_path() {
+            assert_eq!(error::<Record>(r#"{"name": "r", "id": 7}"#), "at /version: missing field");
+        }
+
+        #[test]
+        fn enum_errors() {
+            assert_eq!(
+                error::<External>(r#""paused""#),
+                "unknown variant \"paused\", expected one of stopped, running, moved, failed!"
+            );
+            assert_eq!(error::<External>(r#"{"running": "x"}"#), "at /running: expected integer, found string");
+            assert_eq!(error::<External>(r#""running""#), "expected single-member object, found string");
+            assert_eq!(error::<External>(r#"{"a": 1, "b": 2}"#), "expected string or single-member object, found object");
+            assert_eq!(error::<Internal>(r#"{"radius": 1}"#), "at /type: missing field");
+            assert_eq!(error::<Internal>(r#"{"type": "Circle", "radius": true}"#), "at /radius: expected number, found boolean");
+            assert_eq!(error::<Adjacent>(r#"{"t": "Count"}"#), "at /c: missing field");
+            assert_eq!(error::<Adjacent>(r#"{"t": "Pair", "c": [true, 0]}"#), "at /c/1: expected boolean, found number");
+            assert_eq!(error::<Untagged>("[]"), "value matches no variant of Untagged");
+            assert!(from::<Adjacent>(r#"{"t": "Nope"}"#).is_err());
+        }
+    }
+}
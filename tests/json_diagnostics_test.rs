@@ -0,0 +1,177 @@
+use std::error::Error;
+
+use json_parser_rust::json_definitions::{
+    CommentPolicy, Dialect, DuplicateKeyPolicy, JsonParsingErrorV3, LexerError, ParseOptions,
+};
+use json_parser_rust::json_diagnostics::{render_error, ColorMode, ToDiagnostic};
+use json_parser_rust::json_lexer::lex_all;
+use json_parser_rust::json_lexer_parser::process_json_string_v2;
+use json_parser_rust::json_non_recursive::{process_json_string_v3, process_json_string_v3_with_options};
+use json_parser_rust::json_parsing_naive::process_json_string_v1;
+
+mod display {
+    use super::{lex_all, process_json_string_v1, process_json_string_v2, process_json_string_v3, Error};
+
+    #[test]
+    fn lexer_error_message_includes_location() {
+        let err = lex_all(b"[\n  01]").unwrap_err();
+        assert_eq!(err.to_string(), "invalid number: number has a leading zero at line 2, column 3");
+    }
+
+    #[test]
+    fn v2_error_message() {
+        let err = process_json_string_v2("[1 2]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected ',' or ']' after array element, found number at line 1, column 4"
+        );
+    }
+
+    #[test]
+    fn v3_error_message() {
+        let err = process_json_string_v3(r#"{"a": 1,}"#).unwrap_err();
+        assert_eq!(err.to_string(), "trailing ',' before '}' in object at line 1, column 9");
+    }
+
+    #[test]
+    fn v1_error_message_has_no_location() {
+        let err = process_json_string_v1("").unwrap_err();
+        assert_eq!(err.to_string(), "input is empty");
+    }
+
+    #[test]
+    fn lex_error_is_exposed_as_source() {
+        let err = process_json_string_v3("[tru]").unwrap_err();
+        let source = err.source().expect("lexer error should be the source");
+        assert_eq!(source.to_string(), "invalid literal, expected `true` at line 1, column 2");
+    }
+}
+
+mod render {
+    use super::{
+        lex_all, process_json_string_v3, process_json_string_v3_with_options, render_error, ColorMode,
+        CommentPolicy, Dialect, DuplicateKeyPolicy, JsonParsingErrorV3, LexerError, ParseOptions, ToDiagnostic,
+    };
+
+    #[test]
+    fn plain_report_with_snippet_and_help() {
+        let source = "{\n  \"a\": 1\n  \"b\": 2\n}";
+        let err = process_json_string_v3(source).unwrap_err();
+
+        let expected = "\
+error: expected ',' or '}' after object member, found string
+ --> line 3, column 3
+  |
+3 |   \"b\": 2
+  |   ^^^
+  = help: separate object members with ','
+";
+        assert_eq!(render_error(&err, source, ColorMode::Plain), expected);
+    }
+
+    #[test]
+    fn caret_accounts_for_tabs_and_multibyte_chars() {
+        let source = "[\"é\",\ttrue false]";
+        let err = process_json_string_v3(source).unwrap_err();
+
+        let rendered = render_error(&err, source, ColorMode::Plain);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[3], "1 | [\"é\",    true false]");
+        assert_eq!(lines[4], "  |               ^^^^^");
+    }
+
+    #[test]
+    fn wide_line_numbers_align_gutter() {
+        let source = format!("{}[1,]", "\n".repeat(11));
+        let err = process_json_string_v3(&source).unwrap_err();
+
+        let rendered = render_error(&err, &source, ColorMode::Plain);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "  --> line 12, column 4");
+        assert_eq!(lines[2], "   |");
+        assert_eq!(lines[3], "12 | [1,]");
+        assert_eq!(lines[4], "   |    ^");
+    }
+
+    #[test]
+    fn ansi_mode_adds_colour_codes() {
+        let source = "[1 2]";
+        let err = process_json_string_v3(source).unwrap_err();
+
+        let plain = render_error(&err, source, ColorMode::Plain);
+        let ansi = render_error(&err, source, ColorMode::Ansi);
+        assert!(!plain.contains('\x1b'));
+        assert!(ansi.contains("\x1b[1;31merror\x1b[0m"));
+        assert!(ansi.contains("\x1b[1;31m^\x1b[0m"));
+    }
+
+    #[test]
+    fn error_without_location_renders_message_only() {
+        let rendered = render_error(&JsonParsingErrorV3::EmptyJsonFile, "", ColorMode::Plain);
+        assert_eq!(rendered, "error: input is empty\n");
+    }
+
+    #[test]
+    fn duplicate_key_points_at_second_key() {
+        let source = "{\"a\": 1,\n \"a\": 2}";
+        let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::default() };
+        let err = process_json_string_v3_with_options(source, &options).unwrap_err();
+
+        let expected = "\
+error: duplicate key \"a\"
+ --> line 2, column 2
+  |
+2 |  \"a\": 2}
+  |  ^^^
+  = help: first defined at line 1, column 2
+";
+        assert_eq!(render_error(&err, source, ColorMode::Plain), expected);
+    }
+
+    #[test]
+    fn lexer_diagnostic_has_help() {
+        let err = lex_all(b"\"abc").unwrap_err();
+        assert!(matches!(err, LexerError::InvalidString { .. }));
+        assert_eq!(err.to_diagnostic().help.as_deref(), Some("add the closing '\"'"));
+    }
+
+    #[test]
+    fn unexpected_byte_help_fits_the_error() {
+        let err = lex_all(b"[1, %]").unwrap_err();
+        assert!(err.to_diagnostic().help.unwrap().starts_with("a JSON value starts with"));
+
+        let options = ParseOptions { comments: CommentPolicy::Skip, ..ParseOptions::default() };
+        let source = "{\"a\": 1 /x }";
+        let err = process_json_string_v3_with_options(source, &options).unwrap_err();
+        let expected = "\
+error: unexpected 'x', expected '/' or '*' after '/'
+ --> line 1, column 10
+  |
+1 | {\"a\": 1 /x }
+  |          ^
+  = help: comments are // ... or /* ... */
+";
+        assert_eq!(render_error(&err, source, ColorMode::Plain), expected);
+
+        let options = ParseOptions { dialect: Dialect::Json5, ..ParseOptions::default() };
+        let err = process_json_string_v3_with_options(r"{a\n: 1}", &options).unwrap_err();
+        let help = err.to_diagnostic().help;
+        assert_eq!(help.as_deref(), Some("the only escape allowed in an unquoted key is \\uXXXX"));
+    }
+
+    #[test]
+    fn lone_surrogate_points_at_the_escape() {
+        let source = r#"["ok", "\uD83D!"]"#;
+        let err = process_json_string_v3(source).unwrap_err();
+
+        let expected = "\
+error: invalid string: high surrogate \\uD83D is not followed by a low surrogate
+ --> line 1, column 9
+  |
+1 | [\"ok\", \"\\uD83D!\"]
+  |         ^
+  = help: escape characters above U+FFFF as a pair, e.g. \\uD83D\\uDE00, or parse with LoneSurrogatePolicy::Replace
+";
+        assert_eq!(render_error(&err, source, ColorMode::Plain), expected);
+    }
+}
@@ -0,0 +1,178 @@
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_diff::{json_diff, json_diff_with_options, render_diff, Change, DiffOptions};
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_pointer::JsonPointer;
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+fn pointer(text: &str) -> JsonPointer {
+    JsonPointer::parse(text).unwrap()
+}
+
+fn diff(a: &str, b: &str) -> Vec<Change> {
+    json_diff(&parse(a), &parse(b))
+}
+
+mod objects {
+    use super::{diff, json_diff_with_options, parse, pointer, Change, DiffOptions};
+
+    #[test]
+    fn added_removed_changed() {
+        assert_eq!(
+            diff(r#"{"port": 80, "debug": true, "name": "x"}"#, r#"{"port": 8080, "name": "x", "tls": {}}"#),
+            [
+                Change::Changed { path: pointer("/port"), from: parse("80"), to: parse("8080") },
+                Change::Removed { path: pointer("/debug"), value: parse("true") },
+                Change::Added { path: pointer("/tls"), value: parse("{}") },
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_values_have_no_changes() {
+        assert_eq!(diff(r#"{"a": [1, {"b": null}], "n": 1}"#, r#"{"a": [1, {"b": null}], "n": 1.0}"#), []);
+        assert_eq!(diff("3", "3"), []);
+    }
+
+    #[test]
+    fn nested_changes_have_full_paths() {
+        assert_eq!(
+            diff(r#"{"a": {"b/c": {"d": [1, 2]}}}"#, r#"{"a": {"b/c": {"d": [1, 3]}}}"#),
+            [Change::Changed { path: pointer("/a/b~1c/d/1"), from: parse("2"), to: parse("3") }]
+        );
+    }
+
+    #[test]
+    fn type_changes_replace_the_whole_value() {
+        assert_eq!(
+            diff(r#"{"a": {"b": 1}}"#, r#"{"a": [1]}"#),
+            [Change::Changed { path: pointer("/a"), from: parse(r#"{"b": 1}"#), to: parse("[1]") }]
+        );
+        assert_eq!(diff("1", "\"1\""), [Change::Changed { path: pointer(""), from: parse("1"), to: parse("\"1\"") }]);
+    }
+
+    #[test]
+    fn key_order() {
+        let a = parse(r#"{"a": 1, "b": 2, "c": 3}"#);
+        let b = parse(r#"{"c": 3, "a": 1, "b": 2, "d": 4}"#);
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            json_diff_with_options(&a, &b, &DiffOptions::default()),
+            [
+                Change::Reordered { path: pointer(""), from: keys(&["a", "b", "c"]), to: keys(&["c", "a", "b"]) },
+                Change::Added { path: pointer("/d"), value: parse("4") },
+            ]
+        );
+        let options = DiffOptions { ignore_key_order: true, ..DiffOptions::default() };
+        assert_eq!(
+            json_diff_with_options(&a, &b, &options),
+            [Change::Added { path: pointer("/d"), value: parse("4") }]
+        );
+    }
+}
+
+mod arrays {
+    use super::{diff, json_diff_with_options, parse, pointer, Change, DiffOptions};
+
+    #[test]
+    fn insertions_and_deletions_keep_the_rest_aligned() {
+        assert_eq!(diff("[1, 2, 3]", "[1, 9, 2, 3]"), [Change::Added { path: pointer("/1"), value: parse("9") }]);
+        assert_eq!(diff("[1, 2, 3, 4]", "[1, 4]"), [
+            Change::Removed { path: pointer("/1"), value: parse("2") },
+            Change::Removed { path: pointer("/2"), value: parse("3") },
+        ]);
+    }
+
+    #[test]
+    fn moved_elements() {
+        assert_eq!(
+            diff(r#"["x", "y", "z"]"#, r#"["z", "x", "y"]"#),
+            [Change::Moved { from: pointer("/2"), path: pointer("/0"), value: parse("\"z\"") }]
+        );
+        assert_eq!(
+            diff(r#"[{"id": 1}, {"id": 2}, 3, 4]"#, r#"[3, {"id": 1}, {"id": 2}, 5]"#),
+            [
+                Change::Moved { from: pointer("/2"), path: pointer("/0"), value: parse("3") },
+                Change::Changed { path: pointer("/3"), from: parse("4"), to: parse("5") },
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_elements_are_compared_inside() {
+        assert_eq!(
+            diff(r#"[{"id": 1, "v": "a"}, {"id": 2, "v": "b"}]"#, r#"[{"id": 1, "v": "a"}, {"id": 2, "v": "c"}]"#),
+            [Change::Changed { path: pointer("/1/v"), from: parse("\"b\""), to: parse("\"c\"") }]
+        );
+    }
+
+    #[test]
+    fn removed_paths_point_into_the_old_value() {
+        assert_eq!(
+            diff(r#"[0, {"a": 1, "b": 2}]"#, r#"[{"a": 1}]"#),
+            [
+                Change::Removed { path: pointer("/0"), value: parse("0") },
+                Change::Removed { path: pointer("/1/b"), value: parse("2") },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_array_order() {
+        let options = DiffOptions { ignore_array_order: true, ..DiffOptions::default() };
+        let a = parse(r#"{"tags": ["a", "b", [1, 2]], "n": [1, 2, 3]}"#);
+        let b = parse(r#"{"tags": [[2, 1], "b", "a"], "n": [3, 5, 1]}"#);
+        assert_eq!(
+            json_diff_with_options(&a, &b, &options),
+            [Change::Changed { path: pointer("/n/1"), from: parse("2"), to: parse("5") }]
+        );
+        assert_eq!(json_diff_with_options(&parse("[1, 1, 2]"), &parse("[2, 1]"), &options), [
+            Change::Removed { path: pointer("/1"), value: parse("1") }
+        ]);
+    }
+
+    #[test]
+    fn deep_documents() {
+        let depth = 100_000;
+        let a = parse(&format!("{}1{}", "[".repeat(depth), "]".repeat(depth)));
+        let b = parse(&format!("{}2{}", "[".repeat(depth), "]".repeat(depth)));
+        let changes = json_diff_with_options(&a, &b, &DiffOptions::default());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path().tokens().len(), depth);
+        assert_eq!(json_diff_with_options(&a, &a.clone(), &DiffOptions::default()), []);
+    }
+}
+
+mod rendering {
+    use super::{diff, render_diff};
+
+    #[test]
+    fn one_hunk_per_change() {
+        let changes = diff(
+            r#"{"port": 80, "hosts": ["x", "y", "z"], "debug": true}"#,
+            r#"{"port": 8080, "hosts": ["z", "x", "y"], "tls": {"on": true}}"#,
+        );
+        let expected = "\
+@@ /port @@
+-80
++8080
+@@ /hosts/2 -> /hosts/0 @@
+ \"z\"
+@@ /debug @@
+-true
+@@ /tls @@
++{
++  \"on\": true
++}
+";
+        assert_eq!(render_diff(&changes), expected);
+    }
+
+    #[test]
+    fn root_and_key_order() {
+        let text = render_diff(&diff(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#));
+        assert_eq!(text, "@@ (root) @@\n-keys \"a\", \"b\"\n+keys \"b\", \"a\"\n");
+    }
+}
@@ -0,0 +1,163 @@
+use json_parser_rust::json_definitions::{FrameTypeTag, JsonParsingErrorV3, JsonValueRef, Limit, ParseLimits, TokenTag};
+use json_parser_rust::json_events::{JsonEvent, JsonEventParser};
+use json_parser_rust::json_lexer::{SourceLocation, Span};
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+
+fn loc(offset: usize, line: usize, column: usize) -> SourceLocation {
+    SourceLocation { offset, line, column }
+}
+
+fn events_of(input: &str) -> Result<Vec<JsonEvent<'_>>, JsonParsingErrorV3> {
+    JsonEventParser::new(input).collect()
+}
+
+fn key(k: &str) -> JsonEvent<'_> {
+    JsonEvent::Key(k.into())
+}
+
+fn num(n: f64) -> JsonEvent<'static> {
+    JsonEvent::Value(JsonValueRef::Number(n.into()))
+}
+
+mod pos {
+    use super::{events_of, key, num, JsonEvent, JsonValueRef};
+
+    #[test]
+    fn leaf_at_root() {
+        assert_eq!(events_of(" null "), Ok(vec![JsonEvent::Value(JsonValueRef::Null)]));
+        assert_eq!(events_of("\"s\""), Ok(vec![JsonEvent::Value(JsonValueRef::JsonString("s".into()))]));
+    }
+
+    #[test]
+    fn nested_document() {
+        let input = r#"{"a": [1, true, {}], "b": {"c": null}, "d": []}"#;
+        let expected = vec![
+            JsonEvent::StartObject,
+            key("a"),
+            JsonEvent::StartArray,
+            num(1.0),
+            JsonEvent::Value(JsonValueRef::Boolean(true)),
+            JsonEvent::StartObject,
+            JsonEvent::EndObject,
+            JsonEvent::EndArray,
+            key("b"),
+            JsonEvent::StartObject,
+            key("c"),
+            JsonEvent::Value(JsonValueRef::Null),
+            JsonEvent::EndObject,
+            key("d"),
+            JsonEvent::StartArray,
+            JsonEvent::EndArray,
+            JsonEvent::EndObject,
+        ];
+        assert_eq!(events_of(input), Ok(expected));
+    }
+
+    #[test]
+    fn string_values_are_not_keys() {
+        let expected = vec![
+            JsonEvent::StartObject,
+            key("k"),
+            JsonEvent::Value(JsonValueRef::JsonString("v".into())),
+            JsonEvent::EndObject,
+        ];
+        assert_eq!(events_of(r#"{"k": "v"}"#), Ok(expected));
+    }
+
+    #[test]
+    fn million_deep_without_building_a_tree() {
+        let depth = 1_000_000;
+        let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        let mut max_depth = 0;
+        let mut count = 0;
+        let mut parser = super::JsonEventParser::new(&input);
+        while let Some(event) = parser.next() {
+            event.expect("deep array should stream");
+            max_depth = max_depth.max(parser.depth());
+            count += 1;
+        }
+        assert_eq!(count, 2 * depth);
+        assert_eq!(max_depth, depth);
+    }
+}
+
+mod spans {
+    use super::{loc, JsonEventParser, Span};
+
+    #[test]
+    fn span_follows_each_event() {
+        let mut parser = JsonEventParser::new(r#"{"ab": [1]}"#);
+        let mut spans = Vec::new();
+        while let Some(event) = parser.next() {
+            event.expect("valid input");
+            spans.push(parser.span());
+        }
+
+        assert_eq!(
+            spans,
+            vec![
+                Span { start: loc(0, 1, 1), end: loc(1, 1, 2) },     // {
+                Span { start: loc(1, 1, 2), end: loc(5, 1, 6) },     // "ab"
+                Span { start: loc(7, 1, 8), end: loc(8, 1, 9) },     // [
+                Span { start: loc(8, 1, 9), end: loc(9, 1, 10) },    // 1
+                Span { start: loc(9, 1, 10), end: loc(10, 1, 11) },  // ]
+                Span { start: loc(10, 1, 11), end: loc(11, 1, 12) }, // }
+            ]
+        );
+    }
+}
+
+mod neg {
+    use super::{
+        events_of, key, loc, num, process_json_string_v3, FrameTypeTag, JsonEvent, JsonEventParser,
+        JsonParsingErrorV3, Limit, ParseLimits, TokenTag,
+    };
+
+    #[test]
+    fn error_ends_the_stream() {
+        let mut parser = JsonEventParser::new("[1 2]");
+        assert_eq!(parser.next(), Some(Ok(JsonEvent::StartArray)));
+        assert_eq!(parser.next(), Some(Ok(num(1.0))));
+        assert_eq!(
+            parser.next(),
+            Some(Err(JsonParsingErrorV3::MissingComma { at: loc(3, 1, 4), found: TokenTag::Number, frame: FrameTypeTag::ArrayFrame }))
+        );
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn events_before_the_error_are_delivered() {
+        let mut parser = JsonEventParser::new(r#"{"a": 1, "b": }"#);
+        let seen: Vec<_> = parser.by_ref().take_while(Result::is_ok).map(Result::unwrap).collect();
+        assert_eq!(seen, vec![JsonEvent::StartObject, key("a"), num(1.0)]);
+    }
+
+    #[test]
+    fn same_errors_as_v3() {
+        let cases = [
+            "",
+            "   ",
+            "[1,]",
+            "{\"a\" 1}",
+            "{\"a\": }",
+            "[1] 2",
+            "{1: 2}",
+            "]",
+            "[tru]",
+            "{\"a\": [}",
+            "[[",
+        ];
+        for input in cases {
+            let expected = process_json_string_v3(input).unwrap_err();
+            assert_eq!(events_of(input), Err(expected), "input was: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn depth_limit() {
+        let limits = ParseLimits { max_depth: 1, ..ParseLimits::UNLIMITED };
+        let result: Result<Vec<_>, _> = JsonEventParser::with_limits("[[1]]", &limits).collect();
+        assert_eq!(result, Err(JsonParsingErrorV3::LimitExceeded { limit: Limit::Depth, at: loc(1, 1, 2) }));
+    }
+}
@@ -1,11 +1,19 @@
+use std::borrow::Cow;
+
 use json_parser_rust::json_lexer::{
-    lex_all, NumberError, StringError, TokenKind,
+    lex_all, lex_all_with_limits, Lexer, NumberError, PushLexer, SourceLocation, StringError, Token, TokenKind,
+};
+
+use json_parser_rust::json_definitions::{
+    CommentPolicy, Dialect, LexerError, Limit, LoneSurrogatePolicy, ParseLimits, ParseOptions,
 };
 
-use json_parser_rust::json_definitions::LexerError;
+fn loc(offset: usize, line: usize, column: usize) -> SourceLocation {
+    SourceLocation { offset, line, column }
+}
 
 mod punctuation {
-    use super::{lex_all, LexerError, TokenKind};
+    use super::{lex_all, loc, LexerError, TokenKind};
 
     mod pos {
         use super::{lex_all, TokenKind};
@@ -52,7 +60,7 @@ mod punctuation {
     }
 
     mod neg {
-        use super::{lex_all, LexerError};
+        use super::{lex_all, loc, LexerError};
 
         #[test]
         fn unknown_punctuation() {
@@ -61,7 +69,7 @@ mod punctuation {
             assert_eq!(
                 err,
                 LexerError::UnexpectedByte {
-                    at: 0,
+                    at: loc(0, 1, 1),
                     found: b';',
                     expected: "token"
                 }
@@ -75,7 +83,7 @@ mod punctuation {
             assert_eq!(
                 err,
                 LexerError::UnexpectedByte {
-                    at: 2,
+                    at: loc(2, 1, 3),
                     found: b'%',
                     expected: "token"
                 }
@@ -85,7 +93,7 @@ mod punctuation {
 }
 
 mod literals {
-    use super::{lex_all, LexerError, TokenKind};
+    use super::{lex_all, loc, LexerError, TokenKind};
 
     mod pos {
         use super::{lex_all, TokenKind};
@@ -142,8 +150,8 @@ mod literals {
                 kinds,
                 vec![
                     TokenKind::LBracket,
-                    TokenKind::Number(1.0),
-                    TokenKind::Number(2.0),
+                    TokenKind::Number(1.0.into()),
+                    TokenKind::Number(2.0.into()),
                     TokenKind::RBracket,
                     TokenKind::Eof
                 ]
@@ -152,27 +160,27 @@ mod literals {
     }
 
     mod neg {
-        use super::{lex_all, LexerError};
+        use super::{lex_all, loc, LexerError};
 
         #[test]
         fn rejects_partial_true() {
             let input = b"tru";
             let err = lex_all(input).unwrap_err();
-            assert_eq!(err, LexerError::UnexpectedEof { at: 3, expected: "true" });
+            assert_eq!(err, LexerError::UnexpectedEof { at: loc(3, 1, 4), expected: "true" });
         }
 
         #[test]
         fn rejects_partial_false() {
             let input = b"fals";
             let err = lex_all(input).unwrap_err();
-            assert_eq!(err, LexerError::UnexpectedEof { at: 4, expected: "false" });
+            assert_eq!(err, LexerError::UnexpectedEof { at: loc(4, 1, 5), expected: "false" });
         }
 
         #[test]
         fn rejects_partial_null() {
             let input = b"nul";
             let err = lex_all(input).unwrap_err();
-            assert_eq!(err, LexerError::UnexpectedEof { at: 3, expected: "null" });
+            assert_eq!(err, LexerError::UnexpectedEof { at: loc(3, 1, 4), expected: "null" });
         }
 
         #[test]
@@ -181,7 +189,7 @@ mod literals {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::UnexpectedByte { at: 4, found: b'e', expected: "token" }
+                LexerError::UnexpectedByte { at: loc(4, 1, 5), found: b'e', expected: "token" }
             );
         }
 
@@ -191,7 +199,7 @@ mod literals {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::UnexpectedByte { at: 5, found: b'x', expected: "token" }
+                LexerError::UnexpectedByte { at: loc(5, 1, 6), found: b'x', expected: "token" }
             );
         }
 
@@ -201,14 +209,14 @@ mod literals {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::UnexpectedByte { at: 4, found: b'x', expected: "token" }
+                LexerError::UnexpectedByte { at: loc(4, 1, 5), found: b'x', expected: "token" }
             );
         }
     }
 }
 
 mod strings {
-    use super::{lex_all, LexerError, StringError, TokenKind};
+    use super::{lex_all, loc, LexerError, StringError, TokenKind};
 
     mod pos {
         use super::{lex_all, TokenKind};
@@ -217,47 +225,47 @@ mod strings {
         fn empty() {
             let input = br#""""#;
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::String("".to_string()), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::String("".into()), TokenKind::Eof]);
         }
 
         #[test]
         fn ascii() {
             let input = br#""hello""#;
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::String("hello".to_string()), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::String("hello".into()), TokenKind::Eof]);
         }
 
         #[test]
         fn with_spaces() {
             let input = br#""hello world""#;
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::String("hello world".to_string()), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::String("hello world".into()), TokenKind::Eof]);
         }
 
         #[test]
         fn with_escaped_quote() {
             let input = br#""he\"llo""#;
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::String("he\"llo".to_string()), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::String("he\"llo".into()), TokenKind::Eof]);
         }
 
         #[test]
         fn with_backslash_escape() {
             let input = br#""C:\\path""#;
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::String("C:\\path".to_string()), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::String("C:\\path".into()), TokenKind::Eof]);
         }
 
         #[test]
         fn with_unicode_escape_ascii() {
             let input = br#""\u0041\u0042""#;
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::String("AB".to_string()), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::String("AB".into()), TokenKind::Eof]);
         }
     }
 
     mod neg {
-        use super::{lex_all, LexerError, StringError};
+        use super::{lex_all, loc, LexerError, StringError};
 
         #[test]
         fn rejects_unterminated() {
@@ -265,7 +273,7 @@ mod strings {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::InvalidString { at: 4, reason: StringError::Unterminated }
+                LexerError::InvalidString { at: loc(4, 1, 5), reason: StringError::Unterminated }
             );
         }
 
@@ -275,7 +283,7 @@ mod strings {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::InvalidString { at: 2, reason: StringError::ControlChar { found: 0x01 } }
+                LexerError::InvalidString { at: loc(2, 1, 3), reason: StringError::ControlChar { found: 0x01 } }
             );
         }
 
@@ -310,19 +318,113 @@ mod strings {
         }
 
         #[test]
-        fn rejects_surrogate_unicode_escape() {
+        fn rejects_lone_surrogate_unicode_escape() {
             let input = br#""\uD800""#;
             let err = lex_all(input).unwrap_err();
             assert!(matches!(
                 err,
-                LexerError::InvalidString { reason: StringError::SurrogateNotAllowed, .. }
+                LexerError::InvalidString { reason: StringError::LoneHighSurrogate { code: 0xD800 }, .. }
+            ));
+        }
+    }
+}
+
+mod surrogates {
+    use super::{lex_all, loc, Lexer, LexerError, LoneSurrogatePolicy, ParseOptions, StringError, TokenKind};
+
+    fn lex_lenient(input: &[u8]) -> Result<Vec<TokenKind<'_>>, LexerError> {
+        let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Replace, ..ParseOptions::default() };
+        Lexer::with_options(input, &options).map(|t| t.map(|t| t.kind)).collect()
+    }
+
+    mod pos {
+        use super::{lex_all, lex_lenient, TokenKind};
+
+        #[test]
+        fn pair_decodes_to_one_char() {
+            let cases: [(&[u8], &str); 4] = [
+                (br#""\uD83D\uDE00""#, "\u{1F600}"),
+                (br#""\ud83d\ude00""#, "\u{1F600}"),
+                (br#""\uD800\uDC00""#, "\u{10000}"),
+                (br#""a\uDBFF\uDFFFb""#, "a\u{10FFFF}b"),
+            ];
+            for (input, expected) in cases {
+                let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
+                assert_eq!(kinds, vec![TokenKind::String(expected.into()), TokenKind::Eof]);
+            }
+        }
+
+        #[test]
+        fn lenient_replaces_lone_surrogates() {
+            let cases: [(&[u8], &str); 5] = [
+                (br#""\uD800""#, "\u{FFFD}"),
+                (br#""\uDE00x""#, "\u{FFFD}x"),
+                (br#""\uD83Dx""#, "\u{FFFD}x"),
+                // the escape after a lone high surrogate is decoded on its own
+                (br#""\uD83D\u0041""#, "\u{FFFD}A"),
+                (br#""\uD83D\uD83D\uDE00""#, "\u{FFFD}\u{1F600}"),
+            ];
+            for (input, expected) in cases {
+                assert_eq!(lex_lenient(input), Ok(vec![TokenKind::String(expected.into()), TokenKind::Eof]));
+            }
+        }
+
+        #[test]
+        fn lenient_keeps_valid_pairs() {
+            assert_eq!(
+                lex_lenient(br#""\uD83D\uDE00""#),
+                Ok(vec![TokenKind::String("\u{1F600}".into()), TokenKind::Eof])
+            );
+        }
+    }
+
+    mod neg {
+        use super::{lex_all, lex_lenient, loc, LexerError, StringError};
+
+        #[test]
+        fn lone_high_surrogate() {
+            for input in [&br#""\uD83D""#[..], br#""\uD83Dx""#, br#""\uD83D\n""#, br#""\uD83D\u0041""#] {
+                assert_eq!(
+                    lex_all(input).unwrap_err(),
+                    LexerError::InvalidString { at: loc(1, 1, 2), reason: StringError::LoneHighSurrogate { code: 0xD83D } },
+                    "input was: {:?}",
+                    std::str::from_utf8(input)
+                );
+            }
+        }
+
+        #[test]
+        fn high_surrogate_followed_by_high_surrogate() {
+            assert_eq!(
+                lex_all(br#""\uD83D\uD83D""#).unwrap_err(),
+                LexerError::InvalidString { at: loc(1, 1, 2), reason: StringError::LoneHighSurrogate { code: 0xD83D } }
+            );
+        }
+
+        #[test]
+        fn lone_low_surrogate() {
+            assert_eq!(
+                lex_all(br#""ab\uDE00""#).unwrap_err(),
+                LexerError::InvalidString { at: loc(3, 1, 4), reason: StringError::LoneLowSurrogate { code: 0xDE00 } }
+            );
+        }
+
+        #[test]
+        fn broken_low_half_is_still_an_error_when_lenient() {
+            assert!(matches!(
+                lex_lenient(br#""\uD83D\uDE0""#),
+                Err(LexerError::InvalidString { reason: StringError::InvalidUnicodeEscape, .. })
+            ));
+            assert!(matches!(
+                lex_lenient(br#""\uD83D"#),
+                Err(LexerError::InvalidString { reason: StringError::Unterminated, .. })
             ));
         }
     }
 }
 
 mod numbers {
-    use super::{lex_all, LexerError, TokenKind};
+    use super::{lex_all, loc, LexerError, TokenKind};
 
     mod pos {
         use super::{lex_all, TokenKind};
@@ -331,40 +433,41 @@ mod numbers {
         fn zero() {
             let input = b"0";
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::Number(0.0), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::Number(0.0.into()), TokenKind::Eof]);
         }
 
         #[test]
         fn integer() {
             let input = b"42";
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::Number(42.0), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::Number(42.0.into()), TokenKind::Eof]);
         }
 
         #[test]
+        #[allow(clippy::approx_constant)]
         fn fraction() {
             let input = b"3.14";
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::Number(3.14), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::Number(3.14.into()), TokenKind::Eof]);
         }
 
         #[test]
         fn exponent() {
             let input = b"1e3";
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::Number(1000.0), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::Number(1000.0.into()), TokenKind::Eof]);
         }
 
         #[test]
         fn negative() {
             let input = b"-12";
             let kinds: Vec<TokenKind> = lex_all(input).unwrap().into_iter().map(|t| t.kind).collect();
-            assert_eq!(kinds, vec![TokenKind::Number(-12.0), TokenKind::Eof]);
+            assert_eq!(kinds, vec![TokenKind::Number((-12.0).into()), TokenKind::Eof]);
         }
     }
 
     mod neg {
-        use super::{lex_all, LexerError};
+        use super::{lex_all, loc, LexerError};
         use super::super::NumberError;
 
         #[test]
@@ -373,7 +476,7 @@ mod numbers {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::InvalidNumber { at: 0, reason: NumberError::LeadingZero }
+                LexerError::InvalidNumber { at: loc(0, 1, 1), reason: NumberError::LeadingZero }
             );
         }
 
@@ -383,7 +486,7 @@ mod numbers {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::InvalidNumber { at: 2, reason: NumberError::MissingFracDigit }
+                LexerError::InvalidNumber { at: loc(2, 1, 3), reason: NumberError::MissingFracDigit }
             );
         }
 
@@ -393,7 +496,7 @@ mod numbers {
             let err = lex_all(input).unwrap_err();
             assert_eq!(
                 err,
-                LexerError::InvalidNumber { at: 2, reason: NumberError::MissingExpDigit }
+                LexerError::InvalidNumber { at: loc(2, 1, 3), reason: NumberError::MissingExpDigit }
             );
         }
 
@@ -404,7 +507,7 @@ mod numbers {
             assert_eq!(
                 err,
                 LexerError::UnexpectedByte {
-                    at: 0,
+                    at: loc(0, 1, 1),
                     found: b'+',
                     expected: "token"
                 }
@@ -414,7 +517,7 @@ mod numbers {
 }
 
 mod complex_exceptions {
-    use super::{lex_all, LexerError};
+    use super::{lex_all, loc, LexerError};
 
     #[test]
     fn unquoted_object_key_should_fail() {
@@ -423,10 +526,425 @@ mod complex_exceptions {
         assert_eq!(
             err,
             LexerError::UnexpectedByte {
-                at: 1,
+                at: loc(1, 1, 2),
                 found: b'a',
                 expected: "token"
             }
         );
     }
 }
+
+mod locations {
+    use super::{lex_all, loc, LexerError, StringError};
+
+    #[test]
+    fn spans_track_line_and_column() {
+        let input = "{\n  \"a\": [1,\n\ttrue]\n}".as_bytes();
+        let tokens = lex_all(input).expect("lex_all should succeed");
+
+        let starts: Vec<_> = tokens.iter().map(|t| (t.span.start.line, t.span.start.column)).collect();
+        assert_eq!(
+            starts,
+            vec![(1, 1), (2, 3), (2, 6), (2, 8), (2, 9), (2, 10), (3, 2), (3, 6), (4, 1), (4, 2)]
+        );
+
+        // "a" spans [4, 7) on line 2
+        assert_eq!(tokens[1].span.start, loc(4, 2, 3));
+        assert_eq!(tokens[1].span.end, loc(7, 2, 6));
+    }
+
+    #[test]
+    fn column_counts_chars_not_bytes() {
+        let input = "[\"héllo\", 1]".as_bytes();
+        let tokens = lex_all(input).expect("lex_all should succeed");
+
+        // "héllo" is 8 bytes but 7 chars
+        assert_eq!(tokens[1].span.end, loc(9, 1, 9));
+        assert_eq!(tokens[2].span.start, loc(9, 1, 9));
+        assert_eq!(tokens[3].span.start, loc(11, 1, 11));
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        let input = "[\n  \"ä\", \"abc\u{1}\"]".as_bytes();
+        let err = lex_all(input).unwrap_err();
+        assert_eq!(
+            err,
+            LexerError::InvalidString { at: loc(14, 2, 12), reason: StringError::ControlChar { found: 0x01 } }
+        );
+    }
+}
+
+mod limits {
+    use super::{lex_all_with_limits, loc, LexerError, Limit, ParseLimits, TokenKind};
+
+    #[test]
+    fn within_limits_lexes_normally() {
+        let limits = ParseLimits { max_string_bytes: 5, max_number_len: 3, ..ParseLimits::UNLIMITED };
+        let tokens = lex_all_with_limits(br#"["hello", 123]"#, &limits).expect("should lex within limits");
+        assert_eq!(tokens[1].kind, TokenKind::String("hello".into()));
+        assert_eq!(tokens[3].kind, TokenKind::Number(123.0.into()));
+    }
+
+    #[test]
+    fn lexer_reports_byte_limits() {
+        let limits = ParseLimits { max_string_bytes: 5, max_number_len: 3, max_document_bytes: 16, ..ParseLimits::UNLIMITED };
+        assert_eq!(
+            lex_all_with_limits(br#"["hello!"]"#, &limits),
+            Err(LexerError::LimitExceeded { limit: Limit::StringBytes, at: loc(1, 1, 2) })
+        );
+        assert_eq!(
+            lex_all_with_limits(b"[1234]", &limits),
+            Err(LexerError::LimitExceeded { limit: Limit::NumberLength, at: loc(1, 1, 2) })
+        );
+        assert_eq!(
+            lex_all_with_limits(b"[1, 2, 3, 4, 5, 6]", &limits),
+            Err(LexerError::LimitExceeded { limit: Limit::DocumentBytes, at: loc(16, 1, 17) })
+        );
+    }
+
+    #[test]
+    fn unterminated_long_string_stops_at_limit() {
+        let limits = ParseLimits { max_string_bytes: 10, ..ParseLimits::UNLIMITED };
+        let input = format!("\"{}", "a".repeat(1000));
+        assert_eq!(
+            lex_all_with_limits(input.as_bytes(), &limits),
+            Err(LexerError::LimitExceeded { limit: Limit::StringBytes, at: loc(0, 1, 1) })
+        );
+    }
+}
+
+mod iterator {
+    use super::{lex_all, loc, Cow, Lexer, LexerError, Limit, ParseLimits, StringError, TokenKind};
+
+    #[test]
+    fn tokens_arrive_before_a_later_error() {
+        let mut lexer = Lexer::new(br#"[1, "abc"#);
+        assert_eq!(lexer.next().map(|t| t.map(|t| t.kind)), Some(Ok(TokenKind::LBracket)));
+        assert_eq!(lexer.next().map(|t| t.map(|t| t.kind)), Some(Ok(TokenKind::Number(1.0.into()))));
+        assert_eq!(lexer.next().map(|t| t.map(|t| t.kind)), Some(Ok(TokenKind::Comma)));
+        assert_eq!(
+            lexer.next(),
+            Some(Err(LexerError::InvalidString { at: loc(8, 1, 9), reason: StringError::Unterminated }))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn ends_after_eof() {
+        let mut lexer = Lexer::new(b" null ");
+        assert_eq!(lexer.next().map(|t| t.map(|t| t.kind)), Some(Ok(TokenKind::Null)));
+        assert_eq!(lexer.next().map(|t| t.map(|t| t.kind)), Some(Ok(TokenKind::Eof)));
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn document_limit_is_checked_before_the_first_token() {
+        let limits = ParseLimits { max_document_bytes: 4, ..ParseLimits::UNLIMITED };
+        let mut lexer = Lexer::with_limits(b"[1, 2]", &limits);
+        assert_eq!(
+            lexer.next(),
+            Some(Err(LexerError::LimitExceeded { limit: Limit::DocumentBytes, at: loc(4, 1, 5) }))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn strings_without_escapes_are_borrowed() {
+        let mut lexer = Lexer::new(br#""plain" "esc\n""#);
+        assert!(matches!(lexer.next(), Some(Ok(t)) if matches!(t.kind, TokenKind::String(Cow::Borrowed("plain")))));
+        assert!(matches!(lexer.next(), Some(Ok(t)) if matches!(t.kind, TokenKind::String(Cow::Owned(_)))));
+    }
+
+    #[test]
+    fn lex_all_matches_the_iterator() {
+        let input = br#"{"a": [true, null, -1.5e3]}"#;
+        let collected: Vec<_> = Lexer::new(input).map(Result::unwrap).collect();
+        assert_eq!(lex_all(input).unwrap(), collected);
+    }
+}
+
+mod push {
+    use super::{lex_all, LexerError, PushLexer, Token};
+
+    fn drain(lexer: &mut PushLexer) -> Vec<Result<Token<'static>, LexerError>> {
+        std::iter::from_fn(|| lexer.next_token()).collect()
+    }
+
+    #[test]
+    fn token_split_across_chunks_matches_lexer() {
+        let input = "{\n  \"caf\u{e9}\": [12.5, \"a\\u0041\", false]\n}";
+        let expected: Vec<_> = lex_all(input.as_bytes()).unwrap().into_iter().map(Ok).collect();
+
+        for split in 0..=input.len() {
+            let mut lexer = PushLexer::new();
+            let (a, b) = input.as_bytes().split_at(split);
+            lexer.feed(a);
+            let mut tokens = drain(&mut lexer);
+            lexer.feed(b);
+            tokens.extend(drain(&mut lexer));
+            lexer.finish();
+            tokens.extend(drain(&mut lexer));
+            assert_eq!(tokens, expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn holds_back_what_may_continue() {
+        let mut lexer = PushLexer::new();
+        lexer.feed(b"[tr");
+        assert_eq!(drain(&mut lexer).len(), 1); // only '['
+        lexer.feed(b"ue, 4");
+        assert_eq!(drain(&mut lexer).len(), 2); // true ,
+        lexer.feed(b"2");
+        assert_eq!(drain(&mut lexer).len(), 0);
+        lexer.finish();
+        assert_eq!(drain(&mut lexer).len(), 2); // 42 Eof
+    }
+}
+
+mod json5 {
+    use super::{Cow, Dialect, Lexer, LexerError, ParseOptions, PushLexer, StringError, Token, TokenKind};
+
+    fn options() -> ParseOptions {
+        ParseOptions { dialect: Dialect::Json5, ..ParseOptions::default() }
+    }
+
+    fn lex(input: &str) -> Result<Vec<TokenKind<'_>>, LexerError> {
+        Lexer::with_options(input.as_bytes(), &options()).map(|t| t.map(|t| t.kind)).collect()
+    }
+
+    fn number(input: &str) -> String {
+        match lex(input).unwrap().remove(0) {
+            TokenKind::Number(n) => n.to_string(),
+            other => panic!("expected a number, got {:?}", other),
+        }
+    }
+
+    mod pos {
+        use super::super::loc;
+        use super::{lex, number, options, Cow, Lexer, PushLexer, Token, TokenKind};
+
+        #[test]
+        fn comments_and_whitespace() {
+            let input = "// line\n[ /* block\n */ 1,\u{A0}\u{FEFF}\x0B2 ] // end";
+            let kinds = lex(input).unwrap();
+            assert_eq!(kinds.len(), 6);
+            assert!(matches!(kinds[5], TokenKind::Eof));
+
+            let tokens: Vec<Token> = Lexer::with_options(input.as_bytes(), &options()).map(Result::unwrap).collect();
+            assert_eq!(tokens[1].span.start, loc(23, 3, 5)); // 1
+        }
+
+        #[test]
+        fn unquoted_keys_are_strings() {
+            let kinds = lex("{a: 1, $_b9 : 2, true: 3, Infinity: 4, \\u0063: 5, caf\u{e9}: 6}").unwrap();
+            let keys: Vec<&str> = kinds
+                .iter()
+                .filter_map(|k| match k {
+                    TokenKind::String(s) => Some(s.as_ref()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(keys, ["a", "$_b9", "true", "Infinity", "c", "caf\u{e9}"]);
+            assert!(matches!(kinds[1], TokenKind::String(Cow::Borrowed(_))));
+        }
+
+        #[test]
+        fn literals_where_no_colon_follows() {
+            let kinds = lex("[true, null, NaN, Infinity]").unwrap();
+            assert_eq!(kinds[1], TokenKind::Bool(true));
+            assert_eq!(kinds[3], TokenKind::Null);
+            assert!(matches!(&kinds[5], TokenKind::Number(n) if n.as_f64().is_nan()));
+            assert!(matches!(&kinds[7], TokenKind::Number(n) if n.as_f64() == f64::INFINITY));
+        }
+
+        #[test]
+        fn numbers_become_json_text() {
+            assert_eq!(number("0x1F"), "31");
+            assert_eq!(number("-0XfF"), "-255");
+            assert_eq!(number("0x123456789ABCDEF0123"), "5373003642731685151011");
+            assert_eq!(number("+1"), "1");
+            assert_eq!(number(".5"), "0.5");
+            assert_eq!(number("-5."), "-5.0");
+            assert_eq!(number("5.e3"), "5.0e3");
+            assert_eq!(number("-Infinity"), "null");
+        }
+
+        #[test]
+        fn many_signed_special_numbers() {
+            // each one used to rescan the input from the start
+            let input = format!("[{}0]", "-Infinity,\n+NaN, ".repeat(100_000));
+            let tokens: Vec<Token> = Lexer::with_options(input.as_bytes(), &options()).map(Result::unwrap).collect();
+            assert_eq!(tokens.len(), 4 * 100_000 + 4);
+            assert_eq!(tokens[tokens.len() - 5].span.start, loc(input.len() - 8, 100_001, 1));
+        }
+
+        #[test]
+        fn strings() {
+            let kinds = lex(r#"['it\'s "x"', '\v\0\x41\q', 'a\
+b', "'"]"#).unwrap();
+            assert_eq!(kinds[1], TokenKind::String("it's \"x\"".into()));
+            assert_eq!(kinds[3], TokenKind::String("\x0B\0Aq".into()));
+            assert_eq!(kinds[5], TokenKind::String("ab".into()));
+            assert_eq!(kinds[7], TokenKind::String("'".into()));
+        }
+
+        #[test]
+        fn push_split_anywhere_matches_lexer() {
+            let input = "{a /* c */ : 'x\\'y', true\n:0x1F, k: [+.5, -Infinity,], // end\n}";
+            let expected: Vec<_> = Lexer::with_options(input.as_bytes(), &options()).collect();
+
+            for split in 0..=input.len() {
+                let mut lexer = PushLexer::with_options(&options());
+                let (a, b) = input.as_bytes().split_at(split);
+                let mut tokens = Vec::new();
+                lexer.feed(a);
+                tokens.extend(std::iter::from_fn(|| lexer.next_token()));
+                lexer.feed(b);
+                tokens.extend(std::iter::from_fn(|| lexer.next_token()));
+                lexer.finish();
+                tokens.extend(std::iter::from_fn(|| lexer.next_token()));
+                assert_eq!(tokens, expected, "split at {}", split);
+            }
+        }
+    }
+
+    mod neg {
+        use super::super::{lex_all, loc};
+        use super::{lex, LexerError, StringError};
+
+        #[test]
+        fn identifiers_are_only_keys() {
+            assert_eq!(
+                lex("[1, yes]"),
+                Err(LexerError::InvalidLiteral { at: loc(4, 1, 5), expected: "true, false, null, Infinity or NaN" })
+            );
+            // an escaped literal is an identifier, not the literal
+            assert!(matches!(lex("\\u0074rue"), Err(LexerError::InvalidLiteral { .. })));
+        }
+
+        #[test]
+        fn unterminated_comment() {
+            assert_eq!(lex("[1 /* no end"), Err(LexerError::UnterminatedBlockComment { at: loc(3, 1, 4) }));
+            assert!(matches!(lex("1 / 2"), Err(LexerError::UnexpectedByte { found: b' ', .. })));
+        }
+
+        #[test]
+        fn invalid_escapes_and_line_breaks() {
+            assert!(matches!(
+                lex(r"'\1'"),
+                Err(LexerError::InvalidString { reason: StringError::InvalidEscape { found: b'1' }, .. })
+            ));
+            assert!(matches!(
+                lex(r"'\xG0'"),
+                Err(LexerError::InvalidString { reason: StringError::InvalidEscape { found: b'x' }, .. })
+            ));
+            assert!(matches!(
+                lex("'a\nb'"),
+                Err(LexerError::InvalidString { reason: StringError::ControlChar { found: b'\n' }, .. })
+            ));
+        }
+
+        #[test]
+        fn numbers() {
+            assert!(matches!(lex("0x"), Err(LexerError::InvalidNumber { .. })));
+            assert!(matches!(lex("01"), Err(LexerError::InvalidNumber { .. })));
+            assert!(matches!(lex("."), Err(LexerError::InvalidNumber { .. })));
+        }
+
+        #[test]
+        fn strict_is_the_default() {
+            for input in ["// c\n1", "'a'", "{a: 1}", "0x1", "+1", ".5", "Infinity"] {
+                assert!(lex_all(input.as_bytes()).is_err(), "{}", input);
+            }
+        }
+    }
+}
+
+mod comments {
+    use super::{loc, CommentPolicy, Lexer, LexerError, ParseOptions, PushLexer, Token, TokenKind};
+
+    fn options(comments: CommentPolicy) -> ParseOptions {
+        ParseOptions { comments, ..ParseOptions::default() }
+    }
+
+    fn lex(input: &str, comments: CommentPolicy) -> Result<Vec<Token<'_>>, LexerError> {
+        Lexer::with_options(input.as_bytes(), &options(comments)).collect()
+    }
+
+    mod pos {
+        use super::super::loc;
+        use super::{lex, options, CommentPolicy, Lexer, PushLexer, TokenKind};
+
+        #[test]
+        fn skipped_like_whitespace() {
+            let tokens = lex("// settings\n{\"a\": /* one */ 1 /**/} // end", CommentPolicy::Skip).unwrap();
+            let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    TokenKind::LBrace,
+                    TokenKind::String("a".into()),
+                    TokenKind::Colon,
+                    TokenKind::Number(1.into()),
+                    TokenKind::RBrace,
+                    TokenKind::Eof,
+                ]
+            );
+            assert_eq!(tokens[3].span.start, loc(28, 2, 17));
+        }
+
+        #[test]
+        fn returned_as_tokens() {
+            let tokens = lex("[1, // one\n/* two\n */ 2]", CommentPolicy::Tokens).unwrap();
+            assert_eq!(tokens[3].kind, TokenKind::Comment("// one".into()));
+            assert_eq!((tokens[3].span.start, tokens[3].span.end), (loc(4, 1, 5), loc(10, 1, 11)));
+            assert_eq!(tokens[4].kind, TokenKind::Comment("/* two\n */".into()));
+            assert_eq!((tokens[4].span.start, tokens[4].span.end), (loc(11, 2, 1), loc(21, 3, 4)));
+            assert_eq!(tokens[5].kind, TokenKind::Number(2.into()));
+        }
+
+        #[test]
+        fn push_split_anywhere_matches_lexer() {
+            let input = "[1, // one\n/* two */ 2 /**/] //";
+            let expected: Vec<_> = Lexer::with_options(input.as_bytes(), &options(CommentPolicy::Tokens)).collect();
+
+            for split in 0..=input.len() {
+                let mut lexer = PushLexer::with_options(&options(CommentPolicy::Tokens));
+                let (a, b) = input.as_bytes().split_at(split);
+                let mut tokens = Vec::new();
+                lexer.feed(a);
+                tokens.extend(std::iter::from_fn(|| lexer.next_token()));
+                lexer.feed(b);
+                tokens.extend(std::iter::from_fn(|| lexer.next_token()));
+                lexer.finish();
+                tokens.extend(std::iter::from_fn(|| lexer.next_token()));
+                assert_eq!(tokens, expected, "split at {}", split);
+            }
+        }
+    }
+
+    mod neg {
+        use super::{lex, loc, CommentPolicy, LexerError};
+
+        #[test]
+        fn unterminated_block_comment() {
+            for comments in [CommentPolicy::Skip, CommentPolicy::Tokens] {
+                assert_eq!(
+                    lex("[1, /* two ]", comments),
+                    Err(LexerError::UnterminatedBlockComment { at: loc(4, 1, 5) })
+                );
+            }
+        }
+
+        #[test]
+        fn errors_by_default() {
+            assert!(matches!(
+                lex("[1] // end", CommentPolicy::Error),
+                Err(LexerError::UnexpectedByte { found: b'/', .. })
+            ));
+            assert!(matches!(lex("[1 / 2]", CommentPolicy::Skip), Err(LexerError::UnexpectedByte { found: b' ', .. })));
+        }
+    }
+}
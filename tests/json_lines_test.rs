@@ -0,0 +1,148 @@
+use json_parser_rust::json;
+use json_parser_rust::json_definitions::{JsonParsingErrorV3, JsonValue, Limit, ParseLimits, ParseOptions};
+use json_parser_rust::json_diagnostics::{render_error, ColorMode};
+use json_parser_rust::json_lines::{
+    from_json_lines_str, to_json_lines_string, JsonLinesError, JsonLinesOptions, JsonLinesReader, JsonLinesWriter,
+};
+
+fn read(input: &str, options: &JsonLinesOptions) -> Vec<Result<JsonValue, JsonLinesError>> {
+    JsonLinesReader::with_options(input.as_bytes(), options).collect()
+}
+
+mod reader {
+    mod pos {
+        use super::super::{from_json_lines_str, json, read, JsonLinesOptions, JsonLinesReader};
+
+        #[test]
+        fn one_value_per_line() {
+            let values = from_json_lines_str("{\"a\": 1}\n[true, null]\n\"x\"\n").unwrap();
+            assert_eq!(values, [json!({"a": 1}), json!([true, null]), json!("x")]);
+        }
+
+        #[test]
+        fn crlf_and_missing_final_newline() {
+            let values = from_json_lines_str("1\r\n  2  \r\n3").unwrap();
+            assert_eq!(values, [json!(1), json!(2), json!(3)]);
+            assert_eq!(from_json_lines_str("").unwrap(), []);
+        }
+
+        #[test]
+        fn skip_blank_lines() {
+            let options = JsonLinesOptions { skip_blank_lines: true, ..Default::default() };
+            let values: Vec<_> = read("\n1\n \t\r\n\n2\n\n", &options).into_iter().map(Result::unwrap).collect();
+            assert_eq!(values, [json!(1), json!(2)]);
+        }
+
+        #[test]
+        fn continue_past_bad_records() {
+            let options = JsonLinesOptions { continue_on_error: true, ..Default::default() };
+            let records = read("1\n[1,\n3\n{}\n", &options);
+            assert_eq!(records.len(), 4);
+            assert!(records[1].is_err());
+            assert_eq!(records[2].as_ref().unwrap(), &json!(3));
+            assert_eq!(records[3].as_ref().unwrap(), &json!({}));
+        }
+
+        #[test]
+        fn reads_from_any_buf_read() {
+            let reader = std::io::BufReader::with_capacity(3, &b"[\"long line\"]\n{\"k\": 1}\n"[..]);
+            let values: Vec<_> = JsonLinesReader::new(reader).map(Result::unwrap).collect();
+            assert_eq!(values, [json!(["long line"]), json!({"k": 1})]);
+        }
+    }
+
+    mod neg {
+        use super::super::{
+            from_json_lines_str, read, render_error, ColorMode, JsonLinesError, JsonLinesOptions, JsonLinesReader,
+            JsonParsingErrorV3, JsonValue, Limit, ParseLimits, ParseOptions,
+        };
+        use json_parser_rust::json_non_recursive::process_json_string_v3_with_options;
+        use std::io::{self, BufReader, Read};
+
+        #[test]
+        fn first_error_ends_the_iteration() {
+            let records = read("1\n2 3\n4\n", &JsonLinesOptions::default());
+            assert_eq!(records.len(), 2);
+            let Err(JsonLinesError::Parse { line_start, error }) = &records[1] else {
+                panic!("expected a parse error, got {:?}", records[1]);
+            };
+            assert_eq!((line_start.line, line_start.offset), (2, 2));
+            assert!(matches!(**error, JsonParsingErrorV3::ExpectedEOF { .. }));
+        }
+
+        #[test]
+        fn errors_are_located_in_the_whole_input() {
+            let input = "{\"a\": 1}\n{\"b\" 2}\n";
+            let err = from_json_lines_str(input).unwrap_err();
+            assert_eq!(err.to_string(), "expected ':' after object key, found number at line 2, column 6");
+            let report = render_error(&err, input, ColorMode::Plain);
+            assert!(report.contains("2 | {\"b\" 2}"), "{}", report);
+        }
+
+        #[test]
+        fn blank_lines_are_errors_by_default() {
+            let err = from_json_lines_str("1\n\n2\n").unwrap_err();
+            assert!(matches!(err, JsonLinesError::BlankLine { at } if at.line == 2));
+        }
+
+        #[test]
+        fn invalid_utf8() {
+            let records: Vec<_> = JsonLinesReader::new(&b"1\n\"a\xFF\"\n"[..]).collect();
+            assert!(matches!(records[1], Err(JsonLinesError::InvalidUtf8 { at }) if at.offset == 4 && at.column == 3));
+        }
+
+        #[test]
+        fn limits_apply_per_line() {
+            let limits = ParseLimits { max_document_bytes: 4, ..ParseLimits::UNLIMITED };
+            let options = JsonLinesOptions { parse: ParseOptions { limits, ..Default::default() }, ..Default::default() };
+            let records = read("1234\n1234\n12345\n", &options);
+            assert!(records[0].is_ok() && records[1].is_ok());
+            let Err(JsonLinesError::Parse { error, .. }) = &records[2] else {
+                panic!("expected a parse error, got {:?}", records[2]);
+            };
+            assert!(matches!(**error, JsonParsingErrorV3::LimitExceeded { limit: Limit::DocumentBytes, .. }));
+            assert_eq!(Err(*error.clone()), process_json_string_v3_with_options("12345", &options.parse));
+        }
+
+        #[test]
+        fn long_lines_are_not_buffered() {
+            let limits = ParseLimits { max_document_bytes: 4, ..ParseLimits::UNLIMITED };
+            let parse = ParseOptions { limits, ..Default::default() };
+
+            // a terabyte without a line break
+            let endless = BufReader::new(io::repeat(b'1').take(1 << 40));
+            let mut reader = JsonLinesReader::with_options(endless, &JsonLinesOptions { parse, ..Default::default() });
+            assert!(matches!(reader.next(), Some(Err(JsonLinesError::Parse { .. }))));
+            assert!(reader.next().is_none());
+
+            let options = JsonLinesOptions { parse, continue_on_error: true, ..Default::default() };
+            let records = read(&format!("{}\n5\n", "9".repeat(100_000)), &options);
+            let Err(JsonLinesError::Parse { error, .. }) = &records[0] else {
+                panic!("expected a parse error, got {:?}", records[0]);
+            };
+            assert!(matches!(**error, JsonParsingErrorV3::LimitExceeded { limit: Limit::DocumentBytes, .. }));
+            assert_eq!(records[1].as_ref().unwrap(), &JsonValue::from(5));
+            assert_eq!(records.len(), 2);
+        }
+    }
+}
+
+mod writer {
+    use super::{from_json_lines_str, json, to_json_lines_string, JsonLinesWriter};
+
+    #[test]
+    fn one_compact_document_per_line() {
+        let values = [json!({"a": [1, 2], "text": "line\nbreak"}), json!(null), json!([])];
+        let text = to_json_lines_string(&values);
+        assert_eq!(text, "{\"a\":[1,2],\"text\":\"line\\nbreak\"}\nnull\n[]\n");
+        assert_eq!(from_json_lines_str(&text).unwrap(), values);
+    }
+
+    #[test]
+    fn writes_to_any_writer() {
+        let mut writer = JsonLinesWriter::new(Vec::new());
+        writer.write(&json!(1)).unwrap();
+        writer.write_all(&[json!("a"), json!(true)]).unwrap();
+        assert_eq!(writer.into_inner(), b"1\n\"a\"\ntrue\n");
+    }
+}
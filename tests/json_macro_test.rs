@@ -0,0 +1,115 @@
+use std::collections::{BTreeMap, HashMap};
+
+use indexmap::IndexMap;
+use json_parser_rust::json;
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_writer::to_string;
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+mod literals {
+    use super::{json, parse, to_string, JsonValue};
+
+    #[test]
+    fn scalars() {
+        assert_eq!(json!(null), JsonValue::Null);
+        assert_eq!(json!(true), JsonValue::Boolean(true));
+        assert_eq!(json!(false), JsonValue::Boolean(false));
+        assert_eq!(json!(12), parse("12"));
+        assert_eq!(json!(-1.5), parse("-1.5"));
+        assert_eq!(json!("text"), parse(r#""text""#));
+    }
+
+    #[test]
+    fn nested_containers() {
+        let value = json!({
+            "a": [1, true, null, "x", [], {}],
+            "b": {"c": {"d": [false, [null]]}},
+            "": -0.5,
+        });
+        assert_eq!(value, parse(r#"{"a": [1, true, null, "x", [], {}], "b": {"c": {"d": [false, [null]]}}, "": -0.5}"#));
+    }
+
+    #[test]
+    fn members_keep_their_order_and_the_last_duplicate_wins() {
+        let value = json!({"z": 1, "a": 2, "m": 3, "a": 4});
+        assert_eq!(to_string(&value), r#"{"z":1,"a":4,"m":3}"#);
+    }
+
+    #[test]
+    fn trailing_commas() {
+        assert_eq!(json!([1, 2,]), parse("[1, 2]"));
+        assert_eq!(json!({"a": [null,], "b": {},}), parse(r#"{"a": [null], "b": {}}"#));
+    }
+}
+
+mod interpolation {
+    use super::{json, parse, BTreeMap, HashMap, IndexMap};
+
+    #[test]
+    fn expressions() {
+        let port = 8080;
+        let name = String::from("server");
+        let value = json!({
+            "name": name,
+            "ports": [port, port + 1],
+            "ratio": 3.0 / 4.0,
+            "upper": "abc".to_uppercase(),
+            "nested": json!([1, 2]),
+        });
+        assert_eq!(
+            value,
+            parse(r#"{"name": "server", "ports": [8080, 8081], "ratio": 0.75, "upper": "ABC", "nested": [1, 2]}"#)
+        );
+    }
+
+    #[test]
+    fn computed_keys() {
+        let key = "dynamic";
+        let prefix = String::from("x-");
+        let value = json!({key: 1, (prefix.clone() + "id"): 2, format!("{}{}", prefix, 3): 3});
+        assert_eq!(value, parse(r#"{"dynamic": 1, "x-id": 2, "x-3": 3}"#));
+    }
+
+    #[test]
+    fn options_vectors_and_slices() {
+        let some: Option<u8> = Some(3);
+        let none: Option<&str> = None;
+        let list = vec![Some(1.5), None];
+        let slice: &[bool] = &[true, false];
+        assert_eq!(json!([some, none, list, slice]), parse("[3, null, [1.5, null], [true, false]]"));
+    }
+
+    #[test]
+    fn maps() {
+        let mut ordered = IndexMap::new();
+        ordered.insert("b", 1);
+        ordered.insert("a", 2);
+        let sorted: BTreeMap<String, Vec<i64>> = [("y".to_string(), vec![1]), ("x".to_string(), vec![])].into();
+        let hashed: HashMap<&str, bool> = [("k", true)].into();
+        assert_eq!(
+            json!({"ordered": ordered, "sorted": sorted, "hashed": hashed}),
+            parse(r#"{"ordered": {"b": 1, "a": 2}, "sorted": {"x": [], "y": [1]}, "hashed": {"k": true}}"#)
+        );
+    }
+}
+
+mod conversions {
+    use super::{parse, JsonValue};
+
+    #[test]
+    fn from_rust_values() {
+        assert_eq!(JsonValue::from(true), JsonValue::Boolean(true));
+        assert_eq!(JsonValue::from(u64::MAX), parse("18446744073709551615"));
+        assert_eq!(JsonValue::from(i128::MIN), parse("-170141183460469231731687303715884105728"));
+        assert_eq!(JsonValue::from(0.1f32), parse("0.1"));
+        assert_eq!(JsonValue::from(&String::from("s")), parse(r#""s""#));
+        assert_eq!(JsonValue::from(None::<i32>), JsonValue::Null);
+        assert_eq!(JsonValue::from(vec!["a", "b"]), parse(r#"["a", "b"]"#));
+        let value: JsonValue = 5u8.into();
+        assert_eq!(value, parse("5"));
+    }
+}
@@ -0,0 +1,114 @@
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_merge_patch::generate_merge_patch;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_writer::to_string;
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+fn merged(target: &str, patch: &str) -> JsonValue {
+    let mut value = parse(target);
+    value.merge_patch(&parse(patch));
+    value
+}
+
+mod apply {
+    use super::{merged, parse, to_string};
+
+    // RFC 7386, appendix A
+    #[test]
+    fn rfc_examples() {
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+        for (target, patch, expected) in cases {
+            assert_eq!(merged(target, patch), parse(expected), "target {} patch {}", target, patch);
+            // member order as well, not just map equality
+            assert_eq!(to_string(&merged(target, patch)), to_string(&parse(expected)));
+        }
+    }
+
+    #[test]
+    fn existing_members_keep_their_position() {
+        let result = merged(r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#, r#"{"new": 0, "c": 30, "b": null, "a": {"x": 1}}"#);
+        assert_eq!(to_string(&result), r#"{"a":{"x":1},"c":30,"d":4,"new":0}"#);
+    }
+
+    #[test]
+    fn deep_patch() {
+        let depth = 100_000;
+        let target = format!("{}1{}", r#"{"a":"#.repeat(depth), "}".repeat(depth));
+        let patch = format!("{}{{\"b\":2}}{}", r#"{"a":"#.repeat(depth - 1), "}".repeat(depth - 1));
+        let expected = format!("{}{{\"a\":1,\"b\":2}}{}", r#"{"a":"#.repeat(depth - 1), "}".repeat(depth - 1));
+        assert_eq!(merged(&target, &patch), parse(&expected));
+    }
+}
+
+mod generate {
+    use super::{generate_merge_patch, merged, parse, to_string, JsonValue};
+
+    fn assert_round_trip(from: &str, to: &str) {
+        let patch = generate_merge_patch(&parse(from), &parse(to));
+        assert_eq!(merged(from, &to_string(&patch)), parse(to), "from {} to {}", from, to);
+    }
+
+    #[test]
+    fn minimal_patch() {
+        let from = parse(r#"{"title": "Hello", "author": {"name": "A", "email": "a@x"}, "tags": ["x"], "same": {"k": 1}}"#);
+        let to = parse(r#"{"title": "Bye", "author": {"name": "A"}, "tags": ["x", "y"], "same": {"k": 1.0}, "new": 1}"#);
+        assert_eq!(
+            to_string(&generate_merge_patch(&from, &to)),
+            r#"{"title":"Bye","author":{"email":null},"tags":["x","y"],"new":1}"#
+        );
+    }
+
+    #[test]
+    fn equal_values_give_an_empty_patch() {
+        let doc = parse(r#"{"a": [1, 2], "b": {"c": null}}"#);
+        assert_eq!(generate_merge_patch(&doc, &doc.clone()), parse("{}"));
+    }
+
+    #[test]
+    fn non_objects_are_replaced() {
+        assert_eq!(generate_merge_patch(&parse("[1]"), &parse("[2]")), parse("[2]"));
+        assert_eq!(generate_merge_patch(&parse(r#"{"a": 1}"#), &parse("3")), parse("3"));
+        assert_eq!(generate_merge_patch(&parse("3"), &parse(r#"{"a": 1}"#)), parse(r#"{"a": 1}"#));
+        assert_eq!(generate_merge_patch(&parse("1"), &parse("null")), JsonValue::Null);
+    }
+
+    #[test]
+    fn generated_patches_apply() {
+        let cases = [
+            (r#"{"a": {"b": {"c": 1, "d": 2}}, "e": 3}"#, r#"{"a": {"b": {"c": 5}}, "f": [1]}"#),
+            (r#"{"a": [1, {"b": 2}]}"#, r#"{"a": [1, {"b": 3}]}"#),
+            (r#"{"a": "x"}"#, r#"{"a": {"nested": true}}"#),
+            (r#"{"a": {"nested": true}}"#, r#"{"a": "x"}"#),
+        ];
+        for (from, to) in cases {
+            assert_round_trip(from, to);
+        }
+    }
+
+    #[test]
+    fn deep_documents() {
+        let depth = 100_000;
+        let from = format!("{}1{}", r#"{"a":"#.repeat(depth), "}".repeat(depth));
+        let to = format!("{}2{}", r#"{"a":"#.repeat(depth), "}".repeat(depth));
+        assert_eq!(generate_merge_patch(&parse(&from), &parse(&to)), parse(&to));
+    }
+}
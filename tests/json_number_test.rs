@@ -0,0 +1,126 @@
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_lexer::{lex_all, TokenKind};
+use json_parser_rust::json_lexer_parser::process_json_string_v2;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_number::JsonNumber;
+use json_parser_rust::json_parsing_naive::process_json_string_v1;
+use json_parser_rust::json_writer::to_string;
+
+fn lex_number(input: &str) -> JsonNumber {
+    let tokens = lex_all(input.as_bytes()).expect("number should lex");
+    match &tokens[0].kind {
+        TokenKind::Number(n) => n.clone(),
+        other => panic!("expected a number token, got {:?} for input: {:?}", other, input),
+    }
+}
+
+mod accessors {
+    use super::{lex_number, JsonNumber};
+
+    #[test]
+    fn integers_beyond_f64_precision() {
+        let n = lex_number("9007199254740993");
+        assert_eq!(n.as_i64(), Some(9007199254740993));
+        assert_eq!(n.as_u64(), Some(9007199254740993));
+        assert_eq!(n.as_f64(), 9007199254740992.0);
+
+        assert_eq!(lex_number("18446744073709551615").as_u64(), Some(u64::MAX));
+        assert_eq!(lex_number("18446744073709551615").as_i64(), None);
+        assert_eq!(lex_number("-9223372036854775808").as_i64(), Some(i64::MIN));
+        assert_eq!(lex_number("-170141183460469231731687303715884105728").as_i128(), Some(i128::MIN));
+    }
+
+    #[test]
+    fn integer_accessors_reject_fractions_and_exponents() {
+        for input in ["1.0", "1e2", "1E2", "-0.5"] {
+            let n = lex_number(input);
+            assert!(!n.is_integer(), "input was: {:?}", input);
+            assert_eq!(n.as_i64(), None, "input was: {:?}", input);
+            assert_eq!(n.as_u64(), None, "input was: {:?}", input);
+        }
+        assert_eq!(lex_number("-1").as_u64(), None);
+        assert_eq!(lex_number("-0").as_i64(), Some(0));
+    }
+
+    #[test]
+    fn exact_text_is_kept() {
+        for input in ["0.10", "1E+3", "-0", "123456789012345678901234567890", "2.50e-10"] {
+            assert_eq!(lex_number(input).as_str(), Some(input));
+        }
+    }
+
+    #[test]
+    fn built_from_rust_values() {
+        assert_eq!(JsonNumber::from(u64::MAX).as_str(), Some("18446744073709551615"));
+        assert_eq!(JsonNumber::from(-7i32).as_i64(), Some(-7));
+        assert_eq!(JsonNumber::from(0.1).as_str(), Some("0.1"));
+        assert_eq!(JsonNumber::from(0.1f32).as_str(), Some("0.1"));
+        assert_eq!(JsonNumber::from(1e-7f32).as_str(), Some("1e-7"));
+        assert!(JsonNumber::from(f32::NEG_INFINITY).as_f64().is_infinite());
+        assert_eq!(JsonNumber::from(f64::NAN).as_str(), None);
+        assert!(JsonNumber::from(f64::INFINITY).as_f64().is_infinite());
+    }
+}
+
+mod equality {
+    use super::{lex_number, JsonNumber};
+
+    #[test]
+    fn compares_numerically() {
+        assert_eq!(lex_number("1"), lex_number("1.0"));
+        assert_eq!(lex_number("1e3"), JsonNumber::from(1000));
+        assert_eq!(lex_number("-0"), lex_number("0"));
+        assert_eq!(lex_number("0.1"), JsonNumber::from(0.1));
+        assert_ne!(JsonNumber::from(f64::NAN), JsonNumber::from(f64::NAN));
+    }
+
+    #[test]
+    fn large_integers_compare_exactly() {
+        assert_ne!(lex_number("9007199254740993"), lex_number("9007199254740992"));
+        assert_eq!(lex_number("9007199254740993"), JsonNumber::from(9007199254740993u64));
+    }
+
+    #[test]
+    fn equality_is_transitive() {
+        let a = lex_number("9007199254740993");
+        let b = lex_number("9007199254740992.0");
+        let c = lex_number("9007199254740992");
+        assert_ne!(a, b);
+        assert_eq!(b, c);
+        assert_ne!(a, c);
+        assert_eq!(lex_number("12.50e-1"), lex_number("0.00125E3"));
+        assert_eq!(lex_number("-1200"), lex_number("-1.2e+3"));
+    }
+}
+
+mod parsers {
+    use super::{process_json_string_v1, process_json_string_v2, process_json_string_v3, to_string, JsonValue};
+
+    const ID: &str = "12345678901234567890";
+
+    fn id_of(value: JsonValue) -> Option<u64> {
+        match &value {
+            JsonValue::Array(items) => match items.first() {
+                Some(JsonValue::Number(n)) => n.as_u64(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn every_parser_keeps_large_ids() {
+        let input = format!("[{}]", ID);
+        let expected = ID.parse::<u64>().ok();
+        assert_eq!(id_of(process_json_string_v1(&input).unwrap()), expected);
+        assert_eq!(id_of(process_json_string_v2(&input).unwrap()), expected);
+        assert_eq!(id_of(process_json_string_v3(&input).unwrap()), expected);
+    }
+
+    #[test]
+    fn writer_reproduces_the_lexeme() {
+        let input = format!(r#"{{"id":{},"ratio":0.10,"tiny":2.50E-10}}"#, ID);
+        let value = process_json_string_v3(&input).unwrap();
+        assert_eq!(to_string(&value), input);
+    }
+}
@@ -1,8 +1,9 @@
 use indexmap::IndexMap;
 
-use json_parser_rust::json_definitions::{JsonParsingError, JsonValue};
-use json_parser_rust::json_parsing_naive::process_json_string_v1;
+use json_parser_rust::json_definitions::{JsonParsingError, JsonValue, LoneSurrogatePolicy, ParseOptions};
+use json_parser_rust::json_parsing_naive::{process_json_string_v1, process_json_string_v1_with_options};
 
+#[allow(dead_code)]
 fn char_from_hex(s: &str) -> Option<char> {
     let hex = s.trim_start_matches("0x");
     u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
@@ -90,26 +91,26 @@ mod numbers {
         fn parse_numbers_ok() {
             let cases = [
                 // integers
-                ("-1", JsonValue::Number(-1.0)),
-                ("1", JsonValue::Number(1.0)),
-                ("0", JsonValue::Number(0.0)),
-                ("124453", JsonValue::Number(124453.0)),
-                ("-0", JsonValue::Number(0.0)),
-                ("   42 \n", JsonValue::Number(42.0)),
-                ("   4 \n   ", JsonValue::Number(4.0)),
-                ("0.00000e+00000", JsonValue::Number(0.0)),
+                ("-1", JsonValue::Number((-1.0).into())),
+                ("1", JsonValue::Number(1.0.into())),
+                ("0", JsonValue::Number(0.0.into())),
+                ("124453", JsonValue::Number(124453.0.into())),
+                ("-0", JsonValue::Number(0.0.into())),
+                ("   42 \n", JsonValue::Number(42.0.into())),
+                ("   4 \n   ", JsonValue::Number(4.0.into())),
+                ("0.00000e+00000", JsonValue::Number(0.0.into())),
                 // fractions
-                ("1.0", JsonValue::Number(1.0)),
-                ("0.435", JsonValue::Number(0.435)),
-                ("-12.5", JsonValue::Number(-12.5)),
+                ("1.0", JsonValue::Number(1.0.into())),
+                ("0.435", JsonValue::Number(0.435.into())),
+                ("-12.5", JsonValue::Number((-12.5).into())),
                 // exponent
-                ("1e3", JsonValue::Number(1000.0)),
-                ("1E3", JsonValue::Number(1000.0)),
-                ("1e-3", JsonValue::Number(0.001)),
-                ("1e+3", JsonValue::Number(1000.0)),
-                ("1.2e3", JsonValue::Number(1200.0)),
-                ("-1.2E3", JsonValue::Number(-1200.0)),
-                ("-1.2e00000003", JsonValue::Number(-1200.0)),
+                ("1e3", JsonValue::Number(1000.0.into())),
+                ("1E3", JsonValue::Number(1000.0.into())),
+                ("1e-3", JsonValue::Number(0.001.into())),
+                ("1e+3", JsonValue::Number(1000.0.into())),
+                ("1.2e3", JsonValue::Number(1200.0.into())),
+                ("-1.2E3", JsonValue::Number((-1200.0).into())),
+                ("-1.2e00000003", JsonValue::Number((-1200.0).into())),
             ];
 
             for (case, expected) in cases {
@@ -162,11 +163,16 @@ mod numbers {
 
 mod strings {
     use super::{
-        assert_err_eq, assert_ok_eq, char_from_hex, JsonParsingError, JsonValue, process_json_string_v1,
+        assert_err_eq, assert_ok_eq, char_from_hex, JsonParsingError, JsonValue, LoneSurrogatePolicy, ParseOptions,
+        process_json_string_v1, process_json_string_v1_with_options,
     };
 
     mod pos {
-        use super::{assert_ok_eq, char_from_hex, JsonValue};
+        #[allow(unused_imports)]
+        use super::{
+            assert_ok_eq, char_from_hex, JsonValue, LoneSurrogatePolicy, ParseOptions,
+            process_json_string_v1_with_options,
+        };
 
         #[test]
         fn simple_strinng() {
@@ -236,6 +242,39 @@ mod strings {
             }
         }
 
+        #[test]
+        fn unicode_escape_surrogate_pair_ok() {
+            let cases = [
+                (r#""\uD83D\uDE00""#, JsonValue::JsonString("\u{1F600}".to_string())),
+                (r#""x\ud834\udd1ey""#, JsonValue::JsonString("x\u{1D11E}y".to_string())),
+                (r#""\uDBFF\uDFFF""#, JsonValue::JsonString("\u{10FFFF}".to_string())),
+            ];
+
+            for (case, expected) in cases {
+                assert_ok_eq(case, expected);
+            }
+        }
+
+        #[test]
+        fn unicode_escape_lone_surrogate_replaced() {
+            let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Replace, ..ParseOptions::default() };
+            let cases = [
+                (r#""\uD800""#, "\u{FFFD}"),
+                (r#""\uDE00""#, "\u{FFFD}"),
+                (r#""\u0041\uD800\u0043""#, "A\u{FFFD}C"),
+                (r#""\uD83D\uD83D\uDE00""#, "\u{FFFD}\u{1F600}"),
+            ];
+
+            for (case, expected) in cases {
+                assert_eq!(
+                    process_json_string_v1_with_options(case, &options),
+                    Ok(JsonValue::JsonString(expected.to_string())),
+                    "input was: {:?}",
+                    case
+                );
+            }
+        }
+
         #[test]
         fn unicode_escape_multiple_in_one_string_ok() {
             let cases = [
@@ -263,6 +302,7 @@ mod strings {
     }
 
     mod neg {
+        #[allow(unused_imports)]
         use super::{assert_err_eq, char_from_hex, JsonParsingError, JsonValue, process_json_string_v1};
 
         #[test]
@@ -291,6 +331,8 @@ mod strings {
                 r#""\uD800""#,    // surrogate range (high)
                 r#""\uDFFF""#,    // surrogate range (low)
                 r#""\uDE00""#,    // surrogate range (low)
+                r#""\uD83Dx""#,   // high surrogate without a low one
+                r#""\uD83D\uD83D""#, // high surrogate followed by another high
             ];
 
             for case in cases {
@@ -302,7 +344,7 @@ mod strings {
         fn unicode_escape_multiple_in_one_string_fail() {
             let cases = [
                 r#""\u0041\u12G4\u0043""#, // bad hex in the middle
-                r#""\u0041\uD800\u0043""#, // surrogate in the middle (your v1 rule: error)
+                r#""\u0041\uD800\u0043""#, // lone surrogate in the middle
                 r#""\u0041\u0042\u000""#,  // truncated at end
             ];
 
@@ -335,16 +377,16 @@ mod arrays {
                 // single element
                 (
                     r#"[10]"#,
-                    JsonValue::Array(vec![JsonValue::Number(10.0)]),
+                    JsonValue::Array(vec![JsonValue::Number(10.0.into())]),
                 ),
                 // multiple ints + whitespace
                 (
                     r#"[10, 15, 0, -10]"#,
                     JsonValue::Array(vec![
-                        JsonValue::Number(10.0),
-                        JsonValue::Number(15.0),
-                        JsonValue::Number(0.0),
-                        JsonValue::Number(-10.0),
+                        JsonValue::Number(10.0.into()),
+                        JsonValue::Number(15.0.into()),
+                        JsonValue::Number(0.0.into()),
+                        JsonValue::Number((-10.0).into()),
                     ]),
                 ),
                 // nested empty arrays
@@ -416,18 +458,19 @@ mod objects {
         use super::{assert_ok_eq, IndexMap, JsonValue};
 
         #[test]
+        #[allow(clippy::vec_init_then_push)]
         fn object_simple_ok() {
             // {}
             let expected_empty = JsonValue::Object(IndexMap::new());
 
             // {"a":1}
             let mut m1 = IndexMap::new();
-            m1.insert("a".to_string(), JsonValue::Number(1.0));
+            m1.insert("a".to_string(), JsonValue::Number(1.0.into()));
             let expected_one = JsonValue::Object(m1);
 
             // {"a":1,"b":true,"c":null,"d":"hé","e":[1,2]}
             let mut m2 = IndexMap::new();
-            m2.insert("a".to_string(), JsonValue::Number(1.0));
+            m2.insert("a".to_string(), JsonValue::Number(1.0.into()));
             m2.insert("b".to_string(), JsonValue::Boolean(true));
             m2.insert("c".to_string(), JsonValue::Null);
             m2.insert("d".to_string(), JsonValue::JsonString("hé".to_string()));
@@ -435,8 +478,8 @@ mod objects {
                 "e".to_string(),
                 JsonValue::Array({
                     let mut v = Vec::new();
-                    v.push(JsonValue::Number(1.0));
-                    v.push(JsonValue::Number(2.0));
+                    v.push(JsonValue::Number(1.0.into()));
+                    v.push(JsonValue::Number(2.0.into()));
                     v
                 }),
             );
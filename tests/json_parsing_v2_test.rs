@@ -1,8 +1,9 @@
-use indexmap::IndexMap;
-
-use json_parser_rust::json_definitions::{JsonParsingErrorV2, JsonValue, LexerError};
+use json_parser_rust::json;
+use json_parser_rust::json_definitions::{
+    Dialect, DuplicateKeyPolicy, JsonParsingErrorV2, JsonValue, LexerError, ParseOptions,
+};
 use json_parser_rust::json_lexer::{NumberError, StringError};
-use json_parser_rust::json_lexer_parser::process_json_string_v2;
+use json_parser_rust::json_lexer_parser::{process_json_string_v2, process_json_string_v2_with_options};
 
 fn assert_ok_eq(input: &str, expected: JsonValue) {
     match process_json_string_v2(input) {
@@ -149,14 +150,15 @@ mod numbers {
         use super::{assert_ok_eq, JsonValue};
 
         #[test]
+        #[allow(clippy::approx_constant)]
         fn parse_number_ok() {
             let cases = [
-                ("0", JsonValue::Number(0.0)),
-                ("-1", JsonValue::Number(-1.0)),
-                ("42", JsonValue::Number(42.0)),
-                ("3.14", JsonValue::Number(3.14)),
-                ("1e3", JsonValue::Number(1000.0)),
-                ("-1.2E3", JsonValue::Number(-1200.0)),
+                ("0", JsonValue::Number(0.0.into())),
+                ("-1", JsonValue::Number((-1.0).into())),
+                ("42", JsonValue::Number(42.0.into())),
+                ("3.14", JsonValue::Number(3.14.into())),
+                ("1e3", JsonValue::Number(1000.0.into())),
+                ("-1.2E3", JsonValue::Number((-1200.0).into())),
             ];
             for (case, expected) in cases {
                 assert_ok_eq(case, expected);
@@ -201,32 +203,18 @@ mod numbers {
 }
 
 mod arrays {
-    use super::{assert_err_matches, assert_ok_eq, JsonParsingErrorV2, JsonValue};
+    use super::{assert_err_matches, assert_ok_eq, json, JsonParsingErrorV2};
 
     mod pos {
-        use super::{assert_ok_eq, JsonValue};
+        use super::{assert_ok_eq, json};
 
         #[test]
         fn parse_array_ok_more() {
             let cases = [
-                (r#"[1]"#, JsonValue::Array(vec![JsonValue::Number(1.0)])),
-                (r#"[  ]"#, JsonValue::Array(vec![])),
-                (
-                    r#"[[1,2],[3],[[]]]"#,
-                    JsonValue::Array(vec![
-                        JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)]),
-                        JsonValue::Array(vec![JsonValue::Number(3.0)]),
-                        JsonValue::Array(vec![JsonValue::Array(vec![])]),
-                    ]),
-                ),
-                (
-                    r#"[1, [2, 3], 4]"#,
-                    JsonValue::Array(vec![
-                        JsonValue::Number(1.0),
-                        JsonValue::Array(vec![JsonValue::Number(2.0), JsonValue::Number(3.0)]),
-                        JsonValue::Number(4.0),
-                    ]),
-                ),
+                (r#"[1]"#, json!([1])),
+                (r#"[  ]"#, json!([])),
+                (r#"[[1,2],[3],[[]]]"#, json!([[1, 2], [3], [[]]])),
+                (r#"[1, [2, 3], 4]"#, json!([1, [2, 3], 4])),
             ];
 
             for (case, expected) in cases {
@@ -237,21 +225,7 @@ mod arrays {
         #[test]
         fn parse_array_ok_deep_nested() {
             let input = r#"[1,[2,[3,[4,[5]]]]]"#;
-            let expected = JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::Array(vec![
-                    JsonValue::Number(2.0),
-                    JsonValue::Array(vec![
-                        JsonValue::Number(3.0),
-                        JsonValue::Array(vec![
-                            JsonValue::Number(4.0),
-                            JsonValue::Array(vec![JsonValue::Number(5.0)]),
-                        ]),
-                    ]),
-                ]),
-            ]);
-
-            assert_ok_eq(input, expected);
+            assert_ok_eq(input, json!([1, [2, [3, [4, [5]]]]]));
         }
     }
 
@@ -311,36 +285,24 @@ mod arrays {
         #[test]
         fn parse_array_unexpected_eof_reports_cursor() {
             assert_err_matches(r#"[1,"#, |e| {
-                matches!(e, JsonParsingErrorV2::UnexpectedEOF { at: 3 })
+                matches!(e, JsonParsingErrorV2::UnexpectedEOF { at } if (at.offset, at.line, at.column) == (3, 1, 4))
             });
         }
     }
 }
 
 mod objects {
-    use super::{assert_err_matches, assert_ok_eq, IndexMap, JsonParsingErrorV2, JsonValue};
+    use super::{assert_err_matches, assert_ok_eq, json, JsonParsingErrorV2};
 
     mod pos {
-        use super::{assert_ok_eq, IndexMap, JsonValue};
+        use super::{assert_ok_eq, json};
 
         #[test]
         fn parse_object_ok() {
-            let mut m1 = IndexMap::new();
-            m1.insert("a".to_string(), JsonValue::Number(1.0));
-
-            let mut m2 = IndexMap::new();
-            m2.insert("a".to_string(), JsonValue::Number(1.0));
-            m2.insert("b".to_string(), JsonValue::Boolean(true));
-            m2.insert("c".to_string(), JsonValue::Null);
-            m2.insert("d".to_string(), JsonValue::JsonString("x".to_string()));
-
             let cases = [
-                (r#"{}"#, JsonValue::Object(IndexMap::new())),
-                (r#"{ "a": 1 }"#, JsonValue::Object(m1)),
-                (
-                    r#"{ "a":1, "b":true, "c":null, "d":"x" }"#,
-                    JsonValue::Object(m2),
-                ),
+                (r#"{}"#, json!({})),
+                (r#"{ "a": 1 }"#, json!({"a": 1})),
+                (r#"{ "a":1, "b":true, "c":null, "d":"x" }"#, json!({"a": 1, "b": true, "c": null, "d": "x"})),
             ];
             for (case, expected) in cases {
                 assert_ok_eq(case, expected);
@@ -380,8 +342,85 @@ mod objects {
         #[test]
         fn parse_object_unexpected_eof_reports_cursor() {
             assert_err_matches(r#"{"#, |e| {
-                matches!(e, JsonParsingErrorV2::UnexpectedEOF { at: 1 })
+                matches!(e, JsonParsingErrorV2::UnexpectedEOF { at } if (at.offset, at.line, at.column) == (1, 1, 2))
+            });
+        }
+
+        #[test]
+        fn parse_object_error_reports_line_and_column() {
+            assert_err_matches("{\n  \"a\": 1\n  \"b\": 2\n}", |e| {
+                matches!(
+                    e,
+                    JsonParsingErrorV2::InvalidJsonObject { at: Some(at), .. } if (at.offset, at.line, at.column) == (13, 3, 3)
+                )
             });
         }
     }
 }
+
+mod duplicate_keys {
+    use super::{
+        process_json_string_v2, process_json_string_v2_with_options, DuplicateKeyPolicy, json, JsonParsingErrorV2,
+        JsonValue, ParseOptions,
+    };
+
+    const INPUT: &str = r#"{"a": 1, "b": 2, "a": 3, "a": 4}"#;
+
+    fn parse(policy: DuplicateKeyPolicy) -> Result<JsonValue, JsonParsingErrorV2> {
+        process_json_string_v2_with_options(INPUT, &ParseOptions { duplicate_keys: policy, ..ParseOptions::default() })
+    }
+
+    fn object(a: JsonValue) -> JsonValue {
+        json!({"a": a, "b": 2})
+    }
+
+    #[test]
+    fn last_wins_is_default() {
+        assert_eq!(process_json_string_v2(INPUT), Ok(object(json!(4))));
+        assert_eq!(parse(DuplicateKeyPolicy::LastWins), Ok(object(json!(4))));
+    }
+
+    #[test]
+    fn first_wins() {
+        assert_eq!(parse(DuplicateKeyPolicy::FirstWins), Ok(object(json!(1))));
+    }
+
+    #[test]
+    fn collect() {
+        assert_eq!(parse(DuplicateKeyPolicy::Collect), Ok(object(json!([1, 3, 4]))));
+    }
+
+    #[test]
+    fn error_reports_both_keys() {
+        let err = parse(DuplicateKeyPolicy::Error).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                JsonParsingErrorV2::DuplicateKey { key, first, second }
+                    if key == "a" && (first.start.offset, first.end.offset) == (1, 4) && (second.start.offset, second.end.offset) == (17, 20)
+            ),
+            "err was: {:?}",
+            err
+        );
+    }
+}
+
+mod json5 {
+    use super::{json, process_json_string_v2_with_options, Dialect, JsonParsingErrorV2, JsonValue, ParseOptions};
+
+    fn parse(input: &str) -> Result<JsonValue, JsonParsingErrorV2> {
+        process_json_string_v2_with_options(input, &ParseOptions { dialect: Dialect::Json5, ..ParseOptions::default() })
+    }
+
+    #[test]
+    fn trailing_commas_and_unquoted_keys() {
+        assert_eq!(parse("{a: [1, 2,], 'b': {},}"), Ok(json!({"a": [1, 2], "b": {}})));
+    }
+
+    #[test]
+    fn only_one_trailing_comma() {
+        assert!(parse("[1,,]").is_err());
+        assert!(parse("{a: 1,,}").is_err());
+        assert!(parse("[,]").is_err());
+    }
+}
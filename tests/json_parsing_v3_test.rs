@@ -0,0 +1,555 @@
+use indexmap::IndexMap;
+
+use json_parser_rust::json_definitions::{
+    CommentPolicy, Dialect, DuplicateKeyPolicy, FrameTypeTag, JsonParsingErrorV3, JsonValue, LexerError, Limit,
+    ParseLimits, ParseOptions, TokenTag,
+};
+use json_parser_rust::json_lexer::{SourceLocation, Span};
+use json_parser_rust::json_non_recursive::{
+    process_json_string_v3, process_json_string_v3_with_limits, process_json_string_v3_with_options,
+};
+
+fn loc(offset: usize, line: usize, column: usize) -> SourceLocation {
+    SourceLocation { offset, line, column }
+}
+
+fn assert_ok_eq(input: &str, expected: JsonValue) {
+    match process_json_string_v3(input) {
+        Ok(value) => assert_eq!(value, expected, "input was: {:?}", input),
+        Err(err) => panic!("expected Ok, got Err: {:?} for input: {:?}", err, input),
+    }
+}
+
+fn assert_err_eq(input: &str, expected: JsonParsingErrorV3) {
+    match process_json_string_v3(input) {
+        Ok(value) => panic!("expected Err, got Ok: {:?} for input: {:?}", value, input),
+        Err(err) => assert_eq!(err, expected, "input was: {:?}", input),
+    }
+}
+
+mod input {
+    use super::{assert_err_eq, loc, FrameTypeTag, JsonParsingErrorV3, LexerError, TokenTag};
+
+    #[test]
+    fn empty_json_file() {
+        assert_err_eq("", JsonParsingErrorV3::EmptyJsonFile);
+    }
+
+    #[test]
+    fn whitespace_only() {
+        assert_err_eq("   ", JsonParsingErrorV3::UnexpectedEOF { at: loc(3, 1, 4), frame: None });
+    }
+
+    #[test]
+    fn lexer_error_bubbles_up() {
+        assert_err_eq(
+            "[nul]",
+            JsonParsingErrorV3::LexError(LexerError::InvalidLiteral { at: loc(1, 1, 2), expected: "null" }),
+        );
+    }
+
+    #[test]
+    fn unexpected_eof_reports_open_frame() {
+        assert_err_eq(
+            r#"{"a": [1,2"#,
+            JsonParsingErrorV3::UnexpectedEOF { at: loc(10, 1, 11), frame: Some(FrameTypeTag::ArrayFrame) },
+        );
+        assert_err_eq(
+            r#"{"a": 1"#,
+            JsonParsingErrorV3::UnexpectedEOF { at: loc(7, 1, 8), frame: Some(FrameTypeTag::ObjectFrame) },
+        );
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        assert_err_eq(
+            "{\n  \"a\": [1,\n    2,\n  ]\n}",
+            JsonParsingErrorV3::TrailingComma {
+                at: loc(22, 4, 3),
+                found: TokenTag::RBracket,
+                frame: FrameTypeTag::ArrayFrame,
+            },
+        );
+    }
+}
+
+mod leaves {
+    use super::{assert_err_eq, loc, assert_ok_eq, JsonParsingErrorV3, JsonValue, TokenTag};
+
+    mod pos {
+        use super::{assert_ok_eq, JsonValue};
+
+        #[test]
+        fn parse_leaf_ok() {
+            let cases = [
+                ("null", JsonValue::Null),
+                (" true ", JsonValue::Boolean(true)),
+                ("false", JsonValue::Boolean(false)),
+                ("-1.5e2", JsonValue::Number((-150.0).into())),
+                (r#""x\ny""#, JsonValue::JsonString("x\ny".to_string())),
+            ];
+            for (case, expected) in cases {
+                assert_ok_eq(case, expected);
+            }
+        }
+    }
+
+    mod neg {
+        use super::{assert_err_eq, loc, JsonParsingErrorV3, TokenTag};
+
+        #[test]
+        fn trailing_value_after_root() {
+            assert_err_eq("1 2", JsonParsingErrorV3::ExpectedEOF { at: loc(2, 1, 3), found: TokenTag::Number });
+            assert_err_eq("[] {}", JsonParsingErrorV3::ExpectedEOF { at: loc(3, 1, 4), found: TokenTag::LBrace });
+        }
+
+        #[test]
+        fn separators_at_root() {
+            assert_err_eq("1, 2", JsonParsingErrorV3::CommaOutsideStructure { at: loc(1, 1, 2) });
+            assert_err_eq(r#""a": 1"#, JsonParsingErrorV3::ColonOutsideObject { at: loc(3, 1, 4), frame: None });
+        }
+
+        #[test]
+        fn closing_at_root() {
+            assert_err_eq("]", JsonParsingErrorV3::UnexpectedClosing { at: loc(0, 1, 1), found: TokenTag::RBracket });
+            assert_err_eq("1 }", JsonParsingErrorV3::UnexpectedClosing { at: loc(2, 1, 3), found: TokenTag::RBrace });
+        }
+    }
+}
+
+mod arrays {
+    use super::{
+        assert_err_eq, loc, assert_ok_eq, process_json_string_v3, FrameTypeTag, JsonParsingErrorV3, JsonValue, TokenTag,
+    };
+
+    mod pos {
+        use super::{assert_ok_eq, process_json_string_v3, JsonValue};
+
+        #[test]
+        fn parse_array_ok() {
+            let cases = [
+                (r#"[1]"#, JsonValue::Array(vec![JsonValue::Number(1.0.into())])),
+                (r#"[  ]"#, JsonValue::Array(vec![])),
+                (
+                    r#"[[1,2],[3],[[]]]"#,
+                    JsonValue::Array(vec![
+                        JsonValue::Array(vec![JsonValue::Number(1.0.into()), JsonValue::Number(2.0.into())]),
+                        JsonValue::Array(vec![JsonValue::Number(3.0.into())]),
+                        JsonValue::Array(vec![JsonValue::Array(vec![])]),
+                    ]),
+                ),
+            ];
+
+            for (case, expected) in cases {
+                assert_ok_eq(case, expected);
+            }
+        }
+
+        #[test]
+        fn parse_array_very_deep_nested() {
+            let depth = 100_000;
+            let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+            let value = process_json_string_v3(&input).expect("deep array should parse");
+            let mut current = &value;
+            let mut seen = 0;
+            while let JsonValue::Array(items) = current {
+                seen += 1;
+                match items.first() {
+                    Some(inner) => current = inner,
+                    None => break,
+                }
+            }
+            assert_eq!(seen, depth);
+        }
+    }
+
+    mod neg {
+        use super::{assert_err_eq, loc, FrameTypeTag, JsonParsingErrorV3, TokenTag};
+
+        #[test]
+        fn missing_comma() {
+            assert_err_eq(
+                "[1 2]",
+                JsonParsingErrorV3::MissingComma { at: loc(3, 1, 4), found: TokenTag::Number, frame: FrameTypeTag::ArrayFrame },
+            );
+            assert_err_eq(
+                "[1 []]",
+                JsonParsingErrorV3::MissingComma { at: loc(3, 1, 4), found: TokenTag::LBracket, frame: FrameTypeTag::ArrayFrame },
+            );
+        }
+
+        #[test]
+        fn comma_errors() {
+            assert_err_eq("[,1]", JsonParsingErrorV3::LeadingComma { at: loc(1, 1, 2), frame: FrameTypeTag::ArrayFrame });
+            assert_err_eq("[1,,2]", JsonParsingErrorV3::UnexpectedComma { at: loc(3, 1, 4), frame: FrameTypeTag::ArrayFrame });
+            assert_err_eq(
+                "[1,]",
+                JsonParsingErrorV3::TrailingComma { at: loc(3, 1, 4), found: TokenTag::RBracket, frame: FrameTypeTag::ArrayFrame },
+            );
+        }
+
+        #[test]
+        fn colon_inside_array() {
+            assert_err_eq(
+                "[1: 2]",
+                JsonParsingErrorV3::ColonOutsideObject { at: loc(2, 1, 3), frame: Some(FrameTypeTag::ArrayFrame) },
+            );
+        }
+
+        #[test]
+        fn mismatched_closing() {
+            assert_err_eq(
+                "[1}",
+                JsonParsingErrorV3::MismatchedClosing { at: loc(2, 1, 3), found: TokenTag::RBrace, frame: FrameTypeTag::ArrayFrame },
+            );
+        }
+
+        #[test]
+        fn extra_closing_bracket() {
+            assert_err_eq("[1]]", JsonParsingErrorV3::UnexpectedClosing { at: loc(3, 1, 4), found: TokenTag::RBracket });
+        }
+    }
+}
+
+mod objects {
+    use super::{assert_err_eq, loc, assert_ok_eq, FrameTypeTag, IndexMap, JsonParsingErrorV3, JsonValue, TokenTag};
+
+    mod pos {
+        use super::{assert_ok_eq, IndexMap, JsonValue};
+
+        #[test]
+        fn parse_object_ok() {
+            let mut m1 = IndexMap::new();
+            m1.insert("a".to_string(), JsonValue::Number(1.0.into()));
+
+            let mut inner = IndexMap::new();
+            inner.insert("c".to_string(), JsonValue::Null);
+            let mut m2 = IndexMap::new();
+            m2.insert("a".to_string(), JsonValue::Array(vec![JsonValue::Boolean(true)]));
+            m2.insert("b".to_string(), JsonValue::Object(inner));
+            m2.insert("d".to_string(), JsonValue::JsonString("x".to_string()));
+
+            let cases = [
+                (r#"{}"#, JsonValue::Object(IndexMap::new())),
+                (r#"{ "a": 1 }"#, JsonValue::Object(m1)),
+                (r#"{ "a":[true], "b":{"c":null}, "d":"x" }"#, JsonValue::Object(m2)),
+            ];
+            for (case, expected) in cases {
+                assert_ok_eq(case, expected);
+            }
+        }
+    }
+
+    mod neg {
+        use super::{assert_err_eq, loc, FrameTypeTag, JsonParsingErrorV3, TokenTag};
+
+        #[test]
+        fn key_not_string() {
+            assert_err_eq(r#"{1: "v"}"#, JsonParsingErrorV3::ObjectKeyNotString { at: loc(1, 1, 2), found: TokenTag::Number });
+            assert_err_eq(r#"{[]: 1}"#, JsonParsingErrorV3::ObjectKeyNotString { at: loc(1, 1, 2), found: TokenTag::LBracket });
+            assert_err_eq(r#"{: 1}"#, JsonParsingErrorV3::ObjectKeyNotString { at: loc(1, 1, 2), found: TokenTag::Colon });
+        }
+
+        #[test]
+        fn key_without_value() {
+            assert_err_eq(
+                r#"{"a": }"#,
+                JsonParsingErrorV3::ObjectKeyWithoutValue { at: loc(6, 1, 7), found: TokenTag::RBrace, key: "a".to_string() },
+            );
+            assert_err_eq(
+                r#"{"a"}"#,
+                JsonParsingErrorV3::ObjectKeyWithoutValue { at: loc(4, 1, 5), found: TokenTag::RBrace, key: "a".to_string() },
+            );
+        }
+
+        #[test]
+        fn colon_errors() {
+            assert_err_eq(r#"{"a" 1}"#, JsonParsingErrorV3::MissingColon { at: loc(5, 1, 6), found: TokenTag::Number });
+            assert_err_eq(r#"{"a", 1}"#, JsonParsingErrorV3::MissingColon { at: loc(4, 1, 5), found: TokenTag::Comma });
+            assert_err_eq(r#"{"a":: 1}"#, JsonParsingErrorV3::UnexpectedColon { at: loc(5, 1, 6) });
+        }
+
+        #[test]
+        fn comma_errors() {
+            assert_err_eq(r#"{, "a": 1}"#, JsonParsingErrorV3::LeadingComma { at: loc(1, 1, 2), frame: FrameTypeTag::ObjectFrame });
+            assert_err_eq(
+                r#"{"a":, "b": 1}"#,
+                JsonParsingErrorV3::UnexpectedComma { at: loc(5, 1, 6), frame: FrameTypeTag::ObjectFrame },
+            );
+            assert_err_eq(
+                r#"{"a": 1,}"#,
+                JsonParsingErrorV3::TrailingComma { at: loc(8, 1, 9), found: TokenTag::RBrace, frame: FrameTypeTag::ObjectFrame },
+            );
+            assert_err_eq(
+                r#"{"a": 1 "b": 2}"#,
+                JsonParsingErrorV3::MissingComma { at: loc(8, 1, 9), found: TokenTag::String, frame: FrameTypeTag::ObjectFrame },
+            );
+        }
+
+        #[test]
+        fn mismatched_closing() {
+            assert_err_eq(
+                r#"{"a": 1]"#,
+                JsonParsingErrorV3::MismatchedClosing { at: loc(7, 1, 8), found: TokenTag::RBracket, frame: FrameTypeTag::ObjectFrame },
+            );
+        }
+    }
+}
+
+mod limits {
+    use super::{
+        loc, process_json_string_v3, process_json_string_v3_with_limits, JsonParsingErrorV3, Limit, ParseLimits,
+    };
+
+    fn assert_limit(input: &str, limits: ParseLimits, limit: Limit, at: super::SourceLocation) {
+        match process_json_string_v3_with_limits(input, &limits) {
+            Ok(value) => panic!("expected Err, got Ok: {:?} for input: {:?}", value, input),
+            Err(err) => assert_eq!(err, JsonParsingErrorV3::LimitExceeded { limit, at }, "input was: {:?}", input),
+        }
+    }
+
+    fn assert_within(input: &str, limits: ParseLimits) {
+        let expected = process_json_string_v3(input).expect("input should be valid JSON");
+        assert_eq!(process_json_string_v3_with_limits(input, &limits), Ok(expected), "input was: {:?}", input);
+    }
+
+    #[test]
+    fn default_is_unlimited() {
+        assert_eq!(ParseLimits::default(), ParseLimits::UNLIMITED);
+    }
+
+    #[test]
+    fn depth() {
+        let limits = ParseLimits { max_depth: 2, ..ParseLimits::UNLIMITED };
+        assert_within(r#"[{"a": 1}, []]"#, limits);
+        assert_limit(r#"[{"a": []}]"#, limits, Limit::Depth, loc(7, 1, 8));
+        assert_limit("[[[", limits, Limit::Depth, loc(2, 1, 3));
+        assert_limit("[]", ParseLimits { max_depth: 0, ..limits }, Limit::Depth, loc(0, 1, 1));
+    }
+
+    #[test]
+    fn document_bytes() {
+        let limits = ParseLimits { max_document_bytes: 8, ..ParseLimits::UNLIMITED };
+        assert_within("[1, 2.5]", limits);
+        assert_limit("[\n 1, 2.5]", limits, Limit::DocumentBytes, loc(8, 2, 7));
+    }
+
+    #[test]
+    fn string_bytes() {
+        let limits = ParseLimits { max_string_bytes: 3, ..ParseLimits::UNLIMITED };
+        assert_within(r#"{"abc": "xyz"}"#, limits);
+        assert_limit(r#"["ab", "abcd"]"#, limits, Limit::StringBytes, loc(7, 1, 8));
+        // keys are strings too
+        assert_limit(r#"{"abcd": 1}"#, limits, Limit::StringBytes, loc(1, 1, 2));
+        // escapes count as written, not decoded
+        assert_limit(r#""\n\n""#, limits, Limit::StringBytes, loc(0, 1, 1));
+    }
+
+    #[test]
+    fn array_length() {
+        let limits = ParseLimits { max_array_len: 2, ..ParseLimits::UNLIMITED };
+        assert_within("[[1, 2], [3, 4]]", limits);
+        assert_limit("[1, 2, 3]", limits, Limit::ArrayLength, loc(7, 1, 8));
+        assert_limit("[[], [], []]", limits, Limit::ArrayLength, loc(10, 1, 11));
+    }
+
+    #[test]
+    fn object_members() {
+        let limits = ParseLimits { max_object_members: 1, ..ParseLimits::UNLIMITED };
+        assert_within(r#"{"a": {"b": 1}}"#, limits);
+        // a repeated key replaces the old value and does not add a member
+        assert_within(r#"{"a": 1, "a": 2}"#, limits);
+        assert_limit(r#"{"a": 1, "b": 2}"#, limits, Limit::ObjectMembers, loc(14, 1, 15));
+    }
+
+    #[test]
+    fn number_length() {
+        let limits = ParseLimits { max_number_len: 4, ..ParseLimits::UNLIMITED };
+        assert_within("[-1.5, 1e10]", limits);
+        assert_limit("[1, -1.25]", limits, Limit::NumberLength, loc(4, 1, 5));
+    }
+
+    #[test]
+    fn deep_input_rejected_before_parsing_all_of_it() {
+        let limits = ParseLimits { max_depth: 64, ..ParseLimits::UNLIMITED };
+        let input = "[".repeat(1_000_000);
+        assert_limit(&input, limits, Limit::Depth, loc(64, 1, 65));
+    }
+}
+
+mod duplicate_keys {
+    use super::{
+        loc, process_json_string_v3, process_json_string_v3_with_options, DuplicateKeyPolicy, IndexMap,
+        JsonParsingErrorV3, JsonValue, ParseOptions, Span,
+    };
+
+    const INPUT: &str = r#"{"a": 1, "b": 2, "a": 3, "a": 4}"#;
+
+    fn parse(input: &str, policy: DuplicateKeyPolicy) -> Result<JsonValue, JsonParsingErrorV3> {
+        process_json_string_v3_with_options(input, &ParseOptions { duplicate_keys: policy, ..ParseOptions::default() })
+    }
+
+    fn object(a: JsonValue) -> JsonValue {
+        let mut map = IndexMap::new();
+        map.insert("a".to_string(), a);
+        map.insert("b".to_string(), JsonValue::Number(2.0.into()));
+        JsonValue::Object(map)
+    }
+
+    #[test]
+    fn last_wins_is_default() {
+        assert_eq!(process_json_string_v3(INPUT), Ok(object(JsonValue::Number(4.0.into()))));
+        assert_eq!(parse(INPUT, DuplicateKeyPolicy::LastWins), Ok(object(JsonValue::Number(4.0.into()))));
+    }
+
+    #[test]
+    fn first_wins() {
+        assert_eq!(parse(INPUT, DuplicateKeyPolicy::FirstWins), Ok(object(JsonValue::Number(1.0.into()))));
+    }
+
+    #[test]
+    fn collect() {
+        let collected = JsonValue::Array(vec![JsonValue::Number(1.0.into()), JsonValue::Number(3.0.into()), JsonValue::Number(4.0.into())]);
+        assert_eq!(parse(INPUT, DuplicateKeyPolicy::Collect), Ok(object(collected)));
+
+        // an array value that was never repeated is left alone; a repeated one is nested
+        let value = parse(r#"{"a": [1], "b": 2, "a": [2]}"#, DuplicateKeyPolicy::Collect).unwrap();
+        let nested = JsonValue::Array(vec![
+            JsonValue::Array(vec![JsonValue::Number(1.0.into())]),
+            JsonValue::Array(vec![JsonValue::Number(2.0.into())]),
+        ]);
+        assert_eq!(value, object(nested));
+    }
+
+    #[test]
+    fn error_reports_both_keys() {
+        assert_eq!(
+            parse(INPUT, DuplicateKeyPolicy::Error),
+            Err(JsonParsingErrorV3::DuplicateKey {
+                key: "a".to_string(),
+                first: Span { start: loc(1, 1, 2), end: loc(4, 1, 5) },
+                second: Span { start: loc(17, 1, 18), end: loc(20, 1, 21) },
+            })
+        );
+    }
+
+    #[test]
+    fn policy_applies_to_nested_objects() {
+        let input = r#"[{"x": {"k": 1, "k": 2}}]"#;
+        let err = parse(input, DuplicateKeyPolicy::Error).unwrap_err();
+        assert!(matches!(err, JsonParsingErrorV3::DuplicateKey { ref key, .. } if key == "k"));
+    }
+}
+
+mod json5 {
+    use super::{
+        loc, process_json_string_v3, process_json_string_v3_with_options, Dialect, FrameTypeTag, JsonParsingErrorV3,
+        JsonValue, LexerError, ParseOptions,
+    };
+    use json_parser_rust::json;
+
+    fn parse(input: &str) -> Result<JsonValue, JsonParsingErrorV3> {
+        process_json_string_v3_with_options(input, &ParseOptions { dialect: Dialect::Json5, ..ParseOptions::default() })
+    }
+
+    mod pos {
+        use super::{json, parse, JsonValue};
+
+        #[test]
+        fn config_file() {
+            let input = "// settings\n{\n  name: 'demo', // inline\n  ports: [0x50, +443,],\n  ratio: .5,\n\
+                         /* off */ debug: false,\n}\n";
+            assert_eq!(parse(input), Ok(json!({"name": "demo", "ports": [80, 443], "ratio": 0.5, "debug": false})));
+        }
+
+        #[test]
+        fn non_finite_numbers() {
+            let value = parse("[Infinity, -Infinity, NaN]");
+            let Ok(JsonValue::Array(values)) = &value else {
+                panic!("expected an array");
+            };
+            let numbers: Vec<f64> = values.iter().map(|v| match v {
+                JsonValue::Number(n) => n.as_f64(),
+                other => panic!("expected a number, got {:?}", other),
+            }).collect();
+            assert_eq!(numbers[..2], [f64::INFINITY, f64::NEG_INFINITY]);
+            assert!(numbers[2].is_nan());
+        }
+
+        #[test]
+        fn reserved_words_as_keys() {
+            assert_eq!(parse("{null: 1, true: 2, NaN: 3}"), Ok(json!({"null": 1, "true": 2, "NaN": 3})));
+        }
+    }
+
+    mod neg {
+        use super::{loc, parse, process_json_string_v3, FrameTypeTag, JsonParsingErrorV3, LexerError};
+
+        #[test]
+        fn only_one_trailing_comma() {
+            let frame = FrameTypeTag::ArrayFrame;
+            assert_eq!(parse("[1,,]"), Err(JsonParsingErrorV3::UnexpectedComma { at: loc(3, 1, 4), frame }));
+            let frame = FrameTypeTag::ObjectFrame;
+            assert_eq!(parse("{,}"), Err(JsonParsingErrorV3::LeadingComma { at: loc(1, 1, 2), frame }));
+        }
+
+        #[test]
+        fn unquoted_value() {
+            let err = parse("{a: b}").unwrap_err();
+            let JsonParsingErrorV3::LexError(LexerError::InvalidLiteral { at, .. }) = err else {
+                panic!("expected an invalid literal, got {:?}", err);
+            };
+            assert_eq!(at, loc(4, 1, 5));
+        }
+
+        #[test]
+        fn strict_by_default() {
+            assert!(matches!(process_json_string_v3("[1,]"), Err(JsonParsingErrorV3::TrailingComma { .. })));
+            assert!(process_json_string_v3("{a: 1}").is_err());
+        }
+    }
+}
+
+mod comments {
+    use super::{
+        loc, process_json_string_v3, process_json_string_v3_with_options, CommentPolicy, JsonParsingErrorV3,
+        JsonValue, LexerError, ParseOptions,
+    };
+    use json_parser_rust::json;
+
+    fn parse(input: &str, comments: CommentPolicy) -> Result<JsonValue, JsonParsingErrorV3> {
+        process_json_string_v3_with_options(input, &ParseOptions { comments, ..ParseOptions::default() })
+    }
+
+    mod pos {
+        use super::{json, parse, CommentPolicy};
+
+        #[test]
+        fn settings_file() {
+            let input = "// settings\n{\n  \"editor.tabSize\": 4, /* was 2 */\n\
+                         \"files.exclude\": [\"target\"] // build\n}";
+            for comments in [CommentPolicy::Skip, CommentPolicy::Tokens] {
+                assert_eq!(parse(input, comments), Ok(json!({"editor.tabSize": 4, "files.exclude": ["target"]})));
+            }
+        }
+    }
+
+    mod neg {
+        use super::{loc, parse, process_json_string_v3, CommentPolicy, JsonParsingErrorV3, LexerError};
+
+        #[test]
+        fn unterminated_block_comment() {
+            assert_eq!(
+                parse("{\"a\": 1 /* }", CommentPolicy::Skip),
+                Err(JsonParsingErrorV3::LexError(LexerError::UnterminatedBlockComment { at: loc(8, 1, 9) }))
+            );
+        }
+
+        #[test]
+        fn only_comments_are_allowed() {
+            assert!(matches!(parse("[1,] // c", CommentPolicy::Skip), Err(JsonParsingErrorV3::TrailingComma { .. })));
+            assert!(process_json_string_v3("[1] // c").is_err());
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_patch::{apply_patch, generate_patch, JsonPatch, PatchError, PatchOperation};
+use json_parser_rust::json_pointer::{JsonPointer, PointerError};
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+fn patch(input: &str) -> JsonPatch {
+    JsonPatch::from_value(&parse(input)).expect("test patch should be well-formed")
+}
+
+fn assert_patched(doc: &str, ops: &str, expected: &str) {
+    let mut value = parse(doc);
+    match apply_patch(&mut value, &patch(ops)) {
+        Ok(()) => assert_eq!(value, parse(expected), "patch was: {}", ops),
+        Err(err) => panic!("expected Ok, got Err: {:?} for patch: {}", err, ops),
+    }
+}
+
+fn assert_patch_err(doc: &str, ops: &str, expected: PatchError) {
+    let mut value = parse(doc);
+    assert_eq!(apply_patch(&mut value, &patch(ops)), Err(expected), "patch was: {}", ops);
+    // atomic: nothing was changed
+    assert_eq!(value, parse(doc));
+}
+
+fn pointer(p: &str) -> JsonPointer {
+    JsonPointer::parse(p).unwrap()
+}
+
+mod apply {
+    use super::{assert_patch_err, assert_patched, PatchError, PointerError};
+
+    // Examples from RFC 6902, appendix A.
+    mod pos {
+        use super::assert_patched;
+
+        #[test]
+        fn add_object_member() {
+            assert_patched(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                           r#"{"baz": "qux", "foo": "bar"}"#);
+        }
+
+        #[test]
+        fn add_array_element() {
+            assert_patched(r#"{"foo": ["bar", "baz"]}"#, r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                           r#"{"foo": ["bar", "qux", "baz"]}"#);
+        }
+
+        #[test]
+        fn remove_object_member_and_array_element() {
+            assert_patched(r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "remove", "path": "/baz"}]"#, r#"{"foo": "bar"}"#);
+            assert_patched(r#"{"foo": ["bar", "qux", "baz"]}"#, r#"[{"op": "remove", "path": "/foo/1"}]"#,
+                           r#"{"foo": ["bar", "baz"]}"#);
+        }
+
+        #[test]
+        fn replace_value() {
+            assert_patched(r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                           r#"{"baz": "boo", "foo": "bar"}"#);
+        }
+
+        #[test]
+        fn move_value() {
+            assert_patched(
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            );
+            assert_patched(r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                           r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                           r#"{"foo": ["all", "cows", "eat", "grass"]}"#);
+        }
+
+        #[test]
+        fn test_then_copy() {
+            assert_patched(
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+                r#"[{"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2.0},
+                    {"op": "copy", "from": "/foo", "path": "/bar"}]"#,
+                r#"{"baz": "qux", "foo": ["a", 2, "c"], "bar": ["a", 2, "c"]}"#,
+            );
+        }
+
+        #[test]
+        fn add_nested_container_and_append() {
+            assert_patched(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+                           r#"{"foo": "bar", "child": {"grandchild": {}}}"#);
+            assert_patched(r#"{"foo": ["bar"]}"#, r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+                           r#"{"foo": ["bar", ["abc", "def"]]}"#);
+        }
+
+        #[test]
+        fn extra_members_are_ignored() {
+            assert_patched(r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]"#,
+                           r#"{"foo": "bar", "baz": "qux"}"#);
+        }
+
+        #[test]
+        fn whole_document_and_self_move() {
+            assert_patched("[1]", r#"[{"op": "replace", "path": "", "value": {"a": 1}}]"#, r#"{"a": 1}"#);
+            assert_patched(r#"{"a": 1}"#, r#"[{"op": "move", "from": "/a", "path": "/a"}]"#, r#"{"a": 1}"#);
+        }
+    }
+
+    mod neg {
+        use super::{assert_patch_err, PatchError, PointerError};
+
+        #[test]
+        fn failed_test_leaves_document_untouched() {
+            assert_patch_err(
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+                r#"[{"op": "replace", "path": "/baz", "value": "changed"},
+                    {"op": "test", "path": "/baz", "value": "qux"}]"#,
+                PatchError::TestFailed { index: 1, path: "/baz".to_string() },
+            );
+        }
+
+        #[test]
+        fn missing_target() {
+            assert_patch_err(
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": 1},
+                    {"op": "add", "path": "/baz/bat", "value": "qux"}]"#,
+                PatchError::PointerFailed { index: 1, error: PointerError::NotAContainer { path: "/baz/bat".to_string() } },
+            );
+            assert_patch_err(
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "replace", "path": "/nope", "value": 1}]"#,
+                PatchError::PointerFailed {
+                    index: 0,
+                    error: PointerError::MissingKey { path: "/nope".to_string(), key: "nope".to_string() },
+                },
+            );
+        }
+
+        #[test]
+        fn array_index_out_of_bounds() {
+            assert_patch_err(
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/3", "value": "qux"}]"#,
+                PatchError::PointerFailed {
+                    index: 0,
+                    error: PointerError::IndexOutOfBounds { path: "/foo/3".to_string(), index: 3, len: 2 },
+                },
+            );
+        }
+
+        #[test]
+        fn move_into_own_child() {
+            assert_patch_err(
+                r#"{"a": {"b": {}}}"#,
+                r#"[{"op": "move", "from": "/a", "path": "/a/b/c"}]"#,
+                PatchError::MoveIntoChild { index: 0, from: "/a".to_string(), path: "/a/b/c".to_string() },
+            );
+        }
+
+        #[test]
+        fn test_is_type_sensitive() {
+            assert_patch_err(
+                r#"{"/": 9, "~1": 10}"#,
+                r#"[{"op": "test", "path": "/~01", "value": "10"}]"#,
+                PatchError::TestFailed { index: 0, path: "/~01".to_string() },
+            );
+        }
+    }
+}
+
+mod document {
+    use super::{parse, patch, pointer, JsonPatch, JsonValue, PatchError, PatchOperation, PointerError};
+
+    mod pos {
+        use super::{parse, patch, pointer, JsonValue, PatchOperation};
+
+        #[test]
+        fn reads_every_operation() {
+            let ops = patch(
+                r#"[{"op": "add", "path": "/a", "value": 1},
+                    {"op": "remove", "path": "/b"},
+                    {"op": "replace", "path": "/c~1d", "value": null},
+                    {"op": "move", "from": "/e", "path": "/f"},
+                    {"op": "copy", "from": "/g", "path": "/h"},
+                    {"op": "test", "path": "", "value": true}]"#,
+            );
+            assert_eq!(
+                ops.operations,
+                vec![
+                    PatchOperation::Add { path: pointer("/a"), value: JsonValue::Number(1.0.into()) },
+                    PatchOperation::Remove { path: pointer("/b") },
+                    PatchOperation::Replace { path: pointer("/c~1d"), value: JsonValue::Null },
+                    PatchOperation::Move { from: pointer("/e"), path: pointer("/f") },
+                    PatchOperation::Copy { from: pointer("/g"), path: pointer("/h") },
+                    PatchOperation::Test { path: pointer(""), value: JsonValue::Boolean(true) },
+                ]
+            );
+        }
+
+        #[test]
+        fn to_value_round_trips() {
+            let text = r#"[{"op": "move", "from": "/a~0", "path": "/b"}, {"op": "add", "path": "/x/-", "value": [1]}]"#;
+            assert_eq!(patch(text).to_value(), parse(text));
+        }
+    }
+
+    mod neg {
+        use super::{parse, JsonPatch, PatchError, PointerError};
+
+        #[test]
+        fn malformed_operations() {
+            let cases = [
+                (r#"{"op": "add"}"#, PatchError::NotAnArray),
+                (r#"[1]"#, PatchError::NotAnObject { index: 0 }),
+                (r#"[{"path": "/a"}]"#, PatchError::MissingMember { index: 0, member: "op" }),
+                (r#"[{"op": "remove", "path": "/a"}, {"op": "add", "path": "/a"}]"#,
+                 PatchError::MissingMember { index: 1, member: "value" }),
+                (r#"[{"op": "move", "path": "/a"}]"#, PatchError::MissingMember { index: 0, member: "from" }),
+                (r#"[{"op": "remove", "path": 1}]"#, PatchError::NotAString { index: 0, member: "path" }),
+                (r#"[{"op": "merge", "path": "/a"}]"#, PatchError::UnknownOperation { index: 0, op: "merge".to_string() }),
+            ];
+            for (input, expected) in cases {
+                assert_eq!(JsonPatch::from_value(&parse(input)), Err(expected), "input was: {}", input);
+            }
+        }
+
+        #[test]
+        fn malformed_pointer() {
+            assert_eq!(
+                JsonPatch::from_value(&parse(r#"[{"op": "remove", "path": "a"}]"#)),
+                Err(PatchError::InvalidPointer {
+                    index: 0,
+                    member: "path",
+                    error: PointerError::MissingLeadingSlash { pointer: "a".to_string() },
+                })
+            );
+        }
+    }
+}
+
+mod generate {
+    use super::{apply_patch, generate_patch, parse, patch};
+
+    fn assert_round_trip(from: &str, to: &str) {
+        let (from, to) = (parse(from), parse(to));
+        let mut value = from.clone();
+        apply_patch(&mut value, &generate_patch(&from, &to)).expect("generated patch should apply");
+        assert_eq!(value, to);
+    }
+
+    #[test]
+    fn equal_documents_give_an_empty_patch() {
+        let doc = parse(r#"{"a": [1, {"b": null}], "c": 1.0}"#);
+        assert!(generate_patch(&doc, &parse(r#"{"c": 1, "a": [1, {"b": null}]}"#)).is_empty());
+    }
+
+    #[test]
+    fn object_changes() {
+        let from = parse(r#"{"a": 1, "b": {"c": 2, "d": 3}, "e": 4}"#);
+        let to = parse(r#"{"a": 1, "b": {"c": 5, "d": 3}, "f": 6}"#);
+        assert_eq!(
+            generate_patch(&from, &to),
+            patch(
+                r#"[{"op": "replace", "path": "/b/c", "value": 5},
+                    {"op": "remove", "path": "/e"},
+                    {"op": "add", "path": "/f", "value": 6}]"#
+            )
+        );
+    }
+
+    #[test]
+    fn array_insertions_and_removals_touch_only_the_difference() {
+        let cases = [
+            ("[1, 2, 3]", "[0, 1, 2, 3]", r#"[{"op": "add", "path": "/0", "value": 0}]"#),
+            ("[1, 2, 3]", "[1, 3]", r#"[{"op": "remove", "path": "/1"}]"#),
+            ("[1, 2, 3, 4]", "[1, 4]", r#"[{"op": "remove", "path": "/2"}, {"op": "remove", "path": "/1"}]"#),
+            ("[1, 2]", "[1, 2, 3, 4]",
+             r#"[{"op": "add", "path": "/2", "value": 3}, {"op": "add", "path": "/3", "value": 4}]"#),
+            ("[1, 2, 3]", "[1, 9, 3]", r#"[{"op": "replace", "path": "/1", "value": 9}]"#),
+        ];
+        for (from, to, expected) in cases {
+            assert_eq!(generate_patch(&parse(from), &parse(to)), patch(expected), "from {} to {}", from, to);
+        }
+    }
+
+    #[test]
+    fn type_change_replaces() {
+        assert_eq!(
+            generate_patch(&parse(r#"{"a": [1]}"#), &parse(r#"{"a": {"0": 1}}"#)),
+            patch(r#"[{"op": "replace", "path": "/a", "value": {"0": 1}}]"#)
+        );
+        assert_eq!(generate_patch(&parse("1"), &parse("[]")), patch(r#"[{"op": "replace", "path": "", "value": []}]"#));
+    }
+
+    #[test]
+    fn generated_patches_apply() {
+        let cases = [
+            (r#"{"a/b": {"m~n": [1, 2, {"x": 1}]}}"#, r#"{"a/b": {"m~n": [2, {"x": 2}, 3]}, "new": true}"#),
+            (r#"[[1, 2], [3], []]"#, r#"[[3, 4], [1, 2], [], [5]]"#),
+            (r#"{"keep": 1, "drop": [1, 2, 3]}"#, r#"{"keep": 1}"#),
+            (r#""text""#, r#"{"now": "object"}"#),
+        ];
+        for (from, to) in cases {
+            assert_round_trip(from, to);
+        }
+    }
+
+    #[test]
+    fn deep_documents() {
+        let depth = 100_000;
+        let from = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        let to = format!("{}2{}", "[".repeat(depth), "]".repeat(depth));
+        let generated = generate_patch(&parse(&from), &parse(&to));
+        assert_eq!(generated.operations.len(), 1);
+        assert_round_trip(&from, &to);
+    }
+}
@@ -0,0 +1,408 @@
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_diagnostics::{render_error, ColorMode};
+use json_parser_rust::json_lexer::{NumberError, SourceLocation, StringError};
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_path::{JsonPath, JsonPathError};
+use json_parser_rust::json_pointer::JsonPointer;
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+fn loc(offset: usize, line: usize, column: usize) -> SourceLocation {
+    SourceLocation { offset, line, column }
+}
+
+// The example document from RFC 9535, section 1.5.
+fn bookstore() -> JsonValue {
+    parse(
+        r#"{ "store": {
+            "book": [
+              { "category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95 },
+              { "category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99 },
+              { "category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3",
+                "price": 8.99 },
+              { "category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings",
+                "isbn": "0-395-19395-8", "price": 22.99 }
+            ],
+            "bicycle": { "color": "red", "price": 399 }
+          }
+        }"#,
+    )
+}
+
+// Matched values, written back as one JSON array for easy comparison.
+fn select(query: &str, document: &JsonValue) -> JsonValue {
+    let path = JsonPath::parse(query).unwrap_or_else(|e| panic!("{} should parse: {}", query, e));
+    JsonValue::Array(path.query_values(document).into_iter().cloned().collect())
+}
+
+fn count(query: &str, document: &JsonValue) -> usize {
+    JsonPath::parse(query).unwrap().query_values(document).len()
+}
+
+fn paths(query: &str, document: &JsonValue) -> Vec<String> {
+    JsonPath::parse(query).unwrap().query(document).iter().map(|node| node.path.to_string()).collect()
+}
+
+mod syntax {
+    use super::{loc, render_error, ColorMode, JsonPath, JsonPathError, NumberError, StringError};
+
+    mod pos {
+        use super::JsonPath;
+
+        #[test]
+        fn accepts_rfc_queries() {
+            let queries = [
+                "$",
+                "$.store.book[*].author",
+                "$..author",
+                "$.store.*",
+                "$['store'][\"book\"][-1]",
+                "$..book[0,1]",
+                "$..book[:2]",
+                "$..book[?@.isbn]",
+                "$..book[?@.price<10]",
+                "$..*",
+                "$[1:5:2]",
+                "$[::-1]",
+                "$[ 1 : 2 : 3 ]",
+                "$[?!@.a && (@.b == 'x' || @.c != null)]",
+                "$[?length(@.a) >= 2 && count(@.*) == 1]",
+                "$[?match(@.date, '1974-05-..') && !search(@.b, '[BR]')]",
+                "$[?value(@..color) == \"red\"]",
+                "$ .a\n[0]",
+                "$.ünïcödé._x1",
+            ];
+            for query in queries {
+                assert!(JsonPath::parse(query).is_ok(), "{}", query);
+            }
+        }
+    }
+
+    mod neg {
+        use super::{loc, render_error, ColorMode, JsonPath, JsonPathError, NumberError, StringError};
+
+        #[test]
+        fn reports_unexpected_characters() {
+            assert_eq!(
+                JsonPath::parse("store"),
+                Err(JsonPathError::UnexpectedChar {
+                    at: loc(0, 1, 1),
+                    found: 's',
+                    expected: "'$' at the start of the query"
+                })
+            );
+            assert!(matches!(JsonPath::parse("$.a "), Err(JsonPathError::UnexpectedChar { found: ' ', .. })));
+            assert!(matches!(JsonPath::parse("$[1 2]"), Err(JsonPathError::UnexpectedChar { found: '2', .. })));
+            assert!(matches!(JsonPath::parse("$.1"), Err(JsonPathError::UnexpectedChar { found: '1', .. })));
+            assert!(matches!(JsonPath::parse("$[?@.a = 1]"), Err(JsonPathError::UnexpectedChar { found: '=', .. })));
+        }
+
+        #[test]
+        fn reports_unexpected_end() {
+            assert!(matches!(JsonPath::parse(""), Err(JsonPathError::UnexpectedEnd { .. })));
+            assert!(matches!(JsonPath::parse("$["), Err(JsonPathError::UnexpectedEnd { .. })));
+            assert!(matches!(JsonPath::parse("$.a["), Err(JsonPathError::UnexpectedEnd { at, .. }) if at == loc(4, 1, 5)));
+        }
+
+        #[test]
+        fn rejects_bad_literals() {
+            assert_eq!(
+                JsonPath::parse("$['a\\\"']"),
+                Err(JsonPathError::InvalidString { at: loc(4, 1, 5), reason: StringError::InvalidEscape { found: b'"' } })
+            );
+            assert!(matches!(
+                JsonPath::parse("$['abc"),
+                Err(JsonPathError::InvalidString { reason: StringError::Unterminated, .. })
+            ));
+            assert!(matches!(
+                JsonPath::parse("$['\\uD800']"),
+                Err(JsonPathError::InvalidString { reason: StringError::LoneHighSurrogate { code: 0xD800 }, .. })
+            ));
+            assert!(matches!(
+                JsonPath::parse("$[01]"),
+                Err(JsonPathError::InvalidNumber { reason: NumberError::LeadingZero, .. })
+            ));
+            assert!(matches!(
+                JsonPath::parse("$[-0]"),
+                Err(JsonPathError::InvalidNumber { reason: NumberError::LeadingZero, .. })
+            ));
+            assert!(matches!(
+                JsonPath::parse("$[?@.a == 1.]"),
+                Err(JsonPathError::InvalidNumber { reason: NumberError::MissingFracDigit, .. })
+            ));
+            assert_eq!(JsonPath::parse("$[9007199254740992]"), Err(JsonPathError::IndexOutOfRange { at: loc(2, 1, 3) }));
+        }
+
+        #[test]
+        fn rejects_ill_typed_filters() {
+            assert_eq!(JsonPath::parse("$[?@.* == 1]"), Err(JsonPathError::NonSingularQuery { at: loc(3, 1, 4) }));
+            assert!(matches!(JsonPath::parse("$[?@..a == 1]"), Err(JsonPathError::NonSingularQuery { .. })));
+            assert!(matches!(JsonPath::parse("$[?1]"), Err(JsonPathError::NotATest { .. })));
+            assert!(matches!(JsonPath::parse("$[?length(@)]"), Err(JsonPathError::NotATest { .. })));
+            assert!(matches!(
+                JsonPath::parse("$[?match(@.a, 'x') == true]"),
+                Err(JsonPathError::NotComparable { function: "match", .. })
+            ));
+            assert_eq!(
+                JsonPath::parse("$[?foo(@)]"),
+                Err(JsonPathError::UnknownFunction { at: loc(3, 1, 4), name: "foo".to_string() })
+            );
+            assert!(matches!(
+                JsonPath::parse("$[?count(@.a, @.b) > 1]"),
+                Err(JsonPathError::ArgumentCount { function: "count", expected: 1, found: 2, .. })
+            ));
+            assert_eq!(
+                JsonPath::parse("$[?length(@.*) > 1]"),
+                Err(JsonPathError::ArgumentType {
+                    at: loc(10, 1, 11),
+                    function: "length",
+                    expected: "a value: a literal, a singular query or length()/count()/value()"
+                })
+            );
+            assert!(matches!(JsonPath::parse("$[?count(1) > 1]"), Err(JsonPathError::ArgumentType { .. })));
+            assert!(matches!(JsonPath::parse("$[?length(@.a == 1) > 1]"), Err(JsonPathError::ArgumentType { .. })));
+        }
+
+        #[test]
+        fn deep_nesting_is_an_error() {
+            let query = format!("$[?{}@.a{}]", "(".repeat(1000), ")".repeat(1000));
+            assert!(matches!(JsonPath::parse(&query), Err(JsonPathError::TooDeep { .. })));
+        }
+
+        #[test]
+        fn renders_with_a_caret() {
+            let query = "$.store[?@.* > 1]";
+            let report = render_error(&JsonPath::parse(query).unwrap_err(), query, ColorMode::Plain);
+            assert!(report.starts_with("error: only a singular query can be compared"), "{}", report);
+            assert!(report.contains("^"), "{}", report);
+        }
+    }
+}
+
+mod selectors {
+    use super::{bookstore, count, parse, paths, select};
+
+    #[test]
+    fn rfc_bookstore_examples() {
+        let store = bookstore();
+        let authors = r#"["Nigel Rees", "Evelyn Waugh", "Herman Melville", "J. R. R. Tolkien"]"#;
+        assert_eq!(select("$.store.book[*].author", &store), parse(authors));
+        assert_eq!(select("$..author", &store), parse(authors));
+        assert_eq!(select("$.store..price", &store), parse("[8.95, 12.99, 8.99, 22.99, 399]"));
+        assert_eq!(select("$..book[2].author", &store), parse(r#"["Herman Melville"]"#));
+        assert_eq!(select("$..book[2].publisher", &store), parse("[]"));
+        assert_eq!(select("$..book[-1].title", &store), parse(r#"["The Lord of the Rings"]"#));
+        assert_eq!(select("$..book[0,1].title", &store), parse(r#"["Sayings of the Century", "Sword of Honour"]"#));
+        assert_eq!(select("$..book[:2].price", &store), parse("[8.95, 12.99]"));
+        assert_eq!(select("$..book[?@.isbn].title", &store), parse(r#"["Moby Dick", "The Lord of the Rings"]"#));
+        assert_eq!(select("$..book[?@.price<10].title", &store), parse(r#"["Sayings of the Century", "Moby Dick"]"#));
+        assert_eq!(count("$..*", &store), 27);
+    }
+
+    #[test]
+    fn slices() {
+        let array = parse(r#"["a", "b", "c", "d", "e", "f", "g"]"#);
+        assert_eq!(select("$[1:3]", &array), parse(r#"["b", "c"]"#));
+        assert_eq!(select("$[5:]", &array), parse(r#"["f", "g"]"#));
+        assert_eq!(select("$[1:5:2]", &array), parse(r#"["b", "d"]"#));
+        assert_eq!(select("$[5:1:-2]", &array), parse(r#"["f", "d"]"#));
+        assert_eq!(select("$[::-1]", &array), parse(r#"["g", "f", "e", "d", "c", "b", "a"]"#));
+        assert_eq!(select("$[-2:]", &array), parse(r#"["f", "g"]"#));
+        assert_eq!(select("$[0:100:0]", &array), parse("[]"));
+        assert_eq!(select("$[-100:100]", &array), array);
+    }
+
+    #[test]
+    fn selectors_skip_the_wrong_kind_of_node() {
+        let doc = parse(r#"{"a": [1], "0": "zero", "s": "text"}"#);
+        assert_eq!(select("$[0]", &doc), parse("[]"));
+        assert_eq!(select("$['0']", &doc), parse(r#"["zero"]"#));
+        assert_eq!(select("$.a.b", &doc), parse("[]"));
+        assert_eq!(select("$.s[0]", &doc), parse("[]"));
+        assert_eq!(select("$.s.*", &doc), parse("[]"));
+    }
+
+    #[test]
+    fn descendants_come_in_document_order() {
+        let doc = parse(r#"{"o": {"j": 1, "k": 2}, "a": [5, 3, [{"j": 4}, {"k": 6}]]}"#);
+        assert_eq!(paths("$..j", &doc), ["$['o']['j']", "$['a'][2][0]['j']"]);
+        assert_eq!(paths("$..[0]", &doc), ["$['a'][0]", "$['a'][2][0]"]);
+    }
+
+    #[test]
+    fn duplicate_selectors_give_duplicate_nodes() {
+        let doc = parse("[10, 20]");
+        assert_eq!(select("$[0, 0, -2]", &doc), parse("[10, 10, 10]"));
+    }
+
+    #[test]
+    fn deep_documents() {
+        let depth = 100_000;
+        let doc = parse(&format!("{}1{}", r#"{"a":"#.repeat(depth), "}".repeat(depth)));
+        assert_eq!(count("$..a", &doc), depth);
+        assert_eq!(select("$..*[?@ == 1]", &doc), parse("[1]"));
+    }
+}
+
+mod filters {
+    use super::{count, parse, select, JsonValue};
+
+    #[test]
+    fn comparisons_follow_the_rfc_table() {
+        // RFC 9535, table 11
+        let doc = parse(r#"{"obj": {"x": "y"}, "arr": [2, 3]}"#);
+        let cases = [
+            ("$.absent1 == $.absent2", true),
+            ("$.absent1 <= $.absent2", true),
+            ("$.absent == 'g'", false),
+            ("$.absent1 != $.absent2", false),
+            ("$.absent != 'g'", true),
+            ("1 <= 2", true),
+            ("1 > 2", false),
+            ("13 == '13'", false),
+            ("'a' <= 'b'", true),
+            ("'a' > 'b'", false),
+            ("$.obj == $.arr", false),
+            ("$.obj != $.arr", true),
+            ("$.obj == $.obj", true),
+            ("$.obj != $.obj", false),
+            ("$.arr == $.arr", true),
+            ("$.arr != $.arr", false),
+            ("$.obj == 17", false),
+            ("$.obj != 17", true),
+            ("$.obj <= $.arr", false),
+            ("$.obj < $.arr", false),
+            ("$.obj <= $.obj", true),
+            ("$.arr <= $.arr", true),
+            ("1 <= $.arr", false),
+            ("1 >= $.arr", false),
+            ("1 > $.arr", false),
+            ("1 < $.arr", false),
+            ("true <= true", true),
+            ("true > true", false),
+        ];
+        // a one-element array around the document: the filter selects it or nothing
+        let wrapped = JsonValue::Array(vec![doc]);
+        for (comparison, expected) in cases {
+            let query = format!("$[?{}]", comparison.replace("$.", "$[0]."));
+            assert_eq!(count(&query, &wrapped), expected as usize, "{}", comparison);
+        }
+    }
+
+    #[test]
+    fn numbers_compare_by_value() {
+        let doc = parse("[1, 1.0, 1e0, 2, 10]");
+        assert_eq!(select("$[?@ == 1]", &doc), parse("[1, 1.0, 1e0]"));
+        assert_eq!(select("$[?@ > 1.5]", &doc), parse("[2, 10]"));
+        assert_eq!(select("$[?@ < 9007199254740993]", &parse("[9007199254740992]")), parse("[9007199254740992]"));
+    }
+
+    #[test]
+    fn logical_operators() {
+        let doc = parse(r#"[{"a": 1, "b": "x"}, {"a": 2}, {"b": "y"}, {}]"#);
+        assert_eq!(select("$[?@.a && @.b]", &doc), parse(r#"[{"a": 1, "b": "x"}]"#));
+        assert_eq!(select("$[?@.a || @.b].a", &doc), parse("[1, 2]"));
+        assert_eq!(select("$[?!@.a]", &doc), parse(r#"[{"b": "y"}, {}]"#));
+        assert_eq!(select("$[?!(@.a == 1 || @.b == 'y')]", &doc), parse(r#"[{"a": 2}, {}]"#));
+        assert_eq!(select("$[?@.a == 2 || @.a == 1 && @.b == 'x'].a", &doc), parse("[1, 2]"));
+    }
+
+    #[test]
+    fn existence_is_not_truthiness() {
+        let doc = parse(r#"[{"a": null}, {"a": false}, {"a": []}, {}]"#);
+        assert_eq!(count("$[?@.a]", &doc), 3);
+    }
+
+    #[test]
+    fn absolute_queries_inside_filters() {
+        let doc = parse(r#"{"limit": 2, "items": [1, 2, 3]}"#);
+        assert_eq!(select("$.items[?@ > $.limit]", &doc), parse("[3]"));
+    }
+
+    #[test]
+    fn filters_apply_to_object_members() {
+        let doc = parse(r#"{"a": {"n": 1}, "b": {"n": 5}, "c": 3}"#);
+        assert_eq!(select("$[?@.n > 2]", &doc), parse(r#"[{"n": 5}]"#));
+    }
+}
+
+mod functions {
+    use super::{parse, select};
+
+    #[test]
+    fn length() {
+        let doc = parse(r#"["ab", "ünï", [1, 2, 3], {"a": 1}, 5, null]"#);
+        assert_eq!(select("$[?length(@) == 3]", &doc), parse(r#"["ünï", [1, 2, 3]]"#));
+        assert_eq!(select("$[?length(@) == 1]", &doc), parse(r#"[{"a": 1}]"#));
+        // length of a number is Nothing, which only equals Nothing
+        assert_eq!(select("$[?length(@) == length(@.absent)]", &doc), parse("[5, null]"));
+    }
+
+    #[test]
+    fn count_and_value() {
+        let doc = parse(r#"[{"a": [1, 2]}, {"a": [3]}, {"a": {"b": {"c": 7}}}]"#);
+        assert_eq!(select("$[?count(@.a.*) == 2].a", &doc), parse("[[1, 2]]"));
+        assert_eq!(select("$[?value(@..c) == 7].a", &doc), parse(r#"[{"b": {"c": 7}}]"#));
+        assert_eq!(select("$[?value(@.a.*) == 3].a", &doc), parse("[[3]]"));
+    }
+
+    #[test]
+    fn match_is_anchored_and_search_is_not() {
+        let doc = parse(r#"["1974-05-01", "x1974-05-01", "1974-05-011", "Bob", "Rob", "bobby", 7]"#);
+        assert_eq!(select("$[?match(@, '1974-05-..')]", &doc), parse(r#"["1974-05-01"]"#));
+        assert_eq!(
+            select("$[?search(@, '1974-05-..')]", &doc),
+            parse(r#"["1974-05-01", "x1974-05-01", "1974-05-011"]"#)
+        );
+        assert_eq!(select("$[?search(@, '[BR]ob')]", &doc), parse(r#"["Bob", "Rob"]"#));
+        assert_eq!(select("$[?match(@, '\\\\p{Lu}.*')]", &doc), parse(r#"["Bob", "Rob"]"#));
+    }
+
+    #[test]
+    fn iregexp_differs_from_regex_syntax() {
+        let doc = parse(r#"["a\nb", "a^b", "a$", "(?i)x", "X"]"#);
+        // '.' does not match a line break
+        assert_eq!(select("$[?match(@, 'a.b')]", &doc), parse(r#"["a^b"]"#));
+        // '^' and '$' are ordinary characters
+        assert_eq!(select("$[?search(@, '\\\\^b')]", &doc), parse(r#"["a^b"]"#));
+        assert_eq!(select("$[?match(@, 'a$')]", &doc), parse(r#"["a$"]"#));
+        // not I-Regexp: the function is false rather than an error
+        assert_eq!(select("$[?match(@, '(?i)x')]", &doc), parse("[]"));
+        assert_eq!(select("$[?match(@, '[a')]", &doc), parse("[]"));
+        // inside a class '(' and '?' are plain characters, in either order
+        let doc = parse(r#"["(", "?", "a"]"#);
+        assert_eq!(select("$[?match(@, '[(?]')]", &doc), parse(r#"["(", "?"]"#));
+        assert_eq!(select("$[?match(@, '[?(]')]", &doc), parse(r#"["(", "?"]"#));
+    }
+}
+
+mod normalized_paths {
+    use super::{bookstore, parse, paths, JsonPath, JsonPointer};
+
+    #[test]
+    fn results_carry_their_paths() {
+        let store = bookstore();
+        assert_eq!(
+            paths("$..book[?@.price<10].title", &store),
+            ["$['store']['book'][0]['title']", "$['store']['book'][2]['title']"]
+        );
+        assert_eq!(paths("$", &store), ["$"]);
+    }
+
+    #[test]
+    fn names_are_escaped() {
+        let doc = parse(r#"{"it's": 1, "a\\b": 2, "\n\u0001\u001f": 3, "ü/~": 4}"#);
+        assert_eq!(paths("$.*", &doc), ["$['it\\'s']", "$['a\\\\b']", "$['\\n\\u0001\\u001f']", "$['ü/~']"]);
+    }
+
+    #[test]
+    fn convert_to_pointers() {
+        let doc = parse(r#"{"a/b": [{"~": 1}]}"#);
+        let nodes = JsonPath::parse("$..*[?@ == 1]").unwrap().query(&doc);
+        let pointer = nodes[0].path.to_pointer();
+        assert_eq!(pointer, JsonPointer::parse("/a~1b/0/~0").unwrap());
+        assert_eq!(doc.get_pointer(&pointer), Ok(nodes[0].value));
+    }
+}
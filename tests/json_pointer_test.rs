@@ -0,0 +1,275 @@
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_pointer::{escape_token, JsonPointer, PointerError};
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+// The example document from RFC 6901, section 5.
+fn rfc_document() -> JsonValue {
+    parse(
+        r#"{
+            "foo": ["bar", "baz"],
+            "": 0,
+            "a/b": 1,
+            "c%d": 2,
+            "e^f": 3,
+            "g|h": 4,
+            "i\\j": 5,
+            "k\"l": 6,
+            " ": 7,
+            "m~n": 8
+        }"#,
+    )
+}
+
+mod syntax {
+    use super::{escape_token, JsonPointer, PointerError};
+
+    mod pos {
+        use super::{escape_token, JsonPointer};
+
+        #[test]
+        fn tokens_are_unescaped() {
+            let pointer = JsonPointer::parse("/a~1b/m~0n/~01/0").unwrap();
+            assert_eq!(pointer.tokens(), ["a/b", "m~n", "~1", "0"]);
+        }
+
+        #[test]
+        fn display_round_trips() {
+            for input in ["", "/", "/foo/0", "/a~1b", "/m~0n", "/~01", "//x/"] {
+                assert_eq!(JsonPointer::parse(input).unwrap().to_string(), input);
+            }
+        }
+
+        #[test]
+        fn from_tokens_escapes() {
+            let pointer = JsonPointer::from_tokens(["a/b", "~"]);
+            assert_eq!(pointer.to_string(), "/a~1b/~0");
+            assert_eq!(escape_token("~/"), "~0~1");
+        }
+
+        #[test]
+        fn parent_and_prefix() {
+            let pointer = JsonPointer::parse("/a/b").unwrap();
+            assert_eq!(pointer.parent(), Some(JsonPointer::parse("/a").unwrap()));
+            assert!(JsonPointer::root().is_prefix_of(&pointer));
+            assert!(!pointer.is_prefix_of(&JsonPointer::parse("/a").unwrap()));
+            assert_eq!(JsonPointer::root().parent(), None);
+        }
+    }
+
+    mod neg {
+        use super::{JsonPointer, PointerError};
+
+        #[test]
+        fn missing_leading_slash() {
+            assert_eq!(
+                JsonPointer::parse("a/b"),
+                Err(PointerError::MissingLeadingSlash { pointer: "a/b".to_string() })
+            );
+        }
+
+        #[test]
+        fn invalid_escape() {
+            assert_eq!(
+                JsonPointer::parse("/ok/b~2"),
+                Err(PointerError::InvalidEscape { pointer: "/ok/b~2".to_string(), offset: 5 })
+            );
+            assert!(matches!(JsonPointer::parse("/trailing~"), Err(PointerError::InvalidEscape { offset: 9, .. })));
+        }
+    }
+}
+
+mod lookup {
+    use super::{parse, rfc_document, JsonValue, PointerError};
+
+    mod pos {
+        use super::{parse, rfc_document, JsonValue};
+
+        #[test]
+        fn rfc_examples() {
+            let doc = rfc_document();
+            assert_eq!(doc.pointer(""), Ok(&doc));
+            assert_eq!(doc.pointer("/foo"), Ok(&parse(r#"["bar", "baz"]"#)));
+            assert_eq!(doc.pointer("/foo/0"), Ok(&JsonValue::JsonString("bar".to_string())));
+
+            let expected = [("/", 0.0), ("/a~1b", 1.0), ("/c%d", 2.0), ("/e^f", 3.0), ("/g|h", 4.0),
+                            ("/i\\j", 5.0), ("/k\"l", 6.0), ("/ ", 7.0), ("/m~0n", 8.0)];
+            for (pointer, n) in expected {
+                assert_eq!(doc.pointer(pointer), Ok(&JsonValue::Number(n.into())), "pointer was: {:?}", pointer);
+            }
+        }
+
+        #[test]
+        fn pointer_mut_changes_in_place() {
+            let mut doc = parse(r#"{"a": [1, {"b": null}]}"#);
+            *doc.pointer_mut("/a/1/b").unwrap() = JsonValue::Boolean(true);
+            assert_eq!(doc, parse(r#"{"a": [1, {"b": true}]}"#));
+        }
+
+        #[test]
+        fn deep_pointer() {
+            let depth = 10_000;
+            let doc = parse(&format!("{}{}", "[".repeat(depth), "]".repeat(depth)));
+            let pointer = "/0".repeat(depth - 1);
+            assert_eq!(doc.pointer(&pointer), Ok(&JsonValue::Array(vec![])));
+        }
+    }
+
+    mod neg {
+        use super::{parse, PointerError};
+
+        #[test]
+        fn missing_key() {
+            let doc = parse(r#"{"a": {"b": 1}}"#);
+            assert_eq!(
+                doc.pointer("/a/c/d"),
+                Err(PointerError::MissingKey { path: "/a/c".to_string(), key: "c".to_string() })
+            );
+        }
+
+        #[test]
+        fn index_out_of_bounds() {
+            let doc = parse(r#"{"a": [1, 2]}"#);
+            assert_eq!(
+                doc.pointer("/a/2"),
+                Err(PointerError::IndexOutOfBounds { path: "/a/2".to_string(), index: 2, len: 2 })
+            );
+            assert_eq!(
+                doc.pointer("/a/-"),
+                Err(PointerError::IndexOutOfBounds { path: "/a/-".to_string(), index: 2, len: 2 })
+            );
+        }
+
+        #[test]
+        fn invalid_index() {
+            let doc = parse("[[1]]");
+            for token in ["01", "x", "-1", "1.0", "", "99999999999999999999999"] {
+                let pointer = format!("/0/{}", token);
+                assert_eq!(
+                    doc.pointer(&pointer),
+                    Err(PointerError::InvalidIndex { path: pointer.clone(), token: token.to_string() })
+                );
+            }
+        }
+
+        #[test]
+        fn not_a_container() {
+            let doc = parse(r#"{"a": "text"}"#);
+            assert_eq!(doc.pointer("/a/0"), Err(PointerError::NotAContainer { path: "/a/0".to_string() }));
+        }
+
+        #[test]
+        fn error_message() {
+            let doc = parse(r#"{"a": [1]}"#);
+            let err = doc.pointer("/a/5").unwrap_err();
+            assert_eq!(err.to_string(), "index 5 is out of bounds for an array of length 1 at /a/5");
+        }
+    }
+}
+
+mod mutation {
+    use super::{parse, JsonValue, PointerError};
+
+    mod pos {
+        use super::{parse, JsonValue};
+
+        #[test]
+        fn insert_object_member() {
+            let mut doc = parse(r#"{"a": 1}"#);
+            assert_eq!(doc.insert_at_pointer("/b", JsonValue::Null), Ok(None));
+            assert_eq!(doc.insert_at_pointer("/a", JsonValue::Boolean(true)), Ok(Some(JsonValue::Number(1.0.into()))));
+            assert_eq!(doc, parse(r#"{"a": true, "b": null}"#));
+        }
+
+        #[test]
+        fn insert_array_element_shifts() {
+            let mut doc = parse("[1, 3]");
+            doc.insert_at_pointer("/1", JsonValue::Number(2.0.into())).unwrap();
+            doc.insert_at_pointer("/-", JsonValue::Number(4.0.into())).unwrap();
+            doc.insert_at_pointer("/4", JsonValue::Number(5.0.into())).unwrap();
+            assert_eq!(doc, parse("[1, 2, 3, 4, 5]"));
+        }
+
+        #[test]
+        fn insert_at_root_replaces_document() {
+            let mut doc = parse("[1]");
+            assert_eq!(doc.insert_at_pointer("", JsonValue::Null), Ok(Some(parse("[1]"))));
+            assert_eq!(doc, JsonValue::Null);
+        }
+
+        #[test]
+        fn remove_keeps_member_order() {
+            let mut doc = parse(r#"{"a": 1, "b": 2, "c": 3}"#);
+            assert_eq!(doc.remove_at_pointer("/b"), Ok(JsonValue::Number(2.0.into())));
+            assert_eq!(doc, parse(r#"{"a": 1, "c": 3}"#));
+            assert_eq!(doc.pointer("/c"), Ok(&JsonValue::Number(3.0.into())));
+        }
+
+        #[test]
+        fn remove_array_element_and_root() {
+            let mut doc = parse(r#"{"x~y": [1, [2], 3]}"#);
+            assert_eq!(doc.remove_at_pointer("/x~0y/1"), Ok(parse("[2]")));
+            assert_eq!(doc, parse(r#"{"x~y": [1, 3]}"#));
+            assert_eq!(doc.remove_at_pointer(""), Ok(parse(r#"{"x~y": [1, 3]}"#)));
+            assert_eq!(doc, JsonValue::Null);
+        }
+    }
+
+    mod neg {
+        use super::{parse, JsonValue, PointerError};
+
+        #[test]
+        fn insert_needs_existing_parent() {
+            let mut doc = parse(r#"{"a": {}}"#);
+            assert_eq!(
+                doc.insert_at_pointer("/b/c", JsonValue::Null),
+                Err(PointerError::MissingKey { path: "/b".to_string(), key: "b".to_string() })
+            );
+            assert_eq!(doc, parse(r#"{"a": {}}"#));
+        }
+
+        #[test]
+        fn insert_past_the_end() {
+            let mut doc = parse("[1]");
+            assert_eq!(
+                doc.insert_at_pointer("/2", JsonValue::Null),
+                Err(PointerError::IndexOutOfBounds { path: "/2".to_string(), index: 2, len: 1 })
+            );
+        }
+
+        #[test]
+        fn insert_into_leaf() {
+            let mut doc = parse(r#"{"a": 1}"#);
+            assert_eq!(
+                doc.insert_at_pointer("/a/b", JsonValue::Null),
+                Err(PointerError::NotAContainer { path: "/a/b".to_string() })
+            );
+        }
+
+        #[test]
+        fn remove_missing() {
+            let mut doc = parse(r#"{"a": [1]}"#);
+            assert_eq!(
+                doc.remove_at_pointer("/b"),
+                Err(PointerError::MissingKey { path: "/b".to_string(), key: "b".to_string() })
+            );
+            assert_eq!(
+                doc.remove_at_pointer("/a/-"),
+                Err(PointerError::IndexOutOfBounds { path: "/a/-".to_string(), index: 1, len: 1 })
+            );
+            assert_eq!(
+                doc.remove_at_pointer("/a/01"),
+                Err(PointerError::InvalidIndex { path: "/a/01".to_string(), token: "01".to_string() })
+            );
+        }
+
+        #[test]
+        fn malformed_pointer() {
+            let mut doc = parse("{}");
+            assert!(matches!(doc.remove_at_pointer("a"), Err(PointerError::MissingLeadingSlash { .. })));
+        }
+    }
+}
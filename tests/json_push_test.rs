@@ -0,0 +1,221 @@
+use json_parser_rust::json_definitions::{
+    CommentPolicy, DuplicateKeyPolicy, JsonParsingErrorV3, JsonValue, JsonValueRef, Limit, ParseLimits, ParseOptions,
+};
+use json_parser_rust::json_events::{JsonEvent, JsonEventParser};
+use json_parser_rust::json_lexer::SourceLocation;
+use json_parser_rust::json_non_recursive::{process_json_string_v3, process_json_string_v3_with_options};
+use json_parser_rust::json_push::{JsonPushEventParser, JsonPushParser};
+
+fn loc(offset: usize, line: usize, column: usize) -> SourceLocation {
+    SourceLocation { offset, line, column }
+}
+
+fn push_chunks(chunks: &[&[u8]], options: &ParseOptions) -> Result<JsonValue, JsonParsingErrorV3> {
+    let mut parser = JsonPushParser::with_options(options);
+    for chunk in chunks {
+        parser.feed(chunk)?;
+    }
+    parser.finish()
+}
+
+fn push_events(chunks: &[&[u8]]) -> Result<Vec<JsonEvent<'static>>, JsonParsingErrorV3> {
+    let mut parser = JsonPushEventParser::new();
+    let mut events = Vec::new();
+    for chunk in chunks {
+        events.extend(parser.feed(chunk)?);
+    }
+    events.extend(parser.finish()?);
+    Ok(events)
+}
+
+const DOCUMENTS: &[&str] = &[
+    r#"{"name": "caf\u00e9 \"quoted\" \\ end", "tags": ["a", "b"], "n": null}"#,
+    "[true, false, null, -0, 12.5e-3, 1E+2, 12345678901234567890]",
+    "{\n  \"nested\": {\"deep\": [[[], {}], [1]]},\n  \"emoji\": \"\u{1F600} ü\"\n}",
+    "  42  ",
+    "\"just a string\"",
+    r#"["\uD83D\uDE00", "x\u00e9\uDBFF\uDFFFy"]"#,
+];
+
+const BROKEN: &[&str] = &[
+    "",
+    "   ",
+    "[1, 2,]",
+    "{\"a\" 1}",
+    "{\"a\": tru}",
+    "[\"unterminated",
+    "[\"bad \\x escape\"]",
+    "[\"\\u12\"]",
+    "[01]",
+    "[1.]",
+    "[-]",
+    "{\"a\": 1}\n[2]",
+    "[[",
+    "[\n  nul",
+    r#"["\uD83D"]"#,
+    r#"["\uD83D\u0041"]"#,
+    r#"["\uDE00"]"#,
+];
+
+mod pos {
+    use super::{
+        process_json_string_v3, process_json_string_v3_with_options, push_chunks, push_events, CommentPolicy,
+        JsonEvent, JsonEventParser, JsonPushEventParser, JsonPushParser, JsonValueRef, ParseOptions, DOCUMENTS,
+    };
+
+    #[test]
+    fn single_chunk() {
+        for input in DOCUMENTS {
+            assert_eq!(push_chunks(&[input.as_bytes()], &ParseOptions::default()), process_json_string_v3(input));
+        }
+    }
+
+    #[test]
+    fn split_at_every_byte() {
+        for input in DOCUMENTS {
+            let bytes = input.as_bytes();
+            let expected = process_json_string_v3(input);
+            for split in 0..=bytes.len() {
+                let (a, b) = bytes.split_at(split);
+                assert_eq!(push_chunks(&[a, b], &ParseOptions::default()), expected, "input {:?} split at {}", input, split);
+            }
+        }
+    }
+
+    #[test]
+    fn one_byte_at_a_time() {
+        for input in DOCUMENTS {
+            let chunks: Vec<&[u8]> = input.as_bytes().chunks(1).collect();
+            assert_eq!(push_chunks(&chunks, &ParseOptions::default()), process_json_string_v3(input));
+
+            let expected: Result<Vec<_>, _> = JsonEventParser::new(input).collect();
+            assert_eq!(push_events(&chunks), expected, "input was: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn comments_split_anywhere() {
+        let input = "/* head */ [1, // one\n 2 /**/] // tail";
+        for comments in [CommentPolicy::Skip, CommentPolicy::Tokens] {
+            let options = ParseOptions { comments, ..ParseOptions::default() };
+            let expected = process_json_string_v3_with_options(input, &options);
+            assert!(expected.is_ok());
+            for split in 0..=input.len() {
+                let (a, b) = input.as_bytes().split_at(split);
+                assert_eq!(push_chunks(&[a, b], &options), expected, "split at {}", split);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_chunks_are_harmless() {
+        let chunks: &[&[u8]] = &[b"", b"[1", b"", b"", b"0]", b""];
+        assert_eq!(push_chunks(chunks, &ParseOptions::default()), process_json_string_v3("[10]"));
+    }
+
+    #[test]
+    fn trailing_number_waits_for_finish() {
+        let mut parser = JsonPushEventParser::new();
+        assert_eq!(parser.feed(b"12"), Ok(vec![]));
+        assert_eq!(parser.feed(b"34"), Ok(vec![]));
+        assert_eq!(parser.finish(), Ok(vec![JsonEvent::Value(JsonValueRef::Number(1234.into()))]));
+    }
+
+    #[test]
+    fn events_arrive_as_soon_as_they_are_complete() {
+        let mut parser = JsonPushEventParser::new();
+        assert_eq!(parser.feed(b"[\"ab"), Ok(vec![JsonEvent::StartArray]));
+        assert_eq!(parser.depth(), 1);
+        assert_eq!(
+            parser.feed(b"c\" "),
+            Ok(vec![JsonEvent::Value(JsonValueRef::JsonString("abc".into()))])
+        );
+        assert_eq!(parser.feed(b"]"), Ok(vec![JsonEvent::EndArray]));
+        assert_eq!(parser.finish(), Ok(vec![]));
+    }
+
+    #[test]
+    fn million_deep_in_small_chunks() {
+        let depth = 1_000_000;
+        let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let mut parser = JsonPushParser::new();
+        for chunk in input.as_bytes().chunks(1460) {
+            parser.feed(chunk).expect("deep array should parse");
+        }
+        assert!(parser.finish().is_ok());
+    }
+}
+
+mod neg {
+    use super::{
+        loc, process_json_string_v3, process_json_string_v3_with_options, push_chunks, push_events,
+        DuplicateKeyPolicy, JsonEventParser, JsonParsingErrorV3, JsonPushParser, Limit, ParseLimits, ParseOptions,
+        BROKEN,
+    };
+
+    #[test]
+    fn same_errors_as_v3_wherever_the_input_is_split() {
+        for input in BROKEN {
+            let bytes = input.as_bytes();
+            let expected = process_json_string_v3(input);
+            for split in 0..=bytes.len() {
+                let (a, b) = bytes.split_at(split);
+                assert_eq!(push_chunks(&[a, b], &ParseOptions::default()), expected, "input {:?} split at {}", input, split);
+            }
+        }
+    }
+
+    #[test]
+    fn same_events_error_as_the_pull_parser() {
+        for input in BROKEN {
+            let chunks: Vec<&[u8]> = input.as_bytes().chunks(1).collect();
+            let expected: Result<Vec<_>, _> = JsonEventParser::new(input).collect();
+            assert_eq!(push_events(&chunks), expected, "input was: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn error_is_reported_by_the_chunk_that_completes_it() {
+        let mut parser = JsonPushParser::new();
+        assert_eq!(parser.feed(b"[1 "), Ok(()));
+        let err = parser.feed(b"2]").unwrap_err();
+        assert!(matches!(err, JsonParsingErrorV3::MissingComma { at, .. } if at == loc(3, 1, 4)));
+
+        // the parser stays failed
+        assert_eq!(parser.feed(b"ignored"), Err(err.clone()));
+        assert_eq!(parser.finish(), Err(err));
+    }
+
+    #[test]
+    fn limits_and_duplicate_keys() {
+        let options = ParseOptions {
+            limits: ParseLimits { max_depth: 2, max_string_bytes: 4, ..ParseLimits::UNLIMITED },
+            duplicate_keys: DuplicateKeyPolicy::Error,
+            ..ParseOptions::default()
+        };
+        for input in ["[[[1]]]", "[\"toolong\"]", "{\"a\": 1, \"a\": 2}"] {
+            let chunks: Vec<&[u8]> = input.as_bytes().chunks(2).collect();
+            let expected = process_json_string_v3_with_options(input, &options);
+            assert!(expected.is_err());
+            assert_eq!(push_chunks(&chunks, &options), expected, "input was: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn document_limit() {
+        let limits = ParseLimits { max_document_bytes: 16, ..ParseLimits::UNLIMITED };
+        let options = ParseOptions { limits, ..ParseOptions::default() };
+        let chunks: &[&[u8]] = &[b"[1, 2, 3, ", b"4, 5, 6]"];
+        assert_eq!(
+            push_chunks(chunks, &options),
+            Err(JsonParsingErrorV3::LimitExceeded { limit: Limit::DocumentBytes, at: loc(16, 1, 17) })
+        );
+        assert_eq!(push_chunks(&[b"[1, 2]"], &options), process_json_string_v3("[1, 2]"));
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(push_events(&[]), Err(JsonParsingErrorV3::EmptyJsonFile));
+        assert_eq!(push_chunks(&[b""], &ParseOptions::default()), Err(JsonParsingErrorV3::EmptyJsonFile));
+    }
+}
@@ -0,0 +1,136 @@
+use json_parser_rust::json;
+use json_parser_rust::json_definitions::{JsonParsingErrorV3, JsonValue, TokenTag};
+use json_parser_rust::json_diagnostics::{render_error, ColorMode};
+use json_parser_rust::json_events::{JsonEvent, JsonEventParser};
+use json_parser_rust::json_sequence::{JsonDocuments, JsonSequenceError, JsonTextSequence};
+
+fn documents(input: &str) -> Vec<Result<JsonValue, JsonParsingErrorV3>> {
+    JsonDocuments::new(input).collect()
+}
+
+fn sequence(input: &str) -> Vec<Result<JsonValue, JsonSequenceError>> {
+    JsonTextSequence::new(input).collect()
+}
+
+mod concatenated {
+    mod pos {
+        use super::super::{documents, json, JsonEvent, JsonEventParser};
+
+        #[test]
+        fn back_to_back_documents() {
+            let values: Vec<_> = documents(r#"{"a":1}{"a":2}[3]"x"null"#).into_iter().map(Result::unwrap).collect();
+            assert_eq!(values, [json!({"a": 1}), json!({"a": 2}), json!([3]), json!("x"), json!(null)]);
+        }
+
+        #[test]
+        fn whitespace_separated_scalars() {
+            let values: Vec<_> = documents(" 1 2\n-3.5\ttrue ").into_iter().map(Result::unwrap).collect();
+            assert_eq!(values, [json!(1), json!(2), json!(-3.5), json!(true)]);
+        }
+
+        #[test]
+        fn empty_input_is_zero_documents() {
+            assert!(documents("").is_empty());
+            assert!(documents(" \n ").is_empty());
+        }
+
+        #[test]
+        fn events_return_to_depth_zero_between_documents() {
+            let mut parser = JsonEventParser::multiple_documents("[1] {}", &Default::default());
+            let mut ends = Vec::new();
+            while let Some(event) = parser.next() {
+                let event = event.unwrap();
+                if parser.depth() == 0 {
+                    ends.push(event);
+                }
+            }
+            assert_eq!(ends, [JsonEvent::EndArray, JsonEvent::EndObject]);
+        }
+    }
+
+    mod neg {
+        use super::super::{documents, json, JsonParsingErrorV3, TokenTag};
+
+        #[test]
+        fn documents_before_the_error_are_returned() {
+            let results = documents("[1] [2,] [3]");
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0], Ok(json!([1])));
+            let Err(JsonParsingErrorV3::TrailingComma { at, found: TokenTag::RBracket, .. }) = results[1] else {
+                panic!("expected a trailing comma error, got {:?}", results[1]);
+            };
+            assert_eq!(at.offset, 7);
+        }
+
+        #[test]
+        fn unfinished_last_document() {
+            let results = documents("{} {");
+            assert!(matches!(results[1], Err(JsonParsingErrorV3::UnexpectedEOF { frame: Some(_), .. })));
+        }
+
+        #[test]
+        fn separators_between_documents_are_errors() {
+            assert!(matches!(documents("1, 2")[1], Err(JsonParsingErrorV3::CommaOutsideStructure { .. })));
+        }
+    }
+}
+
+mod text_sequence {
+    mod pos {
+        use super::super::{json, sequence};
+
+        #[test]
+        fn records() {
+            let values: Vec<_> =
+                sequence("\u{1E}{\"a\":1}\n\u{1E}[2]\n\u{1E}3\n").into_iter().map(Result::unwrap).collect();
+            assert_eq!(values, [json!({"a": 1}), json!([2]), json!(3)]);
+        }
+
+        #[test]
+        fn empty_records_are_skipped() {
+            let values: Vec<_> = sequence("\u{1E}\u{1E}\n\u{1E}\"x\"").into_iter().map(Result::unwrap).collect();
+            assert_eq!(values, [json!("x")]);
+            assert!(sequence("").is_empty());
+        }
+    }
+
+    mod neg {
+        use super::super::{json, render_error, sequence, ColorMode, JsonParsingErrorV3, JsonSequenceError};
+
+        #[test]
+        fn truncated_records_are_reported_and_skipped() {
+            let input = "\u{1E}{\"a\": [1,\n\u{1E}{\"b\": 2}\n";
+            let results = sequence(input);
+            assert_eq!(results.len(), 2);
+            let Err(JsonSequenceError::Parse { error, .. }) = &results[0] else {
+                panic!("expected a parse error, got {:?}", results[0]);
+            };
+            assert!(matches!(**error, JsonParsingErrorV3::UnexpectedEOF { .. }));
+            assert_eq!(results[1], Ok(json!({"b": 2})));
+        }
+
+        #[test]
+        fn numbers_and_literals_need_trailing_whitespace() {
+            let results = sequence("\u{1E}12\u{1E}true\n\u{1E}nul\n");
+            assert!(matches!(results[0], Err(JsonSequenceError::PossiblyTruncated { at }) if at.offset == 1));
+            assert_eq!(results[1], Ok(json!(true)));
+            assert!(matches!(results[2], Err(JsonSequenceError::Parse { .. })));
+        }
+
+        #[test]
+        fn errors_are_located_in_the_whole_input() {
+            let input = "\u{1E}[1]\n\u{1E}[1 2]\n";
+            let err = sequence(input).remove(1).unwrap_err();
+            assert_eq!(err.to_string(), "expected ',' or ']' after array element, found number at line 2, column 5");
+            let report = render_error(&err, input, ColorMode::Plain);
+            assert!(report.contains("^"), "{}", report);
+        }
+
+        #[test]
+        fn text_before_the_first_separator() {
+            let results = sequence("{}\u{1E}{}\n");
+            assert!(matches!(results[0], Err(JsonSequenceError::MissingRecordSeparator { .. })));
+            assert_eq!(results[1], Ok(json!({})));
+        }
+    }
+}
@@ -0,0 +1,216 @@
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use json_parser_rust::json;
+use json_parser_rust::json_definitions::{
+    Dialect, FrameTypeTag, JsonParsingErrorV3, JsonValue, Limit, ParseLimits, ParseOptions, TokenTag,
+};
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_serde::{self, SerdeError};
+use serde::{Deserialize, Serialize};
+
+fn parse(input: &str) -> JsonValue {
+    process_json_string_v3(input).expect("test input should be valid JSON")
+}
+
+fn syntax<'a, T: Deserialize<'a> + std::fmt::Debug>(input: &'a str) -> JsonParsingErrorV3 {
+    match json_serde::from_str::<T>(input) {
+        Err(SerdeError::Syntax(e)) => e,
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Server<'a> {
+    host: &'a str,
+    port: u16,
+    tags: Vec<String>,
+    #[serde(default)]
+    backup: Option<Box<Server<'a>>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Line(i32, i32),
+    Rect { w: u32, h: u32 },
+}
+
+mod deserializer {
+    mod pos {
+        use super::super::{json_serde, parse, BTreeMap, Dialect, JsonValue, ParseOptions, Server, Shape};
+
+        #[test]
+        fn typed_structs() {
+            let input = r#"{"host": "a.example", "port": 80, "tags": ["x"], "backup": {"host": "b", "port": 81, "tags": []}}"#;
+            let server: Server = json_serde::from_str(input).unwrap();
+            assert_eq!(server.host, "a.example");
+            assert_eq!(server.backup.as_ref().unwrap().port, 81);
+            let server: Server = json_serde::from_str(r#"{"port": 1, "tags": [], "host": "h", "extra": [{}]}"#).unwrap();
+            assert_eq!(server.backup, None);
+        }
+
+        #[test]
+        fn enums_are_externally_tagged() {
+            let shapes: Vec<Shape> =
+                json_serde::from_str(r#"["Point", {"Circle": 1.5}, {"Line": [1, -2]}, {"Rect": {"w": 3, "h": 4}}]"#).unwrap();
+            assert_eq!(shapes, [Shape::Point, Shape::Circle(1.5), Shape::Line(1, -2), Shape::Rect { w: 3, h: 4 }]);
+        }
+
+        #[test]
+        fn json5_config() {
+            let options = ParseOptions { dialect: Dialect::Json5, ..ParseOptions::default() };
+            let input = "{host: 'a', port: 0x50, tags: ['x',], /* none */}";
+            let server: Server = json_serde::from_str_with_options(input, &options).unwrap();
+            assert_eq!((server.host, server.port, server.tags), ("a", 80, vec!["x".to_string()]));
+            let pair: (i32, i32) = json_serde::from_str_with_options("[1, +2,] // pair", &options).unwrap();
+            assert_eq!(pair, (1, 2));
+        }
+
+        #[test]
+        fn scalars_and_map_keys() {
+            assert_eq!(json_serde::from_str::<u128>("340282366920938463463374607431768211455").unwrap(), u128::MAX);
+            assert_eq!(json_serde::from_str::<i8>("-128").unwrap(), -128);
+            assert_eq!(json_serde::from_str::<f32>("2.5e0").unwrap(), 2.5);
+            assert_eq!(json_serde::from_str::<String>(r#""a\nb""#).unwrap(), "a\nb");
+            assert_eq!(json_serde::from_str::<Option<bool>>("null").unwrap(), None);
+            let map: BTreeMap<u32, bool> = json_serde::from_str(r#"{"2": true, "10": false}"#).unwrap();
+            assert_eq!(map.into_iter().collect::<Vec<_>>(), [(2, true), (10, false)]);
+        }
+
+        #[test]
+        fn json_value_matches_v3() {
+            let input = r#"{"a": [1, -2, 3.5, 18446744073709551616], "b": {"c": null, "d": "é"}, "e": true}"#;
+            let value: JsonValue = json_serde::from_str(input).unwrap();
+            assert_eq!(value, parse(input));
+        }
+
+        #[test]
+        fn reader() {
+            let value: JsonValue = json_serde::from_reader(&b"[1, 2]"[..]).unwrap();
+            assert_eq!(value, parse("[1, 2]"));
+        }
+    }
+
+    mod neg {
+        use super::super::{
+            json_serde, syntax, FrameTypeTag, JsonParsingErrorV3, JsonValue, Limit, ParseLimits, ParseOptions,
+            SerdeError, Server, TokenTag,
+        };
+
+        #[test]
+        fn malformed_text_gives_v3_errors() {
+            assert_eq!(syntax::<JsonValue>(""), JsonParsingErrorV3::EmptyJsonFile);
+            assert!(matches!(syntax::<JsonValue>("[1 2]"), JsonParsingErrorV3::MissingComma { .. }));
+            assert!(matches!(
+                syntax::<Vec<u8>>("[1,]"),
+                JsonParsingErrorV3::TrailingComma { found: TokenTag::RBracket, frame: FrameTypeTag::ArrayFrame, .. }
+            ));
+            assert!(matches!(syntax::<JsonValue>(r#"{"a" 1}"#), JsonParsingErrorV3::MissingColon { .. }));
+            assert!(matches!(
+                syntax::<JsonValue>(r#"{"a": }"#),
+                JsonParsingErrorV3::ObjectKeyWithoutValue { ref key, .. } if key == "a"
+            ));
+            assert!(matches!(syntax::<JsonValue>("[1]]"), JsonParsingErrorV3::ExpectedEOF { .. }));
+            assert!(matches!(syntax::<JsonValue>("[1"), JsonParsingErrorV3::UnexpectedEOF { .. }));
+            assert!(matches!(syntax::<JsonValue>("[1}"), JsonParsingErrorV3::MismatchedClosing { .. }));
+        }
+
+        #[test]
+        fn type_errors_point_at_the_value() {
+            let err = json_serde::from_str::<Server>("{\"host\": \"h\",\n \"port\": \"80\", \"tags\": []}").unwrap_err();
+            let SerdeError::Custom { at: Some(at), .. } = &err else {
+                panic!("expected a located custom error, got {:?}", err);
+            };
+            assert_eq!((at.line, at.column), (2, 10));
+            assert!(err.to_string().contains("invalid type: string \"80\""));
+
+            let err = json_serde::from_str::<Server>(r#"{"host": "h", "tags": []}"#).unwrap_err();
+            assert!(err.to_string().contains("missing field `port`"));
+            assert!(json_serde::from_str::<u8>("256").is_err());
+            assert!(json_serde::from_str::<(u8, u8)>("[1, 2, 3]").is_err());
+        }
+
+        #[test]
+        fn escaped_strings_cannot_be_borrowed() {
+            let err = json_serde::from_str::<&str>(r#""a\nb""#).unwrap_err();
+            assert!(err.to_string().contains("invalid type: string"));
+        }
+
+        #[test]
+        fn limits() {
+            let deep = "[".repeat(200) + &"]".repeat(200);
+            assert!(matches!(syntax::<JsonValue>(&deep), JsonParsingErrorV3::LimitExceeded { limit: Limit::Depth, .. }));
+
+            let limits = ParseLimits { max_array_len: 2, ..ParseLimits::UNLIMITED };
+            let options = ParseOptions { limits, ..Default::default() };
+            let err = json_serde::from_str_with_options::<Vec<u8>>("[1, 2, 3]", &options).unwrap_err();
+            assert!(matches!(
+                err,
+                SerdeError::Syntax(JsonParsingErrorV3::LimitExceeded { limit: Limit::ArrayLength, .. })
+            ));
+        }
+    }
+}
+
+mod serializer {
+    mod pos {
+        use super::super::{json, json_serde, parse, BTreeMap, Server, Shape};
+        use json_parser_rust::json_convert::ToJson;
+        use json_parser_rust::json_writer::{self, Indent, WriterOptions};
+
+        #[test]
+        fn compact_and_pretty() {
+            let server = Server { host: "h", port: 8, tags: vec!["a\"b".to_string()], backup: None };
+            assert_eq!(json_serde::to_string(&server).unwrap(), r#"{"host":"h","port":8,"tags":["a\"b"],"backup":null}"#);
+            assert_eq!(
+                json_serde::to_string_pretty(&server).unwrap(),
+                "{\n  \"host\": \"h\",\n  \"port\": 8,\n  \"tags\": [\n    \"a\\\"b\"\n  ],\n  \"backup\": null\n}"
+            );
+            let options = WriterOptions { indent: Indent::Tabs(1), ascii_only: true };
+            let text = json_serde::to_string_with_options(&("é", Vec::<u8>::new()), &options).unwrap();
+            assert_eq!(text, "[\n\t\"\\u00E9\",\n\t[]\n]");
+        }
+
+        #[test]
+        fn enums_and_numbers() {
+            let shapes = [Shape::Point, Shape::Circle(0.1), Shape::Line(1, 2), Shape::Rect { w: 1, h: 2 }];
+            assert_eq!(
+                json_serde::to_string(&shapes).unwrap(),
+                r#"["Point",{"Circle":0.1},{"Line":[1,2]},{"Rect":{"w":1,"h":2}}]"#
+            );
+            assert_eq!(
+                json_serde::to_string(&(0.1f32, 1e300, f64::NAN, u128::MAX)).unwrap(),
+                "[0.1,1e300,null,340282366920938463463374607431768211455]"
+            );
+            let map: BTreeMap<i32, char> = [(-1, 'x'), (2, 'y')].into_iter().collect();
+            assert_eq!(json_serde::to_string(&map).unwrap(), r#"{"-1":"x","2":"y"}"#);
+            // same text as the ToJson path
+            let floats = (0.1f32, 1e-7f32);
+            assert_eq!(json_serde::to_string(&floats).unwrap(), json_writer::to_string(&floats.to_json()));
+        }
+
+        #[test]
+        fn json_value_round_trips() {
+            let value = parse(r#"{"a": [1, -2, 2.5, {}], "b": {"c": null, "d": "é"}, "e": true}"#);
+            assert_eq!(json_serde::to_string(&value).unwrap(), json_writer::to_string(&value));
+            assert_eq!(json_serde::to_string_pretty(&value).unwrap(), json_writer::to_string_pretty(&value));
+            let text = json_serde::to_string(&json!({"n": [1, 2]})).unwrap();
+            assert_eq!(json_serde::from_str::<super::super::JsonValue>(&text).unwrap(), json!({"n": [1, 2]}));
+        }
+    }
+
+    mod neg {
+        use super::super::{json_serde, BTreeMap, SerdeError};
+
+        #[test]
+        fn keys_must_be_strings() {
+            let map: BTreeMap<(u8, u8), u8> = [((1, 2), 3)].into_iter().collect();
+            assert!(matches!(json_serde::to_string(&map), Err(SerdeError::KeyMustBeString)));
+            let map: BTreeMap<bool, u8> = [(true, 1)].into_iter().collect();
+            assert!(matches!(json_serde::to_string(&map), Err(SerdeError::KeyMustBeString)));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use indexmap::IndexMap;
+
+use std::borrow::Cow;
+
+use json_parser_rust::json_definitions::{DuplicateKeyPolicy, JsonValue, JsonValueRef, ParseOptions};
+use json_parser_rust::json_non_recursive::{
+    process_json_string_v3, process_json_string_v3_borrowed, process_json_string_v3_borrowed_with_options,
+};
+
+const DEPTH: usize = 1_000_000;
+
+fn deep_array(depth: usize) -> JsonValue {
+    let mut value = JsonValue::Null;
+    for _ in 0..depth {
+        value = JsonValue::Array(vec![value]);
+    }
+    value
+}
+
+fn deep_object(depth: usize) -> JsonValue {
+    let mut value = JsonValue::Number(1.0.into());
+    for _ in 0..depth {
+        let mut m = IndexMap::new();
+        m.insert("a".to_string(), value);
+        value = JsonValue::Object(m);
+    }
+    value
+}
+
+fn sample() -> JsonValue {
+    let mut inner = IndexMap::new();
+    inner.insert("k".to_string(), JsonValue::JsonString("v\"".to_string()));
+    inner.insert("e".to_string(), JsonValue::Object(IndexMap::new()));
+
+    JsonValue::Array(vec![
+        JsonValue::Null,
+        JsonValue::Boolean(true),
+        JsonValue::Number(1.5.into()),
+        JsonValue::Array(vec![]),
+        JsonValue::Object(inner),
+    ])
+}
+
+mod drop {
+    use super::{deep_array, deep_object, process_json_string_v3, DEPTH};
+
+    #[test]
+    fn drop_million_deep_parsed_array() {
+        let input = format!("{}{}", "[".repeat(DEPTH), "]".repeat(DEPTH));
+        let value = process_json_string_v3(&input).expect("deep array should parse");
+        drop(value);
+    }
+
+    #[test]
+    fn drop_million_deep_object() {
+        drop(deep_object(DEPTH));
+    }
+
+    #[test]
+    fn drop_million_deep_array() {
+        drop(deep_array(DEPTH));
+    }
+}
+
+mod clone {
+    use super::{deep_array, deep_object, sample, DEPTH};
+
+    #[test]
+    fn clone_is_equal() {
+        let value = sample();
+        assert_eq!(value.clone(), value);
+    }
+
+    #[test]
+    fn clone_million_deep_array() {
+        let value = deep_array(DEPTH);
+        let copy = value.clone();
+        assert!(copy == value);
+    }
+
+    #[test]
+    fn clone_million_deep_object() {
+        let value = deep_object(DEPTH);
+        let copy = value.clone();
+        assert!(copy == value);
+    }
+}
+
+mod eq {
+    use super::{deep_array, deep_object, sample, IndexMap, JsonValue, DEPTH};
+
+    #[test]
+    fn compares_like_derived() {
+        assert_eq!(JsonValue::Null, JsonValue::Null);
+        assert_ne!(JsonValue::Null, JsonValue::Boolean(false));
+        assert_ne!(JsonValue::Number(f64::NAN.into()), JsonValue::Number(f64::NAN.into()));
+        assert_ne!(JsonValue::Array(vec![JsonValue::Null]), JsonValue::Array(vec![]));
+
+        // objects compare as maps, key order does not matter
+        let mut a = IndexMap::new();
+        a.insert("x".to_string(), JsonValue::Number(1.0.into()));
+        a.insert("y".to_string(), JsonValue::Number(2.0.into()));
+        let mut b = IndexMap::new();
+        b.insert("y".to_string(), JsonValue::Number(2.0.into()));
+        b.insert("x".to_string(), JsonValue::Number(1.0.into()));
+        assert_eq!(JsonValue::Object(a.clone()), JsonValue::Object(b));
+
+        let mut c = a.clone();
+        c.insert("x".to_string(), JsonValue::Number(3.0.into()));
+        assert_ne!(JsonValue::Object(a), JsonValue::Object(c));
+
+        assert_ne!(sample(), JsonValue::Array(vec![]));
+    }
+
+    #[test]
+    fn eq_million_deep() {
+        assert!(deep_array(DEPTH) == deep_array(DEPTH));
+        assert!(deep_array(DEPTH) != deep_array(DEPTH - 1));
+        assert!(deep_object(DEPTH) == deep_object(DEPTH));
+    }
+}
+
+mod debug {
+    use super::{deep_array, deep_object, sample, DEPTH};
+
+    #[test]
+    fn matches_derived_format() {
+        assert_eq!(
+            format!("{:?}", sample()),
+            r#"Array([Null, Boolean(true), Number(1.5), Array([]), Object({"k": JsonString("v\""), "e": Object({})})])"#
+        );
+    }
+
+    #[test]
+    fn matches_derived_pretty_format() {
+        let expected = r#"Array(
+    [
+        Null,
+        Boolean(
+            true,
+        ),
+        Number(
+            1.5,
+        ),
+        Array(
+            [],
+        ),
+        Object(
+            {
+                "k": JsonString(
+                    "v\"",
+                ),
+                "e": Object(
+                    {},
+                ),
+            },
+        ),
+    ],
+)"#;
+        assert_eq!(format!("{:#?}", sample()), expected);
+    }
+
+    #[test]
+    fn debug_million_deep() {
+        let out = format!("{:?}", deep_array(DEPTH));
+        assert_eq!(out.len(), DEPTH * "Array([])".len() + "Null".len());
+        assert!(out.starts_with("Array([Array(["));
+
+        let out = format!("{:?}", deep_object(DEPTH));
+        assert!(out.starts_with(r#"Object({"a": Object({"a": "#));
+        assert!(out.contains(r#"{"a": Number(1)})})"#));
+    }
+}
+
+mod borrowed {
+    use super::{
+        process_json_string_v3, process_json_string_v3_borrowed, process_json_string_v3_borrowed_with_options, Cow,
+        DuplicateKeyPolicy, JsonValue, JsonValueRef, ParseOptions, DEPTH,
+    };
+
+    #[test]
+    fn plain_strings_and_keys_borrow_from_the_input() {
+        let input = r#"{"plain": "text", "esc\u0041ped": "a\nb"}"#;
+        let value = process_json_string_v3_borrowed(input).unwrap();
+        let JsonValueRef::Object(members) = &value else { panic!("expected an object, got {:?}", value) };
+
+        let keys: Vec<_> = members.keys().collect();
+        assert!(matches!(keys[0], Cow::Borrowed("plain")));
+        assert!(matches!(keys[1], Cow::Owned(k) if k == "escAped"));
+        assert!(matches!(&members[0], JsonValueRef::JsonString(Cow::Borrowed("text"))));
+        assert!(matches!(&members[1], JsonValueRef::JsonString(Cow::Owned(s)) if s == "a\nb"));
+    }
+
+    #[test]
+    fn to_owned_matches_v3() {
+        let input = r#"[null, true, 1.5, "s", {"k": ["v\"", {}], "e": []}]"#;
+        let borrowed = process_json_string_v3_borrowed(input).unwrap();
+        assert_eq!(borrowed.to_owned(), process_json_string_v3(input).unwrap());
+        assert_eq!(JsonValue::from(borrowed), process_json_string_v3(input).unwrap());
+    }
+
+    #[test]
+    fn equality_ignores_borrowed_vs_owned() {
+        let a = process_json_string_v3_borrowed(r#"{"k": "v"}"#).unwrap();
+        let b = process_json_string_v3_borrowed(r#"{"\u006b": "\u0076"}"#).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(format!("{:?}", a), r#"Object({"k": JsonString("v")})"#);
+    }
+
+    #[test]
+    fn debug_matches_owned() {
+        let input = r#"[null, true, 1.5, "s\"", {"k": ["v", {}], "e\u0041": []}]"#;
+        let borrowed = process_json_string_v3_borrowed(input).unwrap();
+        let owned = process_json_string_v3(input).unwrap();
+        assert_eq!(format!("{:?}", borrowed), format!("{:?}", owned));
+        assert_eq!(format!("{:#?}", borrowed), format!("{:#?}", owned));
+
+        let deep = format!("{}null{}", "[".repeat(DEPTH), "]".repeat(DEPTH));
+        let out = format!("{:?}", process_json_string_v3_borrowed(&deep).unwrap());
+        assert_eq!(out.len(), DEPTH * "Array([])".len() + "Null".len());
+    }
+
+    #[test]
+    fn options_apply() {
+        let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Collect, ..ParseOptions::default() };
+        let value = process_json_string_v3_borrowed_with_options(r#"{"a": 1, "a": 2}"#, &options).unwrap();
+        assert_eq!(value.to_owned(), process_json_string_v3(r#"{"a": [1, 2]}"#).unwrap());
+    }
+
+    #[test]
+    fn million_deep() {
+        let input = format!("{}\"leaf\"{}", "[".repeat(DEPTH), "]".repeat(DEPTH));
+        let value = process_json_string_v3_borrowed(&input).expect("deep value should parse");
+        let owned = value.to_owned();
+        assert_eq!(value, process_json_string_v3_borrowed(&input).unwrap());
+        drop(value);
+        drop(owned);
+    }
+}
@@ -0,0 +1,156 @@
+use indexmap::IndexMap;
+
+use json_parser_rust::json_definitions::JsonValue;
+use json_parser_rust::json_non_recursive::process_json_string_v3;
+use json_parser_rust::json_writer::{
+    format_number, to_string, to_string_pretty, to_string_with_options, write_json, Indent, WriterOptions,
+};
+
+fn sample() -> JsonValue {
+    let mut inner = IndexMap::new();
+    inner.insert("x".to_string(), JsonValue::Null);
+    inner.insert("y".to_string(), JsonValue::Array(vec![]));
+
+    let mut root = IndexMap::new();
+    root.insert(
+        "a".to_string(),
+        JsonValue::Array(vec![JsonValue::Number(1.0.into()), JsonValue::Boolean(true), JsonValue::Object(inner)]),
+    );
+    root.insert("b".to_string(), JsonValue::JsonString("hi".to_string()));
+    root.insert("c".to_string(), JsonValue::Object(IndexMap::new()));
+    JsonValue::Object(root)
+}
+
+mod layout {
+    use super::{sample, to_string, to_string_pretty, to_string_with_options, Indent, JsonValue, WriterOptions};
+
+    #[test]
+    fn compact() {
+        assert_eq!(to_string(&sample()), r#"{"a":[1,true,{"x":null,"y":[]}],"b":"hi","c":{}}"#);
+    }
+
+    #[test]
+    fn pretty_two_spaces() {
+        let expected = r#"{
+  "a": [
+    1,
+    true,
+    {
+      "x": null,
+      "y": []
+    }
+  ],
+  "b": "hi",
+  "c": {}
+}"#;
+        assert_eq!(to_string_pretty(&sample()), expected);
+    }
+
+    #[test]
+    fn custom_indent() {
+        let value = JsonValue::Array(vec![JsonValue::Array(vec![JsonValue::Null])]);
+
+        let four = WriterOptions { indent: Indent::Spaces(4), ascii_only: false };
+        assert_eq!(to_string_with_options(&value, &four), "[\n    [\n        null\n    ]\n]");
+
+        let tabs = WriterOptions { indent: Indent::Tabs(1), ascii_only: false };
+        assert_eq!(to_string_with_options(&value, &tabs), "[\n\t[\n\t\tnull\n\t]\n]");
+    }
+
+    #[test]
+    fn leaves_at_root() {
+        assert_eq!(to_string(&JsonValue::Null), "null");
+        assert_eq!(to_string_pretty(&JsonValue::Boolean(false)), "false");
+        assert_eq!(to_string_pretty(&JsonValue::Array(vec![])), "[]");
+    }
+}
+
+mod strings {
+    use super::{to_string, to_string_with_options, JsonValue, WriterOptions};
+
+    fn s(v: &str) -> JsonValue {
+        JsonValue::JsonString(v.to_string())
+    }
+
+    #[test]
+    fn escapes_quotes_backslash_and_control_chars() {
+        assert_eq!(to_string(&s("a\"b\\c")), r#""a\"b\\c""#);
+        assert_eq!(to_string(&s("\n\r\t\u{8}\u{c}")), r#""\n\r\t\b\f""#);
+        assert_eq!(to_string(&s("\u{0}\u{1f}")), r#""\u0000\u001F""#);
+        assert_eq!(to_string(&s("a/b")), r#""a/b""#);
+    }
+
+    #[test]
+    fn keeps_unicode_by_default() {
+        assert_eq!(to_string(&s("héllo 😀")), "\"héllo 😀\"");
+    }
+
+    #[test]
+    fn ascii_only_escapes_non_ascii() {
+        let options = WriterOptions { ascii_only: true, ..WriterOptions::compact() };
+        assert_eq!(to_string_with_options(&s("héllo 😀"), &options), r#""h\u00E9llo \uD83D\uDE00""#);
+    }
+}
+
+mod numbers {
+    use super::{format_number, process_json_string_v3, to_string, JsonValue};
+
+    #[test]
+    fn shortest_representation() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "-0"),
+            (1.0, "1"),
+            (-42.0, "-42"),
+            (0.1, "0.1"),
+            (1.5e-7, "1.5e-7"),
+            (1e21, "1e21"),
+            (123456789012.5, "123456789012.5"),
+            (f64::MAX, "1.7976931348623157e308"),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(format_number(n), expected, "number was: {:?}", n);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_parser() {
+        for n in [0.1, 1.0 / 3.0, 2.0f64.powi(60), -1e-300, 5e-324, 9007199254740993.0] {
+            let text = to_string(&JsonValue::Number(n.into()));
+            assert_eq!(process_json_string_v3(&text), Ok(JsonValue::Number(n.into())), "text was: {}", text);
+        }
+    }
+
+    #[test]
+    fn non_finite_written_as_null() {
+        assert_eq!(to_string(&JsonValue::Number(f64::NAN.into())), "null");
+        assert_eq!(to_string(&JsonValue::Number(f64::INFINITY.into())), "null");
+    }
+}
+
+mod io {
+    use super::{process_json_string_v3, sample, to_string, write_json, WriterOptions};
+
+    #[test]
+    fn writes_to_io_write() {
+        let mut out: Vec<u8> = Vec::new();
+        write_json(&mut out, &sample(), &WriterOptions::compact()).expect("write should succeed");
+        assert_eq!(String::from_utf8(out).unwrap(), to_string(&sample()));
+    }
+
+    #[test]
+    fn round_trip_complex_document() {
+        let text = r#"{"name":"x","tags":["a","b"],"nested":{"deep":[1,2.5,-3e-9,{"k":null}]},"ok":false}"#;
+        let value = process_json_string_v3(text).expect("should parse");
+        assert_eq!(to_string(&value), text);
+    }
+
+    #[test]
+    fn deeply_nested_does_not_overflow() {
+        let depth = 100_000;
+        let text = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let value = process_json_string_v3(&text).expect("should parse");
+
+        assert_eq!(to_string(&value), text);
+    }
+}